@@ -0,0 +1,71 @@
+// Renders the objects found by components::ComponentLabeler, one color per label.
+// Zoom and view offset work as in render.wgsl; the selected object is drawn white
+// and the others dimmed while one is selected.
+
+struct SimParams {
+    width: u32,
+    height: u32,
+    lucky_chance: f32,
+    seed: u32,
+    enable_lucky_rule: u32,
+    boundary: u32,
+    _pad2: u32,
+    _pad3: u32,
+};
+
+struct RenderParams {
+    zoom: f32,
+    _padding: f32,
+    view_offset: vec2<f32>, // Matches the [f32; 2] in Rust
+    palette: array<vec4<f32>, 8>, // Color per rounded cell value (see render::Palette)
+};
+
+@group(0) @binding(0) var<uniform> sim_params: SimParams;
+@group(0) @binding(1) var<storage, read> labels: array<u32>; // Label per cell, then the selected label
+@group(0) @binding(2) var<uniform> render_params: RenderParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Vertex shader: Output a full-screen triangle
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32(in_vertex_index / 2u) * 4.0 - 1.0;
+    let y = f32(in_vertex_index % 2u) * 4.0 - 1.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+// Bright, well separated hue for a label
+fn label_color(label: u32) -> vec3<f32> {
+    var h = label * 0x9E3779B9u;
+    h = (h ^ (h >> 15u)) * 0x85EBCA6Bu;
+    h = h ^ (h >> 13u);
+    let hue = f32(h & 0xFFFFu) / 65536.0;
+    let k = fract(vec3<f32>(hue) + vec3<f32>(0.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0;
+    return clamp(abs(k - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)) * 0.75 + 0.25;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let scaled_coord = (frag_coord.xy + render_params.view_offset) / render_params.zoom;
+    let grid_x = i32(floor(scaled_coord.x));
+    let grid_y = i32(floor(scaled_coord.y));
+
+    if (grid_x < 0 || grid_x >= i32(sim_params.width) || grid_y < 0 || grid_y >= i32(sim_params.height)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0); // Pure black background
+    }
+
+    let label = labels[u32(grid_y) * sim_params.width + u32(grid_x)];
+    if (label == 0u) {
+        return render_params.palette[0];
+    }
+    let selected = labels[arrayLength(&labels) - 1u];
+    if (label == selected) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    let dim = select(1.0, 0.45, selected != 0u);
+    return vec4<f32>(label_color(label) * dim, 1.0);
+}
@@ -0,0 +1,49 @@
+// Renders a bit-packed grid (packed::PackedSimulation): same zoom, offset and
+// palette handling as render.wgsl, with each cell read from its bit.
+
+struct PackedParams {
+    width: u32,
+    height: u32,
+    words_per_row: u32,
+    boundary: u32,
+};
+
+struct RenderParams {
+    zoom: f32,
+    _padding: f32,
+    view_offset: vec2<f32>, // Matches the [f32; 2] in Rust
+    palette: array<vec4<f32>, 8>, // Color per rounded cell value (see render::Palette)
+};
+
+@group(0) @binding(0) var<uniform> packed_params: PackedParams;
+@group(0) @binding(1) var<storage, read> grid_words: array<u32>; // 32 cells per word
+@group(0) @binding(2) var<uniform> render_params: RenderParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Vertex shader: Output a full-screen triangle
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32(in_vertex_index / 2u) * 4.0 - 1.0;
+    let y = f32(in_vertex_index % 2u) * 4.0 - 1.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let scaled_coord = (frag_coord.xy + render_params.view_offset) / render_params.zoom;
+    let grid_x = i32(floor(scaled_coord.x));
+    let grid_y = i32(floor(scaled_coord.y));
+
+    if (grid_x < 0 || grid_x >= i32(packed_params.width) || grid_y < 0 || grid_y >= i32(packed_params.height)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0); // Pure black background
+    }
+
+    let word = grid_words[u32(grid_y) * packed_params.words_per_row + u32(grid_x) / 32u];
+    let alive = (word >> (u32(grid_x) % 32u)) & 1u;
+    return render_params.palette[alive]; // Slot 0 = dead, 1 = alive
+}
@@ -4,7 +4,7 @@ struct SimParams {
     lucky_chance: f32,
     seed: u32,              // Match compute shader (was frame_counter)
     enable_lucky_rule: u32,
-    _pad1: u32,
+    boundary: u32,
     _pad2: u32,
     _pad3: u32,
 };
@@ -13,6 +13,7 @@ struct RenderParams {
     zoom: f32,
     _padding: f32,
     view_offset: vec2<f32>, // Matches the [f32; 2] in Rust
+    palette: array<vec4<f32>, 8>, // Color per rounded cell value (see render::Palette)
 };
 
 @group(0) @binding(0) var<uniform> sim_params: SimParams;
@@ -64,22 +65,15 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
         return vec4<f32>(0.0, 0.0, 0.5, 1.0); // Dark Blue error
     }
 
-    // Choose color based on cell state:
-    // 0.0 = dead (black)
-    // 1.0 = alive (white)
-    // 2.0 = red cell (special "lucky" cell)
-    var color: vec3<f32>;
-    
-    if (cell_value <= 0.5) {
-        // Dead cell
-        color = vec3<f32>(0.0, 0.0, 0.0); // Pure black background
-    } else if (cell_value < 1.5) {
-        // Normal living cell (white)
-        color = vec3<f32>(1.0, 1.0, 1.0);
-    } else {
-        // Red cell
-        color = vec3<f32>(0.9, 0.1, 0.1);
+    // Choose color based on cell state via the active palette:
+    // 0.0 = dead, 1.0 = alive, 2.0/3.0 = lucky/red, 4.0-7.0 = painted colors
+    return render_params.palette[palette_index(cell_value)];
+}
+
+// Map a raw cell value to its palette slot (mirrors Palette::index_of in render.rs)
+fn palette_index(value: f32) -> u32 {
+    if (value <= 0.5) {
+        return 0u;
     }
-    
-    return vec4<f32>(color, 1.0);
-} 
\ No newline at end of file
+    return u32(clamp(round(value), 1.0, 7.0));
+}
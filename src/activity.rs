@@ -0,0 +1,278 @@
+use crate::rules::Boundary;
+use bytemuck::{Pod, Zeroable};
+use std::borrow::Cow;
+use std::cell::Cell;
+use wgpu::util::DeviceExt;
+
+const SCHEDULE_WORKGROUP_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ActivityParams {
+    tiles_x: u32,
+    tiles_y: u32,
+    boundary: u32,
+    _padding: u32,
+}
+
+/// Per-tile change tracking for `Kernel::Active`.
+///
+/// The grid is cut into workgroup-sized tiles. Each generation only the scheduled
+/// tiles are recomputed (indirect dispatch), every recomputed tile that changed is
+/// flagged, and the tiles that changed or border a changed tile are scheduled for the
+/// next generation, all on the GPU. A tile that is skipped did not change in the
+/// generation before, so the output buffer (which holds that generation) already
+/// has its cells.
+///
+/// Anything written from outside (painting, loads, rule changes) makes the schedule
+/// stale; the next step then recomputes every tile.
+pub struct ActivityTracker {
+    tiles_x: u32,
+    tiles_y: u32,
+    /// Boundary mode the schedule was built for
+    boundary: Boundary,
+    stale: Cell<bool>,
+
+    params_buffer: wgpu::Buffer,
+    count_buffer: wgpu::Buffer,
+    tile_changed_buffer: wgpu::Buffer,
+    dispatch_args_buffer: wgpu::Buffer,
+
+    step_pipeline: wgpu::ComputePipeline,
+    step_bind_group: wgpu::BindGroup,
+    activate_all_pipeline: wgpu::ComputePipeline,
+    schedule_pipeline: wgpu::ComputePipeline,
+    finish_pipeline: wgpu::ComputePipeline,
+    schedule_bind_group: wgpu::BindGroup,
+}
+
+impl ActivityTracker {
+    /// `step_source` is the shared-memory kernel with `rules/conway_active.wgsl` appended;
+    /// `compute_layout` is the simulation's bind group layout (group 0 of the step kernel)
+    pub fn new(
+        device: &wgpu::Device,
+        compute_layout: &wgpu::BindGroupLayout,
+        step_source: &str,
+        width: u32,
+        height: u32,
+        workgroup_size: u32,
+    ) -> Self {
+        let tiles_x = width.div_ceil(workgroup_size);
+        let tiles_y = height.div_ceil(workgroup_size);
+        let tile_bytes = tiles_x as u64 * tiles_y as u64 * std::mem::size_of::<u32>() as u64;
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Activity Parameters"),
+            size: std::mem::size_of::<ActivityParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let storage = |label: &str, size: u64, usage: wgpu::BufferUsages| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+                mapped_at_creation: false,
+            })
+        };
+        let count_buffer = storage("Active Tile Count", 4, wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC);
+        let active_tiles_buffer = storage("Active Tiles", tile_bytes, wgpu::BufferUsages::empty());
+        let tile_changed_buffer = storage("Tile Changed Flags", tile_bytes, wgpu::BufferUsages::COPY_DST);
+        let dispatch_args_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Active Tile Dispatch"),
+            contents: bytemuck::cast_slice(&[0u32; 3]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT,
+        });
+
+        // Group 1 of the step kernel: the list it works through and the flags it sets
+        let step_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Active Step Bind Group Layout"),
+            entries: &[
+                layout_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(1, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(2, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let step_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Active Step Bind Group"),
+            layout: &step_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: count_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: active_tiles_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: tile_changed_buffer.as_entire_binding() },
+            ],
+        });
+        let step_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Active Step Shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(step_source)),
+        });
+        let step_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Active Step Pipeline"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Active Step Pipeline Layout"),
+                bind_group_layouts: &[compute_layout, &step_layout],
+                push_constant_ranges: &[],
+            })),
+            module: &step_module,
+            entry_point: "step_active",
+        });
+
+        let schedule_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Activity Schedule Bind Group Layout"),
+            entries: &[
+                layout_entry(0, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, wgpu::BufferBindingType::Storage { read_only: false }),
+                layout_entry(2, wgpu::BufferBindingType::Storage { read_only: false }),
+                layout_entry(3, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(4, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let schedule_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Activity Schedule Bind Group"),
+            layout: &schedule_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: count_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: active_tiles_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: tile_changed_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: dispatch_args_buffer.as_entire_binding() },
+            ],
+        });
+        let schedule_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Activity Schedule Shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("activity.wgsl"))),
+        });
+        let schedule_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Activity Schedule Pipeline Layout"),
+            bind_group_layouts: &[&schedule_layout],
+            push_constant_ranges: &[],
+        });
+        let schedule_entry = |entry_point: &str| device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(&schedule_pipeline_layout),
+            module: &schedule_module,
+            entry_point,
+        });
+
+        Self {
+            tiles_x,
+            tiles_y,
+            boundary: Boundary::default(),
+            stale: Cell::new(true),
+            params_buffer,
+            count_buffer,
+            tile_changed_buffer,
+            dispatch_args_buffer,
+            step_pipeline,
+            step_bind_group,
+            activate_all_pipeline: schedule_entry("activate_all"),
+            schedule_pipeline: schedule_entry("schedule"),
+            finish_pipeline: schedule_entry("finish_schedule"),
+            schedule_bind_group,
+        }
+    }
+
+    pub fn tile_count(&self) -> u32 {
+        self.tiles_x * self.tiles_y
+    }
+
+    /// Recompute every tile in the next step
+    pub fn invalidate(&self) {
+        self.stale.set(true);
+    }
+
+    /// Record one generation, reading the current generation through `compute_bind_group`
+    pub fn encode_step(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        compute_bind_group: &wgpu::BindGroup,
+        boundary: Boundary,
+    ) {
+        let schedule_dispatch = self.tile_count().div_ceil(SCHEDULE_WORKGROUP_SIZE);
+        if self.stale.get() || boundary != self.boundary {
+            self.boundary = boundary;
+            queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&ActivityParams {
+                tiles_x: self.tiles_x,
+                tiles_y: self.tiles_y,
+                boundary: boundary.shader_value(),
+                _padding: 0,
+            }));
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Activate All Tiles Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.activate_all_pipeline);
+            pass.set_bind_group(0, &self.schedule_bind_group, &[]);
+            pass.dispatch_workgroups(schedule_dispatch, 1, 1);
+            self.stale.set(false);
+        }
+
+        encoder.clear_buffer(&self.tile_changed_buffer, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Active Tiles Step Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.step_pipeline);
+            pass.set_bind_group(0, compute_bind_group, &[]);
+            pass.set_bind_group(1, &self.step_bind_group, &[]);
+            pass.dispatch_workgroups_indirect(&self.dispatch_args_buffer, 0);
+        }
+
+        encoder.clear_buffer(&self.count_buffer, 0, None);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Activity Schedule Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, &self.schedule_bind_group, &[]);
+        pass.set_pipeline(&self.schedule_pipeline);
+        pass.dispatch_workgroups(schedule_dispatch, 1, 1);
+        pass.set_pipeline(&self.finish_pipeline);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+
+    /// Tiles scheduled for the next step.
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn active_tile_count(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<u32, String> {
+        if self.stale.get() {
+            return Ok(self.tile_count());
+        }
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Active Tile Count Staging Buffer"),
+            size: 4,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Active Tile Count Copy Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&self.count_buffer, 0, &staging_buffer, 0, 4);
+        queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = staging_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        match receiver.recv() {
+            Ok(Ok(())) => {
+                let count = bytemuck::cast_slice::<u8, u32>(&buffer_slice.get_mapped_range())[0];
+                staging_buffer.unmap();
+                Ok(count)
+            }
+            Ok(Err(e)) => Err(format!("Failed to map staging buffer: {:?}", e)),
+            Err(e) => Err(format!("Failed to receive map result: {:?}", e)),
+        }
+    }
+}
+
+fn layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
@@ -0,0 +1,79 @@
+// Scheduling for activity-aware stepping (see rules/conway_active.wgsl): builds the
+// list of tiles to recompute in the next generation and its indirect dispatch
+// arguments. A tile is scheduled when it or one of its eight neighbors changed.
+
+// Largest workgroup count per dispatch dimension guaranteed by WebGPU
+const MAX_DISPATCH: u32 = 65535u;
+
+struct ActivityParams {
+    tiles_x: u32,
+    tiles_y: u32,
+    boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
+    _pad0: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: ActivityParams;
+@group(0) @binding(1) var<storage, read_write> active_count: atomic<u32>;
+@group(0) @binding(2) var<storage, read_write> active_tiles: array<u32>;
+@group(0) @binding(3) var<storage, read> tile_changed: array<u32>;
+// x, y, z workgroup counts for dispatch_workgroups_indirect
+@group(0) @binding(4) var<storage, read_write> dispatch_args: array<u32, 3>;
+
+fn write_dispatch_args(count: u32) {
+    dispatch_args[0] = min(count, MAX_DISPATCH);
+    dispatch_args[1] = (count + MAX_DISPATCH - 1u) / MAX_DISPATCH;
+    dispatch_args[2] = 1u;
+}
+
+// Schedule every tile (after the grid was written from outside)
+@compute @workgroup_size(64, 1, 1)
+fn activate_all(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let tile_count = params.tiles_x * params.tiles_y;
+    if (global_id.x >= tile_count) {
+        return;
+    }
+    active_tiles[global_id.x] = global_id.x;
+    if (global_id.x == 0u) {
+        atomicStore(&active_count, tile_count);
+        write_dispatch_args(tile_count);
+    }
+}
+
+fn changed(x: i32, y: i32) -> bool {
+    let tiles_x = i32(params.tiles_x);
+    let tiles_y = i32(params.tiles_y);
+    var tx = x;
+    var ty = y;
+    if (tx < 0 || tx >= tiles_x || ty < 0 || ty >= tiles_y) {
+        if (params.boundary == 1u) {
+            return false;
+        }
+        // x and y are never below -1, so the operands stay non-negative
+        tx = (tx + tiles_x) % tiles_x;
+        ty = (ty + tiles_y) % tiles_y;
+    }
+    return tile_changed[u32(ty) * params.tiles_x + u32(tx)] != 0u;
+}
+
+// Append each tile that changed or borders a changed tile; `active_count` starts at 0
+@compute @workgroup_size(64, 1, 1)
+fn schedule(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= params.tiles_x * params.tiles_y) {
+        return;
+    }
+    let x = i32(global_id.x % params.tiles_x);
+    let y = i32(global_id.x / params.tiles_x);
+    for (var dy = -1; dy <= 1; dy = dy + 1) {
+        for (var dx = -1; dx <= 1; dx = dx + 1) {
+            if (changed(x + dx, y + dy)) {
+                active_tiles[atomicAdd(&active_count, 1u)] = global_id.x;
+                return;
+            }
+        }
+    }
+}
+
+@compute @workgroup_size(1, 1, 1)
+fn finish_schedule() {
+    write_dispatch_args(atomicLoad(&active_count));
+}
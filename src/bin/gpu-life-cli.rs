@@ -0,0 +1,542 @@
+//! Headless batch runner: load a pattern, run N generations and print the result.
+//!
+//! Meant for scripts and parameter sweeps. The GPU engine is used by default;
+//! `--cpu` runs the reference CPU engine instead, which needs no adapter and
+//! produces identical, deterministic output. `--hashlife` jumps ahead on an
+//! infinite plane, for generation counts far beyond what stepping can reach.
+//! `--packed` stores 32 cells per word on the GPU, for very large grids.
+//! `--benchmark` times every GPU kernel on the same grid instead of printing a result.
+//! `--classify` reports whether the pattern is a still life, oscillator or spaceship.
+//! `--census` runs random soups instead of a pattern and counts the objects they leave.
+
+use gpu_life::census::{CensusSettings, SoupSearch};
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::hashlife::HashLife;
+use gpu_life::library::{parse_pattern, to_rle, Pattern};
+use gpu_life::packed::PackedSimulation;
+use gpu_life::period::analyze;
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::{request_headless_device, Kernel, Simulation};
+use gpu_life::stats::{GenerationStats, StatsFormat, StatsLog, StatsRecorder, StatsReducer, StatsSettings};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage: gpu-life-cli [OPTIONS] <PATTERN>
+       gpu-life-cli --census <SOUPS> [--seed <N>] [OPTIONS]
+
+Runs a .rle or .cells pattern headlessly and prints the final pattern as RLE.
+
+Options:
+  -r, --rule <RULE>          Rulestring, e.g. B3/S23 (default: the pattern's rule, else B3/S23)
+  -s, --size <WxH>           Grid size (default: 256x256); the pattern is centered
+  -b, --boundary <MODE>      torus or dead (default: torus)
+  -g, --generations <N>      Generations to run (default: 100; per soup with --census: 10000)
+  -o, --output <FILE>        Write the final RLE to FILE instead of stdout
+      --stats <FILE>         Write per-generation statistics (.jsonl/.json: JSON Lines, else CSV)
+      --stats-every <N>      Sample every N generations (default: 1)
+      --cpu                  Use the CPU engine (no GPU adapter needed)
+      --hashlife             Use HashLife on an infinite plane (ignores --size/--boundary, no --stats)
+      --packed               Use the bit-packed GPU engine, 32 cells per word (no --stats)
+      --kernel <KERNEL>      GPU rule kernel: global, shared, blocked or active (default: global)
+      --workgroup-size <N>   Workgroup (tile) side of the shared, blocked and active kernels (default: 16)
+      --block-generations <N>
+                             Generations per dispatch of the blocked kernel, odd (default: 3)
+      --chunk-rows <N>       Split the GPU grid into buffers of at most N rows (grids larger
+                             than one buffer are split automatically)
+      --benchmark            Report generations and cells per second of every GPU kernel
+      --classify             Print the pattern's period and displacement (still life,
+                             oscillator or spaceship), searching up to --generations
+      --census <SOUPS>       Run random 16x16 soups to stabilization, one per tile of the
+                             --size grid (default for a census: 512x512), and print the
+                             census table (apgcode,count,first_soup) as CSV; with --output
+                             the table is also rewritten every minute while it runs
+      --seed <N>             Seed of the census soups (default: 1)
+      --tile-size <N>        Side of the tile each soup runs in (default: 128)
+      --max-period <N>       Longest period of a stable soup (default: 60)
+  -h, --help                 Print this help
+";
+
+/// Generations encoded per GPU submission
+const GPU_BATCH: u32 = 64;
+
+struct Options {
+    pattern: PathBuf,
+    rule: Option<String>,
+    width: u32,
+    height: u32,
+    boundary: Boundary,
+    generations: u64,
+    output: Option<PathBuf>,
+    stats: Option<PathBuf>,
+    stats_every: u32,
+    cpu: bool,
+    hashlife: bool,
+    packed: bool,
+    kernel: Kernel,
+    chunk_rows: Option<u32>,
+    benchmark: bool,
+    classify: bool,
+    census: Option<u64>,
+    census_settings: CensusSettings,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut pattern = None;
+    let mut options = Options {
+        pattern: PathBuf::new(),
+        rule: None,
+        width: 256,
+        height: 256,
+        boundary: Boundary::default(),
+        generations: 100,
+        output: None,
+        stats: None,
+        stats_every: 1,
+        cpu: false,
+        hashlife: false,
+        packed: false,
+        kernel: Kernel::Global,
+        chunk_rows: None,
+        benchmark: false,
+        classify: false,
+        census: None,
+        census_settings: CensusSettings::default(),
+    };
+    let mut kernel_name = None;
+    let mut generations = None;
+    let mut size = None;
+    let mut workgroup_size = 16;
+    let mut block_generations = 3;
+
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value.clone().or_else(|| args.next()).ok_or_else(|| format!("{} needs a value", name))
+        };
+        let number = |name: &str, text: String| {
+            text.parse::<u64>().map_err(|_| format!("Invalid value \"{}\" for {}", text, name))
+        };
+        let small_number = |name: &str, text: String| {
+            text.parse::<u32>().map_err(|_| format!("Invalid value \"{}\" for {}", text, name))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-r" | "--rule" => options.rule = Some(value(&flag)?),
+            "-s" | "--size" => {
+                let text = value(&flag)?;
+                let (w, h) = text.split_once(['x', 'X']).unwrap_or((&text, &text));
+                size = Some((small_number(&flag, w.to_string())?, small_number(&flag, h.to_string())?));
+            }
+            "-b" | "--boundary" => options.boundary = Boundary::parse(&value(&flag)?)?,
+            "-g" | "--generations" => generations = Some(number(&flag, value(&flag)?)?),
+            "-o" | "--output" => options.output = Some(PathBuf::from(value(&flag)?)),
+            "--stats" => options.stats = Some(PathBuf::from(value(&flag)?)),
+            "--stats-every" => options.stats_every = small_number(&flag, value(&flag)?)?.max(1),
+            "--cpu" => options.cpu = true,
+            "--hashlife" => options.hashlife = true,
+            "--packed" => options.packed = true,
+            "--kernel" => kernel_name = Some(value(&flag)?),
+            "--workgroup-size" => workgroup_size = small_number(&flag, value(&flag)?)?,
+            "--block-generations" => block_generations = small_number(&flag, value(&flag)?)?,
+            "--chunk-rows" => options.chunk_rows = Some(small_number(&flag, value(&flag)?)?),
+            "--benchmark" => options.benchmark = true,
+            "--classify" => options.classify = true,
+            "--census" => options.census = Some(number(&flag, value(&flag)?)?),
+            "--seed" => options.census_settings.seed = number(&flag, value(&flag)?)?,
+            "--tile-size" => options.census_settings.tile_size = small_number(&flag, value(&flag)?)?,
+            "--max-period" => options.census_settings.max_period = small_number(&flag, value(&flag)?)?.max(1) as usize,
+            _ if flag.starts_with('-') && flag.len() > 1 => return Err(format!("Unknown option {}", flag)),
+            _ if pattern.is_none() => pattern = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+
+    if let Some(soups) = options.census {
+        if pattern.is_some() {
+            return Err("--census runs random soups and takes no pattern file".to_string());
+        }
+        if options.cpu || options.hashlife || options.packed || options.stats.is_some() || options.benchmark || options.classify {
+            return Err("--census cannot be combined with --cpu, --hashlife, --packed, --stats, --benchmark or --classify".to_string());
+        }
+        let settings = &mut options.census_settings;
+        settings.soups = soups;
+        if let Some((width, height)) = size {
+            (settings.width, settings.height) = (width, height);
+        }
+        if let Some(generations) = generations {
+            settings.max_generations = generations as usize;
+        }
+        if let Some(rule) = &options.rule {
+            settings.rules = GameRules::parse(rule)?;
+        }
+        return Ok(Some(options));
+    }
+    if let Some((width, height)) = size {
+        (options.width, options.height) = (width, height);
+    }
+    options.generations = generations.unwrap_or(options.generations);
+    options.pattern = pattern.ok_or("Missing pattern file")?;
+    if options.hashlife && (options.cpu || options.stats.is_some()) {
+        return Err("--hashlife cannot be combined with --cpu or --stats".to_string());
+    }
+    if options.packed && (options.cpu || options.hashlife || options.stats.is_some()) {
+        return Err("--packed cannot be combined with --cpu, --hashlife or --stats".to_string());
+    }
+    if let Some(name) = kernel_name {
+        if options.cpu || options.hashlife || options.packed {
+            return Err("--kernel only applies to the default GPU engine".to_string());
+        }
+        options.kernel = match name.as_str() {
+            "global" => Kernel::Global,
+            "shared" => Kernel::Shared { workgroup_size },
+            "blocked" => Kernel::Blocked { workgroup_size, generations: block_generations },
+            "active" => Kernel::Active { workgroup_size },
+            other => return Err(format!("Unknown kernel \"{}\" (expected global, shared, blocked or active)", other)),
+        };
+    }
+    if options.chunk_rows.is_some() && (options.cpu || options.hashlife || options.packed) {
+        return Err("--chunk-rows cannot be combined with --cpu, --hashlife or --packed".to_string());
+    }
+    if options.benchmark && (options.cpu || options.hashlife || options.packed || options.stats.is_some() || options.output.is_some()) {
+        return Err("--benchmark cannot be combined with --cpu, --hashlife, --packed, --stats or --output".to_string());
+    }
+    if options.classify && (options.cpu || options.hashlife || options.packed || options.stats.is_some() || options.benchmark) {
+        return Err("--classify cannot be combined with --cpu, --hashlife, --packed, --stats or --benchmark".to_string());
+    }
+    if !options.hashlife && options.generations > u32::MAX as u64 {
+        return Err("More than 2^32 generations need --hashlife".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Row-major grid with the pattern centered
+fn place_centered(pattern: &Pattern, width: u32, height: u32) -> Result<Vec<f32>, String> {
+    if pattern.width > width || pattern.height > height {
+        return Err(format!(
+            "Pattern is {}x{} but the grid is only {}x{}",
+            pattern.width, pattern.height, width, height
+        ));
+    }
+    let (offset_x, offset_y) = ((width - pattern.width) / 2, (height - pattern.height) / 2);
+    let mut cells = vec![0.0; width as usize * height as usize];
+    for &(x, y) in &pattern.cells {
+        cells[(offset_y + y as u32) as usize * width as usize + (offset_x + x as u32) as usize] = 1.0;
+    }
+    Ok(cells)
+}
+
+fn run_cpu(options: &Options, rules: GameRules, cells: &[f32], mut stats: Option<StatsLog>) -> Result<Vec<f32>, String> {
+    let mut simulation = CpuSimulation::new(options.width, options.height)?;
+    simulation.rules = rules;
+    simulation.boundary = options.boundary;
+    simulation.write_cells(cells)?;
+
+    for generation in 0..=options.generations as usize {
+        if generation > 0 {
+            simulation.step(1);
+        }
+        if let Some(log) = stats.as_mut() {
+            if generation % options.stats_every as usize == 0 {
+                log.write(&GenerationStats::from_cells(
+                    generation, options.width, options.height,
+                    simulation.previous_cells(), simulation.cells(),
+                ));
+            }
+        }
+    }
+    if let Some(log) = stats {
+        log.finish()?;
+    }
+    Ok(simulation.cells().to_vec())
+}
+
+fn run_gpu(options: &Options, rules: GameRules, cells: &[f32]) -> Result<Vec<f32>, String> {
+    let (device, queue) = pollster::block_on(request_headless_device())
+        .map_err(|e| format!("{} (use --cpu to run without a GPU)", e))?;
+    let mut simulation = Simulation::with_chunk_rows(device, queue, options.width, options.height, options.chunk_rows)?;
+    simulation.set_kernel(options.kernel)?;
+    simulation.set_rules(rules);
+    simulation.boundary = options.boundary;
+    simulation.write_cells(cells)?;
+    simulation.write_params();
+
+    let mut stats = match &options.stats {
+        Some(path) => Some((
+            StatsRecorder::start(StatsSettings {
+                format: StatsFormat::from_path(path),
+                every_n: options.stats_every,
+                path: path.clone(),
+                output: String::new(),
+            })?,
+            StatsReducer::new(&simulation.device, &simulation),
+        )),
+        None => None,
+    };
+
+    let mut remaining = options.generations;
+    let mut sample_initial = true;
+    while sample_initial || remaining > 0 {
+        let batch = remaining.min(GPU_BATCH as u64);
+        let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("CLI Step Encoder"),
+        });
+        if stats.is_none() {
+            // Without sampling, blocked kernels only need to produce the last generation
+            simulation.encode_steps(&mut encoder, batch as u32);
+        } else {
+            for i in 0..=batch {
+                // The first iteration only samples the generation reached by the previous batch
+                if i > 0 {
+                    simulation.encode_step(&mut encoder);
+                } else if !sample_initial {
+                    continue;
+                }
+                if let Some((stats, reducer)) = stats.as_mut() {
+                    if stats.wants_generation(simulation.generation) {
+                        if !reducer.has_free_slot() {
+                            simulation.queue.submit(Some(encoder.finish()));
+                            reducer.after_submit();
+                            for sample in &reducer.wait_for_slot(&simulation.device) {
+                                stats.record(sample);
+                            }
+                            encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                label: Some("CLI Step Encoder"),
+                            });
+                        }
+                        reducer.encode_sample(&mut encoder, simulation.generation);
+                    }
+                }
+            }
+        }
+        simulation.queue.submit(Some(encoder.finish()));
+        if let Some((stats, reducer)) = stats.as_mut() {
+            reducer.after_submit();
+            for sample in &reducer.poll(&simulation.device) {
+                stats.record(sample);
+            }
+        }
+        remaining -= batch;
+        sample_initial = false;
+    }
+
+    if let Some((stats, mut reducer)) = stats {
+        stats.finish(Some(&mut reducer), &simulation.device)?;
+    }
+    simulation.read_cells()
+}
+
+fn run_packed(options: &Options, rules: GameRules, cells: &[f32]) -> Result<Vec<f32>, String> {
+    let mut simulation = pollster::block_on(PackedSimulation::new_headless(options.width, options.height))?;
+    simulation.set_rules(rules);
+    simulation.boundary = options.boundary;
+    simulation.write_cells(cells)?;
+
+    let mut remaining = options.generations;
+    while remaining > 0 {
+        let batch = remaining.min(GPU_BATCH as u64);
+        simulation.step(batch as u32);
+        remaining -= batch;
+    }
+    simulation.read_cells()
+}
+
+/// Time `generations` generations of `step` (called with batches of up to `GPU_BATCH`), in seconds
+fn time_generations(device: &wgpu::Device, generations: u64, mut step: impl FnMut(u32)) -> f64 {
+    // One warm-up generation keeps pipeline and buffer first-use costs out of the timing
+    step(1);
+    device.poll(wgpu::Maintain::Wait);
+    let start = Instant::now();
+    let mut remaining = generations;
+    while remaining > 0 {
+        let batch = remaining.min(GPU_BATCH as u64);
+        step(batch as u32);
+        remaining -= batch;
+    }
+    device.poll(wgpu::Maintain::Wait);
+    start.elapsed().as_secs_f64()
+}
+
+/// Run the same grid on every kernel preset and the packed engine, printing their throughput
+fn run_benchmark(options: &Options, rules: GameRules, cells: &[f32]) -> Result<(), String> {
+    let (device, queue) = pollster::block_on(request_headless_device())?;
+    let cell_count = options.width as f64 * options.height as f64;
+    let report = |name: &str, seconds: f64| {
+        let rate = options.generations as f64 / seconds.max(f64::EPSILON);
+        println!("{:<16} {:>14.1} {:>18.0}", name, rate, rate * cell_count);
+    };
+    println!(
+        "{}x{} grid, {} generations, {}",
+        options.width, options.height, options.generations, rules.to_rulestring()
+    );
+    println!("{:<16} {:>14} {:>18}", "kernel", "generations/s", "cells/s");
+
+    for kernel in Kernel::PRESETS {
+        let mut simulation = Simulation::new(device.clone(), queue.clone(), options.width, options.height)?;
+        if let Err(e) = simulation.set_kernel(kernel) {
+            println!("{:<16} skipped: {}", kernel.name(), e);
+            continue;
+        }
+        simulation.set_rules(rules);
+        simulation.boundary = options.boundary;
+        simulation.write_cells(cells)?;
+        report(&kernel.name(), time_generations(&device, options.generations, |count| simulation.step(count)));
+    }
+
+    let mut packed = PackedSimulation::new(device.clone(), queue, options.width, options.height)?;
+    packed.set_rules(rules);
+    packed.boundary = options.boundary;
+    packed.write_cells(cells)?;
+    report("packed", time_generations(&device, options.generations, |count| packed.step(count)));
+    Ok(())
+}
+
+/// Classify the pattern on the configured grid (size, boundary, rules) as a still
+/// life, oscillator or spaceship with its period, and print (or write) the result
+fn run_classify(options: &Options, rules: GameRules, cells: &[f32]) -> Result<(), String> {
+    let (device, queue) = pollster::block_on(request_headless_device())?;
+    let mut simulation = Simulation::with_chunk_rows(device, queue, options.width, options.height, options.chunk_rows)?;
+    simulation.set_rules(rules);
+    simulation.boundary = options.boundary;
+    simulation.write_cells(cells)?;
+    let analysis = analyze(&simulation, None, options.generations as usize)?;
+    let result = analysis.to_string();
+    match &options.output {
+        Some(path) => std::fs::write(path, format!("{}\n", result)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+        None => println!("{}", result),
+    }
+    Ok(())
+}
+
+/// Live cells after running the pattern on an infinite plane
+fn run_hashlife(options: &Options, rules: GameRules, pattern: &Pattern) -> Result<Pattern, String> {
+    let mut cells = vec![0.0; pattern.width as usize * pattern.height as usize];
+    for &(x, y) in &pattern.cells {
+        cells[y as usize * pattern.width as usize + x as usize] = 1.0;
+    }
+    let mut life = HashLife::from_grid(rules, &cells, pattern.width, pattern.height)?;
+    life.step(options.generations);
+
+    let live = life.live_cells();
+    let min_x = live.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = live.iter().map(|c| c.1).min().unwrap_or(0);
+    let mut cells = Vec::with_capacity(live.len());
+    for (x, y) in live {
+        let relative = (i32::try_from(x - min_x), i32::try_from(y - min_y));
+        match relative {
+            (Ok(x), Ok(y)) => cells.push((x, y)),
+            _ => return Err("The final pattern is too large to write as RLE".to_string()),
+        }
+    }
+    Ok(Pattern::from_cells(cells))
+}
+
+/// Census of random soups, printed (or written) as a CSV table
+fn run_census(options: &Options) -> Result<(), String> {
+    let (device, queue) = pollster::block_on(request_headless_device())?;
+    let mut search = SoupSearch::new(device, queue, options.census_settings.clone())?;
+    let start = Instant::now();
+    let mut last_report = Instant::now();
+    while !search.is_finished() {
+        search.run_batch()?;
+        if last_report.elapsed() >= Duration::from_secs(60) {
+            // Keep partial results of long runs
+            if let Some(path) = &options.output {
+                search.census.write_csv(path)?;
+            }
+            let (done, total) = search.progress();
+            log::info!("{}/{} soups, {:.1} soups/s", done, total, done as f64 / start.elapsed().as_secs_f64());
+            last_report = Instant::now();
+        }
+    }
+    let census = &search.census;
+    match &options.output {
+        Some(path) => {
+            census.write_csv(path)?;
+            println!("{}", census.summary());
+        }
+        None => print!("{}", census.to_csv()),
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    if options.census.is_some() {
+        return run_census(&options);
+    }
+    let text = std::fs::read_to_string(&options.pattern)
+        .map_err(|e| format!("Failed to read {}: {}", options.pattern.display(), e))?;
+    let pattern = parse_pattern(&options.pattern, &text)?;
+    let rules = match options.rule.as_deref().or(pattern.rule.as_deref()) {
+        Some(rule) => GameRules::parse(rule)?,
+        None => GameRules::default(),
+    };
+    if options.benchmark {
+        return run_benchmark(&options, rules, &place_centered(&pattern, options.width, options.height)?);
+    }
+    if options.classify {
+        return run_classify(&options, rules, &place_centered(&pattern, options.width, options.height)?);
+    }
+    let mut final_pattern = if options.hashlife {
+        run_hashlife(&options, rules, &pattern)?
+    } else {
+        Pattern::from_grid(&run_grid(&options, rules, &pattern)?, options.width)
+    };
+    let population = final_pattern.cells.len();
+    final_pattern.name = pattern.name.clone();
+    final_pattern.rule = Some(rules.to_rulestring());
+    final_pattern.comments = vec![format!("generation {}, population {}", options.generations, population)];
+    let rle = to_rle(&final_pattern);
+
+    match &options.output {
+        Some(path) => {
+            std::fs::write(path, rle).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            println!("generation={} population={}", options.generations, population);
+        }
+        None => print!("{}", rle),
+    }
+    Ok(())
+}
+
+/// Final grid of the CPU, GPU or packed engine
+fn run_grid(options: &Options, rules: GameRules, pattern: &Pattern) -> Result<Vec<f32>, String> {
+    let cells = place_centered(pattern, options.width, options.height)?;
+    if options.cpu {
+        let stats = match &options.stats {
+            Some(path) => Some(StatsLog::create(path, StatsFormat::from_path(path))?),
+            None => None,
+        };
+        run_cpu(options, rules, &cells, stats)
+    } else if options.packed {
+        run_packed(options, rules, &cells)
+    } else {
+        run_gpu(options, rules, &cells)
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => match run(options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Ok(None) => {
+            print!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::readback::ReadbackRing;
+
+/// A rectangular region of the grid, in cell coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureRegion {
+    /// The whole grid
+    pub fn full(grid_width: u32, grid_height: u32) -> Self {
+        Self { x: 0, y: 0, width: grid_width, height: grid_height }
+    }
+
+    /// Clamp the region so it lies inside a grid of the given size (never empty)
+    pub fn clamped(&self, grid_width: u32, grid_height: u32) -> Self {
+        let x = self.x.min(grid_width.saturating_sub(1));
+        let y = self.y.min(grid_height.saturating_sub(1));
+        Self {
+            x,
+            y,
+            width: self.width.clamp(1, grid_width - x),
+            height: self.height.clamp(1, grid_height - y),
+        }
+    }
+
+    pub fn cell_count(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
+
+/// One generation copied back from the GPU
+pub struct CapturedFrame {
+    pub generation: usize,
+    pub width: u32,
+    pub height: u32,
+    /// Raw cell values of the captured region, row-major
+    pub cells: Vec<f32>,
+}
+
+impl CapturedFrame {
+    fn decode(generation: usize, width: u32, height: u32, data: &[u8]) -> Self {
+        Self { generation, width, height, cells: bytemuck::cast_slice(data).to_vec() }
+    }
+}
+
+/// A ring of staging buffers that copies grid regions off the GPU without blocking.
+///
+/// Copies are recorded into the caller's command encoder; after the encoder is
+/// submitted, `after_submit` requests the mappings and `poll` collects finished
+/// frames. The simulation only waits when every slot is still in flight.
+pub struct CaptureRing {
+    ring: ReadbackRing<usize>,
+    region: CaptureRegion,
+    grid_width: u32,
+}
+
+impl CaptureRing {
+    pub fn new(device: &wgpu::Device, region: CaptureRegion, grid_width: u32, slot_count: usize) -> Self {
+        let size = region.cell_count() * std::mem::size_of::<f32>() as u64;
+        Self { ring: ReadbackRing::new(device, "Capture Staging Buffer", size, slot_count), region, grid_width }
+    }
+
+    pub fn region(&self) -> CaptureRegion {
+        self.region
+    }
+
+    pub fn has_free_slot(&self) -> bool {
+        self.ring.has_free_slot()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.ring.in_use()
+    }
+
+    /// Record a copy of the capture region of `source` into a free slot.
+    /// Returns false (and records nothing) if every slot is busy.
+    pub fn encode_capture(&mut self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::Buffer, generation: usize) -> bool {
+        let Some(index) = self.ring.free_slot() else {
+            return false;
+        };
+        let staging = self.ring.staging(index);
+
+        let cell_size = std::mem::size_of::<f32>() as u64;
+        let region = self.region;
+        if region.x == 0 && region.width == self.grid_width {
+            // Full-width regions are contiguous in the grid buffer
+            let offset = region.y as u64 * self.grid_width as u64 * cell_size;
+            encoder.copy_buffer_to_buffer(source, offset, staging, 0, region.cell_count() * cell_size);
+        } else {
+            let row_bytes = region.width as u64 * cell_size;
+            for row in 0..region.height as u64 {
+                let src_offset = ((region.y as u64 + row) * self.grid_width as u64 + region.x as u64) * cell_size;
+                encoder.copy_buffer_to_buffer(source, src_offset, staging, row * row_bytes, row_bytes);
+            }
+        }
+        self.ring.fill(index, generation);
+        true
+    }
+
+    /// Request mapping for every slot whose copy has just been submitted
+    pub fn after_submit(&mut self) {
+        self.ring.after_submit();
+    }
+
+    /// Collect every frame whose mapping has completed, in capture order (non-blocking)
+    pub fn poll(&mut self, device: &wgpu::Device) -> Vec<CapturedFrame> {
+        let (width, height) = (self.region.width, self.region.height);
+        self.ring.poll(device, |generation, data| CapturedFrame::decode(generation, width, height, data))
+    }
+
+    /// Block until the oldest in-flight frame is available, then collect everything that is ready.
+    /// Used when the ring is full so no generation is ever dropped.
+    pub fn wait_oldest(&mut self, device: &wgpu::Device) -> Vec<CapturedFrame> {
+        let (width, height) = (self.region.width, self.region.height);
+        self.ring.wait_oldest(device, |generation, data| CapturedFrame::decode(generation, width, height, data))
+    }
+
+    /// Block until every in-flight frame has been collected
+    pub fn drain(&mut self, device: &wgpu::Device) -> Vec<CapturedFrame> {
+        let (width, height) = (self.region.width, self.region.height);
+        self.ring.drain(device, |generation, data| CapturedFrame::decode(generation, width, height, data))
+    }
+}
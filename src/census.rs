@@ -0,0 +1,688 @@
+//! Soup search and object census, in the spirit of apgsearch.
+//!
+//! Random 16x16 soups are generated from a seed and run side by side, one per tile
+//! of a large GPU grid (`census.wgsl` keeps a dead wall between tiles and hashes
+//! each tile every generation). Once a tile repeats a state, its ash is read back,
+//! separated into objects and each object is named by its apgcode (e.g. `xs4_33`
+//! for the block, `xp2_7` for the blinker), then the tile gets the next soup.
+//! Spaceships about to reach a wall are counted as escaped and removed.
+
+use crate::cpu::CpuSimulation;
+use crate::period::{HashRecord, StabilityHistory};
+use crate::rules::{Boundary, GameRules};
+use crate::simulation::{read_buffer, Simulation};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Side of a soup in cells
+pub const SOUP_SIZE: u32 = 16;
+
+/// Generations stepped per submission before the tile records are read back
+const TILE_BATCH: usize = 32;
+const TILE_WORKGROUP_SIZE: u32 = 16;
+const RECORD_WORDS: usize = 8;
+
+/// Cells from a wall within which objects are checked for escaping spaceships:
+/// in Life nothing moves faster than c/2, so nothing reaches the wall unseen
+const ESCAPE_MARGIN: u32 = TILE_BATCH as u32 / 2 + 2;
+
+/// Smallest tile that leaves the soup clear of the escape margins
+pub const MIN_TILE_SIZE: u32 = 64;
+
+/// Digits of a column of five cells in the extended Wechsler format
+const WECHSLER_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TileParams {
+    width: u32,
+    height: u32,
+    tile_size: u32,
+    first_record: u32,
+}
+
+/// What a census runs
+#[derive(Debug, Clone)]
+pub struct CensusSettings {
+    /// Soup `i` of a seed is always the same, see `soup`
+    pub seed: u64,
+    pub soups: u64,
+    pub rules: GameRules,
+    /// Size of the GPU grid; both sides must be multiples of `tile_size`
+    pub width: u32,
+    pub height: u32,
+    /// Side of the tile each soup runs in, a multiple of 16 and at least `MIN_TILE_SIZE`
+    pub tile_size: u32,
+    /// Soups still changing after this many generations are counted as unstable
+    pub max_generations: usize,
+    /// Longest period of a whole tile that counts as stable
+    pub max_period: usize,
+}
+
+impl Default for CensusSettings {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            soups: 1000,
+            rules: GameRules::default(),
+            width: 512,
+            height: 512,
+            tile_size: 128,
+            max_generations: 10_000,
+            max_period: 60,
+        }
+    }
+}
+
+/// Object counts over all soups of a search
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    /// Soups finished, stable or not
+    pub soups: u64,
+    /// Soups that did not stabilize within `max_generations`; their objects are not counted
+    pub unstable: u64,
+    pub counts: HashMap<String, u64>,
+    /// Lowest soup index each object was seen in, to reproduce it with `soup`
+    pub first_soup: HashMap<String, u64>,
+}
+
+impl Census {
+    /// Count the objects a stable soup left
+    pub fn add_soup(&mut self, soup: u64, objects: &[String]) {
+        self.soups += 1;
+        for object in objects {
+            *self.counts.entry(object.clone()).or_default() += 1;
+            let first = self.first_soup.entry(object.clone()).or_insert(soup);
+            *first = (*first).min(soup);
+        }
+    }
+
+    pub fn add_unstable_soup(&mut self) {
+        self.soups += 1;
+        self.unstable += 1;
+    }
+
+    pub fn objects(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Objects with their counts, most common first (ties by apgcode)
+    pub fn sorted(&self) -> Vec<(&str, u64)> {
+        let mut sorted: Vec<_> = self.counts.iter().map(|(code, &count)| (code.as_str(), count)).collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        sorted
+    }
+
+    /// Census table as CSV: apgcode, count and the first soup it was seen in
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("apgcode,count,first_soup\n");
+        for (code, count) in self.sorted() {
+            csv.push_str(&format!("{},{},{}\n", code, count, self.first_soup[code]));
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_csv()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} soups ({} unstable), {} objects of {} kinds",
+            self.soups, self.unstable, self.objects(), self.counts.len()
+        )
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Live cells of soup `index` of `seed`: a 16x16 square, each cell alive with chance 1/2
+pub fn soup(seed: u64, index: u64) -> Vec<(i32, i32)> {
+    let mut state = seed ^ index.wrapping_mul(0xD1B54A32D192ED03);
+    let mut cells = Vec::new();
+    for word in 0..(SOUP_SIZE * SOUP_SIZE / 64) {
+        let bits = splitmix64(&mut state);
+        for bit in 0..64 {
+            if bits >> bit & 1 == 1 {
+                let i = (word * 64 + bit) as i32;
+                cells.push((i % SOUP_SIZE as i32, i / SOUP_SIZE as i32));
+            }
+        }
+    }
+    cells
+}
+
+/// Groups of live cells touching each other (including diagonally), each sorted,
+/// in order of their first cell
+pub fn components(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    let mut unvisited: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let mut sorted = cells.to_vec();
+    sorted.sort_unstable_by_key(|&(x, y)| (y, x));
+    let mut components = Vec::new();
+    for start in sorted {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut next = 0;
+        while next < component.len() {
+            let (x, y) = component[next];
+            next += 1;
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                if unvisited.remove(&(x + dx, y + dy)) {
+                    component.push((x + dx, y + dy));
+                }
+            }
+        }
+        component.sort_unstable_by_key(|&(x, y)| (y, x));
+        components.push(component);
+    }
+    components
+}
+
+/// Cells moved so the bounding box starts at (0, 0), sorted, with the corner they came from
+fn normalize(cells: &[(i32, i32)]) -> ((i32, i32), Vec<(i32, i32)>) {
+    let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    let mut shape: Vec<_> = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+    shape.sort_unstable_by_key(|&(x, y)| (y, x));
+    ((min_x, min_y), shape)
+}
+
+/// Extended Wechsler format of cells starting at (0, 0): strips of five rows separated
+/// by `z`, one digit per column, runs of empty columns shortened to `0`, `w`, `x` or `y?`
+pub fn wechsler(cells: &[(i32, i32)]) -> String {
+    let width = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0) as usize;
+    let height = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0) as usize;
+    let mut columns = vec![0usize; width * height.div_ceil(5)];
+    for &(x, y) in cells {
+        columns[y as usize / 5 * width + x as usize] |= 1 << (y % 5);
+    }
+    let mut code = String::new();
+    for (strip, strip_columns) in columns.chunks(width.max(1)).enumerate() {
+        if strip > 0 {
+            code.push('z');
+        }
+        let used = strip_columns.iter().rposition(|&column| column != 0).map_or(0, |last| last + 1);
+        let mut i = 0;
+        while i < used {
+            if strip_columns[i] != 0 {
+                code.push(WECHSLER_DIGITS[strip_columns[i]] as char);
+                i += 1;
+                continue;
+            }
+            let mut run = strip_columns[i..used].iter().take_while(|&&column| column == 0).count();
+            i += run;
+            while run > 0 {
+                let taken = run.min(39);
+                match taken {
+                    1 => code.push('0'),
+                    2 => code.push('w'),
+                    3 => code.push('x'),
+                    _ => {
+                        code.push('y');
+                        code.push(WECHSLER_DIGITS[taken - 4] as char);
+                    }
+                }
+                run -= taken;
+            }
+        }
+    }
+    code
+}
+
+/// The eight rotations and reflections of a shape, each normalized
+fn orientations(cells: &[(i32, i32)]) -> [Vec<(i32, i32)>; 8] {
+    // (x, y) becomes (a x + b y, c x + d y)
+    let transforms = [
+        [1, 0, 0, 1], [-1, 0, 0, 1], [1, 0, 0, -1], [-1, 0, 0, -1],
+        [0, 1, 1, 0], [0, -1, 1, 0], [0, 1, -1, 0], [0, -1, -1, 0],
+    ];
+    transforms.map(|[a, b, c, d]| normalize(&cells.iter().map(|&(x, y)| (a * x + b * y, c * x + d * y)).collect::<Vec<_>>()).1)
+}
+
+/// Live cells of a CPU grid, offset by `origin`
+fn live_cells(simulation: &CpuSimulation, origin: (i32, i32)) -> Vec<(i32, i32)> {
+    let width = simulation.width as usize;
+    simulation.cells().iter().enumerate()
+        .filter(|(_, &cell)| cell > 0.5)
+        .map(|(i, _)| ((i % width) as i32 + origin.0, (i / width) as i32 + origin.1))
+        .collect()
+}
+
+/// CPU grid of `width`x`height` with a dead boundary holding `cells` (relative to `origin`)
+fn cpu_grid(rules: GameRules, width: u32, height: u32, cells: &[(i32, i32)], origin: (i32, i32)) -> Result<CpuSimulation, String> {
+    let mut simulation = CpuSimulation::new(width, height)?;
+    simulation.rules = rules;
+    simulation.boundary = Boundary::Dead;
+    simulation.threads = 1;
+    let mut grid = vec![0.0; width as usize * height as usize];
+    for &(x, y) in cells {
+        grid[(y - origin.1) as usize * width as usize + (x - origin.0) as usize] = 1.0;
+    }
+    simulation.write_cells(&grid)?;
+    Ok(simulation)
+}
+
+/// apgcode of one object: `xs<population>_` for still lifes, `xp<period>_` for
+/// oscillators and `xq<period>_` for spaceships, followed by the extended Wechsler
+/// format of the phase and orientation with the shortest, then lexicographically
+/// first code. Objects that do not repeat within `max_period` generations on their
+/// own give `zz_UNKNOWN`.
+pub fn apgcode(cells: &[(i32, i32)], rules: GameRules, max_period: usize) -> Result<String, String> {
+    Ok(object_motion(cells, rules, max_period)?.map_or_else(|| "zz_UNKNOWN".to_string(), |motion| motion.code))
+}
+
+/// How an object repeats on its own: its apgcode, period and the displacement per period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Motion {
+    pub code: String,
+    pub period: usize,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl Motion {
+    pub fn is_moving(&self) -> bool {
+        (self.dx, self.dy) != (0, 0)
+    }
+}
+
+/// Run one object on its own until it repeats (see `apgcode`); None if it does not
+/// within `max_period` generations or dies
+pub fn object_motion(cells: &[(i32, i32)], rules: GameRules, max_period: usize) -> Result<Option<Motion>, String> {
+    let (corner, shape) = normalize(cells);
+    if shape.is_empty() {
+        return Ok(Some(Motion { code: "xs0_0".to_string(), period: 1, dx: 0, dy: 0 }));
+    }
+    // Room to travel in any direction at up to c
+    let margin = max_period as i32 + 2;
+    let width = shape.iter().map(|c| c.0).max().unwrap_or(0) + 1 + 2 * margin;
+    let height = shape.iter().map(|c| c.1).max().unwrap_or(0) + 1 + 2 * margin;
+    let origin = (corner.0 - margin, corner.1 - margin);
+    let mut simulation = cpu_grid(rules, width as u32, height as u32, cells, origin)?;
+
+    let mut phases = vec![shape];
+    for period in 1..=max_period {
+        simulation.step(1);
+        let (moved_to, shape) = normalize(&live_cells(&simulation, origin));
+        if shape.is_empty() {
+            break;
+        }
+        if shape == phases[0] {
+            let prefix = match moved_to == corner {
+                true if period == 1 => format!("xs{}", cells.len()),
+                true => format!("xp{}", period),
+                false => format!("xq{}", period),
+            };
+            let code = phases.iter()
+                .flat_map(|phase| orientations(phase))
+                .map(|orientation| wechsler(&orientation))
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                .unwrap_or_default();
+            return Ok(Some(Motion {
+                code: format!("{}_{}", prefix, code),
+                period,
+                dx: moved_to.0 - corner.0,
+                dy: moved_to.1 - corner.1,
+            }));
+        }
+        phases.push(shape);
+    }
+    Ok(None)
+}
+
+/// Objects of a periodic pattern on a dead-bounded `width`x`height` grid: cells
+/// that touch in any phase of the `period` belong to the same object
+pub fn separate_objects(
+    cells: &[(i32, i32)], width: u32, height: u32, rules: GameRules, period: usize, max_period: usize,
+) -> Result<Vec<String>, String> {
+    let mut simulation = cpu_grid(rules, width, height, cells, (0, 0))?;
+    let mut all_phases: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    for _ in 1..period {
+        simulation.step(1);
+        all_phases.extend(live_cells(&simulation, (0, 0)));
+    }
+    let current: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let mut objects = Vec::new();
+    for component in components(&all_phases.into_iter().collect::<Vec<_>>()) {
+        let object: Vec<_> = component.into_iter().filter(|cell| current.contains(cell)).collect();
+        if !object.is_empty() {
+            objects.push(apgcode(&object, rules, max_period)?);
+        }
+    }
+    Ok(objects)
+}
+
+/// A tile of the search grid and the soup running in it
+struct Tile {
+    /// None while the tile is idle (no soups left)
+    soup: Option<u64>,
+    /// Generation the soup was placed at
+    started: usize,
+    history: StabilityHistory,
+    /// Spaceships that reached the escape margin and were removed
+    escaped: Vec<String>,
+    /// Something other than a spaceship reached the margin; the wall stays in the
+    /// way from then on
+    overflowed: bool,
+    /// Period of the tile, once it repeated a state during the last batch
+    period: Option<usize>,
+}
+
+/// Runs the soups of a `CensusSettings` on the GPU, a batch of generations at a time.
+pub struct SoupSearch {
+    pub settings: CensusSettings,
+    pub census: Census,
+    simulation: Simulation,
+    pipeline: wgpu::ComputePipeline,
+    /// Bind groups of the tile pass on `grid_buffers[parity]`
+    bind_groups: [wgpu::BindGroup; 2],
+    records: wgpu::Buffer,
+    /// Bytes between the `TileParams` of consecutive generations (dynamic offset alignment)
+    param_stride: u32,
+    tiles: Vec<Tile>,
+    next_soup: u64,
+}
+
+impl SoupSearch {
+    pub fn new(device: std::sync::Arc<wgpu::Device>, queue: std::sync::Arc<wgpu::Queue>, settings: CensusSettings) -> Result<Self, String> {
+        let tile_size = settings.tile_size;
+        if tile_size < MIN_TILE_SIZE || !tile_size.is_multiple_of(TILE_WORKGROUP_SIZE) {
+            return Err(format!("The tile size must be a multiple of {} and at least {}", TILE_WORKGROUP_SIZE, MIN_TILE_SIZE));
+        }
+        if settings.width < tile_size || settings.height < tile_size
+            || !settings.width.is_multiple_of(tile_size) || !settings.height.is_multiple_of(tile_size)
+        {
+            return Err(format!("The grid size must be a multiple of the {}x{} tiles", tile_size, tile_size));
+        }
+        let mut simulation = Simulation::new(device, queue, settings.width, settings.height)?;
+        if simulation.chunks().is_some() {
+            return Err("The grid is too large for one buffer; use a smaller grid".to_string());
+        }
+        simulation.set_rules(settings.rules);
+        simulation.boundary = Boundary::Dead;
+
+        let device = &simulation.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Census Tile Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("census.wgsl").into()),
+        });
+        let buffer_entry = |binding, ty, has_dynamic_offset| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset, min_binding_size: None },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Census Tile Bind Group Layout"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Uniform, true),
+                buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: false }, false),
+                buffer_entry(2, wgpu::BufferBindingType::Storage { read_only: false }, false),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Census Tile Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Census Tile Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "main",
+        });
+
+        let tile_count = (settings.width / tile_size * (settings.height / tile_size)) as usize;
+        let param_size = std::mem::size_of::<TileParams>() as u32;
+        let param_stride = param_size.max(device.limits().min_uniform_buffer_offset_alignment);
+        let mut params = vec![0u8; param_stride as usize * TILE_BATCH];
+        for generation in 0..TILE_BATCH {
+            let block = TileParams {
+                width: settings.width,
+                height: settings.height,
+                tile_size,
+                first_record: (generation * tile_count) as u32,
+            };
+            let start = generation * param_stride as usize;
+            params[start..start + param_size as usize].copy_from_slice(bytemuck::bytes_of(&block));
+        }
+        let param_buffer = wgpu::util::DeviceExt::create_buffer_init(device.as_ref(), &wgpu::util::BufferInitDescriptor {
+            label: Some("Census Tile Parameters"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let records = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Census Tile Records"),
+            size: (TILE_BATCH * tile_count * RECORD_WORDS * std::mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_groups = [0, 1].map(|parity| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("Census Tile Bind Group ({})", parity)),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &param_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(param_size as u64),
+                    }),
+                },
+                wgpu::BindGroupEntry { binding: 1, resource: simulation.grid_buffers[parity].as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: records.as_entire_binding() },
+            ],
+        }));
+
+        let tiles = (0..tile_count)
+            .map(|_| Tile {
+                soup: None,
+                started: 0,
+                history: StabilityHistory::new(settings.max_period),
+                escaped: Vec::new(),
+                overflowed: false,
+                period: None,
+            })
+            .collect();
+        let mut search = Self {
+            settings,
+            census: Census::default(),
+            simulation,
+            pipeline,
+            bind_groups,
+            records,
+            param_stride,
+            tiles,
+            next_soup: 0,
+        };
+        let mut cells = vec![0.0; search.simulation.cell_count()];
+        for tile in 0..search.tiles.len() {
+            search.start_next_soup(tile, &mut cells);
+        }
+        search.simulation.load_generation(0, &cells)?;
+        Ok(search)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tiles.iter().all(|tile| tile.soup.is_none())
+    }
+
+    /// Soups finished so far and in total
+    pub fn progress(&self) -> (u64, u64) {
+        (self.census.soups, self.settings.soups)
+    }
+
+    /// Generations stepped by the whole grid so far
+    pub fn generation(&self) -> usize {
+        self.simulation.generation
+    }
+
+    /// Place the next soup (if any is left) in the middle of `tile`, which must be empty
+    fn start_next_soup(&mut self, tile: usize, cells: &mut [f32]) {
+        let state = &mut self.tiles[tile];
+        state.soup = (self.next_soup < self.settings.soups).then_some(self.next_soup);
+        state.started = self.simulation.generation;
+        state.history.clear();
+        state.escaped.clear();
+        state.overflowed = false;
+        state.period = None;
+        let Some(index) = state.soup else {
+            return;
+        };
+        self.next_soup += 1;
+        let (tile_x, tile_y) = self.tile_origin(tile);
+        let offset = (self.settings.tile_size - SOUP_SIZE) / 2;
+        for (x, y) in soup(self.settings.seed, index) {
+            let (x, y) = (tile_x + offset + x as u32, tile_y + offset + y as u32);
+            cells[(y * self.settings.width + x) as usize] = 1.0;
+        }
+    }
+
+    /// Grid coordinates of the tile's top left corner (on its wall)
+    fn tile_origin(&self, tile: usize) -> (u32, u32) {
+        let tiles_per_row = (self.settings.width / self.settings.tile_size) as usize;
+        ((tile % tiles_per_row) as u32 * self.settings.tile_size, (tile / tiles_per_row) as u32 * self.settings.tile_size)
+    }
+
+    /// Live cells of a tile in its own coordinates, (0, 0) being the first cell inside the walls
+    fn tile_cells(&self, tile: usize, cells: &[f32]) -> Vec<(i32, i32)> {
+        let (tile_x, tile_y) = self.tile_origin(tile);
+        let mut live = Vec::new();
+        for y in 1..self.settings.tile_size {
+            for x in 1..self.settings.tile_size {
+                if cells[((tile_y + y) * self.settings.width + tile_x + x) as usize] > 0.5 {
+                    live.push((x as i32 - 1, y as i32 - 1));
+                }
+            }
+        }
+        live
+    }
+
+    /// Kill a tile's cells, given in its own coordinates
+    fn erase(&self, tile: usize, erased: &[(i32, i32)], cells: &mut [f32]) {
+        let (tile_x, tile_y) = self.tile_origin(tile);
+        for &(x, y) in erased {
+            cells[((tile_y + 1 + y as u32) * self.settings.width + tile_x + 1 + x as u32) as usize] = 0.0;
+        }
+    }
+
+    /// Step every tile `TILE_BATCH` generations, then census the tiles that became
+    /// periodic, remove escaping spaceships and hand out new soups
+    pub fn run_batch(&mut self) -> Result<(), String> {
+        if self.is_finished() {
+            return Ok(());
+        }
+        let device = self.simulation.device.clone();
+        let queue = self.simulation.queue.clone();
+        let first_generation = self.simulation.generation;
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Census Encoder"),
+        });
+        encoder.clear_buffer(&self.records, 0, None);
+        for record in 0..TILE_BATCH {
+            self.simulation.encode_step(&mut encoder);
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Census Tile Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_groups[self.simulation.generation % 2], &[record as u32 * self.param_stride]);
+            pass.dispatch_workgroups(self.settings.width / TILE_WORKGROUP_SIZE, self.settings.height / TILE_WORKGROUP_SIZE, 1);
+        }
+        queue.submit(Some(encoder.finish()));
+        let records = read_buffer(&device, &queue, &self.records, 0, self.records.size())?;
+        let words: &[u32] = bytemuck::cast_slice(&records);
+
+        let tile_count = self.tiles.len();
+        let last_record = |tile: usize| HashRecord::from_words(&words[((TILE_BATCH - 1) * tile_count + tile) * RECORD_WORDS..]);
+        let limit = self.settings.tile_size - 1 - ESCAPE_MARGIN;
+        let mut escaping = Vec::new();
+        for tile in 0..tile_count {
+            let state = &mut self.tiles[tile];
+            if state.soup.is_none() {
+                continue;
+            }
+            for record in 0..TILE_BATCH {
+                let start = (record * tile_count + tile) * RECORD_WORDS;
+                let generation = first_generation + record + 1;
+                if let Some(stabilization) = state.history.push(generation, &HashRecord::from_words(&words[start..])) {
+                    state.period.get_or_insert(stabilization.period);
+                }
+            }
+            let bbox = last_record(tile).bbox;
+            let near_wall = last_record(tile).population > 0
+                && (bbox[0] <= ESCAPE_MARGIN || bbox[1] <= ESCAPE_MARGIN || bbox[2] >= limit || bbox[3] >= limit);
+            if state.period.is_none() && near_wall && !state.overflowed {
+                escaping.push(tile);
+            }
+        }
+
+        let generation = self.simulation.generation;
+        let finished: Vec<usize> = (0..tile_count)
+            .filter(|&tile| {
+                let state = &self.tiles[tile];
+                state.soup.is_some() && (state.period.is_some() || generation - state.started >= self.settings.max_generations)
+            })
+            .collect();
+        if finished.is_empty() && escaping.is_empty() {
+            return Ok(());
+        }
+
+        let mut cells = self.simulation.read_cells()?;
+        let interior = self.settings.tile_size - 1;
+        let (rules, max_period) = (self.settings.rules, self.settings.max_period);
+        for tile in escaping {
+            for object in components(&self.tile_cells(tile, &cells)) {
+                let touches_margin = object.iter().any(|&(x, y)| {
+                    let (x, y) = (x as u32 + 1, y as u32 + 1);
+                    x <= ESCAPE_MARGIN || y <= ESCAPE_MARGIN || x >= limit || y >= limit
+                });
+                if !touches_margin {
+                    continue;
+                }
+                let code = apgcode(&object, rules, max_period)?;
+                if code.starts_with("xq") {
+                    self.erase(tile, &object, &mut cells);
+                    self.tiles[tile].escaped.push(code);
+                } else {
+                    self.tiles[tile].overflowed = true;
+                }
+            }
+            self.tiles[tile].history.clear();
+        }
+        for tile in finished {
+            let (soup, period) = (self.tiles[tile].soup.unwrap_or_default(), self.tiles[tile].period);
+            let live = self.tile_cells(tile, &cells);
+            match period {
+                Some(period) => {
+                    let mut objects = separate_objects(&live, interior, interior, rules, period, max_period)?;
+                    objects.append(&mut self.tiles[tile].escaped);
+                    self.census.add_soup(soup, &objects);
+                }
+                None => {
+                    log::debug!("Soup {} of seed {} is still changing after {} generations", soup, self.settings.seed, self.settings.max_generations);
+                    self.census.add_unstable_soup();
+                }
+            }
+            self.erase(tile, &live, &mut cells);
+            self.start_next_soup(tile, &mut cells);
+        }
+        self.simulation.write_cells(&cells)
+    }
+
+    /// Run every soup to the end
+    pub fn run(&mut self) -> Result<&Census, String> {
+        while !self.is_finished() {
+            self.run_batch()?;
+        }
+        Ok(&self.census)
+    }
+}
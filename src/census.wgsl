@@ -0,0 +1,91 @@
+// Per-tile hashes of a grid holding one soup per tile (see `census::SoupSearch`).
+//
+// Tiles are separated by a dead wall: row and column 0 of every tile are cleared
+// after each step, so the inside of a tile evolves as if surrounded by dead cells.
+// Each tile gets a record like period.wgsl's, with tile-local coordinates.
+
+const FIELD_POPULATION: u32 = 0u;
+const FIELD_MIN_X_INV: u32 = 1u;
+const FIELD_MIN_Y_INV: u32 = 2u;
+const FIELD_MAX_X: u32 = 3u;
+const FIELD_MAX_Y: u32 = 4u;
+const FIELD_HASH_A: u32 = 5u;
+const FIELD_HASH_B: u32 = 6u;
+const RECORD_WORDS: u32 = 8u;
+
+const HASH_A_X: u32 = 0x9E3779B5u;
+const HASH_A_Y: u32 = 0x85EBCA6Bu;
+const HASH_B_X: u32 = 0xC2B2AE35u;
+const HASH_B_Y: u32 = 0x27D4EB2Bu;
+
+struct TileParams {
+    width: u32,
+    height: u32,
+    tile_size: u32,   // Multiple of the workgroup size, so a workgroup never spans two tiles
+    first_record: u32, // Record of tile 0 for this dispatch
+}
+
+@group(0) @binding(0) var<uniform> params: TileParams;
+@group(0) @binding(1) var<storage, read_write> cells: array<f32>;
+@group(0) @binding(2) var<storage, read_write> records: array<atomic<u32>>;
+
+var<workgroup> local_record: array<atomic<u32>, 8>;
+
+fn power(base: u32, exponent: u32) -> u32 {
+    var result = 1u;
+    var factor = base;
+    var remaining = exponent;
+    while (remaining > 0u) {
+        if ((remaining & 1u) == 1u) {
+            result = result * factor;
+        }
+        factor = factor * factor;
+        remaining = remaining >> 1u;
+    }
+    return result;
+}
+
+@compute @workgroup_size(16, 16)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    if (local_index < RECORD_WORDS) {
+        atomicStore(&local_record[local_index], 0u);
+    }
+    workgroupBarrier();
+
+    let x = global_id.x;
+    let y = global_id.y;
+    let local_x = x % params.tile_size;
+    let local_y = y % params.tile_size;
+    if (x < params.width && y < params.height) {
+        let index = y * params.width + x;
+        if (local_x == 0u || local_y == 0u) {
+            cells[index] = 0.0;
+        } else if (cells[index] > 0.5) {
+            atomicAdd(&local_record[FIELD_POPULATION], 1u);
+            atomicMax(&local_record[FIELD_MIN_X_INV], 0xFFFFFFFFu - local_x);
+            atomicMax(&local_record[FIELD_MIN_Y_INV], 0xFFFFFFFFu - local_y);
+            atomicMax(&local_record[FIELD_MAX_X], local_x);
+            atomicMax(&local_record[FIELD_MAX_Y], local_y);
+            atomicAdd(&local_record[FIELD_HASH_A], power(HASH_A_X, local_x) * power(HASH_A_Y, local_y));
+            atomicAdd(&local_record[FIELD_HASH_B], power(HASH_B_X, local_x) * power(HASH_B_Y, local_y));
+        }
+    }
+    workgroupBarrier();
+
+    if (local_index < RECORD_WORDS && atomicLoad(&local_record[FIELD_POPULATION]) != 0u) {
+        let tile_x = workgroup_id.x * 16u / params.tile_size;
+        let tile_y = workgroup_id.y * 16u / params.tile_size;
+        let tile = tile_y * (params.width / params.tile_size) + tile_x;
+        let value = atomicLoad(&local_record[local_index]);
+        let slot = (params.first_record + tile) * RECORD_WORDS + local_index;
+        if (local_index >= FIELD_MIN_X_INV && local_index <= FIELD_MAX_Y) {
+            atomicMax(&records[slot], value);
+        } else {
+            atomicAdd(&records[slot], value);
+        }
+    }
+}
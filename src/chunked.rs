@@ -0,0 +1,172 @@
+//! Grids too large for one storage buffer binding, split into horizontal bands of rows
+//! (chunks) with buffers of their own.
+//!
+//! Every chunk buffer holds the chunk's rows plus a halo row above and below. The
+//! unmodified rule kernel steps each chunk as a grid of `rows + 2` rows: interior rows
+//! only look one row away, so they come out exact, while the halo rows are garbage.
+//! Before every step the halo rows are refreshed with copies of the neighboring
+//! chunks' edge rows (the opposite edge on a torus, dead cells on a bounded grid).
+//! Each chunk's hash seed is offset by its first row, so the lucky rule gives the
+//! same cells as on a single buffer.
+
+use crate::compute::{SimParams, create_compute_bind_groups};
+use crate::rules::Boundary;
+use crate::simulation::read_buffer;
+
+const CELL_BYTES: u64 = std::mem::size_of::<f32>() as u64;
+
+/// One band of rows of a `ChunkedGrid`
+pub struct GridChunk {
+    /// First grid row held by the chunk
+    pub first_row: u32,
+    pub rows: u32,
+    /// `(rows + 2) * width` cells each: a halo row, the chunk's rows, a halo row.
+    /// `buffers[generation % 2]` holds the current generation, like `Simulation::grid_buffers`.
+    pub buffers: [wgpu::Buffer; 2],
+    /// `SimParams` of the chunk seen as a grid of its own (`rows + 2` rows high)
+    pub param_buffer: wgpu::Buffer,
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+pub struct ChunkedGrid {
+    pub width: u32,
+    pub height: u32,
+    pub chunks: Vec<GridChunk>,
+}
+
+impl ChunkedGrid {
+    /// Split a `width` x `height` grid into chunks of at most `chunk_rows` rows
+    pub fn new(
+        device: &wgpu::Device,
+        compute_layout: &wgpu::BindGroupLayout,
+        rules_buffer: &wgpu::Buffer,
+        width: u32,
+        height: u32,
+        chunk_rows: u32,
+    ) -> Self {
+        let chunk_rows = chunk_rows.clamp(1, height);
+        let chunks = (0..height.div_ceil(chunk_rows))
+            .map(|index| {
+                let first_row = index * chunk_rows;
+                let rows = chunk_rows.min(height - first_row);
+                let buffers = [0, 1].map(|i| device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Grid Chunk {} Buffer {}", index, i)),
+                    size: (rows as u64 + 2) * width as u64 * CELL_BYTES,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }));
+                let param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Grid Chunk {} Parameters", index)),
+                    size: std::mem::size_of::<SimParams>() as u64,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                let bind_groups = create_compute_bind_groups(device, compute_layout, &buffers, &param_buffer, rules_buffer);
+                GridChunk { first_row, rows, buffers, param_buffer, bind_groups }
+            })
+            .collect();
+        Self { width, height, chunks }
+    }
+
+    /// Upload `params` (those of the whole grid) adapted to every chunk
+    pub fn write_params(&self, queue: &wgpu::Queue, params: &SimParams) {
+        for chunk in &self.chunks {
+            // Chunk row r is grid row first_row + r - 1
+            let row_shift = chunk.first_row.wrapping_sub(1).wrapping_mul(self.width);
+            queue.write_buffer(&chunk.param_buffer, 0, bytemuck::bytes_of(&SimParams {
+                height: chunk.rows + 2,
+                seed: params.seed.wrapping_add(row_shift),
+                ..*params
+            }));
+        }
+    }
+
+    /// Record one generation from `buffers[parity]` into the other buffers
+    pub fn encode_step(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::ComputePipeline,
+        parity: usize,
+        workgroup_size: u32,
+        boundary: Boundary,
+    ) {
+        self.encode_halo_exchange(encoder, parity, boundary);
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Chunked Game of Life Compute Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(pipeline);
+        for chunk in &self.chunks {
+            compute_pass.set_bind_group(0, &chunk.bind_groups[parity], &[]);
+            compute_pass.dispatch_workgroups(
+                self.width.div_ceil(workgroup_size),
+                (chunk.rows + 2).div_ceil(workgroup_size),
+                1,
+            );
+        }
+    }
+
+    /// Fill the halo rows of `buffers[parity]` from the neighboring chunks
+    fn encode_halo_exchange(&self, encoder: &mut wgpu::CommandEncoder, parity: usize, boundary: Boundary) {
+        let row_bytes = self.width as u64 * CELL_BYTES;
+        let count = self.chunks.len();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let halo = &chunk.buffers[parity];
+            let bottom_halo = (chunk.rows as u64 + 1) * row_bytes;
+            let wraps_above = index == 0;
+            let wraps_below = index == count - 1;
+
+            // The row above comes from the last row of the previous chunk
+            if wraps_above && boundary == Boundary::Dead {
+                encoder.clear_buffer(halo, 0, Some(row_bytes));
+            } else {
+                let above = &self.chunks[(index + count - 1) % count];
+                encoder.copy_buffer_to_buffer(&above.buffers[parity], above.rows as u64 * row_bytes, halo, 0, row_bytes);
+            }
+            // The row below comes from the first row of the next chunk
+            if wraps_below && boundary == Boundary::Dead {
+                encoder.clear_buffer(halo, bottom_halo, Some(row_bytes));
+            } else {
+                let below = &self.chunks[(index + 1) % count];
+                encoder.copy_buffer_to_buffer(&below.buffers[parity], row_bytes, halo, bottom_halo, row_bytes);
+            }
+        }
+    }
+
+    /// The grid rows of `buffers[parity]` (row-major cell values, halos left out).
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn read_cells(&self, device: &wgpu::Device, queue: &wgpu::Queue, parity: usize) -> Result<Vec<f32>, String> {
+        let row_bytes = self.width as u64 * CELL_BYTES;
+        let mut cells = Vec::with_capacity(self.width as usize * self.height as usize);
+        for chunk in &self.chunks {
+            cells.extend(read_buffer(device, queue, &chunk.buffers[parity], row_bytes, chunk.rows as u64 * row_bytes)?);
+        }
+        Ok(cells)
+    }
+
+    /// Replace the grid rows of `buffers[parity]` with `cells` (`width * height` values)
+    pub fn write_cells(&self, queue: &wgpu::Queue, parity: usize, cells: &[f32]) {
+        let width = self.width as usize;
+        for chunk in &self.chunks {
+            let start = chunk.first_row as usize * width;
+            let end = start + chunk.rows as usize * width;
+            queue.write_buffer(&chunk.buffers[parity], width as u64 * CELL_BYTES, bytemuck::cast_slice(&cells[start..end]));
+        }
+    }
+
+    /// Overwrite cells of grid row `y` from column `x` (the caller clips to the grid)
+    pub fn write_row(&self, queue: &wgpu::Queue, parity: usize, x: u32, y: u32, values: &[f32]) {
+        let Some(chunk) = self.chunks.iter().find(|chunk| y < chunk.first_row + chunk.rows) else {
+            return;
+        };
+        let offset = ((y - chunk.first_row + 1) as u64 * self.width as u64 + x as u64) * CELL_BYTES;
+        queue.write_buffer(&chunk.buffers[parity], offset, bytemuck::cast_slice(values));
+    }
+
+    /// Kill every cell of `buffers[parity]`
+    pub fn encode_clear(&self, encoder: &mut wgpu::CommandEncoder, parity: usize) {
+        for chunk in &self.chunks {
+            encoder.clear_buffer(&chunk.buffers[parity], 0, None);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Connected-component labeling: splits the live cells of the grid into objects on
+//! the GPU (`components.wgsl`).
+//!
+//! Labels are propagated in batches of passes recorded into the frame's command
+//! encoder; a tiny status word (changed flag and object count) is read back
+//! asynchronously after each batch, and labeling continues until a batch changes
+//! nothing. The label buffer stays on the GPU, where `components_render.wgsl` colors
+//! each object by its label.
+
+use crate::readback::ReadbackRing;
+use crate::simulation::Simulation;
+
+/// Cells within this Chebyshev distance belong to the same object (1 = 8-connectivity)
+pub const DEFAULT_RADIUS: u32 = 1;
+pub const MAX_RADIUS: u32 = 4;
+/// Propagation passes per batch; compact objects settle within a single batch
+const PASSES_PER_BATCH: u32 = 16;
+const WORKGROUP_SIZE: u32 = 16;
+const STATUS_SIZE: u64 = 2 * std::mem::size_of::<u32>() as u64;
+/// Label of no object, for `ComponentLabeler::select`
+pub const NO_LABEL: u32 = 0;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LabelParams {
+    width: u32,
+    height: u32,
+    radius: u32,
+    boundary: u32,
+}
+
+/// Finished labeling of one generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Labeling {
+    pub generation: usize,
+    /// `Simulation::edit_count` of the labeled grid
+    pub edit_count: u64,
+    pub objects: u32,
+}
+
+/// One object of a labeling, read back from the GPU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub label: u32,
+    pub generation: usize,
+    pub edit_count: u64,
+    /// Grid cells, row by row
+    pub cells: Vec<(u32, u32)>,
+    /// x, y, width, height (like `State::selection`)
+    pub bounds: [u32; 4],
+}
+
+impl Object {
+    /// The cells relative to the bounding box
+    pub fn pattern(&self) -> crate::library::Pattern {
+        crate::library::Pattern::from_cells(self.cells.iter().map(|&(x, y)| (x as i32, y as i32)))
+    }
+}
+
+/// Labels the objects of a `Simulation`'s grid; create a new one when its buffers
+/// are replaced
+pub struct ComponentLabeler {
+    init_pipeline: wgpu::ComputePipeline,
+    propagate_pipeline: wgpu::ComputePipeline,
+    count_pipeline: wgpu::ComputePipeline,
+    /// Bind groups reading `grid_buffers[parity]`
+    bind_groups: [wgpu::BindGroup; 2],
+    param_buffer: wgpu::Buffer,
+    labels: wgpu::Buffer,
+    status: wgpu::Buffer,
+    /// Status of the batch in flight
+    status_readback: ReadbackRing<()>,
+    radius: u32,
+    /// Generation and edit count being labeled
+    labeling: Option<(usize, u64)>,
+    pub latest: Option<Labeling>,
+    width: u32,
+    height: u32,
+}
+
+impl ComponentLabeler {
+    pub fn new(simulation: &Simulation, radius: u32) -> Result<Self, String> {
+        if simulation.chunks().is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        let device = &simulation.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Component Labeling Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("components.wgsl").into()),
+        });
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Component Labeling Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+                storage_entry(3, false),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Component Labeling Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point| device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Component Labeling Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point,
+        });
+
+        let param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Component Labeling Parameters"),
+            size: std::mem::size_of::<LabelParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // One extra word for the selected label
+        let labels = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Component Labels"),
+            size: (simulation.cell_count() as u64 + 1) * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let status = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Component Labeling Status"),
+            size: STATUS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_groups = [0, 1].map(|parity| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("Component Labeling Bind Group ({})", parity)),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: param_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: simulation.grid_buffers[parity].as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: labels.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: status.as_entire_binding() },
+            ],
+        }));
+        Ok(Self {
+            init_pipeline: pipeline("init"),
+            propagate_pipeline: pipeline("propagate"),
+            count_pipeline: pipeline("count"),
+            bind_groups,
+            param_buffer,
+            labels,
+            status,
+            status_readback: ReadbackRing::new(device, "Component Labeling Status Staging", STATUS_SIZE, 1),
+            radius: radius.clamp(1, MAX_RADIUS),
+            labeling: None,
+            latest: None,
+            width: simulation.width,
+            height: simulation.height,
+        })
+    }
+
+    /// Labels per cell (0 = dead), followed by the selected label
+    pub fn labels(&self) -> &wgpu::Buffer {
+        &self.labels
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Connect cells up to `radius` apart; labels the grid over again
+    pub fn set_radius(&mut self, radius: u32) {
+        let radius = radius.clamp(1, MAX_RADIUS);
+        if radius != self.radius {
+            self.radius = radius;
+            self.labeling = None;
+            self.latest = None;
+        }
+    }
+
+    /// The labeling of the simulation's current grid, once it has finished
+    pub fn current(&self, simulation: &Simulation) -> Option<Labeling> {
+        self.latest.filter(|labeling| (labeling.generation, labeling.edit_count) == (simulation.generation, simulation.edit_count()))
+    }
+
+    /// Highlight the object with `label` (or none, with `NO_LABEL`) in components_render.wgsl
+    pub fn select(&self, queue: &wgpu::Queue, label: u32) {
+        let offset = self.width as u64 * self.height as u64 * std::mem::size_of::<u32>() as u64;
+        queue.write_buffer(&self.labels, offset, bytemuck::bytes_of(&label));
+    }
+
+    /// Record a batch of labeling passes: starting over when the grid moved on since
+    /// the labeling in progress, continuing it otherwise. Returns false when the
+    /// current grid is already labeled or a batch is still being read back.
+    pub fn encode(&mut self, encoder: &mut wgpu::CommandEncoder, simulation: &Simulation) -> bool {
+        if !self.status_readback.has_free_slot() || self.current(simulation).is_some() {
+            return false;
+        }
+        let groups = (self.width.div_ceil(WORKGROUP_SIZE), self.height.div_ceil(WORKGROUP_SIZE));
+        let key = (simulation.generation, simulation.edit_count());
+        let restart = self.labeling != Some(key);
+        if restart {
+            let params = LabelParams {
+                width: self.width,
+                height: self.height,
+                radius: self.radius,
+                boundary: simulation.boundary.shader_value(),
+            };
+            simulation.queue.write_buffer(&self.param_buffer, 0, bytemuck::bytes_of(&params));
+            self.labeling = Some(key);
+        }
+        encoder.clear_buffer(&self.status, 0, None);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Component Labeling Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, &self.bind_groups[simulation.generation % 2], &[]);
+        if restart {
+            pass.set_pipeline(&self.init_pipeline);
+            pass.dispatch_workgroups(groups.0, groups.1, 1);
+        }
+        pass.set_pipeline(&self.propagate_pipeline);
+        for _ in 0..PASSES_PER_BATCH {
+            pass.dispatch_workgroups(groups.0, groups.1, 1);
+        }
+        pass.set_pipeline(&self.count_pipeline);
+        pass.dispatch_workgroups(groups.0, groups.1, 1);
+        drop(pass);
+        encoder.copy_buffer_to_buffer(&self.status, 0, self.status_readback.staging(0), 0, STATUS_SIZE);
+        self.status_readback.fill(0, ());
+        true
+    }
+
+    /// Must be called after the encoder holding a batch has been submitted
+    pub fn after_submit(&mut self) {
+        self.status_readback.after_submit();
+    }
+
+    /// Collect the status of the last batch (non-blocking); returns the labeling
+    /// once it has finished
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<Labeling> {
+        let status = self.status_readback.poll(device, decode_status).pop()?;
+        self.finish_batch(status)
+    }
+
+    /// Label the current grid, blocking until it is done
+    pub fn label(&mut self, simulation: &Simulation) -> Result<Labeling, String> {
+        loop {
+            if let Some(labeling) = self.current(simulation) {
+                return Ok(labeling);
+            }
+            if self.status_readback.has_free_slot() {
+                let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Component Labeling Encoder"),
+                });
+                self.encode(&mut encoder, simulation);
+                simulation.queue.submit(Some(encoder.finish()));
+                self.after_submit();
+            }
+            let Some(status) = self.status_readback.wait_oldest(&simulation.device, decode_status).pop() else {
+                return Err("Failed to read the labeling status".to_string());
+            };
+            self.finish_batch(status);
+        }
+    }
+
+    /// Labeling is done once a batch changed no label
+    fn finish_batch(&mut self, [changed, objects]: [u32; 2]) -> Option<Labeling> {
+        if changed != 0 {
+            return None;
+        }
+        let (generation, edit_count) = self.labeling?;
+        self.latest = Some(Labeling { generation, edit_count, objects });
+        self.latest
+    }
+
+    /// The object at (x, y) of the current grid, labeling it first if needed
+    /// (blocking; reads the whole label buffer)
+    pub fn object_at(&mut self, simulation: &Simulation, x: u32, y: u32) -> Result<Option<Object>, String> {
+        if x >= self.width || y >= self.height {
+            return Ok(None);
+        }
+        let labeling = self.label(simulation)?;
+        let size = self.width as u64 * self.height as u64 * std::mem::size_of::<u32>() as u64;
+        let words = crate::simulation::read_buffer(&simulation.device, &simulation.queue, &self.labels, 0, size)?;
+        let labels: &[u32] = bytemuck::cast_slice(&words);
+        let label = labels[(y * self.width + x) as usize];
+        if label == NO_LABEL {
+            return Ok(None);
+        }
+        let cells: Vec<(u32, u32)> = labels.iter().enumerate()
+            .filter(|&(_, &l)| l == label)
+            .map(|(index, _)| (index as u32 % self.width, index as u32 / self.width))
+            .collect();
+        let (min_x, max_x) = cells.iter().fold((u32::MAX, 0), |(lo, hi), &(x, _)| (lo.min(x), hi.max(x)));
+        let (min_y, max_y) = (cells[0].1, cells[cells.len() - 1].1);
+        Ok(Some(Object {
+            label,
+            generation: labeling.generation,
+            edit_count: labeling.edit_count,
+            bounds: [min_x, min_y, max_x - min_x + 1, max_y - min_y + 1],
+            cells,
+        }))
+    }
+}
+
+/// Changed flag and object count of a batch
+fn decode_status((): (), data: &[u8]) -> [u32; 2] {
+    bytemuck::pod_read_unaligned(data)
+}
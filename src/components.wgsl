@@ -0,0 +1,87 @@
+// Connected-component labeling of the grid (components.rs).
+//
+// `init` gives every live cell its own label (cell index + 1, 0 = dead). Each
+// `propagate` pass lowers a cell's label to the smallest label among the live
+// cells within `radius` (Chebyshev distance, 1 = 8-connectivity) and then jumps
+// to the label of the cell it points at, which is always in the same object and
+// never larger. Once no label changes, every object carries the label of its
+// lowest-index cell, so `count` finds one root per object.
+
+struct LabelParams {
+    width: u32,
+    height: u32,
+    radius: u32,
+    boundary: u32, // 0 = torus, 1 = dead (rules::Boundary::shader_value)
+}
+
+struct LabelStatus {
+    changed: atomic<u32>,
+    objects: atomic<u32>,
+}
+
+@group(0) @binding(0) var<uniform> params: LabelParams;
+@group(0) @binding(1) var<storage, read> cells: array<f32>;
+// One label per cell, then the selected label (read by components_render.wgsl)
+@group(0) @binding(2) var<storage, read_write> labels: array<u32>;
+@group(0) @binding(3) var<storage, read_write> status: LabelStatus;
+
+fn cell_index(global_id: vec3<u32>) -> i32 {
+    if (global_id.x >= params.width || global_id.y >= params.height) {
+        return -1;
+    }
+    return i32(global_id.y * params.width + global_id.x);
+}
+
+@compute @workgroup_size(16, 16)
+fn init(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = cell_index(global_id);
+    if (index < 0) {
+        return;
+    }
+    labels[index] = select(0u, u32(index) + 1u, cells[index] > 0.5);
+}
+
+@compute @workgroup_size(16, 16)
+fn propagate(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = cell_index(global_id);
+    if (index < 0) {
+        return;
+    }
+    let label = labels[index];
+    if (label == 0u) {
+        return;
+    }
+    let width = i32(params.width);
+    let height = i32(params.height);
+    let radius = i32(params.radius);
+    var lowest = label;
+    for (var dy = -radius; dy <= radius; dy++) {
+        for (var dx = -radius; dx <= radius; dx++) {
+            var x = i32(global_id.x) + dx;
+            var y = i32(global_id.y) + dy;
+            if (params.boundary == 0u) {
+                x = (x + width) % width;
+                y = (y + height) % height;
+            } else if (x < 0 || x >= width || y < 0 || y >= height) {
+                continue;
+            }
+            let neighbor = labels[y * width + x];
+            if (neighbor != 0u) {
+                lowest = min(lowest, neighbor);
+            }
+        }
+    }
+    lowest = min(lowest, labels[lowest - 1u]);
+    if (lowest < label) {
+        labels[index] = lowest;
+        atomicStore(&status.changed, 1u);
+    }
+}
+
+@compute @workgroup_size(16, 16)
+fn count(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = cell_index(global_id);
+    if (index >= 0 && labels[index] == u32(index) + 1u) {
+        atomicAdd(&status.objects, 1u);
+    }
+}
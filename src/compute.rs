@@ -3,6 +3,8 @@ use wgpu;
  // Needed for NonZeroU64
 use crate::rules::GameRules as RustGameRules;
 
+/// Workgroup side of the global-memory rule kernel (`@workgroup_size(8, 8, 1)` in
+/// rules/conway_classic.wgsl and custom shaders) and the packed kernel
 pub const WORKGROUP_SIZE: u32 = 8;
 
 #[repr(C)]
@@ -13,26 +15,25 @@ pub struct SimParams {
     pub lucky_chance: f32,      // Moved up
     pub seed: u32,
     pub enable_lucky_rule: u32, // 0 = false, 1 = true
-    pub _padding: [u32; 3],     // Padding still needed (4+4+4 + 4+4 + 12 = 32)
+    pub boundary: u32,          // 0 = torus (wrap), 1 = dead cells outside the grid
+    pub _padding: [u32; 2],     // Padding still needed (4+4+4 + 4+4+4 + 8 = 32)
 }
 
 /// Shader-compatible representation of GameRules
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct ShaderGameRules {
-    pub survival_min: u32,
-    pub survival_max: u32,
-    pub birth_count: u32,
-    pub _padding: u32, // Ensure 16-byte alignment
+    pub birth_mask: u32,    // Bit n set: born with n neighbors
+    pub survival_mask: u32, // Bit n set: survives with n neighbors
+    pub _padding: [u32; 2], // Ensure 16-byte alignment
 }
 
 impl From<&RustGameRules> for ShaderGameRules {
     fn from(rules: &RustGameRules) -> Self {
         Self {
-            survival_min: rules.survival_min,
-            survival_max: rules.survival_max,
-            birth_count: rules.birth_count,
-            _padding: 0, // Required for memory alignment
+            birth_mask: rules.birth,
+            survival_mask: rules.survival,
+            _padding: [0; 2], // Required for memory alignment
         }
     }
 }
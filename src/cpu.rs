@@ -0,0 +1,219 @@
+//! Reference CPU engine with the same semantics as conway_classic.wgsl.
+//!
+//! Uses the same cell layout as `Simulation` (row-major f32, 0 = dead), the same
+//! birth/survival masks and boundary handling, the lucky rule with the same hash
+//! and the color-majority birth, so the two engines can be diffed cell by cell.
+//! Rows are split across threads; output does not depend on the thread count.
+
+use crate::rules::{Boundary, GameRules};
+
+/// Grids smaller than this are stepped on the calling thread
+const PARALLEL_MIN_CELLS: usize = 64 * 64;
+
+pub struct CpuSimulation {
+    pub width: u32,
+    pub height: u32,
+    pub rules: GameRules,
+    pub boundary: Boundary,
+    pub lucky_rule_enabled: bool,
+    /// Chance (0-1) for a dying cell to survive as a lucky cell
+    pub lucky_chance: f32,
+    pub generation: usize,
+    /// Worker threads used by `step`; defaults to the available parallelism
+    pub threads: usize,
+    cells: Vec<f32>,
+    next: Vec<f32>,
+}
+
+/// Everything a row needs besides the grid, copied so rows can be computed in parallel
+#[derive(Clone, Copy)]
+struct StepParams {
+    width: u32,
+    height: u32,
+    rules: GameRules,
+    boundary: Boundary,
+    lucky_rule_enabled: bool,
+    lucky_chance: f32,
+    seed: u32,
+}
+
+impl CpuSimulation {
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Invalid grid size {}x{}", width, height));
+        }
+        let count = width as usize * height as usize;
+        Ok(Self {
+            width,
+            height,
+            rules: GameRules::default(),
+            boundary: Boundary::default(),
+            lucky_rule_enabled: false,
+            lucky_chance: 0.1,
+            generation: 0,
+            threads: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            cells: vec![0.0; count],
+            next: vec![0.0; count],
+        })
+    }
+
+    /// Cells of the current generation
+    pub fn cells(&self) -> &[f32] {
+        &self.cells
+    }
+
+    /// Cells of the previous generation (all dead before the first step)
+    pub fn previous_cells(&self) -> &[f32] {
+        &self.next
+    }
+
+    pub fn write_cells(&mut self, cells: &[f32]) -> Result<(), String> {
+        if cells.len() != self.cells.len() {
+            return Err(format!("Expected {} cells, got {}", self.cells.len(), cells.len()));
+        }
+        self.cells.copy_from_slice(cells);
+        Ok(())
+    }
+
+    /// Advance `count` generations.
+    ///
+    /// The lucky rule hash is seeded with the generation being stepped from. This
+    /// matches `Simulation` when it is stepped one generation per `write_params`
+    /// (e.g. `Simulation::step(1)`); batched GPU steps reuse the seed of the batch.
+    pub fn step(&mut self, count: u32) {
+        let width = self.width as usize;
+        for _ in 0..count {
+            let params = StepParams {
+                width: self.width,
+                height: self.height,
+                rules: self.rules,
+                boundary: self.boundary,
+                lucky_rule_enabled: self.lucky_rule_enabled,
+                lucky_chance: self.lucky_chance,
+                seed: self.generation as u32,
+            };
+            let cells = &self.cells;
+            let threads = self.threads.max(1).min(self.height as usize);
+            if threads <= 1 || cells.len() < PARALLEL_MIN_CELLS {
+                for (y, row) in self.next.chunks_mut(width).enumerate() {
+                    step_row(&params, cells, y as u32, row);
+                }
+            } else {
+                let rows_per_thread = (self.height as usize).div_ceil(threads);
+                std::thread::scope(|scope| {
+                    for (chunk, rows) in self.next.chunks_mut(rows_per_thread * width).enumerate() {
+                        scope.spawn(move || {
+                            for (i, row) in rows.chunks_mut(width).enumerate() {
+                                step_row(&params, cells, (chunk * rows_per_thread + i) as u32, row);
+                            }
+                        });
+                    }
+                });
+            }
+            std::mem::swap(&mut self.cells, &mut self.next);
+            self.generation += 1;
+        }
+    }
+}
+
+/// Same hash as the kernel's `hash`, mapped to [0, 1]
+fn hash(value: u32) -> f32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    let result = (word >> 22) ^ word;
+    result as f32 / 4294967295.0
+}
+
+pub(crate) fn is_color(value: f32, base_color: f32) -> bool {
+    (value - base_color).abs() < 0.1
+}
+
+/// Index of the cell `delta` steps from `pos` along an axis of length `len`, wrapped
+/// or `None` past the edge of a bounded grid
+fn offset(pos: u32, delta: i32, len: u32, boundary: Boundary) -> Option<u32> {
+    let target = pos as i64 + delta as i64;
+    if (0..len as i64).contains(&target) {
+        Some(target as u32)
+    } else if boundary == Boundary::Torus {
+        Some(target.rem_euclid(len as i64) as u32)
+    } else {
+        None
+    }
+}
+
+/// Values of the live neighbors of (x, y); returns the buffer and how many are set
+fn live_neighbors(params: &StepParams, cells: &[f32], x: u32, y: u32) -> ([f32; 8], usize) {
+    let mut values = [0.0; 8];
+    let mut count = 0;
+    for dy in -1..=1 {
+        let Some(ny) = offset(y, dy, params.height, params.boundary) else { continue };
+        let row = (ny * params.width) as usize;
+        for dx in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let Some(nx) = offset(x, dx, params.width, params.boundary) else { continue };
+            let value = cells[row + nx as usize];
+            if value > 0.5 {
+                values[count] = value;
+                count += 1;
+            }
+        }
+    }
+    (values, count)
+}
+
+/// Color of a newborn cell: the most common color among live neighbors, ties going
+/// to the earlier color (white, red, green, blue, yellow, purple)
+pub(crate) fn dominant_color(neighbors: &[f32]) -> f32 {
+    const COLORS: [f32; 6] = [1.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+    let mut color_counts = [0u32; 6];
+    for &value in neighbors {
+        let slot = if is_color(value, 1.0) {
+            Some(0)
+        } else if is_color(value, 2.0) || is_color(value, 3.0) {
+            Some(1)
+        } else {
+            (2..6).find(|&i| is_color(value, COLORS[i]))
+        };
+        if let Some(slot) = slot {
+            color_counts[slot] += 1;
+        }
+    }
+    let mut max_count = 0;
+    let mut dominant = 0;
+    for (i, &count) in color_counts.iter().enumerate() {
+        if count > max_count {
+            max_count = count;
+            dominant = i;
+        }
+    }
+    COLORS[dominant]
+}
+
+fn step_row(params: &StepParams, cells: &[f32], y: u32, out: &mut [f32]) {
+    for (x, out) in out.iter_mut().enumerate() {
+        let x = x as u32;
+        let cell = cells[(y * params.width + x) as usize];
+        let (neighbors, live) = live_neighbors(params, cells, x, y);
+        let count = live as u32;
+
+        *out = if cell > 0.5 {
+            if params.rules.survives_on(count) {
+                cell
+            } else {
+                let random_value = hash(x.wrapping_add(y.wrapping_mul(params.width)).wrapping_add(params.seed));
+                if params.lucky_rule_enabled && random_value < params.lucky_chance {
+                    // White and red cells become lucky (3.0), other colors keep theirs
+                    if is_color(cell, 1.0) || is_color(cell, 2.0) || is_color(cell, 3.0) { 3.0 } else { cell }
+                } else {
+                    0.0
+                }
+            }
+        } else if params.rules.births_on(count) {
+            dominant_color(&neighbors[..live])
+        } else {
+            0.0
+        };
+    }
+}
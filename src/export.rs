@@ -0,0 +1,64 @@
+use crate::render::Palette;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Largest image edge we are willing to produce (PNG allows more, most viewers do not)
+pub const MAX_EXPORT_DIMENSION: u64 = 65_535;
+
+/// Encode a grid snapshot as an RGBA PNG with `scale` x `scale` pixels per cell.
+///
+/// Rows are streamed to the encoder one at a time, so even 4096x4096 grids at
+/// large scales never need the full image in memory.
+pub fn write_png(
+    path: &Path,
+    cells: &[f32],
+    width: u32,
+    height: u32,
+    scale: u32,
+    palette: &Palette,
+) -> Result<(), String> {
+    let scale = scale.max(1);
+    let image_width = width as u64 * scale as u64;
+    let image_height = height as u64 * scale as u64;
+    if image_width > MAX_EXPORT_DIMENSION || image_height > MAX_EXPORT_DIMENSION {
+        return Err(format!(
+            "Image would be {}x{} pixels, the maximum is {} per side",
+            image_width, image_height, MAX_EXPORT_DIMENSION
+        ));
+    }
+    if cells.len() < (width as usize * height as usize) {
+        return Err(format!("Expected {} cells, got {}", width as usize * height as usize, cells.len()));
+    }
+
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image_width as u32, image_height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| format!("Failed to write PNG header: {}", e))?;
+    let mut stream = writer.stream_writer().map_err(|e| format!("Failed to start PNG stream: {}", e))?;
+
+    let mut row = vec![0u8; image_width as usize * 4];
+    for y in 0..height as usize {
+        let cell_row = &cells[y * width as usize..(y + 1) * width as usize];
+        fill_scaled_row(&mut row, cell_row, scale, palette);
+        // Each grid row is repeated `scale` times vertically
+        for _ in 0..scale {
+            stream.write_all(&row).map_err(|e| format!("Failed to write PNG data: {}", e))?;
+        }
+    }
+
+    stream.finish().map_err(|e| format!("Failed to finish PNG: {}", e))?;
+    Ok(())
+}
+
+/// Convert one row of cells into RGBA pixels, repeating each cell `scale` times horizontally
+pub fn fill_scaled_row(row: &mut [u8], cells: &[f32], scale: u32, palette: &Palette) {
+    let pixel_run = scale as usize * 4;
+    for (cell, pixels) in cells.iter().zip(row.chunks_exact_mut(pixel_run)) {
+        let rgba = palette.rgba8(*cell);
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+}
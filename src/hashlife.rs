@@ -0,0 +1,622 @@
+//! HashLife: memoized quadtree engine for jumping far ahead in time.
+//!
+//! The universe is an infinite plane of plain live/dead cells (no colors, no lucky
+//! rule) under any life-like rule without B0. Nodes are canonicalized, so identical
+//! regions share storage, and the successor of every node is memoized per step size.
+//! `step` can therefore advance 2^k generations in time roughly proportional to the
+//! pattern's complexity rather than to k or the area. When the node store grows past
+//! `max_nodes` unreachable nodes are dropped and the memo cache is cleared.
+
+use crate::rules::{Boundary, GameRules};
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Node store size that triggers a garbage collection
+pub const DEFAULT_MAX_NODES: usize = 4 << 20;
+
+/// Single generations `advance_grid` runs in a row at a dead edge without finding a
+/// repetition before it leaves the rest to the caller (e.g. the GPU)
+pub const MAX_EDGE_STEPS: usize = 1024;
+
+type NodeId = u32;
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+#[derive(Clone, Copy)]
+struct Node {
+    /// nw, ne, sw, se; unused for leaves
+    children: [NodeId; 4],
+    level: u8,
+    population: u64,
+}
+
+/// Multiplicative hash for the node and memo tables (keys are small integers)
+#[derive(Default)]
+struct NodeHasher(u64);
+
+impl Hasher for NodeHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+type FastMap<K, V> = HashMap<K, V, BuildHasherDefault<NodeHasher>>;
+
+/// Identifies one generation of a `HashLife`: equal keys mean the same cells at the
+/// same positions. Keys from before a garbage collection never equal later ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StateKey {
+    collections: u64,
+    root: NodeId,
+    origin: (i64, i64),
+}
+
+pub struct HashLife {
+    pub rules: GameRules,
+    /// Generations advanced since construction
+    pub generation: u64,
+    /// Node count above which `step` collects garbage
+    pub max_nodes: usize,
+    nodes: Vec<Node>,
+    index: FastMap<[NodeId; 4], NodeId>,
+    /// (node, log2 of the step) -> centered successor
+    results: FastMap<(NodeId, u8), NodeId>,
+    root: NodeId,
+    /// Plane coordinates of the root's top-left cell
+    origin: (i64, i64),
+    /// Garbage collections so far (node ids change with each)
+    collections: u64,
+}
+
+impl HashLife {
+    /// An empty universe. Rules with B0 are rejected because they fill the infinite plane.
+    pub fn new(rules: GameRules) -> Result<Self, String> {
+        if rules.births_on(0) {
+            return Err(format!("HashLife does not support B0 rules ({})", rules.to_rulestring()));
+        }
+        let leaf = |population| Node { children: [DEAD; 4], level: 0, population };
+        let mut life = Self {
+            rules,
+            generation: 0,
+            max_nodes: DEFAULT_MAX_NODES,
+            nodes: vec![leaf(0), leaf(1)],
+            index: FastMap::default(),
+            results: FastMap::default(),
+            root: DEAD,
+            origin: (0, 0),
+            collections: 0,
+        };
+        life.root = life.empty(3);
+        Ok(life)
+    }
+
+    /// Load live cells given in plane coordinates
+    pub fn from_cells(rules: GameRules, cells: &[(i64, i64)]) -> Result<Self, String> {
+        let mut life = Self::new(rules)?;
+        let Some(&(first_x, first_y)) = cells.first() else {
+            return Ok(life);
+        };
+        let (min_x, min_y) = cells.iter().fold((first_x, first_y), |(min_x, min_y), &(x, y)| (min_x.min(x), min_y.min(y)));
+        let extent = cells.iter().map(|&(x, y)| (x - min_x).max(y - min_y) as u64 + 1).max().unwrap_or(1);
+        let level = extent.max(8).next_power_of_two().trailing_zeros() as u8;
+        life.root = life.build_sparse(&mut cells.to_vec(), min_x, min_y, level);
+        life.origin = (min_x, min_y);
+        Ok(life)
+    }
+
+    /// Load a row-major grid (value > 0.5 is alive) with its top-left cell at plane (0, 0)
+    pub fn from_grid(rules: GameRules, cells: &[f32], width: u32, height: u32) -> Result<Self, String> {
+        if cells.len() != width as usize * height as usize {
+            return Err(format!("Expected {} cells, got {}", width as usize * height as usize, cells.len()));
+        }
+        let mut life = Self::new(rules)?;
+        let level = (width.max(height).max(8).next_power_of_two().trailing_zeros()) as u8;
+        life.root = life.build(cells, width, height, 0, 0, level);
+        Ok(life)
+    }
+
+    /// Generations for which a `width` x `height` grid with `boundary` surely evolves
+    /// like the plane, given that the live cells' bounding box grows by at most one cell
+    /// per generation (the speed of light). Within a dead edge (the grid's top-left cell
+    /// at plane (0, 0)) the box must stay inside the grid; on a torus it must not come
+    /// near its own copies one grid size away.
+    pub fn grid_margin(&self, width: u32, height: u32, boundary: Boundary) -> u64 {
+        let Some((min_x, min_y, max_x, max_y)) = self.bounding_box() else {
+            return u64::MAX;
+        };
+        let (width, height) = (width as i64, height as i64);
+        let margin = match boundary {
+            Boundary::Dead => min_x.min(min_y).min(width - 1 - max_x).min(height - 1 - max_y),
+            // Copies across a gap of g dead cells meet after more than g / 2 generations
+            Boundary::Torus => (width - (max_x - min_x + 1)).min(height - (max_y - min_y + 1)) / 2,
+        };
+        margin.max(0) as u64
+    }
+
+    /// Smallest rectangle `(min_x, min_y, max_x, max_y)` holding every live cell
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let (x, y) = self.origin;
+        Some((
+            self.extreme(self.root, x, y, false, false)?,
+            self.extreme(self.root, x, y, true, false)?,
+            self.extreme(self.root, x, y, false, true)?,
+            self.extreme(self.root, x, y, true, true)?,
+        ))
+    }
+
+    /// Identifies the current generation (see `StateKey`)
+    pub fn state_key(&self) -> StateKey {
+        StateKey { collections: self.collections, root: self.root, origin: self.origin }
+    }
+
+    /// Kill every cell outside the `width` x `height` rectangle with its top-left cell at (x, y)
+    pub fn clip(&mut self, x: i64, y: i64, width: u32, height: u32) {
+        let region = (x, y, x + width as i64, y + height as i64);
+        self.root = self.clipped(self.root, self.origin.0, self.origin.1, region);
+        self.shrink();
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    /// Nodes currently stored (live and garbage)
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Live cells inside the rectangle, as a row-major grid of 0.0 / 1.0
+    pub fn to_grid(&self, x: i64, y: i64, width: u32, height: u32) -> Vec<f32> {
+        let mut cells = vec![0.0; width as usize * height as usize];
+        let region = (x, y, x + width as i64, y + height as i64);
+        self.visit_live(self.root, self.origin.0, self.origin.1, region, &mut |cx, cy| {
+            cells[(cy - y) as usize * width as usize + (cx - x) as usize] = 1.0;
+        });
+        cells
+    }
+
+    /// Every live cell in plane coordinates, sorted by (y, x)
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::with_capacity(self.population().min(1 << 24) as usize);
+        let region = (i64::MIN, i64::MIN, i64::MAX, i64::MAX);
+        self.visit_live(self.root, self.origin.0, self.origin.1, region, &mut |x, y| cells.push((x, y)));
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    /// Advance `generations` generations, one power of two at a time
+    pub fn step(&mut self, generations: u64) {
+        for k in 0..64u8 {
+            if generations & (1 << k) != 0 {
+                self.step_pow2(k);
+            }
+        }
+    }
+
+    /// Advance 2^k generations
+    fn step_pow2(&mut self, k: u8) {
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+        // The pattern must sit in the inner half of the root, with one extra level of
+        // margin, so it cannot grow out of the centered successor
+        while self.level() < k + 2 || !self.border_is_empty() {
+            self.expand();
+        }
+        self.expand();
+
+        let level = self.level();
+        self.root = self.successor(self.root, k);
+        let quarter = 1i64 << (level - 2);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.generation += 1 << k;
+        self.shrink();
+    }
+
+    /// Keep only nodes reachable from the root and forget all memoized results
+    pub fn collect_garbage(&mut self) {
+        let before = self.nodes.len();
+        let mut fresh = Self {
+            rules: self.rules,
+            generation: self.generation,
+            max_nodes: self.max_nodes,
+            nodes: self.nodes[..2].to_vec(),
+            index: FastMap::default(),
+            results: FastMap::default(),
+            root: DEAD,
+            origin: self.origin,
+            collections: self.collections + 1,
+        };
+        let mut remap = FastMap::default();
+        fresh.root = fresh.copy_from(self, self.root, &mut remap);
+        *self = fresh;
+        log::info!("HashLife garbage collection: {} -> {} nodes", before, self.nodes.len());
+    }
+
+    fn copy_from(&mut self, other: &Self, id: NodeId, remap: &mut FastMap<NodeId, NodeId>) -> NodeId {
+        if id <= ALIVE {
+            return id;
+        }
+        if let Some(&mapped) = remap.get(&id) {
+            return mapped;
+        }
+        let [nw, ne, sw, se] = other.nodes[id as usize].children;
+        let children = [
+            self.copy_from(other, nw, remap),
+            self.copy_from(other, ne, remap),
+            self.copy_from(other, sw, remap),
+            self.copy_from(other, se, remap),
+        ];
+        let mapped = self.join(children);
+        remap.insert(id, mapped);
+        mapped
+    }
+
+    fn level(&self) -> u8 {
+        self.nodes[self.root as usize].level
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.nodes[id as usize].children
+    }
+
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+        let level = self.nodes[children[0] as usize].level + 1;
+        let population = children.iter().map(|&c| self.nodes[c as usize].population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { children, level, population });
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        let mut node = DEAD;
+        for _ in 0..level {
+            node = self.join([node; 4]);
+        }
+        node
+    }
+
+    fn build(&mut self, cells: &[f32], width: u32, height: u32, x: u32, y: u32, level: u8) -> NodeId {
+        if x >= width || y >= height {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if cells[(y * width + x) as usize] > 0.5 { ALIVE } else { DEAD };
+        }
+        let half = 1 << (level - 1);
+        let nw = self.build(cells, width, height, x, y, level - 1);
+        let ne = self.build(cells, width, height, x + half, y, level - 1);
+        let sw = self.build(cells, width, height, x, y + half, level - 1);
+        let se = self.build(cells, width, height, x + half, y + half, level - 1);
+        self.join([nw, ne, sw, se])
+    }
+
+    /// `cells` (reordered on the way) as a node of `level` with its top-left cell at (x, y)
+    fn build_sparse(&mut self, cells: &mut [(i64, i64)], x: i64, y: i64, level: u8) -> NodeId {
+        if cells.is_empty() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return ALIVE;
+        }
+        let half = 1i64 << (level - 1);
+        let north = partition(cells, |&(_, cell_y)| cell_y < y + half);
+        let (north_cells, south_cells) = cells.split_at_mut(north);
+        let west = partition(north_cells, |&(cell_x, _)| cell_x < x + half);
+        let (nw_cells, ne_cells) = north_cells.split_at_mut(west);
+        let west = partition(south_cells, |&(cell_x, _)| cell_x < x + half);
+        let (sw_cells, se_cells) = south_cells.split_at_mut(west);
+        let nw = self.build_sparse(nw_cells, x, y, level - 1);
+        let ne = self.build_sparse(ne_cells, x + half, y, level - 1);
+        let sw = self.build_sparse(sw_cells, x, y + half, level - 1);
+        let se = self.build_sparse(se_cells, x + half, y + half, level - 1);
+        self.join([nw, ne, sw, se])
+    }
+
+    /// `id` at (x, y) with every cell outside `region` (x0, y0, x1, y1) killed
+    fn clipped(&mut self, id: NodeId, x: i64, y: i64, region: (i64, i64, i64, i64)) -> NodeId {
+        let node = self.nodes[id as usize];
+        let size = 1i64 << node.level;
+        let (x0, y0, x1, y1) = region;
+        if node.population == 0 || (x >= x0 && y >= y0 && x.saturating_add(size) <= x1 && y.saturating_add(size) <= y1) {
+            return id;
+        }
+        if x >= x1 || y >= y1 || x.saturating_add(size) <= x0 || y.saturating_add(size) <= y0 {
+            return self.empty(node.level);
+        }
+        let half = size / 2;
+        let [nw, ne, sw, se] = node.children;
+        let children = [
+            self.clipped(nw, x, y, region),
+            self.clipped(ne, x + half, y, region),
+            self.clipped(sw, x, y + half, region),
+            self.clipped(se, x + half, y + half, region),
+        ];
+        self.join(children)
+    }
+
+    /// Lowest (or, if `highest`, highest) x (or, if `vertical`, y) of a live cell in `id` at (x, y)
+    fn extreme(&self, id: NodeId, x: i64, y: i64, vertical: bool, highest: bool) -> Option<i64> {
+        let node = &self.nodes[id as usize];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some(if vertical { y } else { x });
+        }
+        let half = 1i64 << (node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+        let quadrants = [(nw, x, y), (ne, x + half, y), (sw, x, y + half), (se, x + half, y + half)];
+        // The half on the searched side first; the other one only if that is empty
+        let (near, far) = match (vertical, highest) {
+            (false, false) => ([0, 2], [1, 3]),
+            (false, true) => ([1, 3], [0, 2]),
+            (true, false) => ([0, 1], [2, 3]),
+            (true, true) => ([2, 3], [0, 1]),
+        };
+        [near, far].into_iter().find_map(|side| {
+            let found = side.into_iter().filter_map(|i| {
+                let (child, child_x, child_y) = quadrants[i];
+                self.extreme(child, child_x, child_y, vertical, highest)
+            });
+            if highest { found.max() } else { found.min() }
+        })
+    }
+
+    /// Double the root around its center
+    fn expand(&mut self) {
+        let level = self.level();
+        let empty = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let half = 1i64 << (level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+
+    /// Halve the root while everything alive is in its center
+    fn shrink(&mut self) {
+        while self.level() > 3 && self.border_is_empty() {
+            self.root = self.centre(self.root);
+            let quarter = 1i64 << (self.level() - 1);
+            self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        }
+    }
+
+    /// Whether the outer ring of grandchildren of the root is empty
+    fn border_is_empty(&self) -> bool {
+        let centre = self.children(self.root).iter().enumerate()
+            .map(|(i, &child)| self.nodes[self.children(child)[3 - i] as usize].population)
+            .sum::<u64>();
+        centre == self.population()
+    }
+
+    /// The center half of a node, one level down
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+        let children = [self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]];
+        self.join(children)
+    }
+
+    /// The center of `id` (level L >= 2) advanced 2^k generations, k <= L - 2
+    fn successor(&mut self, id: NodeId, k: u8) -> NodeId {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, k)) {
+            return result;
+        }
+        let result = if node.level == 2 {
+            self.base_successor(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.children(nw);
+            let [ne_nw, _, ne_sw, ne_se] = self.children(ne);
+            let [sw_nw, sw_ne, _, sw_se] = self.children(sw);
+            let [se_nw, se_ne, se_sw, _] = self.children(se);
+            // Nine overlapping sub-squares, one level down
+            let grid = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+            // At full speed both halves advance time, otherwise only the second
+            let full_speed = k == node.level - 2;
+            let mut inner = [DEAD; 9];
+            for (i, &square) in grid.iter().enumerate() {
+                inner[i] = if full_speed { self.successor(square, k - 1) } else { self.centre(square) };
+            }
+            let next_k = if full_speed { k - 1 } else { k };
+            let quads = [
+                self.join([inner[0], inner[1], inner[3], inner[4]]),
+                self.join([inner[1], inner[2], inner[4], inner[5]]),
+                self.join([inner[3], inner[4], inner[6], inner[7]]),
+                self.join([inner[4], inner[5], inner[7], inner[8]]),
+            ];
+            let mut parts = [DEAD; 4];
+            for (i, &quad) in quads.iter().enumerate() {
+                parts[i] = self.successor(quad, next_k);
+            }
+            self.join(parts)
+        };
+        self.results.insert((id, k), result);
+        result
+    }
+
+    /// One generation of the center 2x2 of a 4x4 node
+    fn base_successor(&mut self, id: NodeId) -> NodeId {
+        let mut bits = [[false; 4]; 4];
+        for (q, &quad) in self.children(id).iter().enumerate() {
+            for (c, &cell) in self.children(quad).iter().enumerate() {
+                bits[(q / 2) * 2 + c / 2][(q % 2) * 2 + c % 2] = cell == ALIVE;
+            }
+        }
+        let mut next = [DEAD; 4];
+        for (i, (y, x)) in [(1, 1), (1, 2), (2, 1), (2, 2)].into_iter().enumerate() {
+            let neighbors = bits[y - 1..=y + 1].iter()
+                .flat_map(|row| &row[x - 1..=x + 1])
+                .filter(|&&alive| alive)
+                .count() as u32 - bits[y][x] as u32;
+            let alive = if bits[y][x] { self.rules.survives_on(neighbors) } else { self.rules.births_on(neighbors) };
+            next[i] = if alive { ALIVE } else { DEAD };
+        }
+        self.join(next)
+    }
+
+    fn visit_live(&self, id: NodeId, x: i64, y: i64, region: (i64, i64, i64, i64), visit: &mut impl FnMut(i64, i64)) {
+        let node = &self.nodes[id as usize];
+        let size = 1i64 << node.level;
+        let (x0, y0, x1, y1) = region;
+        if node.population == 0 || x >= x1 || y >= y1 || x.saturating_add(size) <= x0 || y.saturating_add(size) <= y0 {
+            return;
+        }
+        if node.level == 0 {
+            visit(x, y);
+            return;
+        }
+        let half = size / 2;
+        let [nw, ne, sw, se] = node.children;
+        self.visit_live(nw, x, y, region, visit);
+        self.visit_live(ne, x + half, y, region, visit);
+        self.visit_live(sw, x, y + half, region, visit);
+        self.visit_live(se, x + half, y + half, region, visit);
+    }
+}
+
+/// Reorder `cells` so the ones `first` holds for come first; returns how many there are
+fn partition(cells: &mut [(i64, i64)], first: impl Fn(&(i64, i64)) -> bool) -> usize {
+    let mut split = 0;
+    for i in 0..cells.len() {
+        if first(&cells[i]) {
+            cells.swap(split, i);
+            split += 1;
+        }
+    }
+    split
+}
+
+/// Advance a `width` x `height` grid with `boundary` (row-major, alive above 0.5) by up
+/// to `generations` generations with HashLife, with exactly the grid's result.
+///
+/// HashLife runs the plane, so the jump goes in chunks no longer than `grid_margin`
+/// allows, re-checked after each chunk. At a dead edge the pattern may touch, single
+/// generations are exact too once the cells born outside are clipped away. A generation
+/// that comes back means the grid cycles from then on, so whole periods are skipped.
+///
+/// Returns the cells reached (two-state) and the generations advanced. That falls short
+/// of `generations` once the pattern spans a torus, or after `MAX_EDGE_STEPS` single
+/// generations at a dead edge without a repetition; the rest is up to the caller.
+pub fn advance_grid(
+    rules: GameRules, cells: &[f32], width: u32, height: u32, boundary: Boundary, generations: u64,
+) -> Result<(Vec<f32>, u64), String> {
+    if cells.len() != width as usize * height as usize {
+        return Err(format!("Expected {} cells, got {}", width as usize * height as usize, cells.len()));
+    }
+    let live: Vec<(i64, i64)> = cells.iter().enumerate()
+        .filter(|&(_, &cell)| cell > 0.5)
+        .map(|(index, _)| ((index % width as usize) as i64, (index / width as usize) as i64))
+        .collect();
+    // On a torus the pattern is moved so it does not straddle the edges
+    let offset = match boundary {
+        Boundary::Dead => (0, 0),
+        Boundary::Torus => {
+            let mut columns = vec![false; width as usize];
+            let mut rows = vec![false; height as usize];
+            for &(x, y) in &live {
+                columns[x as usize] = true;
+                rows[y as usize] = true;
+            }
+            (torus_start(&columns) as i64, torus_start(&rows) as i64)
+        }
+    };
+    let shifted: Vec<(i64, i64)> = live.iter()
+        .map(|&(x, y)| ((x - offset.0).rem_euclid(width as i64), (y - offset.1).rem_euclid(height as i64)))
+        .collect();
+    let mut life = HashLife::from_cells(rules, &shifted)?;
+
+    let mut seen = FastMap::default();
+    let (mut advanced, mut edge_steps) = (0, 0);
+    while advanced < generations && edge_steps < MAX_EDGE_STEPS {
+        if let Some(earlier) = seen.insert(life.state_key(), advanced) {
+            let period = advanced - earlier;
+            advanced += (generations - advanced) / period * period;
+            seen.clear();
+            continue;
+        }
+        let margin = life.grid_margin(width, height, boundary);
+        let chunk = match boundary {
+            Boundary::Torus if margin == 0 => break,
+            Boundary::Torus => margin,
+            Boundary::Dead => margin.max(1),
+        }.min(generations - advanced);
+        life.step(chunk);
+        if boundary == Boundary::Dead {
+            life.clip(0, 0, width, height);
+        }
+        advanced += chunk;
+        edge_steps = if margin == 0 { edge_steps + 1 } else { 0 };
+    }
+
+    let mut grid = vec![0.0; width as usize * height as usize];
+    for (x, y) in life.live_cells() {
+        let x = (x + offset.0).rem_euclid(width as i64) as usize;
+        let y = (y + offset.1).rem_euclid(height as i64) as usize;
+        grid[y * width as usize + x] = 1.0;
+    }
+    Ok((grid, advanced))
+}
+
+/// Where a pattern starts along one axis of a torus: just past the longest run of
+/// empty lines, wrapping around
+fn torus_start(occupied: &[bool]) -> usize {
+    let Some(last) = occupied.iter().rposition(|&line| line) else {
+        return 0;
+    };
+    let (mut start, mut longest, mut run) = (0, 0, 0);
+    for step in 1..=occupied.len() {
+        let line = (last + step) % occupied.len();
+        if !occupied[line] {
+            run += 1;
+            continue;
+        }
+        if run >= longest {
+            (start, longest) = (line, run);
+        }
+        run = 0;
+    }
+    start
+}
@@ -0,0 +1,220 @@
+//! Population history for the graph panel and GPU snapshots of past generations.
+//!
+//! `PopulationHistory` keeps the statistics samples the GUI gathers: every sampled
+//! generation while the statistics log runs, else one per rendered frame (see
+//! `stats::StatsReducer`); `SnapshotRing` copies the grid into spare GPU buffers every
+//! few generations, so a generation picked on the graph can be restored without
+//! reading the grid back to the CPU.
+
+use crate::stats::{GenerationStats, SPECIES_COUNT};
+use std::collections::VecDeque;
+
+/// Samples kept by default (older ones scroll out)
+pub const DEFAULT_HISTORY_CAPACITY: usize = 20_000;
+
+/// Snapshots kept by default; together they cover `slots * interval` generations
+pub const DEFAULT_SNAPSHOT_SLOTS: usize = 16;
+
+/// What the population graph shows
+#[derive(Debug, Clone)]
+pub struct HistoryView {
+    /// Logarithmic population axis instead of a linear one
+    pub logarithmic: bool,
+    /// Lines drawn, per `stats::SPECIES_NAMES` entry
+    pub species: [bool; SPECIES_COUNT],
+    pub show_births: bool,
+    pub show_deaths: bool,
+    /// Generations visible; the graph scrolls with the newest sample
+    pub span: usize,
+    /// Generations between GPU snapshots used to restore a clicked generation
+    pub snapshot_interval: usize,
+}
+
+impl Default for HistoryView {
+    fn default() -> Self {
+        let mut species = [false; SPECIES_COUNT];
+        species[0] = true;
+        Self {
+            logarithmic: false,
+            species,
+            show_births: false,
+            show_deaths: false,
+            span: 2000,
+            snapshot_interval: 100,
+        }
+    }
+}
+
+/// The numbers of one generation the graph draws
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistorySample {
+    pub generation: usize,
+    /// Live cells per species (slot 0 = all, 1-7 = palette indices, see `stats::SPECIES_NAMES`)
+    pub population: [u32; SPECIES_COUNT],
+    pub births: u32,
+    pub deaths: u32,
+}
+
+impl From<&GenerationStats> for HistorySample {
+    fn from(stats: &GenerationStats) -> Self {
+        Self {
+            generation: stats.generation,
+            population: std::array::from_fn(|s| stats.species[s].population),
+            births: stats.species[0].births,
+            deaths: stats.species[0].deaths,
+        }
+    }
+}
+
+/// Samples in increasing generation order, at most `capacity` of them
+#[derive(Debug, Clone)]
+pub struct PopulationHistory {
+    samples: VecDeque<HistorySample>,
+    capacity: usize,
+}
+
+impl Default for PopulationHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl PopulationHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    /// Append a sample. Samples at or after its generation are dropped first, so
+    /// going back in time (restore, load) starts a new branch of the history.
+    pub fn push(&mut self, sample: HistorySample) {
+        while self.samples.back().is_some_and(|last| last.generation >= sample.generation) {
+            self.samples.pop_back();
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> &VecDeque<HistorySample> {
+        &self.samples
+    }
+
+    /// The sample whose generation is closest to `generation`
+    pub fn nearest(&self, generation: usize) -> Option<&HistorySample> {
+        self.samples.iter().min_by_key(|sample| sample.generation.abs_diff(generation))
+    }
+
+    /// Average generations between consecutive samples from `first` on, when there are two
+    pub fn mean_spacing(&self, first: usize) -> Option<f64> {
+        let mut visible = self.samples.iter().filter(|sample| sample.generation >= first);
+        let start = visible.next()?.generation;
+        let (count, end) = visible.fold((0, start), |(count, _), sample| (count + 1, sample.generation));
+        (count > 0).then(|| (end - start) as f64 / count as f64)
+    }
+
+    /// Drop samples after `generation` (after going back to it)
+    pub fn truncate_after(&mut self, generation: usize) {
+        while self.samples.back().is_some_and(|last| last.generation > generation) {
+            self.samples.pop_back();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Map a value to the plot's vertical unit range [0, 1] on a linear or logarithmic axis
+pub fn plot_fraction(value: f64, max: f64, logarithmic: bool) -> f64 {
+    if max <= 0.0 {
+        return 0.0;
+    }
+    let fraction = if logarithmic {
+        // ln(1 + v) keeps zero on the axis
+        value.max(0.0).ln_1p() / max.ln_1p()
+    } else {
+        value / max
+    };
+    fraction.clamp(0.0, 1.0)
+}
+
+/// Copies of the grid taken every `interval` generations into a ring of GPU buffers
+pub struct SnapshotRing {
+    pub interval: usize,
+    /// Generation held by each buffer, `None` while unused
+    slots: Vec<(Option<usize>, wgpu::Buffer)>,
+    /// `Simulation::edit_count` when the snapshots were taken
+    edits: u64,
+}
+
+impl SnapshotRing {
+    /// `slots` buffers of `size` bytes (one grid buffer each)
+    pub fn new(device: &wgpu::Device, size: u64, slots: usize, interval: usize) -> Self {
+        let slots = (0..slots.max(1))
+            .map(|i| (None, device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("Snapshot Buffer {}", i)),
+                size,
+                usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })))
+            .collect();
+        Self { interval: interval.max(1), slots, edits: 0 }
+    }
+
+    /// Forget every snapshot when the grid was edited since they were taken: stepping
+    /// them forward would no longer reach the generations shown in the history
+    pub fn sync_edits(&mut self, edit_count: u64) {
+        if edit_count != self.edits {
+            self.edits = edit_count;
+            for (held, _) in &mut self.slots {
+                *held = None;
+            }
+        }
+    }
+
+    /// Whether `generation` should be snapshotted: at least `interval` generations
+    /// after the newest snapshot (or none taken yet)
+    pub fn is_due(&self, generation: usize) -> bool {
+        match self.newest() {
+            Some(newest) => generation >= newest + self.interval,
+            None => true,
+        }
+    }
+
+    fn newest(&self) -> Option<usize> {
+        self.slots.iter().filter_map(|(generation, _)| *generation).max()
+    }
+
+    /// Record a copy of `source` (holding `generation`) over the oldest snapshot
+    pub fn encode_snapshot(&mut self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::Buffer, generation: usize) {
+        let slot = self.slots.iter_mut()
+            .min_by_key(|(held, _)| held.map_or(0, |held| held + 1))
+            .expect("at least one snapshot slot");
+        encoder.copy_buffer_to_buffer(source, 0, &slot.1, 0, slot.1.size());
+        slot.0 = Some(generation);
+    }
+
+    /// The newest snapshot at or before `generation`
+    pub fn latest_before(&self, generation: usize) -> Option<(usize, &wgpu::Buffer)> {
+        self.slots.iter()
+            .filter_map(|(held, buffer)| held.filter(|&held| held <= generation).map(|held| (held, buffer)))
+            .max_by_key(|(held, _)| *held)
+    }
+
+    /// Forget snapshots after `generation`; they belong to a future that was abandoned
+    pub fn discard_after(&mut self, generation: usize) {
+        for (held, _) in &mut self.slots {
+            if held.is_some_and(|held| held > generation) {
+                *held = None;
+            }
+        }
+    }
+
+    /// Generations held, oldest first
+    pub fn generations(&self) -> Vec<usize> {
+        let mut generations: Vec<usize> = self.slots.iter().filter_map(|(held, _)| *held).collect();
+        generations.sort_unstable();
+        generations
+    }
+}
@@ -1,5 +1,5 @@
 use crate::state::State;
-use crate::render::{RenderParams, MIN_ZOOM, MAX_ZOOM, ZOOM_FACTOR_STEP};
+use crate::render::{MIN_ZOOM, MAX_ZOOM, ZOOM_FACTOR_STEP};
 use winit::{
     dpi::PhysicalPosition,
     event::{MouseButton, ElementState},
@@ -49,11 +49,7 @@ pub fn handle_zoom(state: &mut State, delta: f32) {
 
     log::info!("Zoom: {:.2}, Offset: [{:.1}, {:.1}]", state.zoom, state.view_offset[0], state.view_offset[1]);
 
-    state.queue.write_buffer(&state.render_param_buffer, 0, bytemuck::bytes_of(&RenderParams {
-        zoom: state.zoom,
-        view_offset: state.view_offset,
-        _padding: 0.0,
-    }));
+    state.write_render_params();
 }
 
 pub fn handle_mouse_input(state: &mut State, button: MouseButton, element_state: ElementState) {
@@ -139,11 +135,7 @@ pub fn handle_cursor_move(state: &mut State, position: PhysicalPosition<f64>) {
                 // Ensure we don't pan outside the grid
                 clamp_offset(state);
 
-                state.queue.write_buffer(&state.render_param_buffer, 0, bytemuck::bytes_of(&RenderParams {
-                    zoom: state.zoom,
-                    view_offset: state.view_offset,
-                    _padding: 0.0,
-                }));
+                state.write_render_params();
             }
         }
         
@@ -162,29 +154,15 @@ pub fn handle_cursor_move(state: &mut State, position: PhysicalPosition<f64>) {
             }
             
             // If dragging, calculate speed and apply action with speed factor
-            if state.is_dragging {
-                if let Some(last_pos) = state.last_mouse_pos {
-                    let dx = position.x - last_pos.x;
-                    let dy = position.y - last_pos.y;
-                    let distance = (dx * dx + dy * dy).sqrt();
-                    
-                    // Calculate time since last action
-                    let now = std::time::Instant::now();
-                    let elapsed = if let Some(last_time) = state.last_action_time {
-                        now.duration_since(last_time).as_secs_f64()
-                    } else {
-                        0.016 // Default to ~60fps timing if no last time
-                    };
-                    
-                    // Speed in pixels per second
-                    let speed = if elapsed > 0.0 { distance / elapsed } else { 0.0 };
-                    
-                    // Apply action with speed factor
-                    apply_cursor_mode_action(state, position, true);
-                    
-                    // Update last action time
-                    state.last_action_time = Some(now);
-                }
+            // (drag speed is derived from last_mouse_pos/last_action_time inside the action)
+            if state.is_dragging && state.last_mouse_pos.is_some() {
+                let now = std::time::Instant::now();
+                
+                // Apply action with speed factor
+                apply_cursor_mode_action(state, position, true);
+                
+                // Update last action time
+                state.last_action_time = Some(now);
             }
         }
         
@@ -214,6 +192,25 @@ fn apply_cursor_mode_action(state: &mut State, position: PhysicalPosition<f64>,
         }
     }
     
+    // Selecting follows the cursor without any rate limit
+    if state.cursor_mode == CursorMode::Select {
+        state.update_selection(position, is_dragging);
+        return;
+    }
+    // Inspecting pins a cell on click and ignores drags
+    if state.cursor_mode == CursorMode::Inspect {
+        if !is_dragging {
+            state.toggle_pinned_cell(position);
+        }
+        return;
+    }
+    if state.cursor_mode == CursorMode::SelectObject {
+        if !is_dragging {
+            state.select_object(position);
+        }
+        return;
+    }
+
     // Always perform action on click (not dragging)
     if !is_dragging {
         perform_action(state, position, state.cursor_mode);
@@ -232,43 +229,8 @@ fn apply_cursor_mode_action(state: &mut State, position: PhysicalPosition<f64>,
                 true
             }
         },
-        CursorMode::PlaceGlider => {
-            if let Some(last_time) = state.last_glider_time {
-                calculate_should_perform(last_time, now, drag_speed)
-            } else {
-                true
-            }
-        },
-        CursorMode::PlaceLWSS => {
-            if let Some(last_time) = state.last_lwss_time {
-                calculate_should_perform(last_time, now, drag_speed)
-            } else {
-                true
-            }
-        },
-        CursorMode::PlacePulsar => {
-            if let Some(last_time) = state.last_pulsar_time {
-                calculate_should_perform(last_time, now, drag_speed)
-            } else {
-                true
-            }
-        },
-        CursorMode::PlaceGosperGun => {
-            if let Some(last_time) = state.last_gosper_gun_time {
-                calculate_should_perform(last_time, now, drag_speed)
-            } else {
-                true
-            }
-        },
-        CursorMode::PlacePentadecathlon => {
-            if let Some(last_time) = state.last_pentadecathlon_time {
-                calculate_should_perform(last_time, now, drag_speed)
-            } else {
-                true
-            }
-        },
-        CursorMode::PlaceSimkinGun => {
-            if let Some(last_time) = state.last_simkin_gun_time {
+        CursorMode::PlacePattern => {
+            if let Some(last_time) = state.last_pattern_time {
                 calculate_should_perform(last_time, now, drag_speed)
             } else {
                 true
@@ -288,6 +250,7 @@ fn apply_cursor_mode_action(state: &mut State, position: PhysicalPosition<f64>,
                 true
             }
         },
+        CursorMode::Select | CursorMode::Inspect | CursorMode::SelectObject => true,
     };
     
     if should_perform {
@@ -296,14 +259,10 @@ fn apply_cursor_mode_action(state: &mut State, position: PhysicalPosition<f64>,
         // Update the appropriate timing field
         match state.cursor_mode {
             CursorMode::Paint => state.last_paint_time = Some(now),
-            CursorMode::PlaceGlider => state.last_glider_time = Some(now),
-            CursorMode::PlaceLWSS => state.last_lwss_time = Some(now),
-            CursorMode::PlacePulsar => state.last_pulsar_time = Some(now),
-            CursorMode::PlaceGosperGun => state.last_gosper_gun_time = Some(now),
-            CursorMode::PlacePentadecathlon => state.last_pentadecathlon_time = Some(now),
-            CursorMode::PlaceSimkinGun => state.last_simkin_gun_time = Some(now),
+            CursorMode::PlacePattern => state.last_pattern_time = Some(now),
             CursorMode::ClearArea => state.last_clear_time = Some(now),
             CursorMode::RandomFill => state.last_random_time = Some(now),
+            CursorMode::Select | CursorMode::Inspect | CursorMode::SelectObject => {}
         }
         
         // Log speed and action for debugging
@@ -347,23 +306,8 @@ fn perform_action(state: &mut State, position: PhysicalPosition<f64>, mode: crat
         CursorMode::Paint => {
             state.paint_cell(position);
         },
-        CursorMode::PlaceGlider => {
-            state.place_glider(position);
-        },
-        CursorMode::PlaceLWSS => {
-            state.place_lwss(position);
-        },
-        CursorMode::PlacePulsar => {
-            state.place_pulsar(position);
-        },
-        CursorMode::PlaceGosperGun => {
-            state.place_gosper_glider_gun(position);
-        },
-        CursorMode::PlacePentadecathlon => {
-            state.place_pentadecathlon(position);
-        },
-        CursorMode::PlaceSimkinGun => {
-            state.place_simkin_glider_gun(position);
+        CursorMode::PlacePattern => {
+            state.place_pattern(position);
         },
         CursorMode::ClearArea => {
             state.clear_area(position, 15);
@@ -371,6 +315,15 @@ fn perform_action(state: &mut State, position: PhysicalPosition<f64>, mode: crat
         CursorMode::RandomFill => {
             state.random_fill(position, 20, 0.4);
         },
+        CursorMode::Select => {
+            state.update_selection(position, false);
+        },
+        CursorMode::Inspect => {
+            state.toggle_pinned_cell(position);
+        },
+        CursorMode::SelectObject => {
+            state.select_object(position);
+        },
     }
 }
 
@@ -385,10 +338,15 @@ pub fn handle_cursor_left(state: &mut State) {
     state.show_submenu = false;
 }
 
-// Clamp view_offset so the visible area never moves outside the grid
-fn clamp_offset(state: &mut State) {
-    let max_x = (state.grid_width as f32 * state.zoom) - state.size.width as f32;
-    let max_y = (state.grid_height as f32 * state.zoom) - state.size.height as f32;
+// Clamp view_offset so the visible area never moves outside the grid.
+// The infinite plane has no edges; the offset is only folded into the view origin.
+pub fn clamp_offset(state: &mut State) {
+    if state.universe.is_some() {
+        state.rebase_view();
+        return;
+    }
+    let max_x = (state.simulation.width as f32 * state.zoom) - state.size.width as f32;
+    let max_y = (state.simulation.height as f32 * state.zoom) - state.size.height as f32;
 
     // If the grid is smaller than the window along an axis, limit stays 0
     let max_x = max_x.max(0.0);
@@ -427,9 +385,5 @@ pub fn set_exact_zoom(state: &mut State, new_zoom: f32) {
     log::info!("Zoom set to exactly: {:.2}, Offset: [{:.1}, {:.1}]", 
                state.zoom, state.view_offset[0], state.view_offset[1]);
     
-    state.queue.write_buffer(&state.render_param_buffer, 0, bytemuck::bytes_of(&RenderParams {
-        zoom: state.zoom,
-        view_offset: state.view_offset,
-        _padding: 0.0,
-    }));
+    state.write_render_params();
 } 
\ No newline at end of file
@@ -0,0 +1,361 @@
+//! Cell inspector: the cells around one grid cell, read back from the GPU without
+//! copying the grid.
+//!
+//! `CellQuery` copies a 5x5 window (and the cell's age) into a tiny staging buffer
+//! and maps it asynchronously, so hovering costs a frame of latency instead of a
+//! stall. `CellAges` keeps a per-cell age buffer up to date on the GPU while the
+//! inspector is open (`inspector.wgsl`).
+
+use crate::cpu::{dominant_color, is_color};
+use crate::rules::{Boundary, GameRules};
+use crate::readback::ReadbackRing;
+use crate::simulation::Simulation;
+use crate::stats::SPECIES_NAMES;
+use std::fmt;
+
+/// Cells read on each side of the inspected one
+pub const WINDOW_RADIUS: i64 = 2;
+pub const WINDOW_SIDE: usize = 2 * WINDOW_RADIUS as usize + 1;
+const WINDOW_CELLS: usize = WINDOW_SIDE * WINDOW_SIDE;
+const AGE_WORKGROUP_SIZE: u32 = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct AgeParams {
+    width: u32,
+    height: u32,
+    _padding: [u32; 2],
+}
+
+/// Name of a cell state value (`stats::SPECIES_NAMES`, or "dead")
+pub fn state_name(value: f32) -> &'static str {
+    match value.round() as usize {
+        0 => "dead",
+        state if state < SPECIES_NAMES.len() => SPECIES_NAMES[state],
+        _ => "unknown",
+    }
+}
+
+/// What a cell becomes in the next generation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NextState {
+    StaysDead,
+    Born(f32),
+    Survives(f32),
+    Dies,
+    /// Dies unless the lucky rule saves it, with `chance`, as `lucky`
+    DiesOrLucky { chance: f32, lucky: f32 },
+}
+
+impl fmt::Display for NextState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NextState::StaysDead => write!(f, "stays dead"),
+            NextState::Born(value) => write!(f, "born ({})", state_name(*value)),
+            NextState::Survives(_) => write!(f, "survives"),
+            NextState::Dies => write!(f, "dies"),
+            NextState::DiesOrLucky { chance, lucky } => {
+                write!(f, "dies ({:.0}% chance to survive as {})", chance * 100.0, state_name(*lucky))
+            }
+        }
+    }
+}
+
+/// Cells around (x, y) at one generation
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellWindow {
+    pub x: u32,
+    pub y: u32,
+    pub generation: usize,
+    /// Row-major, (x, y) in the middle; None past a dead edge of the grid
+    pub cells: [Option<f32>; WINDOW_CELLS],
+    /// Generations the cell has been alive, if ages are tracked
+    pub age: Option<u32>,
+    /// The cell was alive for as long as ages have been tracked, so it may be older
+    pub age_is_lower_bound: bool,
+}
+
+impl CellWindow {
+    /// Value of the cell `(dx, dy)` away from the inspected one
+    pub fn cell(&self, dx: i64, dy: i64) -> Option<f32> {
+        if dx.abs() > WINDOW_RADIUS || dy.abs() > WINDOW_RADIUS {
+            return None;
+        }
+        self.cells[((dy + WINDOW_RADIUS) * WINDOW_SIDE as i64 + dx + WINDOW_RADIUS) as usize]
+    }
+
+    pub fn value(&self) -> f32 {
+        self.cell(0, 0).unwrap_or(0.0)
+    }
+
+    /// Values of the live neighbors
+    fn live_neighbors(&self) -> Vec<f32> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(|(dx, dy)| self.cell(dx, dy))
+            .filter(|&value| value > 0.5)
+            .collect()
+    }
+
+    pub fn neighbors(&self) -> u32 {
+        self.live_neighbors().len() as u32
+    }
+
+    /// Next state under `rules` (see `cpu::CpuSimulation::step`); `lucky_chance` is
+    /// the chance of the lucky rule when it is enabled
+    pub fn next_state(&self, rules: GameRules, lucky_chance: Option<f32>) -> NextState {
+        let value = self.value();
+        let neighbors = self.live_neighbors();
+        let count = neighbors.len() as u32;
+        if value <= 0.5 {
+            return match rules.births_on(count) {
+                true => NextState::Born(dominant_color(&neighbors)),
+                false => NextState::StaysDead,
+            };
+        }
+        if rules.survives_on(count) {
+            return NextState::Survives(value);
+        }
+        match lucky_chance {
+            Some(chance) if chance > 0.0 => {
+                let lucky = if is_color(value, 1.0) || is_color(value, 2.0) || is_color(value, 3.0) { 3.0 } else { value };
+                NextState::DiesOrLucky { chance, lucky }
+            }
+            _ => NextState::Dies,
+        }
+    }
+}
+
+/// Ages of every cell of a `Simulation`, updated on the GPU after each step.
+pub struct CellAges {
+    pipeline: wgpu::ComputePipeline,
+    /// Bind groups reading `grid_buffers[parity]`
+    bind_groups: [wgpu::BindGroup; 2],
+    buffer: wgpu::Buffer,
+    /// Generation tracking started at; older cells show this as a lower bound
+    pub since: usize,
+    /// Generation the ages belong to
+    pub generation: usize,
+    width: u32,
+    height: u32,
+}
+
+impl CellAges {
+    /// Ages of `simulation`'s grid buffers; create new ones when they are replaced
+    pub fn new(simulation: &Simulation) -> Result<Self, String> {
+        if simulation.chunks().is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        let device = &simulation.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Cell Age Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("inspector.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Cell Age Pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "main",
+        });
+        let params = AgeParams { width: simulation.width, height: simulation.height, _padding: [0; 2] };
+        let param_buffer = wgpu::util::DeviceExt::create_buffer_init(device.as_ref(), &wgpu::util::BufferInitDescriptor {
+            label: Some("Cell Age Parameters"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cell Ages"),
+            size: simulation.cell_count() as u64 * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let layout = pipeline.get_bind_group_layout(0);
+        let bind_groups = [0, 1].map(|parity| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("Cell Age Bind Group ({})", parity)),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: param_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: simulation.grid_buffers[parity].as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: buffer.as_entire_binding() },
+            ],
+        }));
+        Ok(Self {
+            pipeline,
+            bind_groups,
+            buffer,
+            since: simulation.generation,
+            generation: simulation.generation,
+            width: simulation.width,
+            height: simulation.height,
+        })
+    }
+
+    /// Age every cell of `generation` (held in `grid_buffers[generation % 2]`), which
+    /// must directly follow `self.generation`
+    pub fn encode_update(&mut self, encoder: &mut wgpu::CommandEncoder, generation: usize) {
+        self.generation = generation;
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Cell Age Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_groups[generation % 2], &[]);
+        pass.dispatch_workgroups(self.width.div_ceil(AGE_WORKGROUP_SIZE), self.height.div_ceil(AGE_WORKGROUP_SIZE), 1);
+    }
+}
+
+/// Where a query was taken and which window cells lie past a dead edge
+struct PendingQuery {
+    x: u32,
+    y: u32,
+    generation: usize,
+    outside: [bool; WINDOW_CELLS],
+    ages_since: Option<usize>,
+}
+
+/// Reads the 5x5 window around a cell back asynchronously, one query at a time.
+pub struct CellQuery {
+    ring: ReadbackRing<PendingQuery>,
+    /// Newest window read back
+    pub latest: Option<CellWindow>,
+}
+
+impl CellQuery {
+    pub fn new(device: &wgpu::Device) -> Self {
+        // The window, then the age of the middle cell
+        let size = ((WINDOW_CELLS + 1) * std::mem::size_of::<u32>()) as u64;
+        Self { ring: ReadbackRing::new(device, "Cell Query Staging Buffer", size, 1), latest: None }
+    }
+
+    /// No query is waiting to be read back
+    pub fn is_free(&self) -> bool {
+        self.ring.has_free_slot()
+    }
+
+    /// Copy the window around (x, y) of the current generation (and its age) into the
+    /// staging buffer. Returns false if a query is still in flight or (x, y) is off the grid.
+    pub fn encode(&mut self, encoder: &mut wgpu::CommandEncoder, simulation: &Simulation, ages: Option<&CellAges>, x: u32, y: u32) -> bool {
+        if !self.is_free() || x >= simulation.width || y >= simulation.height || simulation.chunks().is_some() {
+            return false;
+        }
+        let staging = self.ring.staging(0);
+        let cell_size = std::mem::size_of::<f32>() as u64;
+        let wrap = |position: i64, length: u32| match simulation.boundary {
+            Boundary::Torus => Some(position.rem_euclid(length as i64) as u64),
+            Boundary::Dead => (0..length as i64).contains(&position).then_some(position as u64),
+        };
+        let mut outside = [false; WINDOW_CELLS];
+        for dy in -WINDOW_RADIUS..=WINDOW_RADIUS {
+            for dx in -WINDOW_RADIUS..=WINDOW_RADIUS {
+                let slot = ((dy + WINDOW_RADIUS) * WINDOW_SIDE as i64 + dx + WINDOW_RADIUS) as usize;
+                match (wrap(x as i64 + dx, simulation.width), wrap(y as i64 + dy, simulation.height)) {
+                    (Some(cx), Some(cy)) => {
+                        let index = cy * simulation.width as u64 + cx;
+                        encoder.copy_buffer_to_buffer(simulation.current_buffer(), index * cell_size, staging, slot as u64 * cell_size, cell_size);
+                    }
+                    _ => outside[slot] = true,
+                }
+            }
+        }
+        // Ages that missed generations (e.g. a jump) are left out
+        let ages = ages.filter(|ages| ages.generation == simulation.generation);
+        if let Some(ages) = ages {
+            let index = y as u64 * simulation.width as u64 + x as u64;
+            encoder.copy_buffer_to_buffer(&ages.buffer, index * cell_size, staging, WINDOW_CELLS as u64 * cell_size, cell_size);
+        }
+        self.ring.fill(0, PendingQuery {
+            x,
+            y,
+            generation: simulation.generation,
+            outside,
+            ages_since: ages.map(|ages| ages.since),
+        });
+        true
+    }
+
+    /// Must be called after the encoder holding the query has been submitted
+    pub fn after_submit(&mut self) {
+        self.ring.after_submit();
+    }
+
+    /// Collect the query once it has arrived (non-blocking); returns the new window
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<&CellWindow> {
+        let window = self.ring.poll(device, CellWindow::decode).pop()?;
+        Some(self.latest.insert(window))
+    }
+
+    /// Block until the query in flight has arrived
+    pub fn wait(&mut self, device: &wgpu::Device) -> Option<&CellWindow> {
+        let window = self.ring.wait_oldest(device, CellWindow::decode).pop()?;
+        Some(self.latest.insert(window))
+    }
+}
+
+impl CellWindow {
+    fn decode(pending: PendingQuery, data: &[u8]) -> Self {
+        let words: &[u32] = bytemuck::cast_slice(data);
+        let mut cells = [None; WINDOW_CELLS];
+        for (slot, cell) in cells.iter_mut().enumerate() {
+            if !pending.outside[slot] {
+                *cell = Some(f32::from_bits(words[slot]));
+            }
+        }
+        let age = pending.ages_since.map(|_| words[WINDOW_CELLS]);
+        let tracked = pending.ages_since.map(|since| pending.generation.saturating_sub(since));
+        Self {
+            x: pending.x,
+            y: pending.y,
+            generation: pending.generation,
+            cells,
+            age,
+            age_is_lower_bound: matches!((age, tracked), (Some(age), Some(tracked)) if age as usize >= tracked)
+                && cells[WINDOW_CELLS / 2].is_some_and(|value| value > 0.5),
+        }
+    }
+}
+
+/// The GUI's inspector: queries for the hovered and the pinned cell, and cell ages
+/// while it is open.
+pub struct CellInspector {
+    pub hovered: CellQuery,
+    pub pinned: CellQuery,
+    pub ages: Option<CellAges>,
+}
+
+impl CellInspector {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self { hovered: CellQuery::new(device), pinned: CellQuery::new(device), ages: None }
+    }
+
+    /// Start tracking ages, over again if they missed generations or the grid changed size.
+    /// Call before stepping.
+    pub fn sync_ages(&mut self, simulation: &Simulation) {
+        let current = self.ages.as_ref().is_some_and(|ages| {
+            ages.generation == simulation.generation && (ages.width, ages.height) == (simulation.width, simulation.height)
+        });
+        if !current {
+            self.ages = CellAges::new(simulation).map_err(|e| log::warn!("Cell ages unavailable: {}", e)).ok();
+        }
+    }
+
+    /// Collect arrived windows and query the current generation around `hovered` and `pinned`
+    pub fn update(&mut self, simulation: &Simulation, hovered: Option<(u32, u32)>, pinned: Option<(u32, u32)>) {
+        self.hovered.poll(&simulation.device);
+        self.pinned.poll(&simulation.device);
+        let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Cell Query Encoder"),
+        });
+        let mut encoded = false;
+        for (query, cell) in [(&mut self.hovered, hovered), (&mut self.pinned, pinned)] {
+            if let Some((x, y)) = cell {
+                encoded |= query.encode(&mut encoder, simulation, self.ages.as_ref(), x, y);
+            }
+        }
+        if encoded {
+            simulation.queue.submit(Some(encoder.finish()));
+            self.hovered.after_submit();
+            self.pinned.after_submit();
+        }
+    }
+}
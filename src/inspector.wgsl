@@ -0,0 +1,25 @@
+// Per-cell ages for the cell inspector: generations a cell has been alive.
+// Runs after every step while the inspector is open.
+
+struct AgeParams {
+    width: u32,
+    height: u32,
+    _padding: vec2<u32>,
+}
+
+@group(0) @binding(0) var<uniform> params: AgeParams;
+@group(0) @binding(1) var<storage, read> cells: array<f32>;
+@group(0) @binding(2) var<storage, read_write> ages: array<u32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= params.width || global_id.y >= params.height) {
+        return;
+    }
+    let index = global_id.y * params.width + global_id.x;
+    if (cells[index] > 0.5) {
+        ages[index] = min(ages[index], 0xFFFFFFFEu) + 1u;
+    } else {
+        ages[index] = 0u;
+    }
+}
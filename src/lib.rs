@@ -2,4 +2,25 @@ pub mod state;
 pub mod compute;
 pub mod render;
 pub mod input;
-pub mod rules; 
\ No newline at end of file
+pub mod rules;
+pub mod export;
+pub mod capture;
+pub mod readback;
+pub mod recording;
+pub mod library;
+pub mod stats;
+pub mod simulation;
+pub mod cpu;
+pub mod hashlife;
+pub mod tiled;
+pub mod packed;
+pub mod activity;
+pub mod chunked;
+pub mod resize;
+pub mod history;
+pub mod period;
+pub mod census;
+pub mod inspector;
+pub mod components;
+pub mod tracker;
+
@@ -0,0 +1,428 @@
+//! Pattern library: RLE / plaintext (.cells) parsing and a directory-backed
+//! collection of patterns that can be used as placement stamps.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the default user pattern directory
+pub const PATTERN_DIR_ENV: &str = "GPU_LIFE_PATTERN_DIR";
+/// Directory scanned for user patterns when no override is given
+pub const DEFAULT_PATTERN_DIR: &str = "patterns";
+
+/// Patterns shipped with the binary (category, file name, contents)
+const BUILTIN_PATTERNS: &[(&str, &str, &str)] = &[
+    ("Spaceships", "glider.rle", include_str!("../assets/patterns/spaceships/glider.rle")),
+    ("Spaceships", "lwss.rle", include_str!("../assets/patterns/spaceships/lwss.rle")),
+    ("Oscillators", "blinker.rle", include_str!("../assets/patterns/oscillators/blinker.rle")),
+    ("Oscillators", "toad.rle", include_str!("../assets/patterns/oscillators/toad.rle")),
+    ("Oscillators", "pulsar.rle", include_str!("../assets/patterns/oscillators/pulsar.rle")),
+    ("Oscillators", "pentadecathlon.rle", include_str!("../assets/patterns/oscillators/pentadecathlon.rle")),
+    ("Still Lifes", "block.rle", include_str!("../assets/patterns/still_lifes/block.rle")),
+    ("Guns", "gosper_glider_gun.rle", include_str!("../assets/patterns/guns/gosper_glider_gun.rle")),
+    ("Guns", "simkin_glider_gun.rle", include_str!("../assets/patterns/guns/simkin_glider_gun.rle")),
+];
+
+/// A pattern decoded from an RLE or plaintext file
+#[derive(Debug, Clone, Default)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub comments: Vec<String>,
+    /// Rule string from the RLE header, if any (e.g. "B3/S23")
+    pub rule: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// Live cells relative to the top-left corner of the bounding box
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl Pattern {
+    /// Build a pattern from arbitrary live cells, normalized to the bounding box origin
+    pub fn from_cells(cells: impl IntoIterator<Item = (i32, i32)>) -> Self {
+        let mut cells: Vec<(i32, i32)> = cells.into_iter().collect();
+        let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let max_x = cells.iter().map(|c| c.0).max().unwrap_or(-1);
+        let max_y = cells.iter().map(|c| c.1).max().unwrap_or(-1);
+        for cell in cells.iter_mut() {
+            cell.0 -= min_x;
+            cell.1 -= min_y;
+        }
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        Self {
+            width: (max_x - min_x + 1).max(0) as u32,
+            height: (max_y - min_y + 1).max(0) as u32,
+            cells,
+            ..Default::default()
+        }
+    }
+
+    /// Downsample the pattern into a mask at most `max_size` pixels per side.
+    /// A pixel is set if any cell it covers is alive. Returns (width, height, mask).
+    pub fn thumbnail(&self, max_size: u32) -> (u32, u32, Vec<bool>) {
+        let block = self.width.max(self.height).div_ceil(max_size.max(1)).max(1);
+        let width = self.width.div_ceil(block).max(1);
+        let height = self.height.div_ceil(block).max(1);
+        let mut mask = vec![false; (width * height) as usize];
+        for &(x, y) in &self.cells {
+            let (px, py) = (x as u32 / block, y as u32 / block);
+            mask[(py * width + px) as usize] = true;
+        }
+        (width, height, mask)
+    }
+
+    /// Live cells of a row-major grid (value > 0.5), normalized to their bounding box
+    pub fn from_grid(cells: &[f32], width: u32) -> Self {
+        let width = width.max(1) as usize;
+        Self::from_cells(cells.iter().enumerate()
+            .filter(|(_, &value)| value > 0.5)
+            .map(|(i, _)| ((i % width) as i32, (i / width) as i32)))
+    }
+
+    /// Group cells into horizontal runs (x, y, length), one per contiguous row segment
+    pub fn row_runs(&self) -> Vec<(i32, i32, u32)> {
+        let mut runs: Vec<(i32, i32, u32)> = Vec::new();
+        for &(x, y) in &self.cells {
+            match runs.last_mut() {
+                Some((rx, ry, len)) if *ry == y && *rx + *len as i32 == x => *len += 1,
+                _ => runs.push((x, y, 1)),
+            }
+        }
+        runs
+    }
+}
+
+/// Parse a pattern, picking the format from the file extension (".cells" is plaintext, anything else RLE)
+pub fn parse_pattern(path: &Path, text: &str) -> Result<Pattern, String> {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()) {
+        Some(ext) if ext == "cells" => parse_cells(text),
+        _ => parse_rle(text),
+    }
+}
+
+/// Parse Golly/LifeWiki run-length encoded patterns.
+/// Any non-dead state letter is treated as alive.
+pub fn parse_rle(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0i32, 0i32);
+    let mut run: Option<i32> = None;
+    let mut header_seen = false;
+    let mut finished = false;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if finished || line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next().unwrap_or(' ');
+            let body = chars.as_str().trim().to_string();
+            match kind {
+                'N' => pattern.name = Some(body),
+                'C' | 'c' | 'O' => pattern.comments.push(body),
+                _ => {}
+            }
+            continue;
+        }
+        if !header_seen && line.starts_with('x') {
+            header_seen = true;
+            for field in line.split(',') {
+                let mut parts = field.splitn(2, '=');
+                let key = parts.next().unwrap_or("").trim();
+                let value = parts.next().unwrap_or("").trim();
+                if key == "rule" {
+                    pattern.rule = Some(value.to_string());
+                }
+            }
+            continue;
+        }
+
+        for ch in line.chars() {
+            match ch {
+                '0'..='9' => {
+                    let digit = ch as i32 - '0' as i32;
+                    run = Some(run.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                }
+                'b' | '.' => {
+                    x += run.take().unwrap_or(1);
+                }
+                '$' => {
+                    y += run.take().unwrap_or(1);
+                    x = 0;
+                }
+                '!' => {
+                    finished = true;
+                    break;
+                }
+                c if c.is_ascii_alphabetic() => {
+                    let count = run.take().unwrap_or(1);
+                    for _ in 0..count {
+                        cells.push((x, y));
+                        x += 1;
+                    }
+                }
+                c if c.is_whitespace() => {}
+                other => return Err(format!("Unexpected character '{}' in RLE data", other)),
+            }
+        }
+    }
+
+    if !header_seen {
+        return Err("Missing RLE header line (x = ..., y = ...)".to_string());
+    }
+    let parsed = Pattern::from_cells(cells);
+    Ok(Pattern { cells: parsed.cells, width: parsed.width, height: parsed.height, ..pattern })
+}
+
+/// Encode a pattern as RLE: comments, header (with the rule if known), data wrapped at 70 columns
+pub fn to_rle(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("#N {}\n", name));
+    }
+    for comment in &pattern.comments {
+        out.push_str(&format!("#C {}\n", comment));
+    }
+    out.push_str(&format!("x = {}, y = {}", pattern.width, pattern.height));
+    if let Some(rule) = &pattern.rule {
+        out.push_str(&format!(", rule = {}", rule));
+    }
+    out.push('\n');
+
+    // Tokens like "3o", "2b", "$"; trailing dead cells of a row are dropped
+    let mut tokens: Vec<String> = Vec::new();
+    let push = |tokens: &mut Vec<String>, count: u32, tag: char| {
+        if count > 0 {
+            tokens.push(if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) });
+        }
+    };
+    let (mut row, mut col) = (0i32, 0i32);
+    let mut pending_rows = 0u32;
+    for (x, y, len) in pattern.row_runs() {
+        if y != row {
+            pending_rows += (y - row) as u32;
+            row = y;
+            col = 0;
+        }
+        push(&mut tokens, pending_rows, '$');
+        pending_rows = 0;
+        push(&mut tokens, (x - col) as u32, 'b');
+        push(&mut tokens, len, 'o');
+        col = x + len as i32;
+    }
+    tokens.push("!".to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if line.len() + token.len() > 70 {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+/// Parse plaintext (.cells) patterns: '!' comment lines, 'O' or '*' alive, anything else dead
+pub fn parse_cells(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    let mut y = 0;
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_string()),
+                None => pattern.comments.push(comment.to_string()),
+            }
+            continue;
+        }
+        for (x, ch) in line.chars().enumerate() {
+            if ch == 'O' || ch == '*' {
+                cells.push((x as i32, y));
+            }
+        }
+        y += 1;
+    }
+    let parsed = Pattern::from_cells(cells);
+    Ok(Pattern { cells: parsed.cells, width: parsed.width, height: parsed.height, ..pattern })
+}
+
+/// One pattern in the library
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub name: String,
+    pub category: String,
+    /// File the pattern came from; `None` for bundled patterns
+    pub source: Option<PathBuf>,
+    pub pattern: Pattern,
+}
+
+impl LibraryEntry {
+    /// Whether the entry matches a case-insensitive search string (name, category or comments)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name.to_lowercase().contains(&query)
+            || self.category.to_lowercase().contains(&query)
+            || self.pattern.comments.iter().any(|c| c.to_lowercase().contains(&query))
+    }
+}
+
+/// Bundled patterns plus everything found in a user directory
+#[derive(Debug, Clone, Default)]
+pub struct PatternLibrary {
+    pub directory: PathBuf,
+    pub entries: Vec<LibraryEntry>,
+    /// Files that could not be read or parsed during the last scan
+    pub errors: Vec<String>,
+}
+
+impl PatternLibrary {
+    /// The directory used when none is configured: `$GPU_LIFE_PATTERN_DIR` or `./patterns`
+    pub fn default_directory() -> PathBuf {
+        std::env::var_os(PATTERN_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_PATTERN_DIR))
+    }
+
+    /// Only the bundled patterns
+    pub fn builtin() -> Self {
+        let mut library = Self::default();
+        for (category, file_name, text) in BUILTIN_PATTERNS {
+            match parse_rle(text) {
+                Ok(pattern) => library.insert(LibraryEntry {
+                    name: entry_name(&pattern, Path::new(file_name)),
+                    category: category.to_string(),
+                    source: None,
+                    pattern,
+                }),
+                Err(e) => library.errors.push(format!("{} (built-in): {}", file_name, e)),
+            }
+        }
+        library
+    }
+
+    /// Bundled patterns plus every .rle/.cells file under `directory`.
+    /// The category is the sub-directory a file lives in. A missing directory is not an error.
+    pub fn load(directory: &Path) -> Self {
+        let mut library = Self::builtin();
+        library.directory = directory.to_path_buf();
+        if directory.is_dir() {
+            library.scan_dir(directory, directory);
+        } else {
+            log::info!("Pattern directory {} not found, using built-in patterns only", directory.display());
+        }
+        library.entries.sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+        log::info!("Pattern library: {} patterns ({} failed to load)", library.entries.len(), library.errors.len());
+        library
+    }
+
+    fn scan_dir(&mut self, root: &Path, dir: &Path) {
+        let read_dir = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                self.errors.push(format!("{}: {}", dir.display(), e));
+                return;
+            }
+        };
+        let mut paths: Vec<PathBuf> = read_dir.filter_map(|entry| entry.ok().map(|e| e.path())).collect();
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                self.scan_dir(root, &path);
+                continue;
+            }
+            let is_pattern = path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| matches!(e.to_ascii_lowercase().as_str(), "rle" | "cells"))
+                .unwrap_or(false);
+            if !is_pattern {
+                continue;
+            }
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_pattern(&path, &text));
+            match parsed {
+                Ok(pattern) => self.insert(LibraryEntry {
+                    name: entry_name(&pattern, &path),
+                    category: category_for(root, &path),
+                    source: Some(path),
+                    pattern,
+                }),
+                Err(e) => self.errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    /// Add an entry; a pattern with the same category and name replaces the existing one,
+    /// so user files can override bundled patterns
+    pub fn insert(&mut self, entry: LibraryEntry) {
+        match self.entries.iter_mut().find(|e| e.category == entry.category && e.name == entry.name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Find an entry by name (case-insensitive)
+    pub fn find(&self, name: &str) -> Option<&LibraryEntry> {
+        self.entries.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Entry indices matching `query`, grouped by category in display order
+    pub fn search(&self, query: &str) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut group_index: HashMap<&str, usize> = HashMap::new();
+        for (i, entry) in self.entries.iter().enumerate().filter(|(_, e)| e.matches(query)) {
+            let slot = *group_index.entry(entry.category.as_str()).or_insert_with(|| {
+                groups.push((entry.category.clone(), Vec::new()));
+                groups.len() - 1
+            });
+            groups[slot].1.push(i);
+        }
+        groups
+    }
+}
+
+/// Bundled pattern by name; panics only if the shipped assets are broken
+pub fn builtin_pattern(name: &str) -> Pattern {
+    PatternLibrary::builtin()
+        .find(name)
+        .map(|entry| entry.pattern.clone())
+        .unwrap_or_else(|| panic!("missing built-in pattern {}", name))
+}
+
+fn entry_name(pattern: &Pattern, path: &Path) -> String {
+    pattern.name.clone().filter(|n| !n.is_empty()).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().replace('_', " "))
+            .unwrap_or_else(|| "Unnamed".to_string())
+    })
+}
+
+fn category_for(root: &Path, path: &Path) -> String {
+    path.parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .map(|rel| rel.to_string_lossy().replace(['_', '/', '\\'], " "))
+        .filter(|c| !c.is_empty())
+        .map(|c| title_case(&c))
+        .unwrap_or_else(|| "Uncategorized".to_string())
+}
+
+fn title_case(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -4,6 +4,26 @@ pub mod compute;
 pub mod render;
 pub mod input;
 pub mod rules;
+pub mod export;
+pub mod capture;
+pub mod readback;
+pub mod recording;
+pub mod library;
+pub mod stats;
+pub mod simulation;
+pub mod cpu;
+pub mod hashlife;
+pub mod tiled;
+pub mod packed;
+pub mod activity;
+pub mod chunked;
+pub mod resize;
+pub mod history;
+pub mod period;
+pub mod census;
+pub mod inspector;
+pub mod components;
+pub mod tracker;
 
 // Use types/functions from the declared modules
 use crate::state::State;
@@ -15,16 +35,12 @@ use winit::{
 };
 use std::sync::Arc;
 
-// GUI Imports
-use egui;
-use std::time::{Instant, Duration}; // Import time types
-
 // Constants
 const GRID_WIDTH: u32 = 1024;
 const GRID_HEIGHT: u32 = 1024;
 
-async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
-    let mut state = State::new(window).await;
+async fn run(event_loop: EventLoop<()>, window: Arc<Window>, grid_size: (u32, u32)) {
+    let mut state = State::new(window, grid_size.0, grid_size.1).await;
 
     event_loop.run(move |event, window_target| {
         // Pass winit events to egui_winit - MOVED INSIDE WindowEvent arm
@@ -53,6 +69,17 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                 // Now match on the event for game logic if egui didn't consume it
                 match event {
                 WindowEvent::CloseRequested => {
+                    // Flush an unfinished recording so the file is playable
+                    if state.recorder.is_some() {
+                        if let Err(e) = state.stop_recording() {
+                            log::error!("Recording failed: {}", e);
+                        }
+                    }
+                    if state.stats_recorder.is_some() {
+                        if let Err(e) = state.stop_stats() {
+                            log::error!("Statistics log failed: {}", e);
+                        }
+                    }
                     window_target.exit();
                 }
                 WindowEvent::Resized(new_size) => {
@@ -107,33 +134,18 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                             }
                         };
 
-                        // --- Update Cell Count (throttled) ---
-                        let mut should_update_count = false;
-                        if state.menu_open {
-                            match state.last_count_update_time {
-                                Some(last_update) => {
-                                    if Instant::now().duration_since(last_update) > Duration::from_secs(1) {
-                                        should_update_count = true;
-                                    }
-                                }
-                                None => {
-                                    // No count yet, update immediately when menu opens
-                                    should_update_count = true;
-                                }
-                            }
-                            
-                            // Perform the potentially blocking update
-                            if should_update_count {
-                                log::info!("Updating live cell count (GPU readback)...");
-                                state.update_live_cell_count();
-                                log::info!("Cell count update finished.");
-                            }
+                        // --- Update Cell Count ---
+                        // The reduction runs on the GPU and is read back asynchronously, so it
+                        // can refresh every frame while the menu or the history graph shows it
+                        if state.menu_open || state.show_history {
+                            state.update_live_cell_count();
                         } else {
-                            // When menu is closed, don't perform any cell counting
-                            // This avoids expensive GPU readbacks when not needed
                             state.live_cell_count = None;
+                            state.live_stats = None;
                             state.last_count_update_time = None;
                         }
+                        state.update_inspector();
+                        state.update_objects();
 
                         // --- Egui Frame and UI Definition ---
                         let output_view = output_frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -156,6 +168,21 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                 }
                             });
 
+                        // Store user actions that need the whole state, to perform after UI rendering
+                        let mut render_params_changed = false;
+                        let mut export_png_requested = false;
+                        let mut toggle_recording_requested = false;
+                        let mut toggle_stats_requested = false;
+                        let mut jump_requested = false;
+                        let mut toggle_infinite_plane_requested = false;
+                        let mut toggle_packed_requested = false;
+                        let mut kernel_requested = None;
+                        let mut resize_grid_requested = false;
+                        let mut analysis_requested = None;
+                        let mut object_action = None;
+                        let generation = state.generation();
+                        let object_count = state.object_count();
+
                         if state.menu_open {
                             // Define a frame with a semi-transparent background
                             let panel_frame = egui::Frame {
@@ -194,23 +221,104 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                     state.view_offset[1] = world_y * state.zoom - center_y;
                                     
                                     // Update GPU buffer
-                                    state.queue.write_buffer(&state.render_param_buffer, 0, bytemuck::bytes_of(&crate::render::RenderParams {
-                                        zoom: state.zoom,
-                                        view_offset: state.view_offset,
-                                        _padding: 0.0,
-                                    }));
+                                    render_params_changed = true;
                                 }
                                 
                                 if already_at_min_zoom {
                                     ui.label("Already at 1:1 pixel mapping (one pixel = one cell)");
                                 }
                                 
-                                ui.label(format!("Grid: {}x{}", state.grid_width, state.grid_height));
-                                ui.label(format!("Frame: {}", state.frame_num));
+                                ui.label(format!("Grid: {}x{}", state.simulation.width, state.simulation.height));
+                                // The infinite plane has no fixed size
+                                ui.add_enabled_ui(state.universe.is_none(), |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Grid size:");
+                                        ui.add(egui::DragValue::new(&mut state.grid_size_target[0]).clamp_range(1..=65536));
+                                        ui.label("x");
+                                        ui.add(egui::DragValue::new(&mut state.grid_size_target[1]).clamp_range(1..=65536));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        egui::ComboBox::from_id_source("resize_mode")
+                                            .selected_text(state.resize_mode.name())
+                                            .show_ui(ui, |ui| {
+                                                for mode in crate::resize::ResizeMode::ALL {
+                                                    ui.selectable_value(&mut state.resize_mode, mode, mode.name());
+                                                }
+                                            });
+                                        if ui.button("Apply").clicked() {
+                                            resize_grid_requested = true;
+                                        }
+                                    });
+                                });
+                                if let Some(status) = &state.grid_status {
+                                    ui.label(status);
+                                }
+                                let mut infinite_plane = state.universe.is_some();
+                                if ui.checkbox(&mut infinite_plane, "Infinite plane").changed() {
+                                    toggle_infinite_plane_requested = true;
+                                }
+                                if let Some(universe) = &state.universe {
+                                    ui.label(format!("Tiles: {} ({} in pool)", universe.tile_count(), universe.capacity()));
+                                    ui.label(format!("View origin: ({}, {})", state.view_origin[0], state.view_origin[1]));
+                                    if universe.pool_full() {
+                                        ui.colored_label(egui::Color32::RED, "Tile pool full, activity is clipped");
+                                    }
+                                }
+                                if let Some(status) = &state.universe_status {
+                                    ui.label(status);
+                                }
+                                let mut packed = state.packed.is_some();
+                                if ui.checkbox(&mut packed, "Packed (two-state)").changed() {
+                                    toggle_packed_requested = true;
+                                }
+                                if let Some(status) = &state.packed_status {
+                                    ui.label(status);
+                                }
+                                // The infinite plane and packed mode run kernels of their own
+                                ui.add_enabled_ui(state.universe.is_none() && state.packed.is_none(), |ui| {
+                                    let current = state.simulation.kernel();
+                                    egui::ComboBox::from_label("Kernel")
+                                        .selected_text(current.name())
+                                        .show_ui(ui, |ui| {
+                                            for kernel in crate::simulation::Kernel::PRESETS {
+                                                if ui.selectable_label(kernel == current, kernel.name()).clicked() && kernel != current {
+                                                    kernel_requested = Some(kernel);
+                                                }
+                                            }
+                                        });
+                                });
+                                if let Some(status) = &state.kernel_status {
+                                    ui.label(status);
+                                }
+                                if let Some((active, total)) = state.active_tiles {
+                                    ui.label(format!("Active tiles: {} of {}", active, total));
+                                }
+                                ui.label(format!("Frame: {}", generation));
                                 // Display live cell count
                                 ui.label(format!("Live Cells: {}",
                                     state.live_cell_count.map_or_else(|| "N/A".to_string(), |count| count.to_string())
                                 ));
+                                if let Some(stats) = &state.live_stats {
+                                    let all = &stats.species[0];
+                                    ui.label(format!("Births / deaths: +{} / -{}", all.births, all.deaths));
+                                    if let Some([x0, y0, x1, y1]) = all.bbox {
+                                        ui.label(format!("Bounding box: ({}, {}) to ({}, {})", x0, y0, x1, y1));
+                                    }
+                                    // Counts of the individual cell states, skipping absent ones
+                                    let states: Vec<String> = crate::stats::SPECIES_NAMES.iter().zip(&stats.species).skip(1)
+                                        .filter(|(_, species)| species.population > 0)
+                                        .map(|(name, species)| format!("{} {}", name, species.population))
+                                        .collect();
+                                    if !states.is_empty() {
+                                        ui.label(format!("States: {}", states.join(", ")));
+                                    }
+                                }
+                                if ui.button("Population History...").clicked() {
+                                    state.show_history = !state.show_history;
+                                }
+                                if ui.button("Soup Census...").clicked() {
+                                    state.show_census = !state.show_census;
+                                }
                                 // Display current FPS
                                 let fps_text = format!("FPS: {:.1}", state.fps);
                                 let fps_color = if state.fps > 100.0 {
@@ -289,6 +397,15 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                 });
                                 ui.separator();
 
+                                // Placement stamp from the pattern library
+                                ui.label(format!("Pattern: {}", state.selected_pattern
+                                    .and_then(|i| state.pattern_library.entries.get(i))
+                                    .map_or("None", |entry| entry.name.as_str())));
+                                if ui.button("Pattern Library...").clicked() {
+                                    state.show_pattern_browser = !state.show_pattern_browser;
+                                }
+                                ui.separator();
+
                                 ui.checkbox(&mut state.lucky_rule_enabled, "Enable Lucky Red Cells");
                                 ui.separator();
 
@@ -318,8 +435,618 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                             format!("{:.0} steps/sec", val)
                                         }
                                     }));
+                                ui.horizontal(|ui| {
+                                    if ui.button(if state.paused { "▶ Resume" } else { "⏸ Pause" }).clicked() {
+                                        state.paused = !state.paused;
+                                    }
+                                    ui.checkbox(&mut state.auto_pause.enabled, "Auto-pause when periodic, period ≤");
+                                    ui.add_enabled(state.auto_pause.enabled, egui::DragValue::new(&mut state.auto_pause.max_period).clamp_range(1..=1000));
+                                });
+                                if let Some(status) = &state.stability_status {
+                                    ui.label(status);
+                                }
+                                let generation = state.universe.as_ref().map_or(state.simulation.generation, |universe| universe.generation);
+                                ui.horizontal(|ui| {
+                                    ui.label("Jump to generation:");
+                                    ui.add(egui::DragValue::new(&mut state.jump_target)
+                                        .clamp_range(generation..=usize::MAX)
+                                        .speed(1000.0));
+                                    if ui.button("Jump").clicked() {
+                                        jump_requested = true;
+                                    }
+                                });
+                                if let Some(status) = &state.jump_status {
+                                    ui.label(status);
+                                }
+                                ui.separator();
+
+                                // Still life / oscillator / spaceship classification
+                                ui.label("Pattern analysis:");
+                                match state.selection {
+                                    Some([x, y, width, height]) => ui.label(format!("Selection: {}x{} at ({}, {})", width, height, x, y)),
+                                    None => ui.label("Selection: none (right-click \"Select Region\")"),
+                                };
+                                ui.horizontal(|ui| {
+                                    ui.label("Up to");
+                                    ui.add(egui::DragValue::new(&mut state.analysis_generations).clamp_range(1..=100_000).suffix(" generations"));
+                                });
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(state.selection.is_some(), egui::Button::new("Analyze Selection")).clicked() {
+                                        analysis_requested = Some(true);
+                                    }
+                                    if ui.button("Analyze Grid").clicked() {
+                                        analysis_requested = Some(false);
+                                    }
+                                    if state.selection.is_some() && ui.button("Clear Selection").clicked() {
+                                        state.selection = None;
+                                    }
+                                });
+                                if let Some(status) = &state.analysis_status {
+                                    ui.label(status);
+                                }
+                                ui.separator();
+
+                                // Connected components of the grid
+                                ui.label("Objects:");
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut state.show_objects, "Color objects");
+                                    ui.label("Connect cells up to");
+                                    ui.add(egui::DragValue::new(&mut state.object_radius)
+                                        .clamp_range(1..=crate::components::MAX_RADIUS)
+                                        .suffix(" apart"));
+                                });
+                                match object_count {
+                                    Some(count) => ui.label(format!("{} objects", count)),
+                                    None if state.labeler.is_some() => ui.label("Labeling..."),
+                                    None => ui.label("Right-click \"Select Object\" to pick one"),
+                                };
+                                if state.selected_object.is_some() {
+                                    ui.horizontal(|ui| {
+                                        if ui.button("Copy").clicked() {
+                                            object_action = Some(ObjectAction::Copy);
+                                        }
+                                        if ui.button("Delete").clicked() {
+                                            object_action = Some(ObjectAction::Delete);
+                                        }
+                                        if ui.button("Analyze").clicked() {
+                                            object_action = Some(ObjectAction::Analyze);
+                                        }
+                                    });
+                                }
+                                if let Some(status) = &state.objects_status {
+                                    ui.label(status);
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut state.show_tracker, "Track spaceships");
+                                    if state.tracker.is_some() && ui.button("Reset").clicked() {
+                                        state.tracker = None;
+                                    }
+                                });
+                                if let Some(tracker) = &state.tracker {
+                                    ui.label(format!("{} spaceships in flight, {} tracks ended", tracker.tracks.len(), tracker.lost));
+                                    let emitted = tracker.glider_emissions.len();
+                                    match tracker.emission_interval() {
+                                        Some(interval) => ui.label(format!("Gliders emitted: {} (one per {:.1} generations)", emitted, interval)),
+                                        None => ui.label(format!("Gliders emitted: {}", emitted)),
+                                    };
+                                }
+                                ui.separator();
+
+                                // Palette used for display and export
+                                let previous_palette = state.palette;
+                                egui::ComboBox::from_label("Palette")
+                                    .selected_text(state.palette.name())
+                                    .show_ui(ui, |ui| {
+                                        for palette in crate::render::Palette::ALL {
+                                            ui.selectable_value(&mut state.palette, palette, palette.name());
+                                        }
+                                    });
+                                if state.palette != previous_palette {
+                                    render_params_changed = true;
+                                    // Thumbnails are drawn with the palette colors
+                                    state.pattern_thumbnails.clear();
+                                }
+                                ui.separator();
+
+                                // PNG export at native grid resolution
+                                ui.label("Export:");
+                                ui.add(egui::Slider::new(&mut state.export_scale, 1..=16).text("Pixels per cell"));
+                                ui.label(format!("Image: {}x{} px",
+                                    state.simulation.width as u64 * state.export_scale as u64,
+                                    state.simulation.height as u64 * state.export_scale as u64));
+                                if ui.button("Export PNG").clicked() {
+                                    export_png_requested = true;
+                                }
+                                if let Some(status) = &state.export_status {
+                                    ui.label(status);
+                                }
+                                ui.separator();
+
+                                // Animated recording of every Nth generation
+                                ui.label("Recording:");
+                                let recording = state.recorder.is_some();
+                                ui.add_enabled_ui(!recording, |ui| {
+                                    let settings = &mut state.recording_settings;
+                                    egui::ComboBox::from_label("Format")
+                                        .selected_text(settings.format.name())
+                                        .show_ui(ui, |ui| {
+                                            for format in crate::recording::RecordingFormat::ALL {
+                                                ui.selectable_value(&mut settings.format, format, format.name());
+                                            }
+                                        });
+                                    if settings.format.is_stream() {
+                                        ui.horizontal(|ui| {
+                                            ui.label("Output:");
+                                            ui.add(egui::TextEdit::singleline(&mut settings.stream_target)
+                                                .hint_text("file or FIFO path"));
+                                        });
+                                    }
+                                    ui.add(egui::Slider::new(&mut settings.every_n, 1..=1000)
+                                        .logarithmic(true)
+                                        .text("Every N generations"));
+                                    ui.add(egui::Slider::new(&mut settings.scale, 1..=8).text("Pixels per cell"));
+                                    ui.add(egui::Slider::new(&mut settings.frame_delay_cs, 1..=100).text("Frame delay (1/100 s)"));
+
+                                    let mut crop_enabled = settings.crop.is_some();
+                                    if ui.checkbox(&mut crop_enabled, "Crop region").changed() {
+                                        settings.crop = if crop_enabled {
+                                            Some(crate::capture::CaptureRegion {
+                                                x: 0,
+                                                y: 0,
+                                                width: state.simulation.width.min(256),
+                                                height: state.simulation.height.min(256),
+                                            })
+                                        } else {
+                                            None
+                                        };
+                                    }
+                                    if let Some(crop) = settings.crop.as_mut() {
+                                        ui.horizontal(|ui| {
+                                            ui.label("x");
+                                            ui.add(egui::DragValue::new(&mut crop.x).clamp_range(0..=state.simulation.width - 1));
+                                            ui.label("y");
+                                            ui.add(egui::DragValue::new(&mut crop.y).clamp_range(0..=state.simulation.height - 1));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label("w");
+                                            ui.add(egui::DragValue::new(&mut crop.width).clamp_range(1..=state.simulation.width));
+                                            ui.label("h");
+                                            ui.add(egui::DragValue::new(&mut crop.height).clamp_range(1..=state.simulation.height));
+                                        });
+                                    }
+                                });
+                                if let Some(recorder) = &state.recorder {
+                                    if recorder.waiting_for_reader() {
+                                        ui.colored_label(egui::Color32::YELLOW,
+                                            format!("Waiting for a reader, {} frames dropped", recorder.frames_dropped));
+                                    } else {
+                                        ui.colored_label(egui::Color32::RED,
+                                            format!("● REC  {} frames", recorder.frames_captured));
+                                    }
+                                }
+                                if ui.button(if recording { "Stop Recording" } else { "Start Recording" }).clicked() {
+                                    toggle_recording_requested = true;
+                                }
+                                if let Some(status) = &state.recording_status {
+                                    ui.label(status);
+                                }
+                                ui.separator();
+
+                                // Per-generation statistics log (reduced on the GPU)
+                                ui.label("Statistics:");
+                                let logging = state.stats_recorder.is_some();
+                                ui.add_enabled_ui(!logging, |ui| {
+                                    let settings = &mut state.stats_settings;
+                                    egui::ComboBox::from_label("Log format")
+                                        .selected_text(settings.format.name())
+                                        .show_ui(ui, |ui| {
+                                            for format in crate::stats::StatsFormat::ALL {
+                                                ui.selectable_value(&mut settings.format, format, format.name());
+                                            }
+                                        });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Output:");
+                                        ui.add(egui::TextEdit::singleline(&mut settings.output)
+                                            .hint_text("automatic file name"));
+                                    });
+                                    ui.add(egui::Slider::new(&mut settings.every_n, 1..=1000)
+                                        .logarithmic(true)
+                                        .text("Sample every N generations"));
+                                });
+                                if let Some(stats) = &state.stats_recorder {
+                                    ui.colored_label(egui::Color32::RED,
+                                        format!("● LOG  {} samples", stats.samples_written()));
+                                    if let Some(latest) = &stats.latest {
+                                        let all = &latest.species[0];
+                                        ui.label(format!("Gen {}: {} alive, +{} / -{}",
+                                            latest.generation, all.population, all.births, all.deaths));
+                                    }
+                                }
+                                if ui.button(if logging { "Stop Statistics Log" } else { "Start Statistics Log" }).clicked() {
+                                    toggle_stats_requested = true;
+                                }
+                                if let Some(status) = &state.stats_status {
+                                    ui.label(status);
+                                }
+                            });
+                        }
+
+                        if render_params_changed {
+                            state.write_render_params();
+                        }
+                        if export_png_requested {
+                            state.export_status = Some(match state.export_png() {
+                                Ok(path) => format!("Saved {}", path),
+                                Err(e) => {
+                                    log::error!("PNG export failed: {}", e);
+                                    format!("Export failed: {}", e)
+                                }
+                            });
+                        }
+                        if toggle_recording_requested {
+                            let result = if state.recorder.is_some() {
+                                state.stop_recording()
+                            } else {
+                                state.start_recording().map(|_| "Recording...".to_string())
+                            };
+                            state.recording_status = Some(result.unwrap_or_else(|e| {
+                                log::error!("Recording failed: {}", e);
+                                format!("Recording failed: {}", e)
+                            }));
+                        }
+                        if toggle_infinite_plane_requested {
+                            let result = if state.universe.is_some() {
+                                state.disable_infinite_plane()
+                            } else {
+                                state.enable_infinite_plane()
+                            };
+                            state.universe_status = Some(result.unwrap_or_else(|e| {
+                                log::error!("Infinite plane toggle failed: {}", e);
+                                format!("Infinite plane failed: {}", e)
+                            }));
+                        }
+                        if toggle_packed_requested {
+                            let result = if state.packed.is_some() {
+                                state.disable_packed_mode()
+                            } else {
+                                state.enable_packed_mode()
+                            };
+                            state.packed_status = Some(result.unwrap_or_else(|e| {
+                                log::error!("Packed mode toggle failed: {}", e);
+                                format!("Packed mode failed: {}", e)
+                            }));
+                        }
+                        if let Some(kernel) = kernel_requested {
+                            state.kernel_status = Some(state.set_kernel(kernel).unwrap_or_else(|e| {
+                                log::error!("Kernel change failed: {}", e);
+                                format!("Kernel change failed: {}", e)
+                            }));
+                        }
+                        if resize_grid_requested {
+                            let [width, height] = state.grid_size_target;
+                            state.grid_status = Some(state.resize_grid(width, height, state.resize_mode).unwrap_or_else(|e| {
+                                log::error!("Grid resize failed: {}", e);
+                                format!("Grid resize failed: {}", e)
+                            }));
+                        }
+                        if let Some(action) = object_action {
+                            let result = match action {
+                                ObjectAction::Copy => state.copy_object().map(|rle| {
+                                    state.egui_ctx.output_mut(|output| output.copied_text = rle);
+                                    "Copied to the clipboard and the \"Place Pattern\" stamp".to_string()
+                                }),
+                                ObjectAction::Delete => state.delete_object(),
+                                ObjectAction::Analyze => state.analyze_object(),
+                            };
+                            state.objects_status = Some(result.unwrap_or_else(|e| {
+                                log::error!("Object action failed: {}", e);
+                                format!("Failed: {}", e)
+                            }));
+                        }
+                        if let Some(use_selection) = analysis_requested {
+                            state.analysis_status = Some(state.analyze_pattern(use_selection).unwrap_or_else(|e| {
+                                log::error!("Pattern analysis failed: {}", e);
+                                format!("Analysis failed: {}", e)
+                            }));
+                        }
+                        if jump_requested {
+                            state.jump_status = Some(state.jump_to_generation(state.jump_target).unwrap_or_else(|e| {
+                                log::error!("Jump failed: {}", e);
+                                format!("Jump failed: {}", e)
+                            }));
+                        }
+                        if toggle_stats_requested {
+                            let result = if state.stats_recorder.is_some() {
+                                state.stop_stats()
+                            } else {
+                                state.start_stats().map(|_| "Logging...".to_string())
+                            };
+                            state.stats_status = Some(result.unwrap_or_else(|e| {
+                                log::error!("Statistics log failed: {}", e);
+                                format!("Statistics log failed: {}", e)
+                            }));
+                        }
+
+                        // Pattern library browser
+                        let mut chosen_pattern = None;
+                        let mut rescan_patterns_requested = false;
+                        if state.show_pattern_browser {
+                            state.ensure_pattern_thumbnails();
+                            let mut browser_open = true;
+                            egui::Window::new("Pattern Library")
+                                .open(&mut browser_open)
+                                .default_width(280.0)
+                                .default_height(420.0)
+                                .show(&state.egui_ctx, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Directory:");
+                                        ui.text_edit_singleline(&mut state.pattern_dir_input);
+                                        if ui.button("Rescan").clicked() {
+                                            rescan_patterns_requested = true;
+                                        }
+                                    });
+                                    let errors = &state.pattern_library.errors;
+                                    if !errors.is_empty() {
+                                        ui.colored_label(egui::Color32::YELLOW, format!("⚠️ {} file(s) failed to load", errors.len()))
+                                            .on_hover_text(errors.join("\n"));
+                                    }
+                                    ui.horizontal(|ui| {
+                                        ui.label("Search:");
+                                        ui.text_edit_singleline(&mut state.pattern_search);
+                                    });
+                                    ui.separator();
+
+                                    let groups = state.pattern_library.search(&state.pattern_search);
+                                    // Expand every category while searching so matches are visible
+                                    let searching = !state.pattern_search.trim().is_empty();
+                                    egui::ScrollArea::vertical().show(ui, |ui| {
+                                        if groups.is_empty() {
+                                            ui.label("No matching patterns");
+                                        }
+                                        for (category, indices) in groups {
+                                            egui::CollapsingHeader::new(format!("{} ({})", category, indices.len()))
+                                                .id_source(&category)
+                                                .default_open(true)
+                                                .open(searching.then_some(true))
+                                                .show(ui, |ui| {
+                                                    for index in indices {
+                                                        let entry = &state.pattern_library.entries[index];
+                                                        ui.horizontal(|ui| {
+                                                            // Thumbnail, scaled to fit a fixed slot with crisp cells
+                                                            let (rect, thumb_response) = ui.allocate_exact_size(egui::vec2(48.0, 48.0), egui::Sense::click());
+                                                            if let Some(texture) = state.pattern_thumbnails.get(index) {
+                                                                let size = texture.size_vec2();
+                                                                let scale = (48.0 / size.x.max(size.y)).min(8.0);
+                                                                ui.painter().rect_filled(rect, 2.0, egui::Color32::from_gray(10));
+                                                                ui.painter().image(
+                                                                    texture.id(),
+                                                                    egui::Rect::from_center_size(rect.center(), size * scale),
+                                                                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                                                                    egui::Color32::WHITE,
+                                                                );
+                                                            }
+                                                            let selected = state.selected_pattern == Some(index);
+                                                            let label = format!("{}\n{}x{}, {} cells",
+                                                                entry.name, entry.pattern.width, entry.pattern.height, entry.pattern.cells.len());
+                                                            let response = ui.selectable_label(selected, label);
+                                                            let mut details = entry.pattern.comments.join("\n");
+                                                            if let Some(source) = &entry.source {
+                                                                details = format!("{}\n{}", details, source.display()).trim().to_string();
+                                                            }
+                                                            let response = if details.is_empty() { response } else { response.on_hover_text(details) };
+                                                            if response.clicked() || thumb_response.clicked() {
+                                                                chosen_pattern = Some(index);
+                                                            }
+                                                        });
+                                                    }
+                                                });
+                                        }
+                                    });
+                                });
+                            state.show_pattern_browser = browser_open;
+                        }
+                        if let Some(index) = chosen_pattern {
+                            state.selected_pattern = Some(index);
+                            state.cursor_mode = crate::state::CursorMode::PlacePattern;
+                            log::info!("Selected pattern: {}", state.pattern_library.entries[index].name);
+                        }
+                        if rescan_patterns_requested {
+                            state.reload_pattern_library();
+                        }
+
+                        // Population history graph
+                        let mut restore_requested = None;
+                        if state.show_history {
+                            let mut history_open = true;
+                            egui::Window::new("Population History")
+                                .open(&mut history_open)
+                                .default_width(460.0)
+                                .show(&state.egui_ctx, |ui| {
+                                    let view = &mut state.history_view;
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut view.logarithmic, "Log scale");
+                                        ui.label("Span:");
+                                        ui.add(egui::DragValue::new(&mut view.span).clamp_range(10..=10_000_000).suffix(" gens"));
+                                        if ui.button("Clear").clicked() {
+                                            state.population_history.clear();
+                                        }
+                                    });
+                                    ui.horizontal_wrapped(|ui| {
+                                        for (s, name) in crate::stats::SPECIES_NAMES.iter().enumerate() {
+                                            ui.checkbox(&mut view.species[s], egui::RichText::new(*name).color(species_color(s)));
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut view.show_births, egui::RichText::new("Births").color(BIRTHS_COLOR));
+                                        ui.checkbox(&mut view.show_deaths, egui::RichText::new("Deaths").color(DEATHS_COLOR));
+                                    });
+                                    let snapshot_generations = state.snapshots.as_ref().map(|ring| ring.generations()).unwrap_or_default();
+                                    restore_requested = draw_population_graph(ui, &state.population_history, view, &snapshot_generations);
+                                    let first = state.population_history.samples().back()
+                                        .map_or(0, |sample| sample.generation.saturating_sub(view.span));
+                                    ui.label(match (&state.stats_recorder, state.population_history.mean_spacing(first)) {
+                                        (Some(stats), _) => format!("Every {} generation(s), from the statistics log", stats.settings.every_n.max(1)),
+                                        (None, Some(spacing)) => format!(
+                                            "Sampled once per frame, a point every {:.1} generations on average (the statistics log samples every generation)",
+                                            spacing
+                                        ),
+                                        (None, None) => "Sampled once per frame".to_string(),
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Snapshot every");
+                                        ui.add(egui::DragValue::new(&mut view.snapshot_interval).clamp_range(1..=1_000_000).suffix(" gens"));
+                                        ui.label(format!("({} kept)", snapshot_generations.len()));
+                                    });
+                                    ui.label("Click the graph to go back to a generation");
+                                    if let Some(status) = &state.history_status {
+                                        ui.label(status);
+                                    }
+                                });
+                            state.show_history = history_open;
+                        }
+                        if let Some(generation) = restore_requested {
+                            state.history_status = Some(match state.restore_generation(generation) {
+                                Ok(status) => status,
+                                Err(e) => {
+                                    log::error!("Failed to restore generation {}: {}", generation, e);
+                                    format!("Restore failed: {}", e)
+                                }
+                            });
+                        }
+
+                        // Pinned cell of the inspector
+                        if let Some((x, y)) = state.pinned_cell {
+                            let mut inspector_open = true;
+                            let lucky_chance = state.lucky_rule_enabled.then_some(state.lucky_chance_percent as f32 / 100.0);
+                            egui::Window::new("Cell Inspector")
+                                .open(&mut inspector_open)
+                                .default_width(220.0)
+                                .show(&state.egui_ctx, |ui| {
+                                    let window = state.inspector.as_ref()
+                                        .and_then(|inspector| inspector.pinned.latest.as_ref())
+                                        .filter(|window| (window.x, window.y) == (x, y));
+                                    let Some(window) = window else {
+                                        ui.label(format!("({}, {}): reading...", x, y));
+                                        return;
+                                    };
+                                    ui.label(describe_cell(window, state.simulation.rules, lucky_chance));
+                                    ui.label(format!("Generation {}", window.generation));
+                                    // The window itself, the inspected cell outlined
+                                    let cell_size = 18.0;
+                                    let side = inspector::WINDOW_SIDE as f32 * cell_size;
+                                    let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+                                    let painter = ui.painter_at(rect);
+                                    for (i, cell) in window.cells.iter().enumerate() {
+                                        let (col, row) = ((i % inspector::WINDOW_SIDE) as f32, (i / inspector::WINDOW_SIDE) as f32);
+                                        let cell_rect = egui::Rect::from_min_size(
+                                            rect.min + egui::vec2(col * cell_size, row * cell_size),
+                                            egui::vec2(cell_size - 1.0, cell_size - 1.0),
+                                        );
+                                        let fill = cell.map_or(egui::Color32::from_gray(60), state_color);
+                                        painter.rect_filled(cell_rect, 0.0, fill);
+                                    }
+                                    let center = inspector::WINDOW_RADIUS as f32 * cell_size;
+                                    painter.rect_stroke(
+                                        egui::Rect::from_min_size(rect.min + egui::vec2(center, center), egui::vec2(cell_size - 1.0, cell_size - 1.0)),
+                                        0.0,
+                                        egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 200, 0)),
+                                    );
+                                    if ui.button("Unpin").clicked() {
+                                        state.pinned_cell = None;
+                                    }
+                                });
+                            if !inspector_open {
+                                state.pinned_cell = None;
+                            }
+                        }
+
+                        // Soup search and object census
+                        let mut start_census_requested = false;
+                        let mut export_census_requested = false;
+                        if state.show_census {
+                            let mut census_open = true;
+                            egui::Window::new("Soup Census")
+                                .open(&mut census_open)
+                                .default_width(360.0)
+                                .show(&state.egui_ctx, |ui| {
+                                    ui.add_enabled_ui(!state.census_running, |ui| {
+                                        let settings = &mut state.census_settings;
+                                        ui.horizontal(|ui| {
+                                            ui.label("Seed:");
+                                            ui.add(egui::DragValue::new(&mut settings.seed));
+                                            ui.label("Soups:");
+                                            ui.add(egui::DragValue::new(&mut settings.soups).clamp_range(1..=u64::MAX).speed(100.0));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label("Give up after");
+                                            ui.add(egui::DragValue::new(&mut settings.max_generations).clamp_range(100..=1_000_000).suffix(" gens"));
+                                            ui.label("Max period:");
+                                            ui.add(egui::DragValue::new(&mut settings.max_period).clamp_range(1..=1000));
+                                        });
+                                        ui.label(format!(
+                                            "{} soups at a time on a {}x{} grid, rule {}",
+                                            (settings.width / settings.tile_size) * (settings.height / settings.tile_size),
+                                            settings.width, settings.height, state.simulation.rules.to_rulestring()
+                                        ));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        if state.census_running {
+                                            if ui.button("Stop").clicked() {
+                                                state.census_running = false;
+                                                state.census_status = Some("Stopped".to_string());
+                                            }
+                                        } else if ui.button("Start").clicked() {
+                                            start_census_requested = true;
+                                        }
+                                        if ui.add_enabled(state.census.is_some(), egui::Button::new("Export CSV")).clicked() {
+                                            export_census_requested = true;
+                                        }
+                                    });
+                                    if let Some(search) = &state.census {
+                                        let (done, total) = search.progress();
+                                        ui.add(egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                                            .text(format!("{}/{} soups, generation {}", done, total, search.generation())));
+                                        ui.label(search.census.summary());
+                                        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                                            egui::Grid::new("census_table").striped(true).show(ui, |ui| {
+                                                ui.strong("apgcode");
+                                                ui.strong("count");
+                                                ui.strong("first soup");
+                                                ui.end_row();
+                                                for (code, count) in search.census.sorted() {
+                                                    ui.monospace(code);
+                                                    ui.label(count.to_string());
+                                                    ui.label(search.census.first_soup[code].to_string());
+                                                    ui.end_row();
+                                                }
+                                            });
+                                        });
+                                    }
+                                    if let Some(status) = &state.census_status {
+                                        ui.label(status);
+                                    }
+                                });
+                            state.show_census = census_open;
+                        }
+                        if start_census_requested {
+                            state.census_settings.rules = state.simulation.rules;
+                            state.census_status = Some(state.start_census().unwrap_or_else(|e| {
+                                log::error!("Failed to start the soup census: {}", e);
+                                format!("Census failed: {}", e)
+                            }));
+                        }
+                        if export_census_requested {
+                            state.census_status = Some(match state.export_census() {
+                                Ok(path) => format!("Saved {}", path),
+                                Err(e) => {
+                                    log::error!("Census export failed: {}", e);
+                                    format!("Export failed: {}", e)
+                                }
                             });
                         }
+                        if state.census_running {
+                            state.run_census_batch();
+                        }
                         // --- End UI Definition ---
 
                         // Context menu (if shown)
@@ -352,12 +1079,12 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                                     show_submenu_for = Some("paint".to_string());
                                                 }
                                                 
-                                                let glider_response = ui.button("Place Glider");
-                                                if glider_response.clicked() {
-                                                    new_cursor_mode = Some(crate::state::CursorMode::PlaceGlider);
+                                                let pattern_response = ui.button("Place Pattern");
+                                                if pattern_response.clicked() {
+                                                    new_cursor_mode = Some(crate::state::CursorMode::PlacePattern);
                                                 }
-                                                if glider_response.secondary_clicked() {
-                                                    show_submenu_for = Some("glider".to_string());
+                                                if pattern_response.secondary_clicked() {
+                                                    show_submenu_for = Some("pattern".to_string());
                                                 }
                                                 
                                                 let clear_response = ui.button("Clear Area (15px radius)");
@@ -375,6 +1102,18 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                                 if random_response.secondary_clicked() {
                                                     show_submenu_for = Some("random".to_string());
                                                 }
+
+                                                if ui.button("Select Region").clicked() {
+                                                    new_cursor_mode = Some(crate::state::CursorMode::Select);
+                                                }
+
+                                                if ui.button("Select Object").clicked() {
+                                                    new_cursor_mode = Some(crate::state::CursorMode::SelectObject);
+                                                }
+
+                                                if ui.button("Inspect Cells").clicked() {
+                                                    new_cursor_mode = Some(crate::state::CursorMode::Inspect);
+                                                }
                                             });
                                     });
                                 
@@ -398,8 +1137,8 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                         if state.show_submenu {
                             if let Some(pos) = state.submenu_pos {
                                 // Define a width for the submenu, depending on the parent type
-                                let submenu_width = match state.submenu_parent.as_ref().map(|s| s.as_str()) {
-                                    Some("glider") => 220.0, // Wider for glider submenu (has longer options)
+                                let submenu_width = match state.submenu_parent.as_deref() {
+                                    Some("pattern") => 220.0, // Wider for pattern submenu (has longer options)
                                     Some("paint") => 220.0, // Wider for paint submenu (has more options)
                                     _ => 150.0,
                                 };
@@ -417,7 +1156,7 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                 // If more than 10% would be off-screen, position on the left
                                 let submenu_pos = if offscreen_percent > 10.0 {
                                     // Position on the left side (offset by submenu width + some padding)
-                                    egui::pos2((pos.x as f32 - submenu_width - 10.0), pos.y as f32)
+                                    egui::pos2(pos.x as f32 - submenu_width - 10.0, pos.y as f32)
                                 } else {
                                     // Position on the right side as before
                                     egui::pos2((pos.x + 150.0) as f32, pos.y as f32)
@@ -449,48 +1188,28 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                                 // Different submenu options based on the parent
                                                 if let Some(parent) = &state.submenu_parent {
                                                     match parent.as_str() {
-                                                        "glider" => {
-                                                            // Show different structure placement options
-                                                            if ui.button("Standard Glider").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlaceGlider;
-                                                                state.show_submenu = false;
-                                                                state.show_context_menu = false;
-                                                                log::info!("Selected Standard Glider");
-                                                            }
-                                                            
-                                                            if ui.button("Lightweight Spaceship").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlaceLWSS;
-                                                                state.show_submenu = false;
-                                                                state.show_context_menu = false;
-                                                                log::info!("Selected Lightweight Spaceship");
-                                                            }
-                                                            
-                                                            if ui.button("Pulsar (Period 3)").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlacePulsar;
-                                                                state.show_submenu = false;
-                                                                state.show_context_menu = false;
-                                                                log::info!("Selected Pulsar");
-                                                            }
-                                                            
-                                                            if ui.button("Pentadecathlon (Period 15)").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlacePentadecathlon;
-                                                                state.show_submenu = false;
-                                                                state.show_context_menu = false;
-                                                                log::info!("Selected Pentadecathlon");
-                                                            }
-                                                            
-                                                            if ui.button("Gosper Glider Gun").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlaceGosperGun;
-                                                                state.show_submenu = false;
-                                                                state.show_context_menu = false;
-                                                                log::info!("Selected Gosper Glider Gun");
-                                                            }
-                                                            
-                                                            if ui.button("Simkin Glider Gun").clicked() {
-                                                                state.cursor_mode = crate::state::CursorMode::PlaceSimkinGun;
+                                                        "pattern" => {
+                                                            // Every library entry, grouped by category
+                                                            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                                                for (category, indices) in state.pattern_library.search("") {
+                                                                    ui.label(egui::RichText::new(category).weak());
+                                                                    for index in indices {
+                                                                        let name = &state.pattern_library.entries[index].name;
+                                                                        if ui.selectable_label(state.selected_pattern == Some(index), name).clicked() {
+                                                                            state.selected_pattern = Some(index);
+                                                                            state.cursor_mode = crate::state::CursorMode::PlacePattern;
+                                                                            state.show_submenu = false;
+                                                                            state.show_context_menu = false;
+                                                                            log::info!("Selected pattern: {}", name);
+                                                                        }
+                                                                    }
+                                                                }
+                                                            });
+                                                            ui.separator();
+                                                            if ui.button("Browse Library...").clicked() {
+                                                                state.show_pattern_browser = true;
                                                                 state.show_submenu = false;
                                                                 state.show_context_menu = false;
-                                                                log::info!("Selected Simkin Glider Gun");
                                                             }
                                                         },
                                                         "paint" => {
@@ -598,6 +1317,70 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                             }
                         }
 
+                        // Selection outline, drawn under every window
+                        if let Some([x, y, width, height]) = state.selection {
+                            let pixels_per_point = state.egui_ctx.pixels_per_point();
+                            let (left, top) = state.grid_to_screen(x as i64, y as i64);
+                            let (right, bottom) = state.grid_to_screen(x as i64 + width as i64, y as i64 + height as i64);
+                            let rect = egui::Rect::from_min_max(
+                                egui::pos2(left, top) / pixels_per_point,
+                                egui::pos2(right, bottom) / pixels_per_point,
+                            );
+                            state.egui_ctx.layer_painter(egui::LayerId::background())
+                                .rect_stroke(rect, 0.0, egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 200, 0)));
+                        }
+                        if let Some([x, y, width, height]) = state.selected_object.as_ref().map(|object| object.bounds) {
+                            let pixels_per_point = state.egui_ctx.pixels_per_point();
+                            let (left, top) = state.grid_to_screen(x as i64, y as i64);
+                            let (right, bottom) = state.grid_to_screen(x as i64 + width as i64, y as i64 + height as i64);
+                            let rect = egui::Rect::from_min_max(
+                                egui::pos2(left, top) / pixels_per_point,
+                                egui::pos2(right, bottom) / pixels_per_point,
+                            ).expand(2.0);
+                            state.egui_ctx.layer_painter(egui::LayerId::background())
+                                .rect_stroke(rect, 0.0, egui::Stroke::new(1.0, egui::Color32::WHITE));
+                        }
+                        // Velocity arrows and trails of the tracked spaceships
+                        if let Some(tracker) = &state.tracker {
+                            let pixels_per_point = state.egui_ctx.pixels_per_point();
+                            let (origin_x, origin_y) = state.grid_to_screen(0, 0);
+                            let zoom = state.zoom;
+                            let to_screen = |(x, y): (f32, f32)| egui::pos2(origin_x + x * zoom, origin_y + y * zoom) / pixels_per_point;
+                            let painter = state.egui_ctx.layer_painter(egui::LayerId::background());
+                            let trail_color = egui::Color32::from_rgba_unmultiplied(0, 200, 255, 140);
+                            let arrow_color = egui::Color32::from_rgb(255, 170, 0);
+                            for track in &tracker.tracks {
+                                // Break the trail where it wraps around the torus
+                                for (&(_, from), &(_, to)) in track.trail.iter().zip(track.trail.iter().skip(1)) {
+                                    if (to.0 - from.0).abs() < tracker.width as f32 / 2.0 && (to.1 - from.1).abs() < tracker.height as f32 / 2.0 {
+                                        painter.line_segment([to_screen(from), to_screen(to)], egui::Stroke::new(1.5, trail_color));
+                                    }
+                                }
+                                let position = to_screen(track.position());
+                                let (vx, vy) = track.velocity();
+                                let arrow = egui::vec2(vx, vy) * (TRACK_ARROW_GENERATIONS * zoom / pixels_per_point);
+                                painter.arrow(position, arrow, egui::Stroke::new(2.0, arrow_color));
+                                painter.text(
+                                    position + arrow + egui::vec2(4.0, 0.0),
+                                    egui::Align2::LEFT_CENTER,
+                                    crate::tracker::spaceship_name(&track.motion.code),
+                                    egui::FontId::proportional(11.0),
+                                    arrow_color,
+                                );
+                            }
+                        }
+                        if let Some((x, y)) = state.pinned_cell {
+                            let pixels_per_point = state.egui_ctx.pixels_per_point();
+                            let (left, top) = state.grid_to_screen(x as i64, y as i64);
+                            let (right, bottom) = state.grid_to_screen(x as i64 + 1, y as i64 + 1);
+                            let rect = egui::Rect::from_min_max(
+                                egui::pos2(left, top) / pixels_per_point,
+                                egui::pos2(right, bottom) / pixels_per_point,
+                            ).expand(1.0);
+                            state.egui_ctx.layer_painter(egui::LayerId::background())
+                                .rect_stroke(rect, 0.0, egui::Stroke::new(1.5, egui::Color32::from_rgb(0, 220, 255)));
+                        }
+
                         // Cursor Mode Indicator
                         if let Some(cursor_pos) = state.cursor_pos {
                             use crate::state::CursorMode;
@@ -638,36 +1421,51 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
                                                     .color(color)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::PlaceGlider => {
-                                                ui.label(egui::RichText::new("🚀 Glider").color(egui::Color32::WHITE)
-                                                    .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
-                                            },
-                                            CursorMode::PlaceLWSS => {
-                                                ui.label(egui::RichText::new("🚀 Lightweight Spaceship").color(egui::Color32::WHITE)
-                                                    .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
-                                            },
-                                            CursorMode::PlacePulsar => {
-                                                ui.label(egui::RichText::new("🔄 Pulsar").color(egui::Color32::WHITE)
+                                            CursorMode::PlacePattern => {
+                                                let name = state.selected_pattern
+                                                    .and_then(|i| state.pattern_library.entries.get(i))
+                                                    .map_or("No pattern selected", |entry| entry.name.as_str());
+                                                ui.label(egui::RichText::new(format!("🧩 {}", name)).color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::PlaceGosperGun => {
-                                                ui.label(egui::RichText::new("🔫 Gosper Gun").color(egui::Color32::WHITE)
+                                            CursorMode::ClearArea => {
+                                                ui.label(egui::RichText::new("🧹 Clear").color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::PlacePentadecathlon => {
-                                                ui.label(egui::RichText::new("🔄 Pentadecathlon").color(egui::Color32::WHITE)
+                                            CursorMode::RandomFill => {
+                                                ui.label(egui::RichText::new("🎲 Random").color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::PlaceSimkinGun => {
-                                                ui.label(egui::RichText::new("🔫 Simkin Gun").color(egui::Color32::WHITE)
+                                            CursorMode::Select => {
+                                                ui.label(egui::RichText::new("⬚ Select").color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::ClearArea => {
-                                                ui.label(egui::RichText::new("🧹 Clear").color(egui::Color32::WHITE)
+                                            CursorMode::SelectObject => {
+                                                let text = match state.object_count() {
+                                                    Some(count) => format!("⬚ Object ({} in the grid)", count),
+                                                    None => "⬚ Object".to_string(),
+                                                };
+                                                ui.label(egui::RichText::new(text).color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
-                                            CursorMode::RandomFill => {
-                                                ui.label(egui::RichText::new("🎲 Random").color(egui::Color32::WHITE)
+                                            CursorMode::Inspect => {
+                                                // The window of the hovered cell arrives a frame or two later
+                                                let hovered = state.hovered_cell();
+                                                let window = state.inspector.as_ref()
+                                                    .and_then(|inspector| inspector.hovered.latest.as_ref())
+                                                    .filter(|window| Some((window.x, window.y)) == hovered);
+                                                let text = match (hovered, window) {
+                                                    (Some(_), Some(window)) => {
+                                                        let lucky_chance = state.lucky_rule_enabled.then_some(state.lucky_chance_percent as f32 / 100.0);
+                                                        describe_cell(window, state.simulation.rules, lucky_chance)
+                                                    }
+                                                    (Some((x, y)), None) => format!("🔍 ({}, {})", x, y),
+                                                    (None, _) if state.universe.is_some() || state.packed.is_some() || state.simulation.chunks().is_some() => {
+                                                        "🔍 Not available in this grid mode".to_string()
+                                                    }
+                                                    (None, _) => "🔍 Outside the grid".to_string(),
+                                                };
+                                                ui.label(egui::RichText::new(text).color(egui::Color32::WHITE)
                                                     .background_color(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200)));
                                             },
                                         }
@@ -744,8 +1542,161 @@ async fn run(event_loop: EventLoop<()>, window: Arc<Window>) {
     .unwrap();
 }
 
+/// Starting grid size: `--grid-size WxH`, else `$GPU_LIFE_GRID_SIZE`, else the default.
+/// The window size does not affect it.
+/// Length of the tracker's velocity arrows: the distance covered in this many generations
+const TRACK_ARROW_GENERATIONS: f32 = 24.0;
+
+/// Buttons for the selected object, handled after the UI
+#[derive(Debug, Clone, Copy)]
+enum ObjectAction {
+    Copy,
+    Delete,
+    Analyze,
+}
+
+/// Inspector text for a cell: coordinates, state, neighbors, age and next state
+fn describe_cell(window: &inspector::CellWindow, rules: crate::rules::GameRules, lucky_chance: Option<f32>) -> String {
+    let age = match window.age {
+        Some(age) if window.age_is_lower_bound => format!("\nAge: ≥ {}", age),
+        Some(age) => format!("\nAge: {}", age),
+        None => String::new(),
+    };
+    format!(
+        "🔍 ({}, {})\nState: {} ({})\nNeighbors: {}{}\nNext: {}",
+        window.x, window.y,
+        inspector::state_name(window.value()), window.value(),
+        window.neighbors(),
+        age,
+        window.next_state(rules, lucky_chance),
+    )
+}
+
+/// Fill color of a cell state value in the inspector
+fn state_color(value: f32) -> egui::Color32 {
+    match value.round() as usize {
+        0 => egui::Color32::from_gray(20),
+        state if state < crate::stats::SPECIES_NAMES.len() => species_color(state),
+        _ => egui::Color32::from_gray(128),
+    }
+}
+
+/// Line color of a `stats::SPECIES_NAMES` entry in the population graph
+fn species_color(species: usize) -> egui::Color32 {
+    match species {
+        0 => egui::Color32::from_rgb(90, 170, 255),
+        2 => egui::Color32::from_rgb(255, 140, 0), // Legacy lucky value, told apart from red
+        _ => {
+            let [r, g, b, _] = crate::render::Palette::CellColors.colors()[species];
+            egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+        }
+    }
+}
+
+const BIRTHS_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 220, 120);
+const DEATHS_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 90, 90);
+
+/// Plot the last `view.span` generations of `history` with the painter, marking
+/// generations that have a snapshot. Returns the generation clicked, if any.
+fn draw_population_graph(
+    ui: &mut egui::Ui,
+    history: &crate::history::PopulationHistory,
+    view: &crate::history::HistoryView,
+    snapshot_generations: &[usize],
+) -> Option<usize> {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 220.0), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(15));
+
+    let newest = history.samples().back()?.generation;
+    let first = newest.saturating_sub(view.span);
+    let visible: Vec<_> = history.samples().iter().filter(|sample| sample.generation >= first).collect();
+    let last = newest.max(first + 1);
+
+    // Shared vertical scale for every line drawn
+    let mut max = 1u32;
+    for sample in &visible {
+        for (s, population) in sample.population.iter().enumerate() {
+            if view.species[s] {
+                max = max.max(*population);
+            }
+        }
+        if view.show_births {
+            max = max.max(sample.births);
+        }
+        if view.show_deaths {
+            max = max.max(sample.deaths);
+        }
+    }
+    let x_of = |generation: usize| {
+        rect.left() + (generation - first) as f32 / (last - first) as f32 * rect.width()
+    };
+    let y_of = |value: u32| {
+        let fraction = crate::history::plot_fraction(value as f64, max as f64, view.logarithmic) as f32;
+        rect.bottom() - 4.0 - fraction * (rect.height() - 20.0)
+    };
+    let plot_line = |value: &dyn Fn(&crate::history::HistorySample) -> u32, color: egui::Color32| {
+        let points: Vec<egui::Pos2> = visible.iter().map(|sample| egui::pos2(x_of(sample.generation), y_of(value(sample)))).collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+    };
+    if view.show_births {
+        plot_line(&|sample| sample.births, BIRTHS_COLOR.gamma_multiply(0.7));
+    }
+    if view.show_deaths {
+        plot_line(&|sample| sample.deaths, DEATHS_COLOR.gamma_multiply(0.7));
+    }
+    for s in (0..crate::stats::SPECIES_COUNT).filter(|&s| view.species[s]) {
+        plot_line(&|sample| sample.population[s], species_color(s));
+    }
+
+    // Restorable generations as ticks along the bottom edge
+    for &generation in snapshot_generations.iter().filter(|&&generation| generation >= first) {
+        let x = x_of(generation.min(last));
+        painter.line_segment([egui::pos2(x, rect.bottom() - 4.0), egui::pos2(x, rect.bottom())], egui::Stroke::new(1.0, egui::Color32::GRAY));
+    }
+    let font = egui::FontId::monospace(11.0);
+    let text_color = egui::Color32::from_gray(180);
+    painter.text(rect.left_top() + egui::vec2(4.0, 2.0), egui::Align2::LEFT_TOP,
+                 format!("{}{}", max, if view.logarithmic { " (log)" } else { "" }), font.clone(), text_color);
+    painter.text(rect.left_bottom() + egui::vec2(4.0, -6.0), egui::Align2::LEFT_BOTTOM, first.to_string(), font.clone(), text_color);
+    painter.text(rect.right_bottom() + egui::vec2(-4.0, -6.0), egui::Align2::RIGHT_BOTTOM, last.to_string(), font, text_color);
+
+    // The sample under the pointer
+    let pointer = response.hover_pos()?;
+    let generation = first + ((pointer.x - rect.left()) / rect.width() * (last - first) as f32).round().max(0.0) as usize;
+    let sample = history.nearest(generation)?;
+    let x = x_of(sample.generation.clamp(first, last));
+    painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], egui::Stroke::new(1.0, egui::Color32::from_gray(120)));
+    let response = response.on_hover_text(format!(
+        "Generation {}\nPopulation {}\nBirths +{} / deaths -{}",
+        sample.generation, sample.population[0], sample.births, sample.deaths
+    ));
+    response.clicked().then_some(sample.generation)
+}
+
+fn initial_grid_size() -> Result<(u32, u32), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => {}
+        [flag, value] if flag == "--grid-size" => return crate::resize::parse_grid_size(value),
+        [arg] if arg.starts_with("--grid-size=") => return crate::resize::parse_grid_size(&arg["--grid-size=".len()..]),
+        _ => return Err(format!("Unexpected arguments {:?}; usage: gpu_life [--grid-size WxH]", args)),
+    }
+    match std::env::var(crate::resize::GRID_SIZE_ENV) {
+        Ok(value) => crate::resize::parse_grid_size(&value),
+        Err(_) => Ok((GRID_WIDTH, GRID_HEIGHT)),
+    }
+}
+
 fn main() {
     env_logger::init();
+    let grid_size = match initial_grid_size() {
+        Ok(size) => size,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
     let event_loop = EventLoop::new().unwrap();
 
     let initial_size = winit::dpi::LogicalSize::new(GRID_WIDTH as f64, GRID_HEIGHT as f64);
@@ -764,5 +1715,5 @@ fn main() {
         // let _temp_window = builder.with_name("winit", "winit").build(&event_loop).unwrap();
     }
 
-    pollster::block_on(run(event_loop, window));
+    pollster::block_on(run(event_loop, window, grid_size));
 } 
\ No newline at end of file
@@ -0,0 +1,399 @@
+//! Bit-packed two-state grid: 32 cells per `u32` instead of one `f32` per cell.
+//!
+//! Uses 1/32 of the memory and bandwidth of `Simulation`, at the cost of colors and
+//! the lucky rule: a cell is either dead or alive. Birth/survival masks and both
+//! boundary modes behave exactly as in conway_classic.wgsl. Bit `i` of word `w` in a
+//! row holds cell `x = w * 32 + i`; rows start on a word boundary.
+
+use crate::compute::{ShaderGameRules, WORKGROUP_SIZE, create_compute_bind_groups};
+use crate::rules::{Boundary, GameRules};
+use crate::simulation::request_headless_device;
+use bytemuck::{Pod, Zeroable};
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+
+/// Cells per storage word
+pub const CELLS_PER_WORD: u32 = 32;
+
+/// Edits per `apply_edits` dispatch (workgroups of 64, at most 65535 of them)
+const MAX_EDITS_PER_DISPATCH: usize = 64 * 65535;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PackedParams {
+    pub width: u32,
+    pub height: u32,
+    pub words_per_row: u32,
+    pub boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
+}
+
+pub struct PackedSimulation {
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+
+    pub width: u32,
+    pub height: u32,
+    pub grid_buffers: [wgpu::Buffer; 2],
+    pub param_buffer: wgpu::Buffer,
+    pub rules: GameRules,
+    pub boundary: Boundary,
+    /// Number of steps run since the grid was created or resized
+    pub generation: usize,
+
+    rules_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    bind_groups: [wgpu::BindGroup; 2],
+    edit_bind_group_layout: wgpu::BindGroupLayout,
+    edit_pipeline: wgpu::ComputePipeline,
+}
+
+impl PackedSimulation {
+    /// Create an empty `width` x `height` grid with the default rules
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, width: u32, height: u32) -> Result<Self, String> {
+        check_grid_size(&device, width, height)?;
+        let rules = GameRules::default();
+        let rules_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Packed Game Rules Buffer"),
+            contents: bytemuck::bytes_of(&ShaderGameRules::from(&rules)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let (grid_buffers, param_buffer) = create_grid_buffers(&device, width, height);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Packed Compute Bind Group Layout"),
+            entries: &[
+                layout_entry(0, wgpu::BufferBindingType::Uniform),                          // PackedParams
+                layout_entry(1, wgpu::BufferBindingType::Storage { read_only: true }),  // Input words
+                layout_entry(2, wgpu::BufferBindingType::Storage { read_only: false }), // Output words
+                layout_entry(3, wgpu::BufferBindingType::Uniform),                          // Game Rules
+            ],
+        });
+        let edit_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Packed Edit Bind Group Layout"),
+            entries: &[
+                layout_entry(4, wgpu::BufferBindingType::Storage { read_only: false }), // Edited words
+                layout_entry(5, wgpu::BufferBindingType::Storage { read_only: true }),  // Edits
+            ],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Packed Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("packed.wgsl").into()),
+        });
+        let pipeline_for = |layout: &wgpu::BindGroupLayout, entry_point: &str| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Packed Compute Pipeline Layout"),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Packed Compute Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+            })
+        };
+        let pipeline = pipeline_for(&bind_group_layout, "main");
+        let edit_pipeline = pipeline_for(&edit_bind_group_layout, "apply_edits");
+        let bind_groups = create_compute_bind_groups(
+            &device, &bind_group_layout, &grid_buffers, &param_buffer, &rules_buffer
+        );
+
+        let simulation = Self {
+            device,
+            queue,
+            width,
+            height,
+            grid_buffers,
+            param_buffer,
+            rules,
+            boundary: Boundary::default(),
+            generation: 0,
+            rules_buffer,
+            bind_group_layout,
+            pipeline,
+            bind_groups,
+            edit_bind_group_layout,
+            edit_pipeline,
+        };
+        simulation.write_params();
+        Ok(simulation)
+    }
+
+    /// Create a packed simulation on a device of its own, without any window or surface
+    pub async fn new_headless(width: u32, height: u32) -> Result<Self, String> {
+        let (device, queue) = request_headless_device().await?;
+        Self::new(device, queue, width, height)
+    }
+
+    /// Replace the grid with an empty one of a new size. Resets the generation counter.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        check_grid_size(&self.device, width, height)?;
+        self.width = width;
+        self.height = height;
+        let (grid_buffers, param_buffer) = create_grid_buffers(&self.device, width, height);
+        self.grid_buffers = grid_buffers;
+        self.param_buffer = param_buffer;
+        self.bind_groups = create_compute_bind_groups(
+            &self.device, &self.bind_group_layout, &self.grid_buffers, &self.param_buffer, &self.rules_buffer
+        );
+        self.generation = 0;
+        self.write_params();
+        Ok(())
+    }
+
+    pub fn words_per_row(&self) -> u32 {
+        self.width.div_ceil(CELLS_PER_WORD)
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.words_per_row() as usize * self.height as usize
+    }
+
+    pub fn set_rules(&mut self, rules: GameRules) {
+        self.rules = rules;
+        self.queue.write_buffer(&self.rules_buffer, 0, bytemuck::bytes_of(&ShaderGameRules::from(&rules)));
+    }
+
+    /// Upload grid size and boundary mode
+    pub fn write_params(&self) {
+        self.queue.write_buffer(&self.param_buffer, 0, bytemuck::bytes_of(&PackedParams {
+            width: self.width,
+            height: self.height,
+            words_per_row: self.words_per_row(),
+            boundary: self.boundary.shader_value(),
+        }));
+    }
+
+    /// The buffer holding the current generation
+    pub fn current_buffer(&self) -> &wgpu::Buffer {
+        &self.grid_buffers[self.generation % 2]
+    }
+
+    /// Record one generation into `encoder` and advance the generation counter
+    pub fn encode_step(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Packed Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &self.bind_groups[self.generation % 2], &[]);
+            compute_pass.dispatch_workgroups(
+                self.words_per_row().div_ceil(WORKGROUP_SIZE),
+                self.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        self.generation += 1;
+    }
+
+    /// Run `count` generations in a single submission
+    pub fn step(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.write_params();
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Packed Step Encoder"),
+        });
+        for _ in 0..count {
+            self.encode_step(&mut encoder);
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Copy the packed words of the current generation back to the CPU.
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn read_words(&self) -> Result<Vec<u32>, String> {
+        let buffer_size = self.current_buffer().size();
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Packed Readback Staging Buffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Packed Readback Copy Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(self.current_buffer(), 0, &staging_buffer, 0, buffer_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = staging_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        match receiver.recv() {
+            Ok(Ok(())) => {
+                let data = buffer_slice.get_mapped_range();
+                let words = bytemuck::cast_slice::<u8, u32>(&data)[..self.word_count()].to_vec();
+                drop(data);
+                staging_buffer.unmap();
+                Ok(words)
+            }
+            Ok(Err(e)) => Err(format!("Failed to map staging buffer: {:?}", e)),
+            Err(e) => Err(format!("Failed to receive map result: {:?}", e)),
+        }
+    }
+
+    /// Current generation unpacked to row-major cell values (0.0 or 1.0), as `Simulation::read_cells`.
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn read_cells(&self) -> Result<Vec<f32>, String> {
+        Ok(unpack(&self.read_words()?, self.width, self.height))
+    }
+
+    /// Live cells of the current generation.
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn population(&self) -> Result<u64, String> {
+        Ok(self.read_words()?.iter().map(|word| word.count_ones() as u64).sum())
+    }
+
+    /// Replace the current generation with `cells` (row-major, `width * height`
+    /// values; values above 0.5 are alive)
+    pub fn write_cells(&self, cells: &[f32]) -> Result<(), String> {
+        let count = self.width as usize * self.height as usize;
+        if cells.len() != count {
+            return Err(format!("Expected {} cells, got {}", count, cells.len()));
+        }
+        self.queue.write_buffer(self.current_buffer(), 0, bytemuck::cast_slice(&pack(cells, self.width, self.height)));
+        Ok(())
+    }
+
+    /// Continue from `cells` as generation `generation`, writing both buffers
+    pub fn load_generation(&mut self, generation: usize, cells: &[f32]) -> Result<(), String> {
+        let count = self.width as usize * self.height as usize;
+        if cells.len() != count {
+            return Err(format!("Expected {} cells, got {}", count, cells.len()));
+        }
+        self.generation = generation;
+        let words = pack(cells, self.width, self.height);
+        for buffer in &self.grid_buffers {
+            self.queue.write_buffer(buffer, 0, bytemuck::cast_slice(&words));
+        }
+        self.write_params();
+        Ok(())
+    }
+
+    /// Overwrite a horizontal run of cells starting at (x, y), clipped to the grid.
+    /// Other cells sharing the edited words are left alone.
+    pub fn write_row(&self, x: u32, y: u32, values: &[f32]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let len = values.len().min((self.width - x) as usize);
+        let edits: Vec<[u32; 2]> = values[..len].iter().enumerate()
+            .map(|(i, &value)| {
+                let cell_x = x + i as u32;
+                let word = y * self.words_per_row() + cell_x / CELLS_PER_WORD;
+                let alive = if value > 0.5 { 1 << 31 } else { 0 };
+                [word, (cell_x % CELLS_PER_WORD) | alive]
+            })
+            .collect();
+        self.apply_edits(&edits);
+    }
+
+    fn apply_edits(&self, edits: &[[u32; 2]]) {
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Packed Edit Encoder"),
+        });
+        for chunk in edits.chunks(MAX_EDITS_PER_DISPATCH) {
+            let edit_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Packed Edit Buffer"),
+                contents: bytemuck::cast_slice(chunk),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Packed Edit Bind Group"),
+                layout: &self.edit_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 4, resource: self.current_buffer().as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 5, resource: edit_buffer.as_entire_binding() },
+                ],
+            });
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Packed Edit Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.edit_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((chunk.len() as u32).div_ceil(64), 1, 1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Kill every cell of the current generation
+    pub fn clear(&self) {
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Packed Clear Encoder"),
+        });
+        encoder.clear_buffer(self.current_buffer(), 0, None);
+        self.queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Pack row-major cell values into words (values above 0.5 are alive)
+pub fn pack(cells: &[f32], width: u32, height: u32) -> Vec<u32> {
+    let words_per_row = width.div_ceil(CELLS_PER_WORD) as usize;
+    let mut words = vec![0u32; words_per_row * height as usize];
+    for (y, row) in cells.chunks(width as usize).enumerate() {
+        for (x, &value) in row.iter().enumerate() {
+            if value > 0.5 {
+                words[y * words_per_row + x / 32] |= 1 << (x % 32);
+            }
+        }
+    }
+    words
+}
+
+/// Unpack words into row-major cell values (0.0 or 1.0)
+pub fn unpack(words: &[u32], width: u32, height: u32) -> Vec<f32> {
+    let words_per_row = width.div_ceil(CELLS_PER_WORD) as usize;
+    let mut cells = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height as usize {
+        let row = &words[y * words_per_row..(y + 1) * words_per_row];
+        cells.extend((0..width as usize).map(|x| ((row[x / 32] >> (x % 32)) & 1) as f32));
+    }
+    cells
+}
+
+fn layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
+
+fn check_grid_size(device: &wgpu::Device, width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err("Grid dimensions must be non-zero".to_string());
+    }
+    let bytes = width.div_ceil(CELLS_PER_WORD) as u64 * height as u64 * std::mem::size_of::<u32>() as u64;
+    let limit = device.limits().max_storage_buffer_binding_size as u64;
+    if bytes > limit {
+        return Err(format!("A packed {}x{} grid needs {} bytes per buffer, the device allows {}", width, height, bytes, limit));
+    }
+    Ok(())
+}
+
+fn create_grid_buffers(device: &wgpu::Device, width: u32, height: u32) -> ([wgpu::Buffer; 2], wgpu::Buffer) {
+    let buffer_size = width.div_ceil(CELLS_PER_WORD) as u64 * height as u64 * std::mem::size_of::<u32>() as u64;
+    let grid_buffers = [0, 1].map(|i| device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("Packed Grid Buffer {}", i)),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    }));
+    let param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Packed Parameters"),
+        size: std::mem::size_of::<PackedParams>() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (grid_buffers, param_buffer)
+}
@@ -0,0 +1,131 @@
+// Two-state life-like rules on bit-packed cells.
+//
+// 32 cells per u32: bit i of word w in a row holds cell x = w * 32 + i; bits past
+// the right edge of the grid are always 0. Each invocation computes one word,
+// summing the eight neighbor words with bit-parallel adders so all 32 cells are
+// evaluated at once. Rows are `words_per_row` words apart.
+
+struct PackedParams {
+    width: u32,
+    height: u32,
+    words_per_row: u32,
+    boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
+}
+
+struct GameRules {
+    birth_mask: u32,
+    survival_mask: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: PackedParams;
+@group(0) @binding(1) var<storage, read> cells_in: array<u32>;
+@group(0) @binding(2) var<storage, read_write> cells_out: array<u32>;
+@group(0) @binding(3) var<uniform> game_rules: GameRules;
+
+// Row index after applying the boundary, or -1 for a dead row outside the grid
+fn wrap_row(y: i32) -> i32 {
+    let height = i32(params.height);
+    if (y >= 0 && y < height) {
+        return y;
+    }
+    if (params.boundary == 1u) {
+        return -1;
+    }
+    return (y + height) % height;
+}
+
+// Single cell (0 or 1) of a row, with the boundary applied along x
+fn cell_bit(row: i32, x: i32) -> u32 {
+    let width = i32(params.width);
+    var cell_x = x;
+    if (x < 0 || x >= width) {
+        if (params.boundary == 1u) {
+            return 0u;
+        }
+        cell_x = (x + width) % width;
+    }
+    let word = cells_in[u32(row) * params.words_per_row + u32(cell_x) / 32u];
+    return (word >> (u32(cell_x) % 32u)) & 1u;
+}
+
+// The word at `word_x` of row `y` shifted by one cell west, unshifted, and shifted
+// east, so bit i of each holds the neighbor of cell i in that direction
+fn row_neighbors(y: i32, word_x: u32, valid: u32) -> vec3<u32> {
+    let row = wrap_row(y);
+    if (row < 0) {
+        return vec3<u32>(0u, 0u, 0u);
+    }
+    let x0 = i32(word_x * 32u);
+    let center = cells_in[u32(row) * params.words_per_row + word_x];
+    let west = (center << 1u) | cell_bit(row, x0 - 1);
+    let east = (center >> 1u) | (cell_bit(row, x0 + i32(valid)) << (valid - 1u));
+    return vec3<u32>(west, center, east);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let word_x = global_id.x;
+    let y = global_id.y;
+    if (word_x >= params.words_per_row || y >= params.height) {
+        return;
+    }
+    let valid = min(32u, params.width - word_x * 32u);
+    let valid_mask = select((1u << valid) - 1u, 0xFFFFFFFFu, valid == 32u);
+
+    let above = row_neighbors(i32(y) - 1, word_x, valid);
+    let own = row_neighbors(i32(y), word_x, valid);
+    let below = row_neighbors(i32(y) + 1, word_x, valid);
+    var neighbors = array<u32, 8>(above.x, above.y, above.z, own.x, own.z, below.x, below.y, below.z);
+
+    // Per-cell neighbor count as four bit planes, one ripple-carry add per neighbor
+    var b0 = 0u;
+    var b1 = 0u;
+    var b2 = 0u;
+    var b3 = 0u;
+    for (var i = 0u; i < 8u; i = i + 1u) {
+        let carry0 = b0 & neighbors[i];
+        b0 = b0 ^ neighbors[i];
+        let carry1 = b1 & carry0;
+        b1 = b1 ^ carry0;
+        let carry2 = b2 & carry1;
+        b2 = b2 ^ carry1;
+        b3 = b3 | carry2;
+    }
+
+    let cell = own.y;
+    var next = 0u;
+    for (var count = 0u; count <= 8u; count = count + 1u) {
+        let equal = select(~b0, b0, (count & 1u) != 0u)
+            & select(~b1, b1, (count & 2u) != 0u)
+            & select(~b2, b2, (count & 4u) != 0u)
+            & select(~b3, b3, (count & 8u) != 0u);
+        if (((game_rules.birth_mask >> count) & 1u) == 1u) {
+            next = next | (equal & ~cell);
+        }
+        if (((game_rules.survival_mask >> count) & 1u) == 1u) {
+            next = next | (equal & cell);
+        }
+    }
+    cells_out[y * params.words_per_row + word_x] = next & valid_mask;
+}
+
+// Single-cell edits (painting), applied with atomics so cells sharing a word are kept.
+// x = word index, y = bit index | (1 << 31) to set the cell, bit index alone to clear it.
+@group(0) @binding(4) var<storage, read_write> edit_target: array<atomic<u32>>;
+@group(0) @binding(5) var<storage, read> edits: array<vec2<u32>>;
+
+@compute @workgroup_size(64, 1, 1)
+fn apply_edits(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= arrayLength(&edits)) {
+        return;
+    }
+    let edit = edits[global_id.x];
+    let bit = 1u << (edit.y & 31u);
+    if ((edit.y >> 31u) == 1u) {
+        atomicOr(&edit_target[edit.x], bit);
+    } else {
+        atomicAnd(&edit_target[edit.x], ~bit);
+    }
+}
@@ -0,0 +1,611 @@
+//! Period detection: classify a pattern as a still life, an oscillator or a spaceship.
+//!
+//! The pattern is copied into a scratch `Simulation` and stepped; after every
+//! generation a kernel (`period.wgsl`) hashes the live cells on the GPU. Hashes are
+//! normalized to the bounding box corner, so a state that repeats somewhere else
+//! reveals a spaceship and the distance it moved. Only the small per-generation
+//! records are read back, a batch at a time.
+
+use crate::readback::ReadbackRing;
+use crate::simulation::{Simulation, DEFAULT_SHADER_SOURCE};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Generations hashed per submission (and read back together)
+const HASH_BATCH: usize = 64;
+const HASH_WORKGROUP_SIZE: u32 = 16;
+const RECORD_WORDS: usize = 8;
+
+/// Dead cells around a selection, so the pattern can grow or travel before it
+/// reaches the edge of the scratch grid
+pub const SELECTION_MARGIN: u32 = 64;
+
+// Hash bases, mirroring period.wgsl
+const HASH_A_X: u32 = 0x9E3779B5;
+const HASH_A_Y: u32 = 0x85EBCA6B;
+const HASH_B_X: u32 = 0xC2B2AE35;
+const HASH_B_Y: u32 = 0x27D4EB2B;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct HashParams {
+    width: u32,
+    height: u32,
+    record: u32,
+    _padding: u32,
+}
+
+/// What the live cells of one generation hash to (see `period.wgsl`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HashRecord {
+    pub population: u32,
+    /// Bounding box of the live cells, `[min_x, min_y, max_x, max_y]` (meaningless while empty)
+    pub bbox: [u32; 4],
+    /// Sums of X^x * Y^y over live cells, for two pairs of bases
+    pub hashes: [u32; 2],
+}
+
+/// A state independent of where it is on the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct StateKey {
+    population: u32,
+    size: [u32; 2],
+    hashes: [u32; 2],
+}
+
+impl HashRecord {
+    /// The same record computed on the CPU (row-major cells, alive above 0.5)
+    pub fn from_cells(cells: &[f32], width: u32) -> Self {
+        let mut record = HashRecord { bbox: [u32::MAX, u32::MAX, 0, 0], ..Default::default() };
+        for (i, _) in cells.iter().enumerate().filter(|(_, &cell)| cell > 0.5) {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            record.population += 1;
+            record.bbox = [record.bbox[0].min(x), record.bbox[1].min(y), record.bbox[2].max(x), record.bbox[3].max(y)];
+            record.hashes[0] = record.hashes[0].wrapping_add(power(HASH_A_X, x).wrapping_mul(power(HASH_A_Y, y)));
+            record.hashes[1] = record.hashes[1].wrapping_add(power(HASH_B_X, x).wrapping_mul(power(HASH_B_Y, y)));
+        }
+        if record.population == 0 {
+            record.bbox = [0; 4];
+        }
+        record
+    }
+
+    pub(crate) fn from_words(words: &[u32]) -> Self {
+        let bbox = if words[0] == 0 { [0; 4] } else { [!words[1], !words[2], words[3], words[4]] };
+        Self { population: words[0], bbox, hashes: [words[5], words[6]] }
+    }
+
+    /// Hashes as if the bounding box started at (0, 0): dividing by X^min_x * Y^min_y
+    fn key(&self) -> StateKey {
+        let [min_x, min_y, max_x, max_y] = self.bbox;
+        let normalize = |hash: u32, base_x: u32, base_y: u32| {
+            hash.wrapping_mul(power(inverse(base_x), min_x)).wrapping_mul(power(inverse(base_y), min_y))
+        };
+        StateKey {
+            population: self.population,
+            size: [max_x.wrapping_sub(min_x), max_y.wrapping_sub(min_y)],
+            hashes: [normalize(self.hashes[0], HASH_A_X, HASH_A_Y), normalize(self.hashes[1], HASH_B_X, HASH_B_Y)],
+        }
+    }
+}
+
+fn power(base: u32, exponent: u32) -> u32 {
+    let (mut result, mut factor, mut remaining) = (1u32, base, exponent);
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = result.wrapping_mul(factor);
+        }
+        factor = factor.wrapping_mul(factor);
+        remaining >>= 1;
+    }
+    result
+}
+
+/// Multiplicative inverse of an odd number modulo 2^32 (Newton's iteration)
+fn inverse(odd: u32) -> u32 {
+    let mut x = odd;
+    for _ in 0..5 {
+        x = x.wrapping_mul(2u32.wrapping_sub(odd.wrapping_mul(x)));
+    }
+    x
+}
+
+/// What a pattern turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    /// No live cells left at `generation`
+    DiesOut { generation: usize },
+    StillLife,
+    Oscillator { period: usize },
+    /// Repeats every `period` generations, moved by (dx, dy) cells (y grows downwards)
+    Spaceship { period: usize, dx: i64, dy: i64 },
+    /// Nothing repeated within `generations` generations
+    Unresolved { generations: usize },
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Classification::DiesOut { generation } => write!(f, "Dies out at generation {}", generation),
+            Classification::StillLife => write!(f, "Still life"),
+            Classification::Oscillator { period } => write!(f, "Oscillator, period {}", period),
+            Classification::Spaceship { period, dx, dy } => write!(f, "Spaceship, speed ({}, {})/{}", dx, dy, period),
+            Classification::Unresolved { generations } => write!(f, "No repetition within {} generations", generations),
+        }
+    }
+}
+
+/// The outcome of `analyze`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis {
+    pub classification: Classification,
+    /// Generations before the pattern entered its cycle (0 if it repeats from the start)
+    pub settled_after: usize,
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.classification)?;
+        let cyclic = !matches!(self.classification, Classification::DiesOut { .. } | Classification::Unresolved { .. });
+        if cyclic && self.settled_after > 0 {
+            write!(f, " (after {} generations)", self.settled_after)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the first repeated state in a sequence of per-generation hash records
+#[derive(Debug, Default)]
+pub struct PeriodDetector {
+    /// First generation and bounding box corner of every state seen
+    seen: HashMap<StateKey, (usize, [u32; 2])>,
+}
+
+impl PeriodDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the record of `generation` (generations in increasing order). Returns the
+    /// result once the pattern dies out or repeats a state.
+    pub fn push(&mut self, generation: usize, record: &HashRecord) -> Option<Analysis> {
+        if record.population == 0 {
+            return Some(Analysis { classification: Classification::DiesOut { generation }, settled_after: generation });
+        }
+        let corner = [record.bbox[0], record.bbox[1]];
+        let (first, first_corner) = *self.seen.entry(record.key()).or_insert((generation, corner));
+        if first == generation {
+            return None;
+        }
+        let period = generation - first;
+        let (dx, dy) = (corner[0] as i64 - first_corner[0] as i64, corner[1] as i64 - first_corner[1] as i64);
+        let classification = match (dx, dy) {
+            (0, 0) if period == 1 => Classification::StillLife,
+            (0, 0) => Classification::Oscillator { period },
+            _ => Classification::Spaceship { period, dx, dy },
+        };
+        Some(Analysis { classification, settled_after: first })
+    }
+}
+
+struct HashSlot {
+    records: wgpu::Buffer,
+    /// Bind groups reading `grid_buffers[parity]` into these records
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+/// Hashes the grid of one `Simulation` into per-generation records.
+///
+/// Like `stats::StatsReducer`, records are encoded into the caller's command encoder
+/// and read back through a ring of staging buffers, `HASH_BATCH` records per slot.
+struct PatternHasher {
+    pipeline: wgpu::ComputePipeline,
+    /// Kernel resources of each slot of `ring`
+    slots: Vec<HashSlot>,
+    /// Generation of each record copied into a slot's staging buffer
+    ring: ReadbackRing<Vec<usize>>,
+    /// Bytes between the `HashParams` of consecutive records (dynamic offset alignment)
+    param_stride: u32,
+    width: u32,
+    height: u32,
+}
+
+impl PatternHasher {
+    fn new(simulation: &Simulation, slot_count: usize) -> Self {
+        let device = &simulation.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Period Hash Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("period.wgsl").into()),
+        });
+        let buffer_entry = |binding, ty, has_dynamic_offset| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset, min_binding_size: None },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Period Hash Bind Group Layout"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Uniform, true),
+                buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: true }, false),
+                buffer_entry(2, wgpu::BufferBindingType::Storage { read_only: false }, false),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Period Hash Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Period Hash Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "main",
+        });
+
+        // One parameter block per record, selected with a dynamic offset
+        let param_size = std::mem::size_of::<HashParams>() as u32;
+        let param_stride = param_size.max(device.limits().min_uniform_buffer_offset_alignment);
+        let mut params = vec![0u8; param_stride as usize * HASH_BATCH];
+        for record in 0..HASH_BATCH {
+            let block = HashParams { width: simulation.width, height: simulation.height, record: record as u32, _padding: 0 };
+            let start = record * param_stride as usize;
+            params[start..start + param_size as usize].copy_from_slice(bytemuck::bytes_of(&block));
+        }
+        let param_buffer = wgpu::util::DeviceExt::create_buffer_init(device.as_ref(), &wgpu::util::BufferInitDescriptor {
+            label: Some("Period Hash Parameters"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let records_size = (HASH_BATCH * RECORD_WORDS * std::mem::size_of::<u32>()) as u64;
+        let slots = (0..slot_count.max(1))
+            .map(|i| {
+                let records = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Period Hash Records {}", i)),
+                    size: records_size,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                let bind_groups = [0, 1].map(|parity| device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("Period Hash Bind Group {} ({})", i, parity)),
+                    layout: &bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                                buffer: &param_buffer,
+                                offset: 0,
+                                size: wgpu::BufferSize::new(param_size as u64),
+                            }),
+                        },
+                        wgpu::BindGroupEntry { binding: 1, resource: simulation.grid_buffers[parity].as_entire_binding() },
+                        wgpu::BindGroupEntry { binding: 2, resource: records.as_entire_binding() },
+                    ],
+                }));
+                HashSlot { records, bind_groups }
+            })
+            .collect();
+        let ring = ReadbackRing::new(device, "Period Hash Staging Buffer", records_size, slot_count);
+        Self { pipeline, slots, ring, param_stride, width: simulation.width, height: simulation.height }
+    }
+
+    /// Record the hash of `generation` (held in `grid_buffers[generation % 2]`) into the
+    /// encoder. Returns false (and records nothing) if every slot is full or in flight.
+    fn encode_hash(&mut self, encoder: &mut wgpu::CommandEncoder, generation: usize) -> bool {
+        // Keep filling the slot that has records already, else start a free one
+        let filling = self.ring.filling()
+            .find(|(_, generations)| generations.len() < HASH_BATCH)
+            .map(|(index, generations)| (index, generations.len()));
+        let Some((index, record)) = filling.or_else(|| self.ring.free_slot().map(|index| (index, 0))) else {
+            return false;
+        };
+        let slot = &self.slots[index];
+        if record == 0 {
+            encoder.clear_buffer(&slot.records, 0, None);
+        }
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Period Hash Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &slot.bind_groups[generation % 2], &[record as u32 * self.param_stride]);
+        pass.dispatch_workgroups(self.width.div_ceil(HASH_WORKGROUP_SIZE), self.height.div_ceil(HASH_WORKGROUP_SIZE), 1);
+        drop(pass);
+        // The staging copy of the slot's records is refreshed with every hash, so the
+        // slot can be mapped after whichever submit comes next
+        encoder.copy_buffer_to_buffer(&slot.records, 0, self.ring.staging(index), 0, slot.records.size());
+        match self.ring.tag_mut(index) {
+            Some(generations) => generations.push(generation),
+            None => self.ring.fill(index, vec![generation]),
+        }
+        true
+    }
+
+    /// Must be called after the encoder holding hashes has been submitted
+    fn after_submit(&mut self) {
+        self.ring.after_submit();
+    }
+
+    /// Records that have arrived, oldest first (non-blocking)
+    fn poll(&mut self, device: &wgpu::Device) -> Vec<(usize, HashRecord)> {
+        self.ring.poll(device, decode_records).into_iter().flatten().collect()
+    }
+
+    /// Wait for the oldest slot in flight; returns the records that arrived meanwhile
+    fn wait_for_slot(&mut self, device: &wgpu::Device) -> Vec<(usize, HashRecord)> {
+        self.ring.wait_oldest(device, decode_records).into_iter().flatten().collect()
+    }
+}
+
+/// Records of a staging buffer, paired with the generations they were hashed from
+fn decode_records(generations: Vec<usize>, data: &[u8]) -> Vec<(usize, HashRecord)> {
+    let words: &[u32] = bytemuck::cast_slice(data);
+    generations.into_iter().zip(words.chunks(RECORD_WORDS))
+        .map(|(generation, words)| (generation, HashRecord::from_words(words)))
+        .collect()
+}
+
+/// Step a copy of `source`'s current generation for up to `max_generations` and
+/// classify it. With a `region` (`[x, y, width, height]`), only the cells inside it are
+/// copied, onto a dead-bounded grid with `SELECTION_MARGIN` dead cells around them, so
+/// the selection is classified as an isolated object. Without one, the whole grid is
+/// copied with its boundary mode. The lucky rule is never applied.
+pub fn analyze(source: &Simulation, region: Option<[u32; 4]>, max_generations: usize) -> Result<Analysis, String> {
+    let cells = source.read_cells()?;
+    let (width, height, cells, boundary) = match region {
+        Some([x, y, w, h]) => {
+            if w == 0 || h == 0 || x + w > source.width || y + h > source.height {
+                return Err("The selection is outside the grid".to_string());
+            }
+            let (width, height) = (w + 2 * SELECTION_MARGIN, h + 2 * SELECTION_MARGIN);
+            let mut copy = vec![0.0; width as usize * height as usize];
+            for row in 0..h {
+                let from = ((y + row) * source.width + x) as usize;
+                let to = ((row + SELECTION_MARGIN) * width + SELECTION_MARGIN) as usize;
+                copy[to..to + w as usize].copy_from_slice(&cells[from..from + w as usize]);
+            }
+            (width, height, copy, crate::rules::Boundary::Dead)
+        }
+        None => {
+            if source.chunks().is_some() {
+                return Err("Select a region: the whole grid is split into chunks".to_string());
+            }
+            (source.width, source.height, cells, source.boundary)
+        }
+    };
+    analyze_copy(source, width, height, &cells, boundary, max_generations)
+}
+
+/// Like `analyze` with a region, but only `object`'s cells are copied (e.g. a
+/// `components::Object`), without the other cells inside its bounding box
+pub fn analyze_object(source: &Simulation, object: &[(u32, u32)], max_generations: usize) -> Result<Analysis, String> {
+    if object.iter().any(|&(x, y)| x >= source.width || y >= source.height) {
+        return Err("The object is outside the grid".to_string());
+    }
+    let (Some(min_x), Some(min_y)) = (object.iter().map(|c| c.0).min(), object.iter().map(|c| c.1).min()) else {
+        return Err("The object has no cells".to_string());
+    };
+    let max_x = object.iter().map(|c| c.0).max().unwrap_or(min_x);
+    let max_y = object.iter().map(|c| c.1).max().unwrap_or(min_y);
+    let cells = source.read_cells()?;
+    let width = max_x - min_x + 1 + 2 * SELECTION_MARGIN;
+    let height = max_y - min_y + 1 + 2 * SELECTION_MARGIN;
+    let mut copy = vec![0.0; width as usize * height as usize];
+    for &(x, y) in object {
+        copy[((y - min_y + SELECTION_MARGIN) * width + x - min_x + SELECTION_MARGIN) as usize] = cells[(y * source.width + x) as usize];
+    }
+    analyze_copy(source, width, height, &copy, crate::rules::Boundary::Dead, max_generations)
+}
+
+/// Classify `cells` on a scratch grid with `source`'s rules and shader
+fn analyze_copy(
+    source: &Simulation, width: u32, height: u32, cells: &[f32], boundary: crate::rules::Boundary, max_generations: usize,
+) -> Result<Analysis, String> {
+    let mut scratch = Simulation::new(source.device.clone(), source.queue.clone(), width, height)?;
+    if scratch.chunks().is_some() {
+        return Err("The selection is too large to analyze".to_string());
+    }
+    if source.shader_source() != DEFAULT_SHADER_SOURCE {
+        scratch.load_shader(source.shader_source().to_string())?;
+    }
+    scratch.set_rules(source.rules);
+    scratch.boundary = boundary;
+    scratch.load_generation(0, cells)?;
+
+    let mut hasher = PatternHasher::new(&scratch, 1);
+    let mut detector = PeriodDetector::new();
+    let mut generation = 0;
+    while generation <= max_generations {
+        let count = HASH_BATCH.min(max_generations + 1 - generation);
+        let mut encoder = scratch.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Period Hash Encoder"),
+        });
+        for index in 0..count {
+            // The first record of the first batch is the starting generation itself
+            if generation + index > 0 {
+                scratch.encode_step(&mut encoder);
+            }
+            hasher.encode_hash(&mut encoder, scratch.generation);
+        }
+        scratch.queue.submit(Some(encoder.finish()));
+        hasher.after_submit();
+        for (record_generation, record) in hasher.wait_for_slot(&scratch.device) {
+            if let Some(analysis) = detector.push(record_generation, &record) {
+                log::info!("Period analysis of {}x{} cells: {}", width, height, analysis);
+                return Ok(analysis);
+            }
+            generation = record_generation + 1;
+        }
+    }
+    Ok(Analysis { classification: Classification::Unresolved { generations: max_generations }, settled_after: 0 })
+}
+
+/// Generations of hash records kept to find where a cycle started
+const STABILITY_HISTORY: usize = 4096;
+
+/// When to pause the GUI simulation on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoPauseSettings {
+    pub enabled: bool,
+    /// Longest period of the whole grid that counts as stable. The grid's period is
+    /// the least common multiple of its oscillators' periods (blinkers and pulsars: 6).
+    pub max_period: usize,
+}
+
+impl Default for AutoPauseSettings {
+    fn default() -> Self {
+        Self { enabled: false, max_period: 30 }
+    }
+}
+
+/// The grid has become periodic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stabilization {
+    /// First generation of the cycle (as far back as the kept history reaches)
+    pub generation: usize,
+    pub period: usize,
+    /// Live cells at the generation the repetition was seen
+    pub population: u32,
+    /// Generation whose hash repeated an earlier one
+    pub detected_at: usize,
+}
+
+impl fmt::Display for Stabilization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Stable since generation {}: period {}, population {}", self.generation, self.period, self.population)
+    }
+}
+
+/// Recent hash records of a grid, checked for an exact repetition within `max_period`
+/// generations. Unlike `PeriodDetector`, positions matter: the whole grid repeats.
+#[derive(Debug, Clone)]
+pub struct StabilityHistory {
+    pub max_period: usize,
+    /// Increasing generations; gaps are allowed (e.g. after a jump)
+    records: VecDeque<(usize, HashRecord)>,
+}
+
+impl StabilityHistory {
+    pub fn new(max_period: usize) -> Self {
+        Self { max_period: max_period.max(1), records: VecDeque::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Newest generation held
+    pub fn latest_generation(&self) -> Option<usize> {
+        self.records.back().map(|(generation, _)| *generation)
+    }
+
+    fn record(&self, generation: usize) -> Option<&HashRecord> {
+        self.records
+            .binary_search_by_key(&generation, |(held, _)| *held)
+            .ok()
+            .map(|index| &self.records[index].1)
+    }
+
+    /// Add the record of `generation`. Returns the stabilization once the grid repeats
+    /// a state at most `max_period` generations old (the shortest such period).
+    pub fn push(&mut self, generation: usize, record: &HashRecord) -> Option<Stabilization> {
+        // Going back in time starts over
+        if self.records.back().is_some_and(|(last, _)| *last >= generation) {
+            self.records.clear();
+        }
+        if self.records.len() == STABILITY_HISTORY {
+            self.records.pop_front();
+        }
+        self.records.push_back((generation, *record));
+
+        let period = (1..=self.max_period.min(generation))
+            .find(|&period| self.record(generation - period) == Some(record))?;
+        // Walk back to the first generation that the cycle already covers
+        let mut start = generation - period;
+        while start > 0 {
+            match (self.record(start - 1), self.record(start - 1 + period)) {
+                (Some(earlier), Some(later)) if earlier == later => start -= 1,
+                _ => break,
+            }
+        }
+        Some(Stabilization { generation: start, period, population: record.population, detected_at: generation })
+    }
+}
+
+/// Watches a running `Simulation` for the generation from which it is periodic.
+///
+/// Every generation is hashed on the GPU (`period.wgsl`, positions included) and read
+/// back without blocking; only 32 bytes per generation leave the GPU.
+pub struct StabilityMonitor {
+    hasher: PatternHasher,
+    pub history: StabilityHistory,
+    /// `Simulation::edit_count` the history belongs to
+    edits: u64,
+    /// Set once stable; nothing more is reported until the grid is edited
+    settled: bool,
+}
+
+impl StabilityMonitor {
+    /// Monitor of `simulation`'s grid buffers; create a new one when they are replaced
+    pub fn new(simulation: &Simulation, max_period: usize) -> Result<Self, String> {
+        if simulation.chunks().is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        Ok(Self {
+            hasher: PatternHasher::new(simulation, 3),
+            history: StabilityHistory::new(max_period),
+            edits: simulation.edit_count(),
+            settled: false,
+        })
+    }
+
+    /// Forget the history when the grid was edited: it no longer follows from it
+    pub fn sync_edits(&mut self, edit_count: u64) {
+        if edit_count != self.edits {
+            self.edits = edit_count;
+            self.history.clear();
+            self.settled = false;
+        }
+    }
+
+    /// Record the hash of `generation` into the encoder.
+    /// Returns false (and records nothing) if every slot is busy.
+    pub fn encode_hash(&mut self, encoder: &mut wgpu::CommandEncoder, generation: usize) -> bool {
+        self.hasher.encode_hash(encoder, generation)
+    }
+
+    /// Must be called after the encoder holding hashes has been submitted
+    pub fn after_submit(&mut self) {
+        self.hasher.after_submit();
+    }
+
+    /// Check the records that have arrived (non-blocking)
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<Stabilization> {
+        let records = self.hasher.poll(device);
+        self.check(records)
+    }
+
+    /// Wait for the oldest slot in flight so one becomes free
+    pub fn wait_for_slot(&mut self, device: &wgpu::Device) -> Option<Stabilization> {
+        let records = self.hasher.wait_for_slot(device);
+        self.check(records)
+    }
+
+    fn check(&mut self, records: Vec<(usize, HashRecord)>) -> Option<Stabilization> {
+        let mut found = None;
+        for (generation, record) in records {
+            // Going back (a restored generation) may settle again
+            if self.history.latest_generation().is_some_and(|latest| latest >= generation) {
+                self.settled = false;
+            }
+            if let Some(stabilization) = self.history.push(generation, &record) {
+                if !self.settled {
+                    self.settled = true;
+                    found = Some(stabilization);
+                }
+            }
+        }
+        found
+    }
+}
@@ -0,0 +1,85 @@
+// Translation-aware hash of the live cells of a grid, one record per generation.
+//
+// Every live cell at (x, y) adds X^x * Y^y (wrapping u32 arithmetic) to two hash
+// sums with different odd bases. Moving a pattern by (dx, dy) multiplies its sums
+// by X^dx * Y^dy, so the CPU divides out the bounding box corner to compare
+// patterns independently of their position (see `period::HashRecord`).
+
+const FIELD_POPULATION: u32 = 0u;
+// Minimums are stored inverted like in stats.wgsl, so records start cleared to zero
+const FIELD_MIN_X_INV: u32 = 1u;
+const FIELD_MIN_Y_INV: u32 = 2u;
+const FIELD_MAX_X: u32 = 3u;
+const FIELD_MAX_Y: u32 = 4u;
+const FIELD_HASH_A: u32 = 5u;
+const FIELD_HASH_B: u32 = 6u;
+const RECORD_WORDS: u32 = 8u;
+const WORKGROUP_CELLS: u32 = 256u;
+
+// Bases of the two hashes (odd, so they are invertible modulo 2^32)
+const HASH_A_X: u32 = 0x9E3779B5u;
+const HASH_A_Y: u32 = 0x85EBCA6Bu;
+const HASH_B_X: u32 = 0xC2B2AE35u;
+const HASH_B_Y: u32 = 0x27D4EB2Bu;
+
+struct HashParams {
+    width: u32,
+    height: u32,
+    record: u32, // Record written by this dispatch
+    _padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: HashParams;
+@group(0) @binding(1) var<storage, read> cells: array<f32>;
+@group(0) @binding(2) var<storage, read_write> records: array<atomic<u32>>;
+
+var<workgroup> local_record: array<atomic<u32>, 8>;
+
+fn power(base: u32, exponent: u32) -> u32 {
+    var result = 1u;
+    var factor = base;
+    var remaining = exponent;
+    while (remaining > 0u) {
+        if ((remaining & 1u) == 1u) {
+            result = result * factor;
+        }
+        factor = factor * factor;
+        remaining = remaining >> 1u;
+    }
+    return result;
+}
+
+@compute @workgroup_size(16, 16)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    if (local_index < RECORD_WORDS) {
+        atomicStore(&local_record[local_index], 0u);
+    }
+    workgroupBarrier();
+
+    let x = global_id.x;
+    let y = global_id.y;
+    if (x < params.width && y < params.height && cells[y * params.width + x] > 0.5) {
+        atomicAdd(&local_record[FIELD_POPULATION], 1u);
+        atomicMax(&local_record[FIELD_MIN_X_INV], 0xFFFFFFFFu - x);
+        atomicMax(&local_record[FIELD_MIN_Y_INV], 0xFFFFFFFFu - y);
+        atomicMax(&local_record[FIELD_MAX_X], x);
+        atomicMax(&local_record[FIELD_MAX_Y], y);
+        atomicAdd(&local_record[FIELD_HASH_A], power(HASH_A_X, x) * power(HASH_A_Y, y));
+        atomicAdd(&local_record[FIELD_HASH_B], power(HASH_B_X, x) * power(HASH_B_Y, y));
+    }
+    workgroupBarrier();
+
+    // Flush the workgroup record, skipping workgroups without live cells
+    if (local_index < RECORD_WORDS && atomicLoad(&local_record[FIELD_POPULATION]) != 0u) {
+        let value = atomicLoad(&local_record[local_index]);
+        let slot = params.record * RECORD_WORDS + local_index;
+        if (local_index >= FIELD_MIN_X_INV && local_index <= FIELD_MAX_Y) {
+            atomicMax(&records[slot], value);
+        } else {
+            atomicAdd(&records[slot], value);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Asynchronous readback of small GPU buffers through a ring of staging buffers.
+//!
+//! The owner records a copy into a free slot's staging buffer and tags the slot with
+//! what it holds (e.g. a generation). After the encoder is submitted, `after_submit`
+//! requests the mappings; `poll` decodes the slots whose mapping finished without
+//! blocking, and `wait_oldest` blocks only for the oldest slot in flight. Slots are
+//! decoded in the order they were filled.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Default number of staging buffers in a ring
+pub const DEFAULT_RING_SLOTS: usize = 4;
+
+// Mapping states reported by the map_async callback
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+struct ReadbackSlot<T> {
+    staging: wgpu::Buffer,
+    /// What the staging buffer holds; None while the slot is free
+    tag: Option<T>,
+    /// Order in which the slot was filled
+    sequence: u64,
+    /// Whether map_async has been requested (only after the copy was submitted)
+    map_requested: bool,
+    map_state: Arc<AtomicU8>,
+}
+
+/// A ring of `MAP_READ` staging buffers of one size, each tagged with a `T`
+pub struct ReadbackRing<T> {
+    slots: Vec<ReadbackSlot<T>>,
+    label: String,
+    next_sequence: u64,
+}
+
+impl<T> ReadbackRing<T> {
+    /// `slot_count` (at least one) staging buffers of `size` bytes, labeled "`label` i"
+    pub fn new(device: &wgpu::Device, label: &str, size: u64, slot_count: usize) -> Self {
+        let slots = (0..slot_count.max(1))
+            .map(|i| ReadbackSlot {
+                staging: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("{} {}", label, i)),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                tag: None,
+                sequence: 0,
+                map_requested: false,
+                map_state: Arc::new(AtomicU8::new(MAP_PENDING)),
+            })
+            .collect();
+        Self { slots, label: label.to_string(), next_sequence: 0 }
+    }
+
+    pub fn has_free_slot(&self) -> bool {
+        self.free_slot().is_some()
+    }
+
+    /// Index of a slot that holds nothing, to copy into
+    pub fn free_slot(&self) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.tag.is_none())
+    }
+
+    /// Slots in use: filled, or waiting to be read back
+    pub fn in_use(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.tag.is_some()).count()
+    }
+
+    /// Whether any slot is on its way back from the GPU
+    pub fn is_mapping(&self) -> bool {
+        self.slots.iter().any(|slot| slot.map_requested)
+    }
+
+    /// Slots filled since the last `after_submit`, which can still take more copies
+    pub fn filling(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots.iter().enumerate()
+            .filter(|(_, slot)| !slot.map_requested)
+            .filter_map(|(index, slot)| slot.tag.as_ref().map(|tag| (index, tag)))
+    }
+
+    /// Tag of slot `index` while it is still filling
+    pub fn tag_mut(&mut self, index: usize) -> Option<&mut T> {
+        let slot = &mut self.slots[index];
+        if slot.map_requested { None } else { slot.tag.as_mut() }
+    }
+
+    pub fn staging(&self, index: usize) -> &wgpu::Buffer {
+        &self.slots[index].staging
+    }
+
+    /// Mark slot `index` as holding `tag`; its copy must be recorded into the encoder
+    /// submitted before the next `after_submit`
+    pub fn fill(&mut self, index: usize, tag: T) {
+        let slot = &mut self.slots[index];
+        debug_assert!(!slot.map_requested, "slot {} of {} is in flight", index, self.label);
+        slot.tag = Some(tag);
+        slot.sequence = self.next_sequence;
+        self.next_sequence += 1;
+    }
+
+    /// Request mapping for every slot whose copy has just been submitted
+    pub fn after_submit(&mut self) {
+        for slot in self.slots.iter_mut().filter(|slot| slot.tag.is_some() && !slot.map_requested) {
+            let map_state = slot.map_state.clone();
+            map_state.store(MAP_PENDING, Ordering::Release);
+            slot.staging.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                map_state.store(if result.is_ok() { MAP_OK } else { MAP_FAILED }, Ordering::Release);
+            });
+            slot.map_requested = true;
+        }
+    }
+
+    /// Decode every slot whose mapping has completed, oldest first (non-blocking)
+    pub fn poll<R>(&mut self, device: &wgpu::Device, decode: impl FnMut(T, &[u8]) -> R) -> Vec<R> {
+        if !self.is_mapping() {
+            return Vec::new();
+        }
+        device.poll(wgpu::Maintain::Poll);
+        self.collect_ready(decode)
+    }
+
+    /// Block until the oldest slot in flight is available, then decode everything
+    /// that is ready. Used when the ring is full so no sample is ever dropped.
+    pub fn wait_oldest<R>(&mut self, device: &wgpu::Device, decode: impl FnMut(T, &[u8]) -> R) -> Vec<R> {
+        let oldest = self.slots.iter()
+            .filter(|slot| slot.map_requested)
+            .min_by_key(|slot| slot.sequence);
+        if let Some(slot) = oldest {
+            while slot.map_state.load(Ordering::Acquire) == MAP_PENDING {
+                device.poll(wgpu::Maintain::Wait);
+            }
+        }
+        self.collect_ready(decode)
+    }
+
+    /// Block until every slot in flight has been decoded
+    pub fn drain<R>(&mut self, device: &wgpu::Device, mut decode: impl FnMut(T, &[u8]) -> R) -> Vec<R> {
+        let mut decoded = Vec::new();
+        while self.is_mapping() {
+            decoded.extend(self.wait_oldest(device, &mut decode));
+        }
+        decoded
+    }
+
+    /// Slots whose mapping failed are logged and freed without being decoded
+    fn collect_ready<R>(&mut self, mut decode: impl FnMut(T, &[u8]) -> R) -> Vec<R> {
+        let mut ready: Vec<&mut ReadbackSlot<T>> = self.slots.iter_mut()
+            .filter(|slot| slot.map_requested && slot.map_state.load(Ordering::Acquire) != MAP_PENDING)
+            .collect();
+        ready.sort_by_key(|slot| slot.sequence);
+        let mut decoded = Vec::new();
+        for slot in ready {
+            slot.map_requested = false;
+            let Some(tag) = slot.tag.take() else { continue };
+            if slot.map_state.load(Ordering::Acquire) == MAP_FAILED {
+                log::error!("Failed to map {}", self.label);
+                continue;
+            }
+            let data = slot.staging.slice(..).get_mapped_range();
+            decoded.push(decode(tag, &data));
+            drop(data);
+            slot.staging.unmap();
+        }
+        decoded
+    }
+}
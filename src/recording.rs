@@ -0,0 +1,450 @@
+use crate::capture::{CaptureRegion, CaptureRing, CapturedFrame};
+use crate::readback::DEFAULT_RING_SLOTS;
+use crate::render::Palette;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Frames queued between the render loop and the encoder thread.
+/// When the queue is full the render loop waits, so slow consumers (e.g. a
+/// FIFO read by an external encoder) apply backpressure instead of dropping frames.
+/// Until a FIFO has a reader at all, frames are dropped instead (see `Recorder::waiting_for_reader`).
+const ENCODER_QUEUE_FRAMES: usize = 16;
+
+/// Output container for a recording
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RecordingFormat {
+    #[default]
+    Gif,
+    Apng,
+    /// Headerless RGBA8 frames, back to back
+    RawRgba,
+    /// YUV4MPEG2 stream (4:4:4), readable by ffmpeg and most encoders
+    Y4m,
+}
+
+impl RecordingFormat {
+    pub const ALL: [RecordingFormat; 4] = [
+        RecordingFormat::Gif,
+        RecordingFormat::Apng,
+        RecordingFormat::RawRgba,
+        RecordingFormat::Y4m,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordingFormat::Gif => "Animated GIF",
+            RecordingFormat::Apng => "APNG",
+            RecordingFormat::RawRgba => "Raw RGBA stream",
+            RecordingFormat::Y4m => "Y4M stream",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordingFormat::Gif => "gif",
+            RecordingFormat::Apng => "png",
+            RecordingFormat::RawRgba => "rgba",
+            RecordingFormat::Y4m => "y4m",
+        }
+    }
+
+    /// Stream formats are written frame by frame to a user-chosen file or named pipe
+    pub fn is_stream(&self) -> bool {
+        matches!(self, RecordingFormat::RawRgba | RecordingFormat::Y4m)
+    }
+}
+
+/// Everything needed to start a recording
+#[derive(Debug, Clone)]
+pub struct RecordingSettings {
+    pub format: RecordingFormat,
+    /// Capture one frame every `every_n` generations
+    pub every_n: u32,
+    /// Output pixels per cell (N x N)
+    pub scale: u32,
+    /// Region of the grid to record; `None` records the whole grid
+    pub crop: Option<CaptureRegion>,
+    pub palette: Palette,
+    /// Delay between frames in the output, in hundredths of a second
+    pub frame_delay_cs: u16,
+    pub path: PathBuf,
+    /// Output file or named pipe (FIFO) for stream formats; empty picks a file name
+    pub stream_target: String,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::default(),
+            every_n: 1,
+            scale: 1,
+            crop: None,
+            palette: Palette::default(),
+            frame_delay_cs: 4, // 25 frames per second
+            path: PathBuf::new(),
+            stream_target: String::new(),
+        }
+    }
+}
+
+/// Captures every Nth generation into an animated image.
+///
+/// Frames are copied off the GPU through a `CaptureRing` and encoded on a
+/// worker thread, so the render loop never waits on `map_async` unless the
+/// whole ring is still in flight.
+pub struct Recorder {
+    pub settings: RecordingSettings,
+    ring: CaptureRing,
+    sender: Option<SyncSender<CapturedFrame>>,
+    worker: Option<JoinHandle<Result<usize, String>>>,
+    /// Set once the output is open; a FIFO only opens when a reader connects
+    output_open: Arc<AtomicBool>,
+    pub frames_captured: usize,
+    /// Frames dropped while waiting for a FIFO reader
+    pub frames_dropped: usize,
+}
+
+impl Recorder {
+    pub fn start(device: &wgpu::Device, settings: RecordingSettings, grid_width: u32, grid_height: u32) -> Result<Self, String> {
+        let region = settings.crop
+            .unwrap_or_else(|| CaptureRegion::full(grid_width, grid_height))
+            .clamped(grid_width, grid_height);
+        let scale = settings.scale.max(1);
+        let (out_width, out_height) = (region.width as u64 * scale as u64, region.height as u64 * scale as u64);
+        if out_width > u16::MAX as u64 || out_height > u16::MAX as u64 || out_width * out_height > u32::MAX as u64 {
+            return Err(format!("Recording would be {}x{} pixels, the maximum is {} per side", out_width, out_height, u16::MAX));
+        }
+
+        // Regular files are created up front so errors show immediately. Streams are opened
+        // by the encoder thread, because opening a FIFO blocks until a reader connects.
+        let file = if settings.format.is_stream() {
+            None
+        } else {
+            Some(File::create(&settings.path)
+                .map_err(|e| format!("Failed to create {}: {}", settings.path.display(), e))?)
+        };
+
+        let (sender, receiver) = mpsc::sync_channel::<CapturedFrame>(ENCODER_QUEUE_FRAMES);
+        let output_open = Arc::new(AtomicBool::new(file.is_some()));
+        let worker_output_open = output_open.clone();
+        let worker_settings = settings.clone();
+        let worker = std::thread::Builder::new()
+            .name("recording-encoder".to_string())
+            .spawn(move || {
+                let file = match file {
+                    Some(file) => file,
+                    None => open_stream(&worker_settings.path)?,
+                };
+                worker_output_open.store(true, Ordering::Release);
+                match worker_settings.format {
+                    RecordingFormat::Gif => encode_gif(file, &worker_settings, out_width as u16, out_height as u16, receiver.iter()),
+                    RecordingFormat::Apng => encode_apng(file, &worker_settings, out_width as u32, out_height as u32, receiver.iter()),
+                    RecordingFormat::RawRgba => encode_raw_rgba(file, &worker_settings, receiver.iter()),
+                    RecordingFormat::Y4m => encode_y4m(file, &worker_settings, out_width as u32, out_height as u32, receiver.iter()),
+                }
+            })
+            .map_err(|e| format!("Failed to start encoder thread: {}", e))?;
+
+        if settings.format == RecordingFormat::RawRgba {
+            log::info!("Raw stream is {}x{} RGBA, e.g. ffmpeg -f rawvideo -pix_fmt rgba -s {}x{} -i {} out.mp4",
+                       out_width, out_height, out_width, out_height, settings.path.display());
+        }
+        log::info!("Recording {}x{} cells every {} generations to {}",
+                   region.width, region.height, settings.every_n, settings.path.display());
+
+        Ok(Self {
+            ring: CaptureRing::new(device, region, grid_width, DEFAULT_RING_SLOTS),
+            settings,
+            sender: Some(sender),
+            worker: Some(worker),
+            output_open,
+            frames_captured: 0,
+            frames_dropped: 0,
+        })
+    }
+
+    /// Whether a stream target is a FIFO nobody reads yet. Frames are dropped
+    /// meanwhile, so the render loop does not block on the full queue.
+    pub fn waiting_for_reader(&self) -> bool {
+        !self.output_open.load(Ordering::Acquire) && self.worker.as_ref().is_some_and(|worker| !worker.is_finished())
+    }
+
+    /// Whether the given generation should be captured
+    pub fn wants_generation(&self, generation: usize) -> bool {
+        generation.is_multiple_of(self.settings.every_n.max(1) as usize)
+    }
+
+    pub fn has_free_slot(&self) -> bool {
+        self.ring.has_free_slot()
+    }
+
+    /// Record the copy of `source` (holding `generation`) into the encoder
+    pub fn encode_capture(&mut self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::Buffer, generation: usize) -> bool {
+        self.ring.encode_capture(encoder, source, generation)
+    }
+
+    /// Must be called after the encoder holding captures has been submitted
+    pub fn after_submit(&mut self) {
+        self.ring.after_submit();
+    }
+
+    /// Hand finished frames to the encoder thread (non-blocking)
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        let frames = self.ring.poll(device);
+        self.send(frames);
+    }
+
+    /// Wait for the oldest in-flight frame so a slot becomes free
+    pub fn wait_for_slot(&mut self, device: &wgpu::Device) {
+        let frames = self.ring.wait_oldest(device);
+        self.send(frames);
+    }
+
+    fn send(&mut self, frames: Vec<CapturedFrame>) {
+        let Some(sender) = &self.sender else { return };
+        if !self.output_open.load(Ordering::Acquire) {
+            if self.waiting_for_reader() {
+                self.frames_dropped += frames.len();
+                return;
+            }
+            // The stream could not be opened; `finish` reports why
+            self.sender = None;
+            return;
+        }
+        for frame in frames {
+            self.frames_captured += 1;
+            if sender.send(frame).is_err() {
+                log::error!("Recording encoder thread stopped unexpectedly");
+                self.sender = None;
+                return;
+            }
+        }
+    }
+
+    /// Flush all pending frames, finish the file and return a short summary
+    pub fn finish(mut self, device: &wgpu::Device) -> Result<String, String> {
+        let frames = self.ring.drain(device);
+        self.send(frames);
+        self.sender = None; // Closing the channel ends the encoder loop
+
+        // Nobody ever read the FIFO: connect to it ourselves so the encoder thread's
+        // open returns, then hang up so it stops at its first write
+        let abandoned = self.waiting_for_reader();
+        if abandoned {
+            drop(File::open(&self.settings.path));
+        }
+        let result = match self.worker.take() {
+            Some(worker) => worker.join().map_err(|_| "Recording encoder thread panicked".to_string())?,
+            None => Ok(0),
+        };
+        if abandoned {
+            log::warn!("No reader connected to {}; {} frames dropped", self.settings.path.display(), self.frames_dropped);
+            return Ok(format!("No reader connected to {}, {} frames dropped", self.settings.path.display(), self.frames_dropped));
+        }
+        let written = result?;
+        log::info!("Recording finished: {} frames written to {}", written, self.settings.path.display());
+        Ok(format!("Saved {} frames to {}", written, self.settings.path.display()))
+    }
+}
+
+/// Convert a captured frame into palette indices at the requested scale
+fn indexed_pixels(frame: &CapturedFrame, scale: u32) -> Vec<u8> {
+    let scale = scale.max(1) as usize;
+    let out_width = frame.width as usize * scale;
+    let mut pixels = Vec::with_capacity(out_width * frame.height as usize * scale);
+    let mut row = vec![0u8; out_width];
+    for cell_row in frame.cells.chunks_exact(frame.width as usize) {
+        for (cell, run) in cell_row.iter().zip(row.chunks_exact_mut(scale)) {
+            run.fill(Palette::index_of(*cell) as u8);
+        }
+        for _ in 0..scale {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    pixels
+}
+
+/// Open a stream target for writing. Existing regular files are truncated;
+/// named pipes are opened as-is (this blocks until the reading side connects).
+fn open_stream(path: &Path) -> Result<File, String> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(!is_fifo(path))
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).map(|m| m.file_type().is_fifo()).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+/// The palette as packed 8-bit RGB triples (GIF and PNG PLTE layout)
+fn rgb_palette(palette: &Palette) -> Vec<u8> {
+    palette.colors().iter()
+        .flat_map(|c| [c[0], c[1], c[2]].map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8))
+        .collect()
+}
+
+fn encode_gif(
+    file: File,
+    settings: &RecordingSettings,
+    width: u16,
+    height: u16,
+    frames: impl Iterator<Item = CapturedFrame>,
+) -> Result<usize, String> {
+    let palette = rgb_palette(&settings.palette);
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &palette)
+        .map_err(|e| format!("Failed to start GIF: {}", e))?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| format!("Failed to write GIF: {}", e))?;
+
+    let mut written = 0;
+    for frame in frames {
+        let pixels = indexed_pixels(&frame, settings.scale);
+        let gif_frame = gif::Frame {
+            width,
+            height,
+            delay: settings.frame_delay_cs,
+            buffer: std::borrow::Cow::Owned(pixels),
+            ..Default::default()
+        };
+        encoder.write_frame(&gif_frame).map_err(|e| format!("Failed to write GIF frame: {}", e))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// APNG needs the frame count up front, so frames are spilled to a temporary
+/// file while recording and assembled when the recording stops.
+fn encode_apng(
+    file: File,
+    settings: &RecordingSettings,
+    width: u32,
+    height: u32,
+    frames: impl Iterator<Item = CapturedFrame>,
+) -> Result<usize, String> {
+    let spill_path = spill_path(&settings.path);
+    let mut frame_count = 0u32;
+    {
+        let spill = File::create(&spill_path).map_err(|e| format!("Failed to create {}: {}", spill_path.display(), e))?;
+        let mut spill = BufWriter::new(spill);
+        for frame in frames {
+            spill.write_all(&indexed_pixels(&frame, settings.scale))
+                .map_err(|e| format!("Failed to write {}: {}", spill_path.display(), e))?;
+            frame_count += 1;
+        }
+        spill.flush().map_err(|e| format!("Failed to write {}: {}", spill_path.display(), e))?;
+    }
+
+    let result = write_apng_from_spill(file, settings, width, height, frame_count, &spill_path);
+    let _ = std::fs::remove_file(&spill_path);
+    result.map(|_| frame_count as usize)
+}
+
+fn write_apng_from_spill(file: File, settings: &RecordingSettings, width: u32, height: u32, frame_count: u32, spill_path: &Path) -> Result<(), String> {
+    if frame_count == 0 {
+        return Err("No frames were captured".to_string());
+    }
+    let png_err = |e: png::EncodingError| format!("Failed to write APNG: {}", e);
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(rgb_palette(&settings.palette));
+    encoder.set_animated(frame_count, 0).map_err(png_err)?;
+    encoder.set_frame_delay(settings.frame_delay_cs, 100).map_err(png_err)?;
+    let mut writer = encoder.write_header().map_err(png_err)?;
+
+    let spill = File::open(spill_path).map_err(|e| format!("Failed to open {}: {}", spill_path.display(), e))?;
+    let mut spill = BufReader::new(spill);
+    let mut pixels = vec![0u8; width as usize * height as usize];
+    for _ in 0..frame_count {
+        spill.read_exact(&mut pixels).map_err(|e| format!("Failed to read {}: {}", spill_path.display(), e))?;
+        writer.write_image_data(&pixels).map_err(png_err)?;
+    }
+    writer.finish().map_err(png_err)
+}
+
+fn spill_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".frames.tmp");
+    PathBuf::from(name)
+}
+
+fn encode_raw_rgba(
+    file: File,
+    settings: &RecordingSettings,
+    frames: impl Iterator<Item = CapturedFrame>,
+) -> Result<usize, String> {
+    let mut out = BufWriter::new(file);
+    let mut written = 0;
+    for frame in frames {
+        let mut row = vec![0u8; frame.width as usize * settings.scale.max(1) as usize * 4];
+        for cell_row in frame.cells.chunks_exact(frame.width as usize) {
+            crate::export::fill_scaled_row(&mut row, cell_row, settings.scale.max(1), &settings.palette);
+            for _ in 0..settings.scale.max(1) {
+                out.write_all(&row).map_err(|e| format!("Failed to write frame: {}", e))?;
+            }
+        }
+        written += 1;
+    }
+    out.flush().map_err(|e| format!("Failed to write frame: {}", e))?;
+    Ok(written)
+}
+
+fn encode_y4m(
+    file: File,
+    settings: &RecordingSettings,
+    width: u32,
+    height: u32,
+    frames: impl Iterator<Item = CapturedFrame>,
+) -> Result<usize, String> {
+    let io_err = |e: std::io::Error| format!("Failed to write frame: {}", e);
+    let mut out = BufWriter::new(file);
+    // Frame rate is 100 / frame_delay_cs frames per second
+    writeln!(out, "YUV4MPEG2 W{} H{} F100:{} Ip A1:1 C444", width, height, settings.frame_delay_cs.max(1))
+        .map_err(io_err)?;
+
+    // Precompute the limited-range BT.601 YCbCr value of every palette entry
+    let yuv: Vec<[u8; 3]> = settings.palette.colors().iter().map(|c| rgb_to_ycbcr(c[0], c[1], c[2])).collect();
+
+    let plane_size = width as usize * height as usize;
+    let mut planes = vec![0u8; plane_size * 3];
+    let mut written = 0;
+    for frame in frames {
+        let indices = indexed_pixels(&frame, settings.scale);
+        let (y_plane, chroma) = planes.split_at_mut(plane_size);
+        let (u_plane, v_plane) = chroma.split_at_mut(plane_size);
+        for (i, index) in indices.iter().enumerate() {
+            let [y, u, v] = yuv[*index as usize];
+            y_plane[i] = y;
+            u_plane[i] = u;
+            v_plane[i] = v;
+        }
+        out.write_all(b"FRAME\n").map_err(io_err)?;
+        out.write_all(&planes).map_err(io_err)?;
+        written += 1;
+    }
+    out.flush().map_err(io_err)?;
+    Ok(written)
+}
+
+fn rgb_to_ycbcr(r: f32, g: f32, b: f32) -> [u8; 3] {
+    let (r, g, b) = (r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0));
+    let y = 16.0 + 65.481 * r + 128.553 * g + 24.966 * b;
+    let cb = 128.0 - 37.797 * r - 74.203 * g + 112.0 * b;
+    let cr = 128.0 + 112.0 * r - 93.786 * g - 18.214 * b;
+    [y, cb, cr].map(|v| v.round().clamp(0.0, 255.0) as u8)
+}
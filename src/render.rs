@@ -6,6 +6,9 @@ pub const MIN_ZOOM: f32 = 1.0; // Min zoom is 1:1 pixel mapping
 pub const MAX_ZOOM: f32 = 16.0; // Max zoom factor
 pub const ZOOM_FACTOR_STEP: f32 = 1.2; // How much each wheel step zooms
 
+/// Number of palette slots; cell values are rounded to an index in `0..PALETTE_SIZE`
+pub const PALETTE_SIZE: usize = 8;
+
 // Uniforms specific to rendering
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -13,6 +16,70 @@ pub struct RenderParams {
     pub zoom: f32,
     pub _padding: f32,            // 4-byte padding so view_offset is 8-byte aligned
     pub view_offset: [f32; 2],
+    pub palette: [[f32; 4]; PALETTE_SIZE], // RGBA per rounded cell value (16-byte stride in WGSL)
+}
+
+/// Color schemes used to turn cell values into pixels.
+/// The same table drives the render shader and the PNG export so both always match.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Palette {
+    /// White living cells, red for every other state (lucky and colored cells)
+    #[default]
+    Classic,
+    /// One distinct color per `CellColor`
+    CellColors,
+    /// Every living cell is white
+    Monochrome,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Classic, Palette::CellColors, Palette::Monochrome];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Classic => "Classic",
+            Palette::CellColors => "Cell Colors",
+            Palette::Monochrome => "Monochrome",
+        }
+    }
+
+    /// RGBA colors (0.0-1.0) indexed by rounded cell value
+    pub fn colors(&self) -> [[f32; 4]; PALETTE_SIZE] {
+        const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+        const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+        match self {
+            Palette::Classic => {
+                let red = [0.9, 0.1, 0.1, 1.0];
+                [BLACK, WHITE, red, red, red, red, red, red]
+            }
+            Palette::CellColors => [
+                BLACK,
+                WHITE,
+                [1.0, 0.0, 0.0, 1.0], // 2.0 legacy lucky value
+                [1.0, 0.0, 0.0, 1.0], // Red / lucky
+                [0.0, 1.0, 0.0, 1.0], // Green
+                [0.0, 0.3, 1.0, 1.0], // Blue
+                [1.0, 1.0, 0.0, 1.0], // Yellow
+                [0.8, 0.2, 1.0, 1.0], // Purple
+            ],
+            Palette::Monochrome => [BLACK, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE, WHITE],
+        }
+    }
+
+    /// Palette slot for a raw cell value (mirrors `palette_index` in render.wgsl)
+    pub fn index_of(value: f32) -> usize {
+        if value <= 0.5 {
+            0
+        } else {
+            (value.round() as usize).clamp(1, PALETTE_SIZE - 1)
+        }
+    }
+
+    /// 8-bit RGBA color for a raw cell value
+    pub fn rgba8(&self, value: f32) -> [u8; 4] {
+        let color = self.colors()[Self::index_of(value)];
+        color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
 }
 
 pub fn create_render_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
@@ -83,4 +150,88 @@ pub fn create_render_bind_groups(
             ],
         }),
     ]
-} 
\ No newline at end of file
+} 
+
+/// Render uniforms and bind groups of one chunk of a chunked grid. The chunk is drawn
+/// with the ordinary grid shader, seen as a grid of `rows + 2` rows whose view is
+/// shifted to the chunk's first row (minus its halo row).
+pub struct ChunkRenderTarget {
+    pub first_row: u32,
+    pub rows: u32,
+    pub render_param_buffer: wgpu::Buffer,
+    pub bind_groups: [wgpu::BindGroup; 2],
+}
+
+impl ChunkRenderTarget {
+    /// `params` for the whole grid, with the view moved to this chunk's buffer
+    pub fn write_render_params(&self, queue: &wgpu::Queue, params: &RenderParams) {
+        let mut params = *params;
+        params.view_offset[1] -= (self.first_row as f32 - 1.0) * params.zoom;
+        queue.write_buffer(&self.render_param_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    /// Screen rows showing the chunk's own rows, as (first, count), clipped to `screen_height`
+    pub fn scissor_rows(&self, zoom: f32, view_offset_y: f32, screen_height: u32) -> Option<(u32, u32)> {
+        let top = (self.first_row as f32 * zoom - view_offset_y).floor().clamp(0.0, screen_height as f32) as u32;
+        let bottom = ((self.first_row + self.rows) as f32 * zoom - view_offset_y).ceil().clamp(0.0, screen_height as f32) as u32;
+        (bottom > top).then_some((top, bottom - top))
+    }
+}
+
+pub fn create_chunk_render_targets(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    chunked: &crate::chunked::ChunkedGrid,
+) -> Vec<ChunkRenderTarget> {
+    chunked.chunks.iter().map(|chunk| {
+        let render_param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Chunk Render Parameters"),
+            size: std::mem::size_of::<RenderParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_groups = create_render_bind_groups(device, layout, &chunk.buffers, &chunk.param_buffer, &render_param_buffer);
+        ChunkRenderTarget { first_row: chunk.first_row, rows: chunk.rows, render_param_buffer, bind_groups }
+    }).collect()
+}
+
+/// Full-screen triangle pipeline for a grid render shader (`vs_main` / `fs_main`)
+/// whose bindings follow `create_render_bind_group_layout`
+pub fn create_grid_render_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    label: &str,
+    source: &str,
+) -> wgpu::RenderPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: "fs_main",
+            targets: &[Some(format.into())],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
@@ -0,0 +1,85 @@
+//! Carry grid contents over to a grid of another size.
+//!
+//! Cells use the `Simulation` layout (row-major f32, 0 = dead).
+
+/// Environment variable with the GUI's starting grid size, e.g. `2048x1024`
+pub const GRID_SIZE_ENV: &str = "GPU_LIFE_GRID_SIZE";
+
+/// How existing cells are placed on a grid of a different size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeMode {
+    /// Keep the top-left corner; cells past the new edges are dropped
+    Crop,
+    /// Keep the center; new space is dead and shrinking trims every side evenly
+    #[default]
+    PadCentered,
+    /// Stretch the old grid over the new one (nearest cell)
+    Rescale,
+}
+
+impl ResizeMode {
+    pub const ALL: [ResizeMode; 3] = [ResizeMode::Crop, ResizeMode::PadCentered, ResizeMode::Rescale];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResizeMode::Crop => "Crop",
+            ResizeMode::PadCentered => "Pad centered",
+            ResizeMode::Rescale => "Rescale",
+        }
+    }
+}
+
+/// The `width` x `height` cells of `cells` placed on a `new_width` x `new_height` grid
+pub fn resize_cells(
+    cells: &[f32], width: u32, height: u32, new_width: u32, new_height: u32, mode: ResizeMode,
+) -> Vec<f32> {
+    let (width, height) = (width as usize, height as usize);
+    let (new_width, new_height) = (new_width as usize, new_height as usize);
+    let mut resized = vec![0.0; new_width * new_height];
+    match mode {
+        ResizeMode::Crop | ResizeMode::PadCentered => {
+            // Offset of the old grid on the new one; negative when the old grid is larger
+            let (offset_x, offset_y) = if mode == ResizeMode::Crop {
+                (0, 0)
+            } else {
+                ((new_width as i64 - width as i64) / 2, (new_height as i64 - height as i64) / 2)
+            };
+            for y in 0..new_height {
+                let old_y = y as i64 - offset_y;
+                if old_y < 0 || old_y >= height as i64 {
+                    continue;
+                }
+                // Columns present in both grids
+                let first = offset_x.max(0) as usize;
+                let last = (offset_x + width as i64).min(new_width as i64);
+                if last <= first as i64 {
+                    continue;
+                }
+                let last = last as usize;
+                let old_first = (first as i64 - offset_x) as usize;
+                let old_row = old_y as usize * width;
+                resized[y * new_width + first..y * new_width + last]
+                    .copy_from_slice(&cells[old_row + old_first..old_row + old_first + (last - first)]);
+            }
+        }
+        ResizeMode::Rescale => {
+            for y in 0..new_height {
+                let old_y = y * height / new_height;
+                for x in 0..new_width {
+                    resized[y * new_width + x] = cells[old_y * width + x * width / new_width];
+                }
+            }
+        }
+    }
+    resized
+}
+
+/// Parse `WxH` (or a single `N` for a square grid) into positive dimensions
+pub fn parse_grid_size(text: &str) -> Result<(u32, u32), String> {
+    let (width, height) = text.split_once(['x', 'X']).unwrap_or((text, text));
+    let side = |value: &str| match value.trim().parse::<u32>() {
+        Ok(side) if side > 0 => Ok(side),
+        _ => Err(format!("Invalid grid size '{}', expected WxH", text)),
+    };
+    Ok((side(width)?, side(height)?))
+}
@@ -1,11 +1,11 @@
-/// Rules module for Conway's Game of Life simulation
-///
-/// This module contains rule definitions, cell state representations, and preset patterns
-/// for the Game of Life simulation.
+//! Rules module for Conway's Game of Life simulation
+//!
+//! This module contains rule definitions and cell state representations
+//! for the Game of Life simulation. Placeable patterns live in the pattern library
+//! (see `crate::library`).
 
 // Declare sub-modules
 pub mod presets;
-pub mod patterns;
 
 // Re-export contents for easier access
 
@@ -14,78 +14,119 @@ pub mod patterns;
 /// 2. Any live cell with two or three live neighbors lives (survival)
 /// 3. Any live cell with more than three live neighbors dies (overpopulation)
 /// 4. Any dead cell with exactly three live neighbors becomes alive (reproduction)
-#[derive(Debug, Clone, Copy)]
+///
+/// Other life-like rules are expressed as neighbor-count bitmasks: bit `n` of
+/// `birth` / `survival` is set if a cell is born / survives with `n` live neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct GameRules {
-    /// Minimum neighbors for a live cell to survive
-    pub survival_min: u32,
-    /// Maximum neighbors for a live cell to survive
-    pub survival_max: u32,
-    /// Number of neighbors for a dead cell to become alive
-    pub birth_count: u32,
+    /// Neighbor counts (bits 0-8) on which a dead cell becomes alive
+    pub birth: u32,
+    /// Neighbor counts (bits 0-8) on which a live cell survives
+    pub survival: u32,
 }
 
 impl Default for GameRules {
     fn default() -> Self {
         // Classic Conway's Game of Life rules (B3/S23)
-        Self {
-            survival_min: 2,
-            survival_max: 3,
-            birth_count: 3,
-        }
+        Self::from_counts(&[3], &[2, 3])
     }
 }
 
 impl GameRules {
-    /// Create a new rule set with custom parameters
+    /// Create a new rule set from a survival range and a single birth count
     pub fn new(survival_min: u32, survival_max: u32, birth_count: u32) -> Self {
-        Self {
-            survival_min,
-            survival_max,
-            birth_count,
+        let survival = (survival_min..=survival_max.min(8)).fold(0, |mask, n| mask | (1 << n));
+        Self { birth: 1 << birth_count.min(8), survival }
+    }
+
+    /// Create a rule set from lists of neighbor counts
+    pub fn from_counts(birth: &[u32], survival: &[u32]) -> Self {
+        let mask = |counts: &[u32]| counts.iter().filter(|&&n| n <= 8).fold(0, |mask, n| mask | (1 << n));
+        Self { birth: mask(birth), survival: mask(survival) }
+    }
+
+    /// Parse a life-like rulestring: "B3/S23", "b36s23" or the older "23/3" (survival/birth) form
+    pub fn parse(rulestring: &str) -> Result<Self, String> {
+        let text: String = rulestring.trim().chars().filter(|c| !c.is_whitespace()).collect();
+        let upper = text.to_ascii_uppercase();
+        let digits = |part: &str| -> Result<u32, String> {
+            part.chars().try_fold(0u32, |mask, c| match c.to_digit(10) {
+                Some(n) if n <= 8 => Ok(mask | (1 << n)),
+                _ => Err(format!("Invalid neighbor count '{}' in rule \"{}\"", c, rulestring)),
+            })
+        };
+
+        if upper.starts_with('B') || upper.starts_with('S') {
+            // B.../S... in either order, with or without the slash
+            let mut birth = None;
+            let mut survival = None;
+            let mut current = None;
+            let mut part = String::new();
+            for c in upper.chars().chain(std::iter::once('/')) {
+                match c {
+                    'B' | 'S' | '/' => {
+                        match current {
+                            Some('B') => birth = Some(digits(&part)?),
+                            Some('S') => survival = Some(digits(&part)?),
+                            _ if !part.is_empty() => return Err(format!("Invalid rule \"{}\"", rulestring)),
+                            _ => {}
+                        }
+                        part.clear();
+                        current = if c == '/' { None } else { Some(c) };
+                    }
+                    c => part.push(c),
+                }
+            }
+            match (birth, survival) {
+                (Some(birth), Some(survival)) => Ok(Self { birth, survival }),
+                _ => Err(format!("Rule \"{}\" needs both a B and an S part", rulestring)),
+            }
+        } else if let Some((survival, birth)) = upper.split_once('/') {
+            Ok(Self { birth: digits(birth)?, survival: digits(survival)? })
+        } else {
+            Err(format!("Invalid rule \"{}\", expected e.g. B3/S23", rulestring))
         }
     }
+
+    /// Canonical "B.../S..." form
+    pub fn to_rulestring(&self) -> String {
+        let counts = |mask: u32| (0..=8).filter(|n| mask & (1 << n) != 0).map(|n| n.to_string()).collect::<String>();
+        format!("B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+
+    pub fn births_on(&self, neighbors: u32) -> bool {
+        self.birth & (1 << neighbors) != 0
+    }
+
+    pub fn survives_on(&self, neighbors: u32) -> bool {
+        self.survival & (1 << neighbors) != 0
+    }
 }
 
-/// Predefined patterns for initializing the grid
-pub enum Pattern {
-    /// A small oscillator
-    Blinker,
-    /// A small oscillator
-    Toad,
-    /// A small stationary pattern
-    Block,
-    /// A diagonal spaceship
-    Glider,
-    /// A horizontal spaceship
-    LightweightSpaceship,
-    /// A pattern that grows indefinitely
-    GosperGliderGun,
+/// What lies beyond the edges of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Edges wrap around (the grid is a torus)
+    #[default]
+    Torus,
+    /// Everything outside the grid is permanently dead
+    Dead,
 }
 
-/// Utility to place a pattern on a grid
-pub fn place_pattern_on_grid(grid: &mut [f32], width: u32, height: u32, pattern: &Pattern, x: u32, y: u32) {
-    let cells = pattern.cells(x, y);
-    
-    for (cell_x, cell_y) in cells {
-        if cell_x < width && cell_y < height {
-            let idx = (cell_y * width + cell_x) as usize;
-            if idx < grid.len() {
-                grid[idx] = 1.0;
-            }
+impl Boundary {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "torus" | "wrap" => Ok(Boundary::Torus),
+            "dead" | "bounded" | "plane" => Ok(Boundary::Dead),
+            other => Err(format!("Unknown boundary mode \"{}\", expected torus or dead", other)),
         }
     }
-}
 
-/// Initialize a grid with a specific pattern at the center
-pub fn initialize_grid_with_pattern(width: u32, height: u32, pattern: &Pattern) -> Vec<f32> {
-    let size = (width * height) as usize;
-    let mut grid = vec![0.0f32; size];
-    
-    // Place the pattern at the center of the grid
-    let center_x = width / 2;
-    let center_y = height / 2;
-    
-    place_pattern_on_grid(&mut grid, width, height, pattern, center_x, center_y);
-    
-    grid
-}
\ No newline at end of file
+    /// Value passed to the shaders in `SimParams::boundary`
+    pub fn shader_value(&self) -> u32 {
+        match self {
+            Boundary::Torus => 0,
+            Boundary::Dead => 1,
+        }
+    }
+}
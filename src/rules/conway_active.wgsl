@@ -0,0 +1,37 @@
+// Activity-aware stepping, appended to conway_shared.wgsl. Only the tiles listed in
+// `active_tiles` (a tile is one workgroup's block of cells) are recomputed; the others
+// did not change and neither did their neighbors, so the output buffer, which holds
+// the generation before, already has their cells. Tiles whose cells change are
+// flagged in `tile_changed` for activity.wgsl to schedule the next generation.
+
+@group(1) @binding(0) var<storage, read> active_count: u32;
+@group(1) @binding(1) var<storage, read> active_tiles: array<u32>;
+@group(1) @binding(2) var<storage, read_write> tile_changed: array<u32>;
+
+@compute @workgroup_size(WORKGROUP_SIZE, WORKGROUP_SIZE, 1)
+fn step_active(
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    // Large lists are dispatched as rows of workgroups; the last row may run past the list
+    let slot = workgroup_id.y * num_workgroups.x + workgroup_id.x;
+    let listed = slot < active_count;
+    let tile_index = active_tiles[min(slot, active_count - 1u)];
+    let tiles_x = (sim_params.width + WORKGROUP_SIZE - 1u) / WORKGROUP_SIZE;
+    let block = vec2<u32>(tile_index % tiles_x, tile_index / tiles_x);
+    load_tile(block, local_index);
+    workgroupBarrier();
+
+    let x = block.x * WORKGROUP_SIZE + local_id.x;
+    let y = block.y * WORKGROUP_SIZE + local_id.y;
+    if (!listed || x >= sim_params.width || y >= sim_params.height) {
+        return;
+    }
+    let next = next_state(local_id.xy, x, y);
+    cell_state_out[y * sim_params.width + x] = next;
+    if (next != tile[(local_id.y + 1u) * TILE_SIDE + local_id.x + 1u]) {
+        tile_changed[tile_index] = 1u;
+    }
+}
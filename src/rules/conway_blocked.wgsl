@@ -0,0 +1,157 @@
+// Same rules as conway_classic.wgsl, advanced BLOCK_GENERATIONS generations per dispatch
+// (temporal blocking). Each workgroup loads a WORKGROUP_SIZE x WORKGROUP_SIZE tile: an
+// output block plus a halo of BLOCK_GENERATIONS cells on every side. Every sub-step
+// ping-pongs between two tiles in workgroup memory and the region whose neighbors are
+// still exact shrinks by one cell per side, so after the last sub-step exactly the
+// output block is valid and written back.
+//
+// `WORKGROUP_SIZE` and `BLOCK_GENERATIONS` are not declared here: simulation.rs
+// prepends them. BLOCK_GENERATIONS is odd so the result lands in the buffer the
+// ping-pong order expects. Every sub-step uses the same lucky seed, like a batch of
+// single-generation dispatches submitted together.
+
+const OUTPUT_SIZE: u32 = WORKGROUP_SIZE - 2u * BLOCK_GENERATIONS;
+const TILE_CELLS: u32 = WORKGROUP_SIZE * WORKGROUP_SIZE;
+const BOTH_TILES: u32 = 2u * TILE_CELLS;
+
+struct SimParams {
+    width: u32,
+    height: u32,
+    lucky_chance: f32,
+    seed: u32,
+    enable_lucky_rule: u32,
+    boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
+    _pad2: u32,
+    _pad3: u32,
+}
+
+struct GameRules {
+    birth_mask: u32,
+    survival_mask: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+@group(0) @binding(0) var<uniform> sim_params: SimParams;
+@group(0) @binding(1) var<storage, read> cell_state_in: array<f32>;
+@group(0) @binding(2) var<storage, read_write> cell_state_out: array<f32>;
+@group(0) @binding(3) var<uniform> game_rules: GameRules;
+
+// Two tiles of TILE_CELLS cells, row-major with WORKGROUP_SIZE columns
+var<workgroup> tiles: array<f32, BOTH_TILES>;
+
+fn hash(value: u32) -> f32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    let result = (word >> 22u) ^ word;
+    return f32(result) / 4294967295.0;
+}
+
+fn is_color(value: f32, base_color: f32) -> bool {
+    return abs(value - base_color) < 0.1;
+}
+
+// Next state of the tile cell at `local` (not on the tile edge) of the tile starting
+// at `source`, which is grid cell (x, y)
+fn next_state(source: u32, local: u32, x: u32, y: u32) -> f32 {
+    let cell = tiles[source + local];
+
+    // One pass collects the count and the colors of the living neighbors
+    var neighbors = 0u;
+    var color_counts = array<u32, 6>(0u, 0u, 0u, 0u, 0u, 0u);
+    for (var dy = 0u; dy < 3u; dy = dy + 1u) {
+        for (var dx = 0u; dx < 3u; dx = dx + 1u) {
+            if (dx == 1u && dy == 1u) {
+                continue;
+            }
+            let value = tiles[source + local + dy * WORKGROUP_SIZE + dx - WORKGROUP_SIZE - 1u];
+            if (value > 0.5) {
+                neighbors = neighbors + 1u;
+                if (is_color(value, 1.0)) {
+                    color_counts[0] += 1u;
+                } else if (is_color(value, 2.0) || is_color(value, 3.0)) {
+                    color_counts[1] += 1u;
+                } else if (is_color(value, 4.0)) {
+                    color_counts[2] += 1u;
+                } else if (is_color(value, 5.0)) {
+                    color_counts[3] += 1u;
+                } else if (is_color(value, 6.0)) {
+                    color_counts[4] += 1u;
+                } else if (is_color(value, 7.0)) {
+                    color_counts[5] += 1u;
+                }
+            }
+        }
+    }
+
+    if (cell > 0.5) {
+        if (((game_rules.survival_mask >> neighbors) & 1u) == 1u) {
+            return cell;
+        }
+        let random_value = hash(x + y * sim_params.width + sim_params.seed);
+        if (sim_params.enable_lucky_rule == 1u && random_value < sim_params.lucky_chance) {
+            if (is_color(cell, 1.0) || is_color(cell, 2.0) || is_color(cell, 3.0)) {
+                return 3.0;
+            }
+            return cell;
+        }
+        return 0.0;
+    }
+    if (((game_rules.birth_mask >> neighbors) & 1u) == 1u) {
+        var max_count = 0u;
+        var dominant = 0u;
+        for (var i = 0u; i < 6u; i = i + 1u) {
+            if (color_counts[i] > max_count) {
+                max_count = color_counts[i];
+                dominant = i;
+            }
+        }
+        var colors = array<f32, 6>(1.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+        return colors[dominant];
+    }
+    return 0.0;
+}
+
+@compute @workgroup_size(WORKGROUP_SIZE, WORKGROUP_SIZE, 1)
+fn main(
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    // Grid cell of this invocation, BLOCK_GENERATIONS cells up and left of the output block
+    let grid_x = i32(workgroup_id.x * OUTPUT_SIZE + local_id.x) - i32(BLOCK_GENERATIONS);
+    let grid_y = i32(workgroup_id.y * OUTPUT_SIZE + local_id.y) - i32(BLOCK_GENERATIONS);
+    let width = i32(sim_params.width);
+    let height = i32(sim_params.height);
+    let in_grid = grid_x >= 0 && grid_x < width && grid_y >= 0 && grid_y < height;
+    // Cells outside a bounded grid stay dead in every sub-step
+    let live_area = in_grid || sim_params.boundary == 0u;
+    // grid_x and grid_y are never below -BLOCK_GENERATIONS, so the operands stay non-negative
+    let x = u32(grid_x + i32(BLOCK_GENERATIONS) * width) % sim_params.width;
+    let y = u32(grid_y + i32(BLOCK_GENERATIONS) * height) % sim_params.height;
+
+    let local = local_id.y * WORKGROUP_SIZE + local_id.x;
+    tiles[local] = select(0.0, cell_state_in[y * sim_params.width + x], live_area);
+    workgroupBarrier();
+
+    for (var generation = 0u; generation < BLOCK_GENERATIONS; generation = generation + 1u) {
+        let source = (generation % 2u) * TILE_CELLS;
+        // Cells `margin` or more away from the tile edge still have exact neighbors
+        let margin = generation + 1u;
+        let lo = min(local_id.x, local_id.y);
+        let hi = max(local_id.x, local_id.y);
+        if (lo >= margin && hi < WORKGROUP_SIZE - margin) {
+            var next = 0.0;
+            if (live_area) {
+                next = next_state(source, local, x, y);
+            }
+            tiles[TILE_CELLS - source + local] = next;
+        }
+        workgroupBarrier();
+    }
+
+    let lo = min(local_id.x, local_id.y);
+    let hi = max(local_id.x, local_id.y);
+    if (in_grid && lo >= BLOCK_GENERATIONS && hi < WORKGROUP_SIZE - BLOCK_GENERATIONS) {
+        cell_state_out[y * sim_params.width + x] = tiles[(BLOCK_GENERATIONS % 2u) * TILE_CELLS + local];
+    }
+}
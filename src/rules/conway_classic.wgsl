@@ -4,16 +4,16 @@ struct SimParams {
     lucky_chance: f32,
     seed: u32,
     enable_lucky_rule: u32,
-    _pad1: u32,
+    boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
     _pad2: u32,
     _pad3: u32,
 }
 
 struct GameRules {
-    survival_min: u32,
-    survival_max: u32,
-    birth_count: u32,
-    _padding: u32, // Ensure 16-byte alignment
+    birth_mask: u32,    // Bit n set: a dead cell with n neighbors is born
+    survival_mask: u32, // Bit n set: a live cell with n neighbors survives
+    _pad0: u32,
+    _pad1: u32, // Ensure 16-byte alignment
 }
 
 @group(0) @binding(0) var<uniform> sim_params: SimParams;
@@ -50,6 +50,11 @@ fn count_neighbors(x: u32, y: u32) -> u32 {
             var nx: u32 = u32(i32(x) + dx);
             var ny: u32 = u32(i32(y) + dy);
             
+            // Outside a bounded grid everything is dead
+            if (sim_params.boundary == 1u && (nx >= width || ny >= height)) {
+                continue;
+            }
+            
             // Wrap around grid boundaries
             if (i32(nx) < 0) { nx = width - 1u; } 
             else if (nx >= width) { nx = 0u; }
@@ -94,6 +99,11 @@ fn get_neighbor_colors(x: u32, y: u32) -> f32 {
             var nx: u32 = u32(i32(x) + dx);
             var ny: u32 = u32(i32(y) + dy);
             
+            // Outside a bounded grid everything is dead
+            if (sim_params.boundary == 1u && (nx >= width || ny >= height)) {
+                continue;
+            }
+            
             // Wrap around grid boundaries
             if (i32(nx) < 0) { nx = width - 1u; } 
             else if (nx >= width) { nx = 0u; }
@@ -184,7 +194,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // Apply Game of Life rules
     if (is_alive) {
         // Survival rules
-        if (neighbors >= game_rules.survival_min && neighbors <= game_rules.survival_max) {
+        if (((game_rules.survival_mask >> neighbors) & 1u) == 1u) {
             cell_state_out[idx] = cell; // Keep the same color
         } else {
             // Underpopulation or Overpopulation - Cell would normally die.
@@ -206,7 +216,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
     } else { // Cell is dead
         // Birth rules
-        if (neighbors == game_rules.birth_count) {
+        if (((game_rules.birth_mask >> neighbors) & 1u) == 1u) {
             // Use dominant color of neighbors instead of average
             cell_state_out[idx] = get_neighbor_colors(x, y);
         } else {
@@ -0,0 +1,154 @@
+// Same rules as conway_classic.wgsl (birth/survival masks, lucky rule, color-majority
+// births), but each workgroup first stages its block of cells plus a one-cell halo in
+// workgroup memory, so the neighbors are read from there instead of 8-16 scattered
+// global loads per cell.
+//
+// `WORKGROUP_SIZE` is not declared here: simulation.rs prepends
+// `const WORKGROUP_SIZE: u32 = N;` for the configured size. conway_active.wgsl is
+// appended to this file and reuses `load_tile` and `next_state`.
+
+const TILE_SIDE: u32 = WORKGROUP_SIZE + 2u;
+const TILE_CELLS: u32 = TILE_SIDE * TILE_SIDE;
+
+struct SimParams {
+    width: u32,
+    height: u32,
+    lucky_chance: f32,
+    seed: u32,
+    enable_lucky_rule: u32,
+    boundary: u32, // 0 = torus (wrap), 1 = dead cells outside the grid
+    _pad2: u32,
+    _pad3: u32,
+}
+
+struct GameRules {
+    birth_mask: u32,
+    survival_mask: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+@group(0) @binding(0) var<uniform> sim_params: SimParams;
+@group(0) @binding(1) var<storage, read> cell_state_in: array<f32>;
+@group(0) @binding(2) var<storage, read_write> cell_state_out: array<f32>;
+@group(0) @binding(3) var<uniform> game_rules: GameRules;
+
+// The workgroup's cells with a one-cell border, row-major with TILE_SIDE columns
+var<workgroup> tile: array<f32, TILE_CELLS>;
+
+fn hash(value: u32) -> f32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    let result = (word >> 22u) ^ word;
+    return f32(result) / 4294967295.0;
+}
+
+fn is_color(value: f32, base_color: f32) -> bool {
+    return abs(value - base_color) < 0.1;
+}
+
+// Cell at (x, y), which may lie one cell (or a partial workgroup) outside the grid
+fn load_cell(x: i32, y: i32) -> f32 {
+    let width = i32(sim_params.width);
+    let height = i32(sim_params.height);
+    if (x >= 0 && x < width && y >= 0 && y < height) {
+        return cell_state_in[u32(y) * sim_params.width + u32(x)];
+    }
+    if (sim_params.boundary == 1u) {
+        return 0.0;
+    }
+    // x and y are never below -1, so the operands stay non-negative
+    let wrapped_x = u32(x + width) % sim_params.width;
+    let wrapped_y = u32(y + height) % sim_params.height;
+    return cell_state_in[wrapped_y * sim_params.width + wrapped_x];
+}
+
+// Fill `tile` with the block of workgroup-sized tiles at `block` and its halo; every
+// invocation of the workgroup must call this, followed by a workgroupBarrier()
+fn load_tile(block: vec2<u32>, local_index: u32) {
+    let origin_x = i32(block.x * WORKGROUP_SIZE) - 1;
+    let origin_y = i32(block.y * WORKGROUP_SIZE) - 1;
+    for (var i = local_index; i < TILE_CELLS; i = i + WORKGROUP_SIZE * WORKGROUP_SIZE) {
+        tile[i] = load_cell(origin_x + i32(i % TILE_SIDE), origin_y + i32(i / TILE_SIDE));
+    }
+}
+
+// Next state of grid cell (x, y), which sits at `local` in the loaded tile
+fn next_state(local: vec2<u32>, x: u32, y: u32) -> f32 {
+    let center = (local.y + 1u) * TILE_SIDE + local.x + 1u;
+    let cell = tile[center];
+
+    // One pass collects the count and the colors of the living neighbors
+    var neighbors = 0u;
+    var color_counts = array<u32, 6>(0u, 0u, 0u, 0u, 0u, 0u);
+    for (var dy = 0u; dy < 3u; dy = dy + 1u) {
+        for (var dx = 0u; dx < 3u; dx = dx + 1u) {
+            if (dx == 1u && dy == 1u) {
+                continue;
+            }
+            let value = tile[center + dy * TILE_SIDE + dx - TILE_SIDE - 1u];
+            if (value > 0.5) {
+                neighbors = neighbors + 1u;
+                if (is_color(value, 1.0)) {
+                    color_counts[0] += 1u;
+                } else if (is_color(value, 2.0) || is_color(value, 3.0)) {
+                    color_counts[1] += 1u;
+                } else if (is_color(value, 4.0)) {
+                    color_counts[2] += 1u;
+                } else if (is_color(value, 5.0)) {
+                    color_counts[3] += 1u;
+                } else if (is_color(value, 6.0)) {
+                    color_counts[4] += 1u;
+                } else if (is_color(value, 7.0)) {
+                    color_counts[5] += 1u;
+                }
+            }
+        }
+    }
+
+    if (cell > 0.5) {
+        if (((game_rules.survival_mask >> neighbors) & 1u) == 1u) {
+            return cell;
+        }
+        let random_value = hash(x + y * sim_params.width + sim_params.seed);
+        if (sim_params.enable_lucky_rule == 1u && random_value < sim_params.lucky_chance) {
+            if (is_color(cell, 1.0) || is_color(cell, 2.0) || is_color(cell, 3.0)) {
+                return 3.0;
+            }
+            return cell;
+        }
+        return 0.0;
+    }
+    if (((game_rules.birth_mask >> neighbors) & 1u) == 1u) {
+        var max_count = 0u;
+        var dominant = 0u;
+        for (var i = 0u; i < 6u; i = i + 1u) {
+            if (color_counts[i] > max_count) {
+                max_count = color_counts[i];
+                dominant = i;
+            }
+        }
+        var colors = array<f32, 6>(1.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+        return colors[dominant];
+    }
+    return 0.0;
+}
+
+@compute @workgroup_size(WORKGROUP_SIZE, WORKGROUP_SIZE, 1)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    // Every invocation, including those past the grid edge, helps fill the tile
+    load_tile(workgroup_id.xy, local_index);
+    workgroupBarrier();
+
+    let x = global_id.x;
+    let y = global_id.y;
+    if (x >= sim_params.width || y >= sim_params.height) {
+        return;
+    }
+    cell_state_out[y * sim_params.width + x] = next_state(local_id.xy, x, y);
+}
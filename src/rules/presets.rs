@@ -8,25 +8,11 @@ impl GameRules {
 
     /// HighLife variant (B36/S23) - has a self-replicating pattern
     pub fn high_life() -> Self {
-        Self {
-            survival_min: 2,
-            survival_max: 3,
-            birth_count: 6, // Birth on 3 or 6 neighbors
-            // Note: Original HighLife birth rule is B36. We need to ensure the shader
-            // handles multiple birth counts if we want true HighLife via parameters.
-            // Currently, the shader only supports one birth_count.
-        }
+        Self::from_counts(&[3, 6], &[2, 3])
     }
 
     /// Day & Night variant (B3678/S34678)
     pub fn day_and_night() -> Self {
-        Self {
-            survival_min: 3,
-            survival_max: 8,
-            birth_count: 3,
-            // Note: Similar to HighLife, Day & Night has more complex rules (B3678/S34678)
-            // than can be represented solely by the current GameRules struct parameters.
-            // A custom shader would be needed for this rule set.
-        }
+        Self::from_counts(&[3, 6, 7, 8], &[3, 4, 6, 7, 8])
     }
-} 
\ No newline at end of file
+}
\ No newline at end of file
@@ -0,0 +1,862 @@
+use crate::activity::ActivityTracker;
+use crate::chunked::ChunkedGrid;
+use crate::compute::{SimParams, ShaderGameRules, WORKGROUP_SIZE, create_compute_bind_groups};
+use crate::hashlife;
+use crate::rules::{Boundary, GameRules};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+
+/// Jumps at least this long go through HashLife when the rules allow it (see `Simulation::jump`)
+pub const HASHLIFE_MIN_JUMP: usize = 10_000;
+
+/// Most generations a jump steps on the GPU (the GUI blocks meanwhile)
+pub const GPU_MAX_JUMP: usize = 1_000_000;
+
+/// Generations per submission when jumping on the GPU
+pub const GPU_JUMP_BATCH: u32 = 1_000;
+
+/// Generations a jump steps on the GPU before handing back to HashLife
+const GPU_JUMP_STRETCH: usize = 10_000;
+
+/// WGSL source of the default rule kernel
+pub const DEFAULT_SHADER_SOURCE: &str = include_str!("rules/conway_classic.wgsl");
+
+/// WGSL source of the shared-memory variant of the default kernel, without its
+/// `WORKGROUP_SIZE` declaration
+const SHARED_SHADER_SOURCE: &str = include_str!("rules/conway_shared.wgsl");
+
+/// WGSL source of the temporally blocked kernel, without its `WORKGROUP_SIZE` and
+/// `BLOCK_GENERATIONS` declarations
+const BLOCKED_SHADER_SOURCE: &str = include_str!("rules/conway_blocked.wgsl");
+
+/// Entry point of the activity-aware kernel, appended to `SHARED_SHADER_SOURCE`
+const ACTIVE_SHADER_SOURCE: &str = include_str!("rules/conway_active.wgsl");
+
+/// Implementations of the default rules. All give identical results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kernel {
+    /// One invocation per cell reading its neighbors from the grid buffer
+    /// (`rules/conway_classic.wgsl`, 8x8 workgroups). Custom shaders always run this way.
+    #[default]
+    Global,
+    /// Each `workgroup_size` x `workgroup_size` workgroup stages its cells plus a
+    /// one-cell halo in workgroup memory and counts neighbors from there
+    Shared { workgroup_size: u32 },
+    /// Advances `generations` (odd) generations per dispatch: each workgroup keeps a
+    /// tile with a `generations`-cell halo in workgroup memory and writes back the
+    /// `workgroup_size - 2 * generations` square in its middle. Leftover generations of
+    /// a batch, and single steps, run on the shared kernel of the same workgroup size.
+    /// Works for every rule of the default kernel, since they all only look one cell away.
+    Blocked { workgroup_size: u32, generations: u32 },
+    /// Like `Shared`, but only recomputes the workgroup-sized tiles that changed in the
+    /// previous generation or border one that did (see `activity::ActivityTracker`).
+    /// Steps with the lucky rule are random even in settled regions, so they run every tile.
+    Active { workgroup_size: u32 },
+}
+
+impl Kernel {
+    /// Choices offered in the GUI and benchmarked by `gpu-life-cli --benchmark`
+    pub const PRESETS: [Kernel; 9] = [
+        Kernel::Global,
+        Kernel::Shared { workgroup_size: 8 },
+        Kernel::Shared { workgroup_size: 16 },
+        Kernel::Shared { workgroup_size: 32 },
+        Kernel::Blocked { workgroup_size: 16, generations: 3 },
+        Kernel::Blocked { workgroup_size: 32, generations: 3 },
+        Kernel::Blocked { workgroup_size: 32, generations: 5 },
+        Kernel::Active { workgroup_size: 8 },
+        Kernel::Active { workgroup_size: 16 },
+    ];
+
+    /// Side length of the square workgroups
+    pub fn workgroup_size(&self) -> u32 {
+        match self {
+            Kernel::Global => WORKGROUP_SIZE,
+            Kernel::Shared { workgroup_size }
+            | Kernel::Blocked { workgroup_size, .. }
+            | Kernel::Active { workgroup_size } => *workgroup_size,
+        }
+    }
+
+    /// Generations advanced by one dispatch
+    pub fn generations_per_dispatch(&self) -> u32 {
+        match self {
+            Kernel::Blocked { generations, .. } => *generations,
+            _ => 1,
+        }
+    }
+
+    /// Whether the kernel can step a grid split into row chunks. Only kernels that
+    /// look a single cell away, without state of their own, can.
+    pub fn supports_chunks(&self) -> bool {
+        matches!(self, Kernel::Global | Kernel::Shared { .. })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Kernel::Global => "global".to_string(),
+            Kernel::Shared { workgroup_size } => format!("shared {}x{}", workgroup_size, workgroup_size),
+            Kernel::Blocked { workgroup_size, generations } => {
+                format!("blocked {}x{}, {} gens", workgroup_size, workgroup_size, generations)
+            }
+            Kernel::Active { workgroup_size } => format!("active tiles {}x{}", workgroup_size, workgroup_size),
+        }
+    }
+}
+
+/// The cellular automaton on the GPU: grid buffers, rules and the compute pipeline.
+///
+/// A `Simulation` only needs a `wgpu::Device` and `Queue`, so it can run without a
+/// window or surface (tools, tests, software adapters). The GUI `State` composes one.
+///
+/// The grid is double buffered; `grid_buffers[generation % 2]` always holds the
+/// current generation and is the input of the next step. Grids larger than one
+/// storage buffer binding are split into row chunks instead (see `chunked`).
+pub struct Simulation {
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+
+    pub width: u32,
+    pub height: u32,
+    /// The whole grid, or a single placeholder cell when the grid is chunked
+    pub grid_buffers: [wgpu::Buffer; 2],
+    pub sim_param_buffer: wgpu::Buffer,
+    pub rules_buffer: wgpu::Buffer,
+    pub rules: GameRules,
+    pub boundary: Boundary,
+    pub lucky_rule_enabled: bool,
+    /// Chance (0-1) for a dying cell to survive as a lucky cell
+    pub lucky_chance: f32,
+    /// Number of steps run since the grid was created or resized
+    pub generation: usize,
+
+    shader_source: String,
+    kernel: Kernel,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::ComputePipeline,
+    /// Multi-generation pipeline of `Kernel::Blocked`
+    block_pipeline: Option<wgpu::ComputePipeline>,
+    /// Tile schedule of `Kernel::Active`
+    activity: Option<ActivityTracker>,
+    bind_groups: [wgpu::BindGroup; 2],
+    /// Row chunks of a grid that does not fit one buffer
+    chunked: Option<ChunkedGrid>,
+    /// Rows per chunk requested with `with_chunk_rows`, `None` to chunk only when needed
+    chunk_rows: Option<u32>,
+    /// The grid buffers were freed by `release_grid`
+    released: bool,
+    /// Changes made from outside the kernel (see `edit_count`)
+    edits: Cell<u64>,
+}
+
+/// How `Simulation::jump` got there
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Jump {
+    pub hashlife_generations: usize,
+    pub gpu_generations: usize,
+}
+
+impl Simulation {
+    /// Create an empty `width` x `height` grid with the default rules and kernel
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_chunk_rows(device, queue, width, height, None)
+    }
+
+    /// Like `new`, but split the grid into chunks of at most `chunk_rows` rows even
+    /// when it would fit a single buffer (e.g. to test chunking on small grids)
+    pub fn with_chunk_rows(
+        device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, width: u32, height: u32, chunk_rows: Option<u32>,
+    ) -> Result<Self, String> {
+        let rows_per_chunk = rows_per_chunk(&device, width, height, chunk_rows)?;
+
+        let rules = GameRules::default();
+        let rules_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Game Rules Buffer"),
+            contents: bytemuck::bytes_of(&ShaderGameRules::from(&rules)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let (grid_buffers, sim_param_buffer) = if rows_per_chunk < height {
+            create_grid_buffers(&device, 1, 1)
+        } else {
+            create_grid_buffers(&device, width, height)
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry { // SimParams
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry { // Input Grid
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry { // Output Grid
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry { // Game Rules
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = compile_pipeline(&device, &pipeline_layout, DEFAULT_SHADER_SOURCE);
+        let bind_groups = create_compute_bind_groups(
+            &device, &bind_group_layout, &grid_buffers, &sim_param_buffer, &rules_buffer
+        );
+        let chunked = (rows_per_chunk < height).then(|| {
+            ChunkedGrid::new(&device, &bind_group_layout, &rules_buffer, width, height, rows_per_chunk)
+        });
+
+        let simulation = Self {
+            device,
+            queue,
+            width,
+            height,
+            grid_buffers,
+            sim_param_buffer,
+            rules_buffer,
+            rules,
+            boundary: Boundary::default(),
+            lucky_rule_enabled: false,
+            lucky_chance: 0.1,
+            generation: 0,
+            shader_source: DEFAULT_SHADER_SOURCE.to_string(),
+            kernel: Kernel::Global,
+            bind_group_layout,
+            pipeline_layout,
+            pipeline,
+            block_pipeline: None,
+            activity: None,
+            bind_groups,
+            chunked,
+            chunk_rows,
+            released: false,
+            edits: Cell::new(0),
+        };
+        simulation.write_params();
+        Ok(simulation)
+    }
+
+    /// Like `new`, but with the grid buffers released from the start (see `release_grid`),
+    /// for a grid whose cells are kept elsewhere and that may be too large to allocate
+    pub fn new_released(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, width: u32, height: u32) -> Result<Self, String> {
+        let mut simulation = Self::new(device, queue, 1, 1)?;
+        simulation.release_grid(width, height);
+        Ok(simulation)
+    }
+
+    /// Create a simulation on a device of its own, without any window or surface.
+    /// Falls back to a software adapter when no hardware adapter is available.
+    pub async fn new_headless(width: u32, height: u32) -> Result<Self, String> {
+        let (device, queue) = request_headless_device().await?;
+        Self::new(device, queue, width, height)
+    }
+
+    /// Replace the grid with an empty one of a new size. Resets the generation counter.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let rows_per_chunk = rows_per_chunk(&self.device, width, height, self.chunk_rows)?;
+        if rows_per_chunk < height && !self.kernel.supports_chunks() {
+            return Err(format!("The {} kernel cannot run a grid split into chunks", self.kernel.name()));
+        }
+        self.width = width;
+        self.height = height;
+        self.chunked = (rows_per_chunk < height).then(|| {
+            ChunkedGrid::new(&self.device, &self.bind_group_layout, &self.rules_buffer, width, height, rows_per_chunk)
+        });
+        let (grid_buffers, sim_param_buffer) = if self.chunked.is_some() {
+            create_grid_buffers(&self.device, 1, 1)
+        } else {
+            create_grid_buffers(&self.device, width, height)
+        };
+        self.grid_buffers = grid_buffers;
+        self.sim_param_buffer = sim_param_buffer;
+        self.bind_groups = create_compute_bind_groups(
+            &self.device, &self.bind_group_layout, &self.grid_buffers, &self.sim_param_buffer, &self.rules_buffer
+        );
+        if let Kernel::Active { workgroup_size } = self.kernel {
+            self.activity = Some(self.create_activity_tracker(workgroup_size));
+        }
+        self.released = false;
+        self.generation = 0;
+        self.mark_edited();
+        self.write_params();
+        Ok(())
+    }
+
+    /// Free the grid buffers while the cells are kept elsewhere (the GUI's packed mode),
+    /// taking on a `width` x `height` size without allocating it. Cells cannot be read
+    /// or written until `resize` allocates the grid again.
+    pub fn release_grid(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.chunked = None;
+        self.activity = None;
+        let (grid_buffers, sim_param_buffer) = create_grid_buffers(&self.device, 1, 1);
+        self.grid_buffers = grid_buffers;
+        self.sim_param_buffer = sim_param_buffer;
+        self.bind_groups = create_compute_bind_groups(
+            &self.device, &self.bind_group_layout, &self.grid_buffers, &self.sim_param_buffer, &self.rules_buffer
+        );
+        self.released = true;
+        self.mark_edited();
+        self.write_params();
+    }
+
+    /// Whether the grid buffers are freed (see `release_grid`)
+    pub fn is_released(&self) -> bool {
+        self.released
+    }
+
+    fn check_allocated(&self) -> Result<(), String> {
+        if self.released {
+            return Err("The grid buffers have been released".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn shader_source(&self) -> &str {
+        &self.shader_source
+    }
+
+    /// Compile new WGSL rule source and use it for the following steps
+    pub fn load_shader(&mut self, source: String) -> Result<(), String> {
+        // Note: Shader compilation errors are not directly exposed in a user-friendly way by wgpu's create_shader_module.
+        // Errors might be reported through logs or device loss if severe.
+        self.pipeline = compile_pipeline(&self.device, &self.pipeline_layout, &source);
+        self.shader_source = source;
+        self.kernel = Kernel::Global;
+        self.block_pipeline = None;
+        self.activity = None;
+        self.mark_edited();
+        Ok(())
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Switch between implementations of the default rules. Fails for custom shaders
+    /// and for workgroup sizes the device cannot run.
+    pub fn set_kernel(&mut self, kernel: Kernel) -> Result<(), String> {
+        if self.shader_source != DEFAULT_SHADER_SOURCE {
+            return Err("Custom shaders only run on the global kernel".to_string());
+        }
+        if self.chunked.is_some() && !kernel.supports_chunks() {
+            return Err(format!("The {} kernel cannot run a grid split into chunks", kernel.name()));
+        }
+        let shared_source = |workgroup_size: u32| {
+            format!("const WORKGROUP_SIZE: u32 = {}u;\n{}", workgroup_size, SHARED_SHADER_SOURCE)
+        };
+        let (source, block_source) = match kernel {
+            Kernel::Global => (DEFAULT_SHADER_SOURCE.to_string(), None),
+            Kernel::Shared { workgroup_size } | Kernel::Active { workgroup_size } => {
+                check_workgroup_size(&self.device, workgroup_size, (workgroup_size + 2).pow(2))?;
+                (shared_source(workgroup_size), None)
+            }
+            Kernel::Blocked { workgroup_size, generations } => {
+                if generations % 2 == 0 || workgroup_size <= 2 * generations {
+                    return Err(format!(
+                        "Blocking needs an odd number of generations and a workgroup wider than twice that, got {}x{} with {}",
+                        workgroup_size, workgroup_size, generations
+                    ));
+                }
+                check_workgroup_size(&self.device, workgroup_size, 2 * workgroup_size.pow(2))?;
+                check_workgroup_size(&self.device, workgroup_size, (workgroup_size + 2).pow(2))?;
+                let block_source = format!(
+                    "const WORKGROUP_SIZE: u32 = {}u;\nconst BLOCK_GENERATIONS: u32 = {}u;\n{}",
+                    workgroup_size, generations, BLOCKED_SHADER_SOURCE
+                );
+                (shared_source(workgroup_size), Some(block_source))
+            }
+        };
+        self.pipeline = compile_pipeline(&self.device, &self.pipeline_layout, &source);
+        self.block_pipeline = block_source.map(|source| compile_pipeline(&self.device, &self.pipeline_layout, &source));
+        self.activity = match kernel {
+            Kernel::Active { workgroup_size } => Some(self.create_activity_tracker(workgroup_size)),
+            _ => None,
+        };
+        self.kernel = kernel;
+        Ok(())
+    }
+
+    fn create_activity_tracker(&self, workgroup_size: u32) -> ActivityTracker {
+        let source = format!(
+            "const WORKGROUP_SIZE: u32 = {}u;\n{}\n{}",
+            workgroup_size, SHARED_SHADER_SOURCE, ACTIVE_SHADER_SOURCE
+        );
+        ActivityTracker::new(&self.device, &self.bind_group_layout, &source, self.width, self.height, workgroup_size)
+    }
+
+    /// Tiles the active-tile kernel recomputes in the next step, out of how many
+    /// (`None` for other kernels).
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn active_tiles(&self) -> Option<Result<(u32, u32), String>> {
+        let activity = self.activity.as_ref()?;
+        Some(activity.active_tile_count(&self.device, &self.queue).map(|count| (count, activity.tile_count())))
+    }
+
+    /// Everything written from outside the kernel may wake settled tiles
+    fn invalidate_activity(&self) {
+        if let Some(activity) = &self.activity {
+            activity.invalidate();
+        }
+    }
+
+    /// Cells, rules or the shader changed other than by stepping
+    fn mark_edited(&self) {
+        self.edits.set(self.edits.get() + 1);
+        self.invalidate_activity();
+    }
+
+    /// Counts edits (cell writes, clears, rule or shader changes, resizes). Copies of
+    /// earlier generations cannot be stepped into later ones once this changes.
+    pub fn edit_count(&self) -> u64 {
+        self.edits.get()
+    }
+
+    /// Change the parameterized rules (uniform buffer only, the kernel stays the same)
+    pub fn set_rules(&mut self, rules: GameRules) {
+        self.rules = rules;
+        self.mark_edited();
+        self.queue.write_buffer(&self.rules_buffer, 0, bytemuck::bytes_of(&ShaderGameRules::from(&rules)));
+    }
+
+    /// Upload grid size, boundary mode, lucky rule settings and the hash seed (the current generation).
+    /// Every step encoded before the next submit uses these values.
+    pub fn write_params(&self) {
+        let params = SimParams {
+            width: self.width,
+            height: self.height,
+            lucky_chance: self.lucky_chance,
+            seed: self.generation as u32,
+            enable_lucky_rule: if self.lucky_rule_enabled { 1 } else { 0 },
+            boundary: self.boundary.shader_value(),
+            _padding: [0; 2],
+        };
+        match &self.chunked {
+            Some(chunked) => chunked.write_params(&self.queue, &params),
+            None => self.queue.write_buffer(&self.sim_param_buffer, 0, bytemuck::bytes_of(&params)),
+        }
+    }
+
+    /// The buffer holding the current generation (a placeholder when the grid is chunked)
+    pub fn current_buffer(&self) -> &wgpu::Buffer {
+        &self.grid_buffers[self.generation % 2]
+    }
+
+    /// The row chunks, when the grid is split into several buffers
+    pub fn chunks(&self) -> Option<&ChunkedGrid> {
+        self.chunked.as_ref()
+    }
+
+    /// Record one generation into `encoder` and advance the generation counter
+    pub fn encode_step(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(activity) = self.activity.as_mut() {
+            if !self.lucky_rule_enabled {
+                activity.encode_step(&self.queue, encoder, &self.bind_groups[self.generation % 2], self.boundary);
+                self.generation += 1;
+                return;
+            }
+            activity.invalidate();
+        }
+        if let Some(chunked) = &self.chunked {
+            chunked.encode_step(encoder, &self.pipeline, self.generation % 2, self.kernel.workgroup_size(), self.boundary);
+            self.generation += 1;
+            return;
+        }
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Game of Life Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &self.bind_groups[self.generation % 2], &[]);
+            let workgroup_size = self.kernel.workgroup_size();
+            compute_pass.dispatch_workgroups(
+                self.width.div_ceil(workgroup_size),
+                self.height.div_ceil(workgroup_size),
+                1,
+            );
+        }
+        self.generation += 1;
+    }
+
+    /// Record `count` generations into `encoder`. Blocked kernels cover them with as few
+    /// dispatches as possible, so only the last generation is guaranteed to be in a buffer
+    /// (use `encode_step` when every generation is sampled).
+    pub fn encode_steps(&mut self, encoder: &mut wgpu::CommandEncoder, count: u32) {
+        let mut remaining = count;
+        if let (Some(pipeline), Kernel::Blocked { workgroup_size, generations }) = (&self.block_pipeline, self.kernel) {
+            let output_size = workgroup_size - 2 * generations;
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Blocked Game of Life Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(pipeline);
+            while remaining >= generations {
+                // An odd generation count swaps the buffers just like a single step
+                compute_pass.set_bind_group(0, &self.bind_groups[self.generation % 2], &[]);
+                compute_pass.dispatch_workgroups(
+                    self.width.div_ceil(output_size),
+                    self.height.div_ceil(output_size),
+                    1,
+                );
+                self.generation += generations as usize;
+                remaining -= generations;
+            }
+        }
+        for _ in 0..remaining {
+            self.encode_step(encoder);
+        }
+    }
+
+    /// Run `count` generations in a single submission
+    pub fn step(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.write_params();
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Simulation Step Encoder"),
+        });
+        self.encode_steps(&mut encoder, count);
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Advance `generations` generations. Long jumps of plain life-like rules go through
+    /// HashLife as far as it reproduces this grid exactly (see `hashlife::advance_grid`),
+    /// which turns colored cells white. Whatever it leaves is stepped on the GPU, in
+    /// stretches after which HashLife gets another try, up to `GPU_MAX_JUMP` generations
+    /// in all. If that runs out the grid stays at the generation reached.
+    pub fn jump(&mut self, generations: usize) -> Result<Jump, String> {
+        self.check_allocated()?;
+        let hashlife_ok = !self.lucky_rule_enabled
+            && self.shader_source == DEFAULT_SHADER_SOURCE
+            && !self.rules.births_on(0);
+        let mut jump = Jump::default();
+        let mut remaining = generations;
+        while remaining > 0 {
+            if hashlife_ok && remaining >= HASHLIFE_MIN_JUMP {
+                let (cells, advanced) = hashlife::advance_grid(
+                    self.rules, &self.read_cells()?, self.width, self.height, self.boundary, remaining as u64,
+                )?;
+                if advanced > 0 {
+                    self.load_generation(self.generation + advanced as usize, &cells)?;
+                    jump.hashlife_generations += advanced as usize;
+                    remaining -= advanced as usize;
+                    continue;
+                }
+            }
+            let stretch = if hashlife_ok { remaining.min(GPU_JUMP_STRETCH) } else { remaining };
+            if jump.gpu_generations + stretch > GPU_MAX_JUMP {
+                return Err(format!(
+                    "Stopped at generation {}: {} generations are left, more than the GPU steps in one jump ({})",
+                    self.generation, remaining, GPU_MAX_JUMP
+                ));
+            }
+            let mut left = stretch;
+            while left > 0 {
+                let batch = left.min(GPU_JUMP_BATCH as usize) as u32;
+                self.step(batch);
+                left -= batch as usize;
+            }
+            self.device.poll(wgpu::Maintain::Wait);
+            jump.gpu_generations += stretch;
+            remaining -= stretch;
+        }
+        Ok(jump)
+    }
+
+    /// Copy the current generation back to the CPU (row-major cell values).
+    /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn read_cells(&self) -> Result<Vec<f32>, String> {
+        self.check_allocated()?;
+        if let Some(chunked) = &self.chunked {
+            return chunked.read_cells(&self.device, &self.queue, self.generation % 2);
+        }
+        let buffer_size = self.cell_count() as u64 * std::mem::size_of::<f32>() as u64;
+        read_buffer(&self.device, &self.queue, self.current_buffer(), 0, buffer_size)
+    }
+
+    /// Replace the current generation with `cells` (row-major, `width * height` values)
+    pub fn write_cells(&self, cells: &[f32]) -> Result<(), String> {
+        self.check_allocated()?;
+        if cells.len() != self.cell_count() {
+            return Err(format!("Expected {} cells, got {}", self.cell_count(), cells.len()));
+        }
+        match &self.chunked {
+            Some(chunked) => chunked.write_cells(&self.queue, self.generation % 2, cells),
+            None => self.queue.write_buffer(self.current_buffer(), 0, bytemuck::cast_slice(cells)),
+        }
+        self.mark_edited();
+        Ok(())
+    }
+
+    /// Continue from `cells` as generation `generation` (e.g. after a jump computed elsewhere).
+    /// Both buffers receive the cells, so views of the previous generation show them too.
+    pub fn load_generation(&mut self, generation: usize, cells: &[f32]) -> Result<(), String> {
+        self.check_allocated()?;
+        if cells.len() != self.cell_count() {
+            return Err(format!("Expected {} cells, got {}", self.cell_count(), cells.len()));
+        }
+        self.generation = generation;
+        for parity in 0..2 {
+            match &self.chunked {
+                Some(chunked) => chunked.write_cells(&self.queue, parity, cells),
+                None => self.queue.write_buffer(&self.grid_buffers[parity], 0, bytemuck::cast_slice(cells)),
+            }
+        }
+        self.mark_edited();
+        self.write_params();
+        Ok(())
+    }
+
+    /// Continue from a GPU copy of generation `generation` (a `history::SnapshotRing`
+    /// buffer) without a round trip through the CPU. Both buffers receive the cells.
+    pub fn restore_from(&mut self, generation: usize, snapshot: &wgpu::Buffer) -> Result<(), String> {
+        self.check_allocated()?;
+        if self.chunked.is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        let buffer_size = self.cell_count() as u64 * std::mem::size_of::<f32>() as u64;
+        if snapshot.size() != buffer_size {
+            return Err(format!("Snapshot holds {} bytes, the grid needs {}", snapshot.size(), buffer_size));
+        }
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Snapshot Restore Encoder"),
+        });
+        for buffer in &self.grid_buffers {
+            encoder.copy_buffer_to_buffer(snapshot, 0, buffer, 0, buffer_size);
+        }
+        self.queue.submit(Some(encoder.finish()));
+        self.generation = generation;
+        self.invalidate_activity();
+        self.write_params();
+        Ok(())
+    }
+
+    /// Overwrite a horizontal run of cells starting at (x, y), clipped to the grid
+    pub fn write_row(&self, x: u32, y: u32, values: &[f32]) {
+        if self.released || x >= self.width || y >= self.height {
+            return;
+        }
+        let len = values.len().min((self.width - x) as usize);
+        match &self.chunked {
+            Some(chunked) => chunked.write_row(&self.queue, self.generation % 2, x, y, &values[..len]),
+            None => {
+                let offset = (y as u64 * self.width as u64 + x as u64) * std::mem::size_of::<f32>() as u64;
+                self.queue.write_buffer(self.current_buffer(), offset, bytemuck::cast_slice(&values[..len]));
+            }
+        }
+        self.mark_edited();
+    }
+
+    /// Kill every cell of the current generation
+    pub fn clear(&self) {
+        if self.released {
+            return;
+        }
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Grid Clear Encoder"),
+        });
+        match &self.chunked {
+            Some(chunked) => chunked.encode_clear(&mut encoder, self.generation % 2),
+            None => encoder.clear_buffer(self.current_buffer(), 0, None),
+        }
+        self.queue.submit(Some(encoder.finish()));
+        self.mark_edited();
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+}
+
+/// Request a device without a surface, preferring hardware and falling back to a
+/// software adapter (e.g. llvmpipe/WARP) when none is available
+pub async fn request_headless_device() -> Result<(Arc<wgpu::Device>, Arc<wgpu::Queue>), String> {
+    let instance = wgpu::Instance::default();
+    let mut adapter = None;
+    for force_fallback_adapter in [false, true] {
+        adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter,
+                compatible_surface: None,
+            })
+            .await;
+        if adapter.is_some() {
+            break;
+        }
+    }
+    let adapter = adapter.ok_or_else(|| "No GPU or software adapter available".to_string())?;
+    log::info!("Using adapter: {:?}", adapter.get_info());
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Headless Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+            },
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to create device: {}", e))?;
+    Ok((Arc::new(device), Arc::new(queue)))
+}
+
+/// Whether a `width` x `height` f32 grid fits one storage buffer binding, i.e. runs
+/// without being split into chunks
+pub fn fits_one_buffer(device: &wgpu::Device, width: u32, height: u32) -> bool {
+    width as u64 * height as u64 * std::mem::size_of::<f32>() as u64 <= grid_buffer_limit(device)
+}
+
+fn grid_buffer_limit(device: &wgpu::Device) -> u64 {
+    let limits = device.limits();
+    (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size)
+}
+
+/// Rows per buffer for a `width` x `height` grid: all of them when the grid fits one
+/// storage buffer binding (and `requested` does not ask for less), else as many as fit
+fn rows_per_chunk(device: &wgpu::Device, width: u32, height: u32, requested: Option<u32>) -> Result<u32, String> {
+    if width == 0 || height == 0 {
+        return Err("Grid dimensions must be non-zero".to_string());
+    }
+    if fits_one_buffer(device, width, height) && requested.is_none_or(|rows| rows >= height) {
+        return Ok(height);
+    }
+    let limit = grid_buffer_limit(device);
+    let row_bytes = width as u64 * std::mem::size_of::<f32>() as u64;
+    // A chunk also holds a halo row above and below
+    let fitting_rows = (limit / row_bytes).saturating_sub(2);
+    if fitting_rows == 0 {
+        return Err(format!("A grid {} cells wide needs {} bytes per row, the device allows {} per buffer", width, row_bytes, limit));
+    }
+    Ok(requested.unwrap_or(u32::MAX).clamp(1, fitting_rows.min(height as u64) as u32))
+}
+
+/// A square workgroup of `size` invocations per side and its `tile_cells` cells of
+/// workgroup memory must fit the device limits
+fn check_workgroup_size(device: &wgpu::Device, size: u32, tile_cells: u32) -> Result<(), String> {
+    let limits = device.limits();
+    let tile_bytes = tile_cells * std::mem::size_of::<f32>() as u32;
+    if size == 0
+        || size > limits.max_compute_workgroup_size_x
+        || size > limits.max_compute_workgroup_size_y
+        || size * size > limits.max_compute_invocations_per_workgroup
+        || tile_bytes > limits.max_compute_workgroup_storage_size
+    {
+        return Err(format!(
+            "Workgroup size {}x{} is not supported (at most {} invocations per workgroup)",
+            size, size, limits.max_compute_invocations_per_workgroup
+        ));
+    }
+    Ok(())
+}
+
+fn create_grid_buffers(device: &wgpu::Device, width: u32, height: u32) -> ([wgpu::Buffer; 2], wgpu::Buffer) {
+    let buffer_size = width as u64 * height as u64 * std::mem::size_of::<f32>() as u64;
+    let grid_buffers = [0, 1].map(|i| device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("Grid Buffer {}", i)),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    }));
+    let sim_param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Simulation Parameters"),
+        size: std::mem::size_of::<SimParams>() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (grid_buffers, sim_param_buffer)
+}
+
+/// Copy `size` bytes of `buffer` from `offset` back to the CPU as cell values.
+/// WARNING: This is a blocking operation and will stall the GPU pipeline!
+pub(crate) fn read_buffer(
+    device: &wgpu::Device, queue: &wgpu::Queue, buffer: &wgpu::Buffer, offset: u64, size: u64,
+) -> Result<Vec<f32>, String> {
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Grid Readback Staging Buffer"),
+        size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Grid Readback Copy Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(buffer, offset, &staging_buffer, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    // Blocks until the GPU finishes the copy and mapping
+    device.poll(wgpu::Maintain::Wait);
+
+    match receiver.recv() {
+        Ok(Ok(())) => {
+            let data = buffer_slice.get_mapped_range();
+            let cells = bytemuck::cast_slice::<u8, f32>(&data).to_vec();
+            drop(data);
+            staging_buffer.unmap();
+            Ok(cells)
+        }
+        Ok(Err(e)) => Err(format!("Failed to map staging buffer: {:?}", e)),
+        Err(e) => Err(format!("Failed to receive map result: {:?}", e)),
+    }
+}
+
+fn compile_pipeline(device: &wgpu::Device, layout: &wgpu::PipelineLayout, source: &str) -> wgpu::ComputePipeline {
+    log::info!("Compiling compute shader...");
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Dynamic Compute Shader"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(source)),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Dynamic Compute Pipeline"),
+        layout: Some(layout),
+        module: &shader_module,
+        entry_point: "main",
+    });
+    log::info!("Compute shader compiled successfully.");
+    pipeline
+}
@@ -1,38 +1,49 @@
-use crate::compute::{SimParams, WORKGROUP_SIZE, create_compute_bind_groups, ShaderGameRules};
-use crate::render::{RenderParams, MIN_ZOOM, create_render_bind_group_layout, create_render_bind_groups};
-use crate::rules::{Pattern, place_pattern_on_grid, GameRules};
+use crate::render::{RenderParams, Palette, MIN_ZOOM, ChunkRenderTarget, create_chunk_render_targets, create_grid_render_pipeline, create_render_bind_group_layout, create_render_bind_groups};
+use crate::rules::GameRules;
+use crate::simulation::{fits_one_buffer, Kernel, Simulation, DEFAULT_SHADER_SOURCE, GPU_JUMP_BATCH, GPU_MAX_JUMP, HASHLIFE_MIN_JUMP};
+use crate::hashlife::HashLife;
+use crate::library::{Pattern, PatternLibrary, builtin_pattern};
+use crate::recording::{Recorder, RecordingSettings};
+use crate::stats::{GenerationStats, StatsReducer, StatsRecorder, StatsSettings};
+use crate::tiled::{TiledRenderer, TiledUniverse};
+use crate::packed::PackedSimulation;
+use crate::resize::{ResizeMode, resize_cells};
+use crate::census::{CensusSettings, SoupSearch};
+use crate::inspector::CellInspector;
+use crate::components::{ComponentLabeler, Object, NO_LABEL};
+use crate::tracker::SpaceshipTracker;
+use crate::period::{AutoPauseSettings, Stabilization, StabilityMonitor};
+use crate::history::{HistorySample, HistoryView, PopulationHistory, SnapshotRing, DEFAULT_SNAPSHOT_SLOTS};
 use wgpu::util::DeviceExt;
 use winit::{
     dpi::PhysicalPosition,
     window::Window,
 };
 use std::sync::Arc;
-use std::borrow::Cow; // Needed for ShaderSource
 
 // GUI Imports
 use egui_winit::State as EguiWinitState;
 use egui_wgpu::Renderer as EguiWgpuRenderer;
 use egui::Context as EguiContext;
-use std::time::Instant;
-use std::time::Duration; // For throttling
+use std::time::{Duration, Instant};
 
 // Cursor modes for different tools
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum CursorMode {
+    #[default]
     Paint,               // Default - paint cells
-    PlaceGlider,         // Place standard gliders
-    PlaceLWSS,           // Place lightweight spaceships
-    PlacePulsar,         // Place pulsar oscillator
-    PlaceGosperGun,      // Place Gosper glider gun
-    PlacePentadecathlon, // Place pentadecathlon oscillator
-    PlaceSimkinGun,      // Place Simkin glider gun
+    PlacePattern,        // Stamp the selected pattern library entry
     ClearArea,           // Clear cells in an area
     RandomFill,          // Fill with random cells
+    Select,              // Drag a rectangle to select a region for analysis
+    Inspect,             // Hover to inspect a cell, click to pin it
+    SelectObject,        // Click an object (connected live cells) to select it
 }
 
 // Cell colors for placed cells
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum CellColor {
+    #[default]
     White,  // Default white (1.0)
     Red,    // Red (3.0)
     Green,  // Green (4.0)
@@ -41,12 +52,6 @@ pub enum CellColor {
     Purple, // Purple (7.0)
 }
 
-impl Default for CellColor {
-    fn default() -> Self {
-        Self::White
-    }
-}
-
 impl CellColor {
     // Convert the enum to its float representation for the shader
     pub fn to_value(&self) -> f32 {
@@ -61,45 +66,54 @@ impl CellColor {
     }
 }
 
-impl Default for CursorMode {
-    fn default() -> Self {
-        Self::Paint
+// Largest edge, in pixels, of a pattern preview in the library browser
+const PATTERN_THUMBNAIL_SIZE: u32 = 64;
+
+// Stamp a pattern into CPU-side grid data with its top-left corner at (x, y)
+fn stamp_pattern(grid: &mut [f32], width: u32, height: u32, pattern: &Pattern, x: u32, y: u32) {
+    for &(dx, dy) in &pattern.cells {
+        let (cell_x, cell_y) = (x + dx as u32, y + dy as u32);
+        if cell_x < width && cell_y < height {
+            grid[(cell_y * width + cell_x) as usize] = 1.0;
+        }
+    }
+}
+
+/// Patterns placed on a new grid, with their top-left corners
+fn initial_patterns(width: u32, height: u32) -> Vec<(Pattern, u32, u32)> {
+    if width <= 10 || height <= 10 {
+        return Vec::new();
     }
+    // A glider near the center and a glider gun, from the bundled library
+    vec![
+        (builtin_pattern("Glider"), width / 4, height / 4),
+        (builtin_pattern("Gosper glider gun"), width / 5, height / 2),
+    ]
 }
 
 // const BRUSH_RADIUS: i32 = 3; // Remove constant, will use state field
 
 pub struct State {
     pub surface: wgpu::Surface<'static>,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub window: Arc<Window>,
 
-    pub grid_width: u32,
-    pub grid_height: u32,
-    pub grid_buffers: [wgpu::Buffer; 2],
-    pub sim_param_buffer: wgpu::Buffer,
-    pub rules_buffer: wgpu::Buffer,
-    pub current_rules: GameRules,
-
-    // --- Compute related fields ---
-    pub compute_shader_source: String, // Store the source code
-    pub compute_bind_group_layout: wgpu::BindGroupLayout,
-    pub compute_pipeline_layout: wgpu::PipelineLayout, // Store the layout
-    pub compute_pipeline: wgpu::ComputePipeline, // The current pipeline
-    pub compute_bind_groups: [wgpu::BindGroup; 2],
-    // --- End Compute ---
+    // The automaton itself (grid buffers, rules, compute pipeline, generation counter)
+    pub simulation: Simulation,
 
     pub render_pipeline: wgpu::RenderPipeline,
     pub render_bind_group_layout: wgpu::BindGroupLayout,
     pub render_bind_groups: [wgpu::BindGroup; 2],
     pub render_param_buffer: wgpu::Buffer,
+    /// One per chunk when the grid is split into row chunks, drawn instead of `render_bind_groups`
+    pub chunk_render_targets: Vec<ChunkRenderTarget>,
 
-    pub frame_num: usize,
     pub zoom: f32,
     pub view_offset: [f32; 2], // Current view offset (in grid coordinates)
+    pub view_origin: [i64; 2], // World cell that view_offset is relative to (infinite plane only)
     pub is_right_mouse_pressed: bool,
     pub is_left_mouse_pressed: bool,
     pub last_mouse_pos: Option<PhysicalPosition<f64>>,
@@ -109,15 +123,10 @@ pub struct State {
     pub is_dragging: bool,
     pub drag_start_pos: Option<PhysicalPosition<f64>>,
     pub last_action_time: Option<std::time::Instant>,
-    pub last_glider_time: Option<std::time::Instant>,
     pub last_paint_time: Option<std::time::Instant>,
     pub last_clear_time: Option<std::time::Instant>,
     pub last_random_time: Option<std::time::Instant>,
-    pub last_lwss_time: Option<std::time::Instant>,
-    pub last_pulsar_time: Option<std::time::Instant>,
-    pub last_gosper_gun_time: Option<std::time::Instant>,
-    pub last_pentadecathlon_time: Option<std::time::Instant>,
-    pub last_simkin_gun_time: Option<std::time::Instant>,
+    pub last_pattern_time: Option<std::time::Instant>,
 
     // Context menu state
     pub right_click_start_pos: Option<PhysicalPosition<f64>>,
@@ -138,13 +147,86 @@ pub struct State {
     pub brush_radius: u32,
     pub lucky_chance_percent: u32,
     pub current_cell_color: CellColor, // Current color for placed cells
+    pub palette: Palette,              // Active color scheme (display and export)
+    // PNG export state
+    pub export_scale: u32,             // Output pixels per cell (N x N)
+    pub export_status: Option<String>, // Result of the last export, shown in the menu
+    // Animated recording state
+    pub recorder: Option<Recorder>,              // Active recording, if any
+    pub recording_settings: RecordingSettings,   // Settings used for the next recording
+    pub recording_status: Option<String>,        // Result of the last recording, shown in the menu
+    // Statistics log state
+    pub stats_recorder: Option<StatsRecorder>,   // Active statistics log, if any
+    pub stats_settings: StatsSettings,           // Settings used for the next statistics log
+    pub stats_status: Option<String>,            // Result of the last statistics log, shown in the menu
+    // Pattern library state
+    pub pattern_library: PatternLibrary,
+    pub selected_pattern: Option<usize>,         // Library entry used by CursorMode::PlacePattern
+    pub pattern_dir_input: String,               // Directory field in the library browser
+    pub pattern_search: String,                  // Filter text in the library browser
+    pub show_pattern_browser: bool,
+    pub pattern_thumbnails: Vec<egui::TextureHandle>, // Cached previews, parallel to the library entries
     // Cell counting state
     pub live_cell_count: Option<u32>,
     pub last_count_update_time: Option<Instant>,
+    pub live_stats: Option<GenerationStats>,    // Latest reduction of the grid (per-state counts, births/deaths, bounding box)
+    pub stats_reducer: Option<StatsReducer>,    // Reduces the grid for live_stats and the statistics log; recreated when the grid buffers change
+    pub last_readback_time: Option<Instant>,    // Last blocking count (packed population, active tiles), at most once per second
+    // Population history state
+    pub population_history: PopulationHistory,  // Statistics log samples, else live_stats once per frame, drawn by the history graph
+    pub history_view: HistoryView,              // Lines, axis and snapshot interval of the history graph
+    pub snapshots: Option<SnapshotRing>,        // GPU copies of past generations, kept while the graph is open
+    pub show_history: bool,
+    pub history_status: Option<String>,         // Result of the last restore, shown in the graph window
+    // Selection and pattern analysis state
+    pub selection: Option<[u32; 4]>,             // Region picked with CursorMode::Select: x, y, width, height
+    pub selection_anchor: Option<(i64, i64)>,    // Grid cell where the current selection drag started
+    pub analysis_generations: usize,             // Generations the period analysis runs before giving up
+    pub analysis_status: Option<String>,         // Result of the last analysis, shown in the menu
+    // Stabilization detection state
+    pub auto_pause: AutoPauseSettings,
+    pub stability: Option<StabilityMonitor>,     // Hashes every generation while auto-pause is enabled
+    pub stability_status: Option<String>,        // Generation and population the grid stabilized at
+    // Soup census state
+    pub census_settings: CensusSettings,
+    pub census: Option<SoupSearch>,              // Latest search; kept after it finishes to show its table
+    pub census_running: bool,                    // A batch of the search runs every frame while set
+    pub show_census: bool,
+    pub census_status: Option<String>,
+    // Cell inspector state
+    pub inspector: Option<CellInspector>,        // Exists while CursorMode::Inspect is active or a cell is pinned
+    pub pinned_cell: Option<(u32, u32)>,
+    // Objects (connected components)
+    pub show_objects: bool,                      // Color every object while set
+    pub object_radius: u32,                      // Cells up to this far apart belong to one object
+    pub labeler: Option<ComponentLabeler>,       // Exists while objects are shown, picked or selected
+    pub objects_render_pipeline: wgpu::RenderPipeline,
+    pub objects_render_bind_group: Option<wgpu::BindGroup>,
+    pub selected_object: Option<Object>,
+    pub objects_status: Option<String>,
+    pub show_tracker: bool,                      // Track spaceships and draw their velocity and trail
+    pub tracker: Option<SpaceshipTracker>,
     // Simulation speed control
     pub simulation_speed: u32,           // Steps per second (1-240)
     pub last_update_time: Instant,       // When we last ran a simulation step
     pub accumulated_time: f32,           // Accumulated time for simulation steps
+    pub paused: bool,                    // No steps are run while set (also set by auto-pause)
+    pub jump_target: usize,              // Generation entered in the "Jump to generation" field
+    pub jump_status: Option<String>,     // Result of the last jump, shown in the menu
+
+    pub universe: Option<TiledUniverse>, // Infinite plane replacing the grid while enabled
+    pub tiled_renderer: TiledRenderer,
+    pub universe_status: Option<String>, // Result of the last infinite plane toggle, shown in the menu
+
+    pub packed: Option<PackedSimulation>, // Bit-packed two-state grid replacing the f32 grid while enabled
+    pub packed_render_pipeline: wgpu::RenderPipeline,
+    pub packed_render_bind_groups: Option<[wgpu::BindGroup; 2]>,
+    pub packed_status: Option<String>,    // Result of the last packed mode toggle, shown in the menu
+    pub kernel_status: Option<String>,    // Result of the last kernel change, shown in the menu
+    pub grid_size_target: [u32; 2],       // Width and height entered in the "Grid size" fields
+    pub resize_mode: ResizeMode,          // How cells are carried over when the grid size changes
+    pub grid_status: Option<String>,      // Result of the last grid resize, shown in the menu
+    pub active_tiles: Option<(u32, u32)>, // Scheduled and total tiles of the active-tile kernel, updated with the live count
     // FPS tracking
     pub frame_times: Vec<f32>,           // Circular buffer of recent frame times in seconds
     pub frame_time_index: usize,         // Current position in the circular buffer
@@ -153,10 +235,10 @@ pub struct State {
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>) -> Self {
+    /// The grid is `grid_width` x `grid_height` cells whatever the window size;
+    /// the window only shows part of it (see `resize_grid` to change it later)
+    pub async fn new(window: Arc<Window>, grid_width: u32, grid_height: u32) -> Self {
         let size = window.inner_size();
-        let initial_grid_width = size.width.max(1);
-        let initial_grid_height = size.height.max(1);
 
         log::info!("Initializing wgpu...");
 
@@ -177,12 +259,14 @@ impl State {
                 &wgpu::DeviceDescriptor {
                     label: None,
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    // Everything the adapter offers, so grids can use all of its memory
+                    required_limits: adapter.limits(),
                 },
                 None,
             )
             .await
             .expect("Failed to create device");
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
 
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps.formats[0];
@@ -190,36 +274,31 @@ impl State {
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: initial_grid_width,
-            height: initial_grid_height,
+            width: size.width.max(1),
+            height: size.height.max(1),
             present_mode: wgpu::PresentMode::Immediate,
             alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![surface_format.into()],
+            view_formats: vec![surface_format],
             desired_maximum_frame_latency: 2,
         };
         surface.configure(&device, &config);
 
-        // Create Game Rules
-        let game_rules = GameRules::default(); // Default Conway's rules
-        let shader_rules = ShaderGameRules::from(&game_rules);
-        let rules_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Game Rules Buffer"),
-            contents: bytemuck::bytes_of(&shader_rules),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
-        // Create Grid Resources
-        let (grid_buffers, sim_param_buffer) =
-            Self::create_grid_buffers(&device, initial_grid_width, initial_grid_height);
-        queue.write_buffer(&sim_param_buffer, 0, bytemuck::bytes_of(&SimParams {
-            width: initial_grid_width,
-            height: initial_grid_height,
-            lucky_chance: 0.1,
-            seed: 0,
-            enable_lucky_rule: 0,
-            _padding: [0; 3],
-        }));
-        Self::initialize_grid_buffer(&queue, &grid_buffers[0], initial_grid_width, initial_grid_height);
+        // Create the simulation (grid buffers, rules, compute pipeline). A grid too large
+        // for one f32 buffer starts packed, without ever allocating the f32 grid.
+        let (simulation, packed) = if fits_one_buffer(&device, grid_width, grid_height) {
+            let simulation = Simulation::new(device.clone(), queue.clone(), grid_width, grid_height)
+                .expect("Failed to create simulation");
+            Self::initialize_grid(&simulation);
+            (simulation, None)
+        } else {
+            let simulation = Simulation::new_released(device.clone(), queue.clone(), grid_width, grid_height)
+                .expect("Failed to create simulation");
+            let packed = PackedSimulation::new(device.clone(), queue.clone(), grid_width, grid_height)
+                .expect("Failed to create packed simulation");
+            Self::initialize_packed_grid(&packed);
+            log::info!("Starting packed: a {}x{} f32 grid does not fit one buffer", grid_width, grid_height);
+            (simulation, Some(packed))
+        };
 
         // Create Render Resources
         let initial_zoom = MIN_ZOOM;
@@ -228,6 +307,7 @@ impl State {
             zoom: initial_zoom,
             view_offset: initial_view_offset,
             _padding: 0.0,
+            palette: Palette::default().colors(),
         };
         let render_param_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Render Parameters"),
@@ -235,144 +315,32 @@ impl State {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        // Load initial compute shader source (will be compiled later)
-        let initial_compute_shader_source = include_str!("rules/conway_classic.wgsl").to_string();
-
-        // Define a minimal placeholder compute shader for the initial temporary pipeline.
-        // It MUST define the same structs as the real shader for layout compatibility.
-        let placeholder_compute_shader_source = r#"
-struct SimParams {
-    width: u32,
-    height: u32,
-    lucky_chance: f32,
-    seed: u32,
-    enable_lucky_rule: u32,
-    // _padding: array<u32, 3>,
-    _pad1: u32,
-    _pad2: u32,
-    _pad3: u32,
-}
-
-struct GameRules {
-    survival_min: u32,
-    survival_max: u32,
-    birth_count: u32,
-    _padding: u32,
-}
-
-@group(0) @binding(0) var<uniform> sim_params: SimParams;
-@group(0) @binding(1) var<storage, read> cell_state_in: array<f32>;
-@group(0) @binding(2) var<storage, read_write> cell_state_out: array<f32>;
-@group(0) @binding(3) var<uniform> game_rules: GameRules;
-
-@compute @workgroup_size(8, 8, 1)
-fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
-    // Minimal main function for placeholder - does nothing useful
-    let idx = global_id.x + global_id.y * sim_params.width;
-    if (idx < arrayLength(&cell_state_out)) {
-        cell_state_out[idx] = 0.0; // Just write 0
-    }
-}
-        "#;
-
-        // Load render shader (doesn't need dynamic loading for now)
-        let render_shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Render Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../render.wgsl").into()),
-        });
-
-        // --- Setup Compute Pipeline ---
-        let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Compute Bind Group Layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry { // SimParams
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry { // Input Grid
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry { // Output Grid
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry { // Game Rules
-                    binding: 3,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        });
-
-        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Compute Pipeline Layout"),
-            bind_group_layouts: &[&compute_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        // Initial pipeline creation will happen via recreate_compute_pipeline
-
-        let compute_bind_groups = create_compute_bind_groups(
-            &device, &compute_bind_group_layout, &grid_buffers, &sim_param_buffer, &rules_buffer
-        );
-        // --- End Compute Pipeline Setup ---
-
-
-        // Render Pipeline
+        // Render pipelines for the f32 grid and the bit-packed grid (same bind group layout)
         let render_bind_group_layout = create_render_bind_group_layout(&device);
-        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&render_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &render_shader_module,
-                entry_point: "vs_main",
-                buffers: &[],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &render_shader_module,
-                entry_point: "fs_main",
-                targets: &[Some(config.format.into())],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                ..Default::default()
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-        });
+        let render_pipeline = create_grid_render_pipeline(
+            &device, &render_bind_group_layout, config.format, "Render Pipeline", include_str!("../render.wgsl"),
+        );
+        let packed_render_pipeline = create_grid_render_pipeline(
+            &device, &render_bind_group_layout, config.format, "Packed Render Pipeline", include_str!("../packed_render.wgsl"),
+        );
+        // Objects are drawn from the component labels instead of the grid
+        let objects_render_pipeline = create_grid_render_pipeline(
+            &device, &render_bind_group_layout, config.format, "Objects Render Pipeline", include_str!("../components_render.wgsl"),
+        );
         let render_bind_groups = create_render_bind_groups(
-            &device, &render_bind_group_layout, &grid_buffers, &sim_param_buffer, &render_param_buffer
+            &device, &render_bind_group_layout, &simulation.grid_buffers, &simulation.sim_param_buffer, &render_param_buffer
         );
+        let packed_render_bind_groups = packed.as_ref().map(|packed| create_render_bind_groups(
+            &device, &render_bind_group_layout, &packed.grid_buffers, &packed.param_buffer, &render_param_buffer
+        ));
+        let chunk_render_targets = simulation.chunks()
+            .map(|chunked| create_chunk_render_targets(&device, &render_bind_group_layout, chunked))
+            .unwrap_or_default();
+        for target in &chunk_render_targets {
+            target.write_render_params(&queue, &render_param_data);
+        }
+        let tiled_renderer = TiledRenderer::new(&device, config.format);
+        tiled_renderer.write_render_params(&queue, &render_param_data);
 
         log::info!("Initializing egui...");
         let egui_ctx = EguiContext::default();
@@ -380,52 +348,28 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         let egui_renderer = EguiWgpuRenderer::new(&device, config.format, None, 1);
         log::info!("egui initialized.");
 
-        log::info!("wgpu initialized successfully.");
-
-        // Temporary compute pipeline before the real one is compiled
-        // This is slightly awkward but necessary because recreate_compute_pipeline needs `&mut self`
-        let temp_compute_pipeline = {
-             let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                 label: Some("Temp Initial Compute Shader"),
-                 // source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(&initial_compute_shader_source)), // Use placeholder instead
-                 source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(placeholder_compute_shader_source)),
-             });
-             device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                 label: Some("Temp Initial Compute Pipeline"),
-                 layout: Some(&compute_pipeline_layout),
-                 module: &module,
-                 entry_point: "main",
-             })
-        };
+        // Bundled patterns plus the user's pattern directory
+        let pattern_library = PatternLibrary::load(&PatternLibrary::default_directory());
 
+        log::info!("wgpu initialized successfully.");
 
-        let mut state = Self {
+        Self {
             surface,
             device,
             queue,
             config,
             size,
             window,
-            grid_width: initial_grid_width,
-            grid_height: initial_grid_height,
-            grid_buffers,
-            sim_param_buffer,
-            rules_buffer,
-            current_rules: game_rules,
-
-            compute_shader_source: initial_compute_shader_source, // Store source
-            compute_bind_group_layout,
-            compute_pipeline_layout, // Store layout
-            compute_pipeline: temp_compute_pipeline, // Store pipeline (will be replaced)
-            compute_bind_groups,
+            simulation,
 
             render_pipeline,
             render_bind_group_layout,
             render_bind_groups,
+            chunk_render_targets,
             render_param_buffer,
-            frame_num: 0,
             zoom: initial_zoom,
             view_offset: initial_view_offset,
+            view_origin: [0, 0],
             is_right_mouse_pressed: false,
             is_left_mouse_pressed: false,
             last_mouse_pos: None,
@@ -440,10 +384,56 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
             // Cell counting state
             live_cell_count: None,
             last_count_update_time: None,
+            live_stats: None,
+            stats_reducer: None,
+            last_readback_time: None,
+            population_history: PopulationHistory::default(),
+            history_view: HistoryView::default(),
+            snapshots: None,
+            show_history: false,
+            history_status: None,
+            selection: None,
+            selection_anchor: None,
+            analysis_generations: 1000,
+            analysis_status: None,
+            auto_pause: AutoPauseSettings::default(),
+            stability: None,
+            stability_status: None,
+            census_settings: CensusSettings::default(),
+            census: None,
+            census_running: false,
+            show_census: false,
+            census_status: None,
+            inspector: None,
+            pinned_cell: None,
+            show_objects: false,
+            object_radius: crate::components::DEFAULT_RADIUS,
+            labeler: None,
+            objects_render_pipeline,
+            objects_render_bind_group: None,
+            selected_object: None,
+            objects_status: None,
+            show_tracker: false,
+            tracker: None,
             // Initialize simulation speed to 60 steps per second
             simulation_speed: 60,
             last_update_time: Instant::now(),
             accumulated_time: 0.0,
+            paused: false,
+            jump_target: 1_000_000,
+            jump_status: None,
+            universe: None,
+            tiled_renderer,
+            universe_status: None,
+            packed_status: packed.as_ref().map(|_| "Packed: the f32 grid would not fit one buffer".to_string()),
+            packed,
+            packed_render_pipeline,
+            packed_render_bind_groups,
+            kernel_status: None,
+            grid_size_target: [grid_width, grid_height],
+            resize_mode: ResizeMode::default(),
+            grid_status: None,
+            active_tiles: None,
             // FPS tracking
             frame_times: vec![0.0; 60],    // Track last 60 frames (1 second at 60fps)
             frame_time_index: 0,
@@ -462,107 +452,72 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
             is_dragging: false,
             drag_start_pos: None,
             last_action_time: None,
-            last_glider_time: None,
             last_paint_time: None,
             last_clear_time: None,
             last_random_time: None,
-            last_lwss_time: None,
-            last_pulsar_time: None,
-            last_gosper_gun_time: None,
-            last_pentadecathlon_time: None,
-            last_simkin_gun_time: None,
+            last_pattern_time: None,
             current_cell_color: CellColor::default(),
-        };
-
-        // Now compile the *real* initial pipeline
-        state.recreate_compute_pipeline_from_source()
-             .expect("Failed to compile initial compute shader");
-
-        state
-    }
-
-    /// Compiles the WGSL source stored in `self.compute_shader_source` and
-    /// replaces `self.compute_pipeline`.
-    fn recreate_compute_pipeline_from_source(&mut self) -> Result<(), String> {
-        log::info!("Compiling compute shader...");
-        let shader_module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Dynamic Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(&self.compute_shader_source)),
-        });
-
-        // Note: Shader compilation errors are not directly exposed in a user-friendly way by wgpu's create_shader_module.
-        // Errors might be reported through logs or device loss if severe.
-        // For more robust error handling, WGSL validation libraries (like naga) could be used beforehand.
-
-        self.compute_pipeline = self.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Dynamic Compute Pipeline"),
-            layout: Some(&self.compute_pipeline_layout), // Use stored layout
-            module: &shader_module,
-            entry_point: "main",
-        });
-        log::info!("Compute shader compiled successfully.");
-        Ok(())
+            palette: Palette::default(),
+            export_scale: 1,
+            export_status: None,
+            recorder: None,
+            recording_settings: RecordingSettings::default(),
+            recording_status: None,
+            stats_recorder: None,
+            stats_settings: StatsSettings::default(),
+            stats_status: None,
+            pattern_dir_input: pattern_library.directory.display().to_string(),
+            selected_pattern: pattern_library.entries.iter().position(|e| e.name == "Glider"),
+            pattern_library,
+            pattern_search: String::new(),
+            show_pattern_browser: false,
+            pattern_thumbnails: Vec::new(),
+        }
     }
 
     /// Loads new WGSL source code, attempts to compile it, and replaces the
     /// current compute pipeline if successful.
     pub fn load_new_compute_shader(&mut self, new_shader_source: String) -> Result<(), String> {
-        self.compute_shader_source = new_shader_source;
-        self.recreate_compute_pipeline_from_source() // Attempt recompilation
+        self.simulation.load_shader(new_shader_source)
     }
 
-    // Helper function to create grid buffers (kept internal to State)
-    fn create_grid_buffers(device: &wgpu::Device, width: u32, height: u32) -> ([wgpu::Buffer; 2], wgpu::Buffer) {
-        let grid_size = (width * height) as u64;
-        let buffer_size = grid_size * std::mem::size_of::<f32>() as u64;
-
-        let grid_buffers = [
-            device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("Grid Buffer 0"),
-                size: buffer_size,
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
-                mapped_at_creation: false,
-            }),
-            device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("Grid Buffer 1"),
-                size: buffer_size,
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
-                mapped_at_creation: false,
-            }),
-        ];
-
-        let sim_param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-             label: Some("Simulation Parameters"),
-             size: std::mem::size_of::<SimParams>() as u64,
-             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-             mapped_at_creation: false,
-        });
-
-        (grid_buffers, sim_param_buffer)
+    /// Switch the implementation of the default rules (see `simulation::Kernel`)
+    pub fn set_kernel(&mut self, kernel: Kernel) -> Result<String, String> {
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        self.simulation.set_kernel(kernel)?;
+        Ok(format!("Using the {} kernel", kernel.name()))
     }
 
-    // Helper function to initialize one grid buffer (kept internal to State)
-    fn initialize_grid_buffer(queue: &wgpu::Queue, buffer: &wgpu::Buffer, width: u32, height: u32) {
-        let grid_size = (width * height) as usize;
-        let mut initial_data = vec![0.0f32; grid_size];
-
-        if width > 10 && height > 10 {
-            // Place a glider pattern near the center
-            let pattern_pos_x = width / 4;
-            let pattern_pos_y = height / 4;
-
-            // Use the Pattern enum from the rules module
-            place_pattern_on_grid(&mut initial_data, width, height, &Pattern::Glider, pattern_pos_x, pattern_pos_y);
+    // Fill a new grid with the default starting patterns (kept internal to State)
+    fn initialize_grid(simulation: &Simulation) {
+        let (width, height) = (simulation.width, simulation.height);
+        let mut initial_data = vec![0.0f32; simulation.cell_count()];
+        for (pattern, x, y) in initial_patterns(width, height) {
+            stamp_pattern(&mut initial_data, width, height, &pattern, x, y);
+        }
 
-            // You could add more patterns or different ones based on parameters
-            // For example:
-            // place_pattern_on_grid(&mut initial_data, width, height, &Pattern::Blinker, width/2, height/2);
-            place_pattern_on_grid(&mut initial_data, width, height, &Pattern::GosperGliderGun, width/5, height/2);
+        if let Err(e) = simulation.write_cells(&initial_data) {
+            log::error!("Failed to initialize grid: {}", e);
         }
+    }
 
-        queue.write_buffer(buffer, 0, bytemuck::cast_slice(&initial_data));
+    /// Same patterns as `initialize_grid`, written row by row so the (possibly huge)
+    /// grid is never built on the CPU
+    fn initialize_packed_grid(packed: &PackedSimulation) {
+        for (pattern, x, y) in initial_patterns(packed.width, packed.height) {
+            for row in 0..pattern.height as i32 {
+                let values: Vec<f32> = (0..pattern.width as i32)
+                    .map(|column| if pattern.cells.contains(&(column, row)) { 1.0 } else { 0.0 })
+                    .collect();
+                packed.write_row(x, y + row as u32, &values);
+            }
+        }
     }
 
+    /// Follow a window resize. Only the surface and the view change; the grid keeps
+    /// its size and cells.
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
@@ -570,63 +525,103 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
 
-            // Update grid dimensions
-            self.grid_width = new_size.width;
-            self.grid_height = new_size.height;
-
-            // Recreate buffers with new size
-            let (new_grid_buffers, new_sim_param_buffer) =
-                Self::create_grid_buffers(&self.device, self.grid_width, self.grid_height);
-            self.grid_buffers = new_grid_buffers;
-            self.sim_param_buffer = new_sim_param_buffer;
-
-            // Update uniform buffer content
-            self.queue.write_buffer(&self.sim_param_buffer, 0, bytemuck::bytes_of(&SimParams {
-                width: self.grid_width,
-                height: self.grid_height,
-                lucky_chance: self.lucky_chance_percent as f32 / 100.0,
-                seed: self.frame_num as u32,
-                enable_lucky_rule: if self.lucky_rule_enabled { 1 } else { 0 },
-                _padding: [0; 3],
-            }));
-
-            // Re-initialize buffer 0 (clears state on resize)
-            Self::initialize_grid_buffer(&self.queue, &self.grid_buffers[0], self.grid_width, self.grid_height);
+            // A larger window may now show space past the grid edge
+            crate::input::clamp_offset(self);
+            self.write_render_params();
 
-            // Recreate bind groups using the functions from the modules
-            // Note: The compute pipeline itself does *not* need to be recreated on resize
-            self.compute_bind_groups = create_compute_bind_groups(
-                &self.device, &self.compute_bind_group_layout, &self.grid_buffers,
-                &self.sim_param_buffer, &self.rules_buffer
-            );
-             self.render_bind_groups = create_render_bind_groups(
-                &self.device, &self.render_bind_group_layout, &self.grid_buffers, &self.sim_param_buffer, &self.render_param_buffer
-            );
-
-            // Reset frame counter to ensure correct initial buffer read
-            self.frame_num = 0;
-            // Reset view offset on resize to avoid confusion
-            self.view_offset = [0.0, 0.0];
-            self.zoom = MIN_ZOOM;
-             self.queue.write_buffer(&self.render_param_buffer, 0, bytemuck::bytes_of(&RenderParams {
-                 zoom: self.zoom,
-                 view_offset: self.view_offset,
-                 _padding: 0.0,
-             }));
-
-            log::info!("Resized grid and reconfigured surface to: {}x{}", self.grid_width, self.grid_height);
+            log::info!("Reconfigured surface to: {}x{}", new_size.width, new_size.height);
         } else {
             log::warn!("Ignoring resize to zero dimensions: {}x{}", new_size.width, new_size.height);
         }
     }
 
+    /// Give the grid new dimensions, carrying the current cells over as `mode` says.
+    /// The generation counter is kept; the size is limited by the device.
+    pub fn resize_grid(&mut self, width: u32, height: u32, mode: ResizeMode) -> Result<String, String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        let (old_width, old_height) = (self.simulation.width, self.simulation.height);
+        let generation = self.generation();
+        let cells = resize_cells(&self.read_grid_cells()?, old_width, old_height, width, height, mode);
+
+        // The grid buffers are about to be replaced, so finish any recording of the old grid
+        if self.recorder.is_some() {
+            self.recording_status = Some(match self.stop_recording() {
+                Ok(summary) => summary,
+                Err(e) => format!("Recording failed: {}", e),
+            });
+        }
+        if self.stats_recorder.is_some() {
+            self.stats_status = Some(match self.stop_stats() {
+                Ok(summary) => summary,
+                Err(e) => format!("Statistics log failed: {}", e),
+            });
+        }
+
+        // A grid too large for one f32 buffer is packed unless a custom kernel needs the
+        // f32 cells; the f32 grid is then never allocated at the new size
+        let pack = self.packed.is_none()
+            && !fits_one_buffer(&self.device, width, height)
+            && self.simulation.shader_source() == DEFAULT_SHADER_SOURCE;
+        if pack {
+            let mut packed = PackedSimulation::new(self.device.clone(), self.queue.clone(), width, height)?;
+            packed.set_rules(self.simulation.rules);
+            packed.boundary = self.simulation.boundary;
+            self.packed = Some(packed);
+            self.packed_status = Some("Packed: the f32 grid would not fit one buffer".to_string());
+        }
+        match self.packed.as_mut() {
+            Some(packed) => {
+                // Only the packed grid is allocated; the f32 grid stays released
+                if !pack {
+                    packed.resize(width, height)?;
+                }
+                packed.load_generation(generation, &cells)?;
+                self.packed_render_bind_groups = Some(create_render_bind_groups(
+                    &self.device, &self.render_bind_group_layout, &packed.grid_buffers, &packed.param_buffer, &self.render_param_buffer
+                ));
+                self.simulation.release_grid(width, height);
+            }
+            None => {
+                self.simulation.resize(width, height)?;
+                self.simulation.load_generation(generation, &cells)?;
+            }
+        }
+        self.stats_reducer = None;
+        self.live_stats = None;
+        self.snapshots = None;
+        self.stability = None;
+        self.inspector = None;
+        self.pinned_cell = None;
+        self.labeler = None;
+        self.objects_render_bind_group = None;
+        self.selected_object = None;
+        self.tracker = None;
+        self.selection = None;
+
+        self.bind_grid_render_targets();
+
+        crate::input::clamp_offset(self);
+        self.write_render_params();
+        self.update_live_cell_count();
+
+        log::info!("Resized grid from {}x{} to {}x{} ({:?})", old_width, old_height, width, height, mode);
+        Ok(format!("Grid is now {}x{}", width, height))
+    }
+
     /// Change the Game of Life rules (parameterized approach, retained for compatibility/flexibility)
     pub fn change_rules(&mut self, rules: GameRules) {
-        self.current_rules = rules;
-        let shader_rules = ShaderGameRules::from(&self.current_rules);
-        self.queue.write_buffer(&self.rules_buffer, 0, bytemuck::bytes_of(&shader_rules));
-        log::info!("Game rules (uniform buffer) changed to: S{}-{}/B{}",
-                   rules.survival_min, rules.survival_max, rules.birth_count);
+        self.simulation.set_rules(rules);
+        if let Some(packed) = self.packed.as_mut() {
+            packed.set_rules(rules);
+        }
+        if let Some(universe) = self.universe.as_mut() {
+            if let Err(e) = universe.set_rules(rules) {
+                log::error!("Infinite plane keeps its rules: {}", e);
+            }
+        }
+        log::info!("Game rules (uniform buffer) changed to: {}", rules.to_rulestring());
         // Note: This only changes the uniform buffer. To swap the actual shader logic,
         // call `load_new_compute_shader` with the new WGSL source.
     }
@@ -636,21 +631,19 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         // Update FPS calculation
         self.update_fps();
         
-        // Update the simulation parameters with the current frame number
-        self.queue.write_buffer(&self.sim_param_buffer, 0, bytemuck::bytes_of(&SimParams {
-            width: self.grid_width,
-            height: self.grid_height,
-            lucky_chance: self.lucky_chance_percent as f32 / 100.0,
-            seed: self.frame_num as u32,
-            enable_lucky_rule: if self.lucky_rule_enabled { 1 } else { 0 },
-            _padding: [0; 3],
-        }));
+        // Update the simulation parameters with the GUI settings and the current generation
+        self.simulation.lucky_rule_enabled = self.lucky_rule_enabled;
+        self.simulation.lucky_chance = self.lucky_chance_percent as f32 / 100.0;
+        self.simulation.write_params();
 
         // Calculate how many simulation steps to run this frame
         let current_time = Instant::now();
         let elapsed_time = current_time.duration_since(self.last_update_time);
         self.accumulated_time += elapsed_time.as_secs_f32();
         self.last_update_time = current_time;
+        if self.paused {
+            self.accumulated_time = 0.0;
+        }
         
         // Determine number of steps to simulate
         let time_per_step = 1.0 / self.simulation_speed as f32;
@@ -668,37 +661,135 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
             }
         }
         
-        if steps_to_run > 0 {
+        let mut stabilized = None;
+        let mut stats_samples = Vec::new();
+        if let Some(universe) = self.universe.as_mut() {
+            // The infinite plane replaces the grid; recording and statistics are stopped while it is on
+            universe.lucky_rule_enabled = self.lucky_rule_enabled;
+            universe.lucky_chance = self.lucky_chance_percent as f32 / 100.0;
+            if let Err(e) = universe.step(steps_to_run) {
+                log::error!("Failed to step the infinite plane: {}", e);
+            }
+        } else if let Some(packed) = self.packed.as_mut() {
+            // Packed mode is two-state: no lucky rule, recording or statistics
+            packed.step(steps_to_run);
+        } else if steps_to_run > 0 {
+            self.update_snapshot_ring();
+            self.update_stability_monitor();
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.sync_ages(&self.simulation);
+            }
+
             // Create a single command encoder for all steps
             let mut compute_encoder = self.device
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor { 
                     label: Some("Batched Compute Encoder") 
                 });
             
-            // Run multiple simulation steps with the same encoder
-            for _ in 0..steps_to_run {
-                // Track which buffer is input vs output
-                let input_idx = self.frame_num % 2;
-                let output_idx = (self.frame_num + 1) % 2;
-                
-                {
-                    let mut compute_pass = compute_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                        label: Some("Game of Life Compute Pass"),
-                        timestamp_writes: None,
-                    });
-                    compute_pass.set_pipeline(&self.compute_pipeline);
-                    compute_pass.set_bind_group(0, &self.compute_bind_groups[input_idx], &[]);
-                    
-                    let dispatch_x = (self.grid_width + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
-                    let dispatch_y = (self.grid_height + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
-                    compute_pass.dispatch_workgroups(dispatch_x, dispatch_y, 1);
+            let ages_tracked = self.inspector.as_ref().is_some_and(|inspector| inspector.ages.is_some());
+            if self.recorder.is_none() && self.stats_recorder.is_none() && self.stability.is_none() && !ages_tracked {
+                // Nothing samples single generations, so blocked kernels may skip them
+                self.simulation.encode_steps(&mut compute_encoder, steps_to_run);
+            } else {
+                // Run multiple simulation steps with the same encoder
+                for _ in 0..steps_to_run {
+                    self.simulation.encode_step(&mut compute_encoder);
+                    if let Some(ages) = self.inspector.as_mut().and_then(|inspector| inspector.ages.as_mut()) {
+                        ages.encode_update(&mut compute_encoder, self.simulation.generation);
+                    }
+
+                    // Capture this generation for the recording (copies are recorded in the same encoder)
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        if recorder.wants_generation(self.simulation.generation) {
+                            if !recorder.has_free_slot() {
+                                // Every staging buffer is in flight: submit what we have and wait for
+                                // the oldest one rather than dropping a generation
+                                self.queue.submit(Some(compute_encoder.finish()));
+                                recorder.after_submit();
+                                recorder.wait_for_slot(&self.device);
+                                compute_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                    label: Some("Batched Compute Encoder")
+                                });
+                            }
+                            recorder.encode_capture(&mut compute_encoder, self.simulation.current_buffer(), self.simulation.generation);
+                        }
+                    }
+
+                    // Reduce this generation's statistics on the GPU for the log
+                    if self.stats_recorder.as_ref().is_some_and(|stats| stats.wants_generation(self.simulation.generation)) {
+                        let reducer = self.stats_reducer.get_or_insert_with(|| StatsReducer::new(&self.device, &self.simulation));
+                        if !reducer.has_free_slot() {
+                            self.queue.submit(Some(compute_encoder.finish()));
+                            if let Some(recorder) = self.recorder.as_mut() {
+                                recorder.after_submit();
+                            }
+                            reducer.after_submit();
+                            stats_samples.extend(reducer.wait_for_slot(&self.device));
+                            compute_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                label: Some("Batched Compute Encoder")
+                            });
+                        }
+                        reducer.encode_sample(&mut compute_encoder, self.simulation.generation);
+                    }
+
+                    // Hash this generation to notice when the grid has become periodic
+                    if let Some(stability) = self.stability.as_mut() {
+                        if !stability.encode_hash(&mut compute_encoder, self.simulation.generation) {
+                            self.queue.submit(Some(compute_encoder.finish()));
+                            if let Some(recorder) = self.recorder.as_mut() {
+                                recorder.after_submit();
+                            }
+                            if let Some(reducer) = self.stats_reducer.as_mut() {
+                                reducer.after_submit();
+                            }
+                            stability.after_submit();
+                            if let Some(stabilization) = stability.wait_for_slot(&self.device) {
+                                stabilized = Some(stabilization);
+                            }
+                            compute_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                label: Some("Batched Compute Encoder")
+                            });
+                            stability.encode_hash(&mut compute_encoder, self.simulation.generation);
+                        }
+                    }
                 }
-                
-                self.frame_num += 1;
             }
-            
+
+            // Keep a copy of the newest generation for restoring it from the history graph
+            if let Some(snapshots) = self.snapshots.as_mut() {
+                if snapshots.is_due(self.simulation.generation) {
+                    snapshots.encode_snapshot(&mut compute_encoder, self.simulation.current_buffer(), self.simulation.generation);
+                }
+            }
+
             // Submit all simulation steps at once
             self.queue.submit(Some(compute_encoder.finish()));
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.after_submit();
+            }
+            if let Some(reducer) = self.stats_reducer.as_mut() {
+                reducer.after_submit();
+            }
+            if let Some(stability) = self.stability.as_mut() {
+                stability.after_submit();
+            }
+        }
+
+        // Hand finished captures to the encoder thread without blocking
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.poll(&self.device);
+        }
+        if let Some(reducer) = self.stats_reducer.as_mut() {
+            stats_samples.extend(reducer.poll(&self.device));
+        }
+        self.take_stats_samples(stats_samples);
+        if let Some(stability) = self.stability.as_mut() {
+            if let Some(stabilization) = stability.poll(&self.device) {
+                stabilized = Some(stabilization);
+            }
+        }
+        if let Some(stabilization) = stabilized {
+            self.pause_stabilized(stabilization);
         }
 
         // --- Get Surface Texture (early exit on error) ---
@@ -720,6 +811,11 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         let output_view = output_frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        if let Some(universe) = &self.universe {
+            self.tiled_renderer.prepare(
+                universe, self.view_origin, self.view_offset, self.zoom, (self.size.width, self.size.height),
+            );
+        }
         let mut render_encoder = self.device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Render Encoder") });
         {
@@ -739,10 +835,32 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-            render_pass.set_pipeline(&self.render_pipeline);
-            // Use the output of the compute pass (which is frame_num % 2) as input for render pass
-            render_pass.set_bind_group(0, &self.render_bind_groups[(self.frame_num + 1) % 2], &[]);
-            render_pass.draw(0..3, 0..1); // Draw full-screen triangle
+            if self.universe.is_some() {
+                self.tiled_renderer.draw(&mut render_pass);
+            } else if let (Some(packed), Some(bind_groups)) = (&self.packed, &self.packed_render_bind_groups) {
+                render_pass.set_pipeline(&self.packed_render_pipeline);
+                render_pass.set_bind_group(0, &bind_groups[packed.generation % 2], &[]);
+                render_pass.draw(0..3, 0..1);
+            } else if !self.chunk_render_targets.is_empty() {
+                render_pass.set_pipeline(&self.render_pipeline);
+                // Each chunk only covers the screen rows of its own grid rows, not its halo
+                for target in &self.chunk_render_targets {
+                    if let Some((top, rows)) = target.scissor_rows(self.zoom, self.view_offset[1], self.size.height) {
+                        render_pass.set_scissor_rect(0, top, self.size.width, rows);
+                        render_pass.set_bind_group(0, &target.bind_groups[(self.simulation.generation + 1) % 2], &[]);
+                        render_pass.draw(0..3, 0..1);
+                    }
+                }
+            } else if let Some(bind_group) = self.objects_render_bind_group.as_ref().filter(|_| self.objects_shown()) {
+                render_pass.set_pipeline(&self.objects_render_pipeline);
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            } else {
+                render_pass.set_pipeline(&self.render_pipeline);
+                // Use the output of the compute pass (which is generation % 2) as input for render pass
+                render_pass.set_bind_group(0, &self.render_bind_groups[(self.simulation.generation + 1) % 2], &[]);
+                render_pass.draw(0..3, 0..1); // Draw full-screen triangle
+            }
         }
         self.queue.submit(Some(render_encoder.finish()));
         // output_frame.present(); // DON'T present here, egui will do it later
@@ -751,104 +869,745 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         Ok(output_frame)
     }
 
-    /// Reads the current grid state back from the GPU and updates the live cell count.
+    /// Current generation of the grid or, when enabled, the infinite plane
+    pub fn generation(&self) -> usize {
+        match (&self.universe, &self.packed) {
+            (Some(universe), _) => universe.generation,
+            (None, Some(packed)) => packed.generation,
+            (None, None) => self.simulation.generation,
+        }
+    }
+
+    /// Uploads zoom, view offset and the active palette to the render uniform buffer
+    pub fn write_render_params(&self) {
+        let params = RenderParams {
+            zoom: self.zoom,
+            view_offset: self.view_offset,
+            _padding: 0.0,
+            palette: self.palette.colors(),
+        };
+        self.queue.write_buffer(&self.render_param_buffer, 0, bytemuck::bytes_of(&params));
+        for target in &self.chunk_render_targets {
+            target.write_render_params(&self.queue, &params);
+        }
+        self.tiled_renderer.write_render_params(&self.queue, &params);
+    }
+
+    /// Move whole cells from `view_offset` into `view_origin`, so the offset stays
+    /// small (and f32-exact) however far the view pans on the infinite plane
+    pub fn rebase_view(&mut self) {
+        for axis in 0..2 {
+            let cells = (self.view_offset[axis] / self.zoom).floor();
+            self.view_origin[axis] += cells as i64;
+            self.view_offset[axis] -= cells * self.zoom;
+        }
+    }
+
+    /// Replace the grid with an infinite plane holding a copy of it, with grid cell
+    /// (0, 0) at world cell (0, 0). Recording and statistics are stopped.
+    pub fn enable_infinite_plane(&mut self) -> Result<String, String> {
+        if self.universe.is_some() {
+            return Err("The infinite plane is already enabled".to_string());
+        }
+        if self.packed.is_some() {
+            return Err("Turn off packed mode first".to_string());
+        }
+        if self.simulation.shader_source() != DEFAULT_SHADER_SOURCE {
+            return Err("The infinite plane only runs the built-in rule kernel".to_string());
+        }
+        let mut universe = TiledUniverse::new(self.device.clone(), self.queue.clone(), self.simulation.rules)?;
+        let cells = self.simulation.read_cells()?;
+        universe.generation = self.simulation.generation;
+        universe.write_grid(0, 0, &cells, self.simulation.width, self.simulation.height)?;
+        universe.update_tiles()?;
+
+        if self.recorder.is_some() {
+            self.recording_status = Some(self.stop_recording().unwrap_or_else(|e| format!("Recording failed: {}", e)));
+        }
+        if self.stats_recorder.is_some() {
+            self.stats_status = Some(self.stop_stats().unwrap_or_else(|e| format!("Statistics log failed: {}", e)));
+        }
+        let status = format!("Infinite plane: {} tiles", universe.tile_count());
+        self.universe = Some(universe);
+        log::info!("{}", status);
+        Ok(status)
+    }
+
+    /// Return to the grid, keeping the part of the plane that covers it
+    pub fn disable_infinite_plane(&mut self) -> Result<String, String> {
+        let Some(universe) = self.universe.as_ref() else {
+            return Err("The infinite plane is not enabled".to_string());
+        };
+        let (width, height) = (self.simulation.width, self.simulation.height);
+        let cells = universe.read_region(0, 0, width, height)?;
+        let inside = cells.iter().filter(|&&cell| cell > 0.5).count() as u64;
+        let outside = universe.population().saturating_sub(inside);
+        self.simulation.load_generation(universe.generation, &cells)?;
+        self.universe = None;
+
+        // The grid view has no origin; start again from its top-left corner
+        self.view_origin = [0, 0];
+        self.view_offset = [0.0, 0.0];
+        self.write_render_params();
+
+        let mut status = format!("Back to the {}x{} grid", width, height);
+        if outside > 0 {
+            status.push_str(&format!(", {} cells outside the grid dropped", outside));
+        }
+        Ok(status)
+    }
+
+    /// Copies the current grid buffer (the input of the next compute pass) back to the CPU.
     /// WARNING: This is a blocking operation and will stall the GPU pipeline!
+    pub fn read_grid_cells(&self) -> Result<Vec<f32>, String> {
+        match &self.packed {
+            Some(packed) => packed.read_cells(),
+            None => self.simulation.read_cells(),
+        }
+    }
+
+    /// Recreate the render bind groups after the grid buffers were replaced
+    fn bind_grid_render_targets(&mut self) {
+        self.render_bind_groups = create_render_bind_groups(
+            &self.device, &self.render_bind_group_layout, &self.simulation.grid_buffers,
+            &self.simulation.sim_param_buffer, &self.render_param_buffer
+        );
+        self.chunk_render_targets = self.simulation.chunks()
+            .map(|chunked| create_chunk_render_targets(&self.device, &self.render_bind_group_layout, chunked))
+            .unwrap_or_default();
+    }
+
+    /// Replace the grid with a bit-packed copy (32 cells per word) and release the
+    /// f32 grid buffers. Cells become two-state: colors turn white and the lucky rule
+    /// is ignored while packed.
+    pub fn enable_packed_mode(&mut self) -> Result<String, String> {
+        if self.packed.is_some() {
+            return Err("Packed mode is already enabled".to_string());
+        }
+        if self.universe.is_some() {
+            return Err("Turn off the infinite plane first".to_string());
+        }
+        if self.simulation.shader_source() != DEFAULT_SHADER_SOURCE {
+            return Err("Packed mode only runs the built-in rule kernel".to_string());
+        }
+        let mut packed = PackedSimulation::new(
+            self.device.clone(), self.queue.clone(), self.simulation.width, self.simulation.height,
+        )?;
+        packed.set_rules(self.simulation.rules);
+        packed.boundary = self.simulation.boundary;
+        packed.load_generation(self.simulation.generation, &self.simulation.read_cells()?)?;
+
+        if self.recorder.is_some() {
+            self.recording_status = Some(self.stop_recording().unwrap_or_else(|e| format!("Recording failed: {}", e)));
+        }
+        if self.stats_recorder.is_some() {
+            self.stats_status = Some(self.stop_stats().unwrap_or_else(|e| format!("Statistics log failed: {}", e)));
+        }
+        let bytes = packed.word_count() * std::mem::size_of::<u32>();
+        self.packed_render_bind_groups = Some(create_render_bind_groups(
+            &self.device, &self.render_bind_group_layout, &packed.grid_buffers, &packed.param_buffer, &self.render_param_buffer
+        ));
+        self.packed = Some(packed);
+
+        // Everything holding on to the f32 grid goes with it
+        self.stats_reducer = None;
+        self.live_stats = None;
+        self.snapshots = None;
+        self.stability = None;
+        self.inspector = None;
+        self.labeler = None;
+        self.objects_render_bind_group = None;
+        self.tracker = None;
+        let f32_bytes = self.simulation.cell_count() * std::mem::size_of::<f32>();
+        self.simulation.release_grid(self.simulation.width, self.simulation.height);
+        self.bind_grid_render_targets();
+        Ok(format!("Packed: {} KiB per buffer instead of {} KiB", bytes / 1024, f32_bytes / 1024))
+    }
+
+    /// Return to the f32 grid with the packed cells (all white)
+    pub fn disable_packed_mode(&mut self) -> Result<String, String> {
+        let Some(packed) = self.packed.as_ref() else {
+            return Err("Packed mode is not enabled".to_string());
+        };
+        let (generation, cells) = (packed.generation, packed.read_cells()?);
+        self.simulation.resize(packed.width, packed.height)?;
+        self.simulation.load_generation(generation, &cells)?;
+        self.bind_grid_render_targets();
+        self.write_render_params();
+        self.packed = None;
+        self.packed_render_bind_groups = None;
+        Ok("Back to the full grid".to_string())
+    }
+
+    /// Refreshes the live cell count and `live_stats` without stalling the GPU: samples
+    /// reduced in earlier frames are collected and the current generation is queued for
+    /// reduction. Meant to be called every frame; the numbers trail by a frame or two.
     pub fn update_live_cell_count(&mut self) {
-        // Buffer containing the latest simulation state (the one about to be rendered)
-        let source_buffer_index = (self.frame_num + 1) % 2;
-        let source_buffer = &self.grid_buffers[source_buffer_index];
-
-        let buffer_size = (self.grid_width * self.grid_height * std::mem::size_of::<f32>() as u32) as wgpu::BufferAddress;
-
-        // Create a staging buffer (CPU-visible) to copy the data into
-        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Cell Count Staging Buffer"),
-            size: buffer_size,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
+        // The infinite plane counts its tiles after every batch anyway
+        if let Some(universe) = &self.universe {
+            self.live_cell_count = Some(universe.population().min(u32::MAX as u64) as u32);
+            self.last_count_update_time = Some(Instant::now());
+            return;
+        }
+        // The packed popcount and the active tile count are still read back synchronously
+        let readback_due = self.last_readback_time.is_none_or(|time| time.elapsed() >= Duration::from_secs(1));
+        if let Some(packed) = &self.packed {
+            if readback_due {
+                self.live_cell_count = packed.population().ok().map(|count| count as u32);
+                self.live_stats = None;
+                self.last_count_update_time = Some(Instant::now());
+                self.last_readback_time = Some(Instant::now());
+            }
+            return;
+        }
+        if readback_due {
+            self.active_tiles = self.simulation.active_tiles().and_then(|result| result.ok());
+            self.last_readback_time = Some(Instant::now());
+        }
 
-        // Create command encoder to copy data
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Cell Count Copy Encoder"),
+        let reducer = self.stats_reducer.get_or_insert_with(|| StatsReducer::new(&self.device, &self.simulation));
+        let samples = reducer.poll(&self.device);
+        if reducer.has_free_slot() {
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Live Statistics Encoder"),
+            });
+            reducer.encode_sample(&mut encoder, self.simulation.generation);
+            self.queue.submit(Some(encoder.finish()));
+            reducer.after_submit();
+        }
+        self.take_stats_samples(samples);
+    }
+
+    /// Hand samples of the statistics reduction to the log, the history graph and the
+    /// live numbers. The history gets the logged samples while a log runs (evenly
+    /// spaced), else every sample: one per frame.
+    fn take_stats_samples(&mut self, samples: Vec<GenerationStats>) {
+        for sample in &samples {
+            let logged = self.stats_recorder.as_mut().map(|stats| stats.record(sample));
+            if logged != Some(false) {
+                self.population_history.push(HistorySample::from(sample));
+            }
+        }
+        if let Some(latest) = samples.into_iter().next_back() {
+            self.live_cell_count = Some(latest.species[0].population);
+            self.live_stats = Some(latest);
+            self.last_count_update_time = Some(Instant::now());
+        }
+    }
+
+    /// Create the snapshot ring while the history graph is open (dropping it otherwise)
+    /// and forget snapshots the grid was edited after
+    fn update_snapshot_ring(&mut self) {
+        if !self.show_history || self.simulation.chunks().is_some() {
+            self.snapshots = None;
+            return;
+        }
+        let interval = self.history_view.snapshot_interval;
+        let size = self.simulation.current_buffer().size();
+        let snapshots = self.snapshots.get_or_insert_with(|| {
+            SnapshotRing::new(&self.device, size, DEFAULT_SNAPSHOT_SLOTS, interval)
         });
+        snapshots.interval = interval.max(1);
+        snapshots.sync_edits(self.simulation.edit_count());
+    }
 
-        // Copy data from GPU grid buffer to CPU staging buffer
-        encoder.copy_buffer_to_buffer(
-            source_buffer,       // Source GPU buffer
-            0,                   // Source offset
-            &staging_buffer,     // Destination CPU buffer
-            0,                   // Destination offset
-            buffer_size,         // Size
+    /// Hash generations while auto-pause is enabled (dropping the monitor otherwise)
+    /// and start over when the grid was edited or the threshold changed
+    fn update_stability_monitor(&mut self) {
+        if !self.auto_pause.enabled || self.simulation.chunks().is_some() {
+            self.stability = None;
+            return;
+        }
+        if self.stability.as_ref().is_some_and(|stability| stability.history.max_period != self.auto_pause.max_period.max(1)) {
+            self.stability = None;
+        }
+        if self.stability.is_none() {
+            self.stability = StabilityMonitor::new(&self.simulation, self.auto_pause.max_period)
+                .map_err(|e| log::warn!("Stabilization detection unavailable: {}", e))
+                .ok();
+        }
+        if let Some(stability) = self.stability.as_mut() {
+            stability.sync_edits(self.simulation.edit_count());
+        }
+    }
+
+    /// Stop stepping once the grid has become periodic and report where
+    fn pause_stabilized(&mut self, stabilization: Stabilization) {
+        let status = format!(
+            "Stabilized at generation {}: period {}, population {}",
+            stabilization.generation, stabilization.period, stabilization.population
         );
+        log::info!("{} (noticed at generation {})", status, stabilization.detected_at);
+        self.paused = true;
+        self.stability_status = Some(status);
+    }
 
-        // Submit the copy command to the GPU queue
-        self.queue.submit(Some(encoder.finish()));
+    /// Grid cell under the cursor, if it is on the grid
+    pub fn hovered_cell(&self) -> Option<(u32, u32)> {
+        let (x, y) = self.screen_to_grid(self.cursor_pos?);
+        (self.universe.is_none() && self.in_grid(x, y)).then_some((x as u32, y as u32))
+    }
 
-        // Request mapping of the staging buffer
-        let buffer_slice = staging_buffer.slice(..);
-        let (sender, receiver) = std::sync::mpsc::channel(); // Use a channel for async map result
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            sender.send(result).unwrap();
-        });
+    /// Pin the cell under `screen_pos` in the inspector, or unpin it if it already is
+    pub fn toggle_pinned_cell(&mut self, screen_pos: PhysicalPosition<f64>) {
+        let (x, y) = self.screen_to_grid(screen_pos);
+        if self.universe.is_some() || !self.in_grid(x, y) {
+            return;
+        }
+        let cell = (x as u32, y as u32);
+        self.pinned_cell = if self.pinned_cell == Some(cell) { None } else { Some(cell) };
+    }
 
-        // Poll the device Csync!! THIS WILL BLOCK until the GPU finishes the copy and mapping.
-        self.device.poll(wgpu::Maintain::Wait);
+    /// Query the hovered and pinned cells of the current generation; called every frame.
+    /// The windows arrive a frame or two later in `inspector`.
+    pub fn update_inspector(&mut self) {
+        let active = self.cursor_mode == CursorMode::Inspect || self.pinned_cell.is_some();
+        if !active || self.universe.is_some() || self.packed.is_some() || self.simulation.chunks().is_some() {
+            self.inspector = None;
+            return;
+        }
+        let hovered = if self.cursor_mode == CursorMode::Inspect { self.hovered_cell() } else { None };
+        let inspector = self.inspector.get_or_insert_with(|| CellInspector::new(&self.device));
+        inspector.update(&self.simulation, hovered, self.pinned_cell);
+    }
 
-        // Receive the mapping result
-        match receiver.recv() {
-            Ok(Ok(())) => {
-                // Get the mapped data
-                let data = buffer_slice.get_mapped_range();
-                let cell_states: &[f32] = bytemuck::cast_slice(&data);
+    /// Objects are drawn in their own colors instead of the grid
+    pub fn objects_shown(&self) -> bool {
+        self.show_objects || self.cursor_mode == CursorMode::SelectObject || self.selected_object.is_some()
+    }
 
-                // Count live cells (value > 0.5)
-                let count = cell_states.iter().filter(|&&state| state > 0.5).count();
+    /// Label the objects of the current generation while they are shown, picked,
+    /// selected or tracked; called every frame. A selection is dropped once the grid
+    /// changes.
+    pub fn update_objects(&mut self) {
+        let active = self.objects_shown() || self.show_tracker;
+        if !active || self.universe.is_some() || self.packed.is_some() || self.simulation.chunks().is_some() {
+            self.labeler = None;
+            self.objects_render_bind_group = None;
+            self.selected_object = None;
+            self.tracker = None;
+            return;
+        }
+        self.create_labeler();
+        let Some(labeler) = self.labeler.as_mut() else {
+            return;
+        };
+        let relabeled = labeler.radius() != self.object_radius;
+        labeler.set_radius(self.object_radius);
+        let stale = self.selected_object.as_ref().is_some_and(|object| {
+            (object.generation, object.edit_count) != (self.simulation.generation, self.simulation.edit_count())
+        });
+        if (stale || relabeled) && self.selected_object.is_some() {
+            self.selected_object = None;
+            labeler.select(&self.queue, NO_LABEL);
+        }
+        labeler.poll(&self.device);
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Component Labeling Encoder"),
+        });
+        // The tracker samples finished labelings before the labeler moves on
+        let mut sampled = false;
+        if self.show_tracker {
+            let (width, height, boundary) = (self.simulation.width, self.simulation.height, self.simulation.boundary);
+            if self.tracker.as_ref().is_some_and(|tracker| tracker.boundary != boundary) {
+                self.tracker = None;
+            }
+            let tracker = self.tracker.get_or_insert_with(|| SpaceshipTracker::new(width, height, boundary, self.simulation.rules));
+            tracker.set_rules(self.simulation.rules);
+            tracker.poll(&self.device);
+            sampled = tracker.encode_sample(&mut encoder, &self.simulation, labeler);
+        } else {
+            self.tracker = None;
+        }
+        let labeled = labeler.encode(&mut encoder, &self.simulation);
+        if sampled || labeled {
+            self.queue.submit(Some(encoder.finish()));
+        }
+        if labeled {
+            labeler.after_submit();
+        }
+        if let Some(tracker) = self.tracker.as_mut().filter(|_| sampled) {
+            tracker.after_submit();
+        }
+    }
+
+    /// Create the labeler and its render bind group on first use
+    fn create_labeler(&mut self) {
+        if self.labeler.is_none() {
+            let Ok(labeler) = ComponentLabeler::new(&self.simulation, self.object_radius)
+                .map_err(|e| log::warn!("Object labeling unavailable: {}", e))
+            else {
+                return;
+            };
+            labeler.select(&self.queue, NO_LABEL);
+            self.objects_render_bind_group = Some(self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Objects Render Bind Group"),
+                layout: &self.render_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: self.simulation.sim_param_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: labeler.labels().as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: self.render_param_buffer.as_entire_binding() },
+                ],
+            }));
+            self.labeler = Some(labeler);
+        }
+    }
 
-                // Update state
-                self.live_cell_count = Some(count as u32);
-                self.last_count_update_time = Some(Instant::now()); // Record update time
+    /// Number of objects in the current generation, once it has been labeled
+    pub fn object_count(&self) -> Option<u32> {
+        self.labeler.as_ref()?.current(&self.simulation).map(|labeling| labeling.objects)
+    }
 
-                // Drop the mapped view
-                drop(data);
-                // Unmap the buffer
-                staging_buffer.unmap();
+    /// Select the object under `screen_pos` (or clear the selection on a dead cell) and
+    /// pause, so the selection stays valid for copying, deleting or analysis
+    pub fn select_object(&mut self, screen_pos: PhysicalPosition<f64>) {
+        let (x, y) = self.screen_to_grid(screen_pos);
+        if self.universe.is_some() || self.packed.is_some() || self.simulation.chunks().is_some() {
+            self.objects_status = Some("Objects are not available in this grid mode".to_string());
+            return;
+        }
+        if !self.in_grid(x, y) {
+            return;
+        }
+        self.create_labeler();
+        let Some(labeler) = self.labeler.as_mut() else {
+            return;
+        };
+        let result = labeler.object_at(&self.simulation, x as u32, y as u32);
+        match result {
+            Ok(Some(object)) => {
+                labeler.select(&self.queue, object.label);
+                let [bx, by, width, height] = object.bounds;
+                self.objects_status = Some(format!("Selected {} cells at ({}, {}), {}x{}", object.cells.len(), bx, by, width, height));
+                self.selected_object = Some(object);
+                self.paused = true;
             }
-            Ok(Err(e)) => {
-                log::error!("Failed to map staging buffer for cell count: {:?}", e);
-                self.live_cell_count = None; // Indicate error/unknown state
+            Ok(None) => {
+                labeler.select(&self.queue, NO_LABEL);
+                self.selected_object = None;
+                self.objects_status = None;
             }
             Err(e) => {
-                 log::error!("Failed to receive cell count map result: {:?}", e);
-                 self.live_cell_count = None;
+                log::error!("Object selection failed: {}", e);
+                self.objects_status = Some(format!("Object selection failed: {}", e));
             }
         }
     }
 
+    /// Make the selected object the placement stamp (library category "Clipboard").
+    /// Returns it as RLE for the system clipboard.
+    pub fn copy_object(&mut self) -> Result<String, String> {
+        let object = self.selected_object.as_ref().ok_or("No object selected")?;
+        let mut pattern = object.pattern();
+        pattern.name = Some("Copied object".to_string());
+        pattern.rule = Some(self.simulation.rules.to_rulestring());
+        let rle = crate::library::to_rle(&pattern);
+        self.pattern_library.insert(crate::library::LibraryEntry {
+            name: "Copied object".to_string(),
+            category: "Clipboard".to_string(),
+            source: None,
+            pattern,
+        });
+        self.selected_pattern = self.pattern_library.entries.iter().position(|e| e.category == "Clipboard" && e.name == "Copied object");
+        Ok(rle)
+    }
+
+    /// Kill every cell of the selected object
+    pub fn delete_object(&mut self) -> Result<String, String> {
+        let object = self.selected_object.take().ok_or("No object selected")?;
+        let mut cells = self.simulation.read_cells()?;
+        for &(x, y) in &object.cells {
+            cells[(y * self.simulation.width + x) as usize] = 0.0;
+        }
+        self.simulation.write_cells(&cells)?;
+        if let Some(labeler) = &self.labeler {
+            labeler.select(&self.queue, NO_LABEL);
+        }
+        Ok(format!("Deleted {} cells", object.cells.len()))
+    }
+
+    /// Classify the selected object on its own, see `period::analyze_object`
+    pub fn analyze_object(&self) -> Result<String, String> {
+        let object = self.selected_object.as_ref().ok_or("No object selected")?;
+        let started = Instant::now();
+        let analysis = crate::period::analyze_object(&self.simulation, &object.cells, self.analysis_generations)?;
+        Ok(format!("{} ({:.2?})", analysis, started.elapsed()))
+    }
+
+    /// Start a new soup search with `census_settings` on a grid of its own
+    pub fn start_census(&mut self) -> Result<String, String> {
+        let search = SoupSearch::new(self.device.clone(), self.queue.clone(), self.census_settings.clone())?;
+        self.census = Some(search);
+        self.census_running = true;
+        Ok(format!("Running {} soups from seed {}", self.census_settings.soups, self.census_settings.seed))
+    }
+
+    /// Run one batch of the soup search; called every frame while it is running
+    pub fn run_census_batch(&mut self) {
+        let Some(search) = self.census.as_mut() else {
+            self.census_running = false;
+            return;
+        };
+        if let Err(e) = search.run_batch() {
+            log::error!("Soup census failed: {}", e);
+            self.census_status = Some(format!("Census failed: {}", e));
+            self.census_running = false;
+        } else if search.is_finished() {
+            log::info!("Soup census of seed {}: {}", search.settings.seed, search.census.summary());
+            self.census_status = Some(format!("Finished: {}", search.census.summary()));
+            self.census_running = false;
+        }
+    }
+
+    /// Write the census table to the working directory
+    pub fn export_census(&self) -> Result<String, String> {
+        let search = self.census.as_ref().ok_or("No census has run yet")?;
+        let path = format!("gpu_life_census_seed{}.csv", search.settings.seed);
+        search.census.write_csv(std::path::Path::new(&path))?;
+        log::info!("Exported the census of {} soups to {}", search.census.soups, path);
+        Ok(path)
+    }
+
+    /// Go back to `target`, picked on the history graph: the newest snapshot at or
+    /// before it is restored and stepped forward with the current rules and settings
+    pub fn restore_generation(&mut self, target: usize) -> Result<String, String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        if self.simulation.chunks().is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        if self.recorder.is_some() || self.stats_recorder.is_some() {
+            return Err("Stop the recording and the statistics log first".to_string());
+        }
+        if target > self.simulation.generation {
+            return Err(format!("Generation {} has not been reached yet", target));
+        }
+        let Some(snapshots) = self.snapshots.as_mut() else {
+            return Err("No snapshots taken yet".to_string());
+        };
+        snapshots.sync_edits(self.simulation.edit_count());
+        let Some((start, snapshot)) = snapshots.latest_before(target) else {
+            return Err(format!("No snapshot at or before generation {} (the grid was edited or it is too old)", target));
+        };
+        self.simulation.restore_from(start, snapshot)?;
+        snapshots.discard_after(target);
+
+        self.simulation.lucky_rule_enabled = self.lucky_rule_enabled;
+        self.simulation.lucky_chance = self.lucky_chance_percent as f32 / 100.0;
+        let mut remaining = target - start;
+        while remaining > 0 {
+            let batch = remaining.min(GPU_JUMP_BATCH as usize) as u32;
+            self.simulation.step(batch);
+            remaining -= batch as usize;
+        }
+
+        // Samples still in flight belong to the abandoned generations
+        self.stats_reducer = None;
+        self.live_stats = None;
+        self.population_history.truncate_after(target);
+        log::info!("Restored generation {} from the snapshot of generation {}", target, start);
+        Ok(format!("Restored generation {} (snapshot {} + {} steps)", target, start, target - start))
+    }
+
+    /// Classify the selection (or the whole grid) as a still life, oscillator or
+    /// spaceship, see `period::analyze`
+    pub fn analyze_pattern(&self, use_selection: bool) -> Result<String, String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        let region = match (use_selection, self.selection) {
+            (true, None) => return Err("Nothing selected".to_string()),
+            (true, selection) => selection,
+            (false, _) => None,
+        };
+        let started = Instant::now();
+        let analysis = crate::period::analyze(&self.simulation, region, self.analysis_generations)?;
+        Ok(format!("{} ({:.2?})", analysis, started.elapsed()))
+    }
+
+    /// Writes the current grid to a PNG with `export_scale` x `export_scale` pixels per cell,
+    /// using the active palette. The output does not depend on window size, zoom or the GUI.
+    /// Returns the path of the written file.
+    pub fn export_png(&self) -> Result<String, String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        let cells = self.read_grid_cells()?;
+        let path = format!("gpu_life_gen{:08}.png", self.generation());
+        crate::export::write_png(
+            std::path::Path::new(&path),
+            &cells,
+            self.simulation.width,
+            self.simulation.height,
+            self.export_scale,
+            &self.palette,
+        )?;
+        log::info!("Exported {}x{} grid at scale {} to {}", self.simulation.width, self.simulation.height, self.export_scale, path);
+        Ok(path)
+    }
+
+    /// Starts recording with `recording_settings`. Files are written to the working
+    /// directory unless a stream format has an explicit target (file or FIFO).
+    pub fn start_recording(&mut self) -> Result<(), String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        if self.simulation.chunks().is_some() {
+            return Err("Not available for a grid split into chunks".to_string());
+        }
+        if self.recorder.is_some() {
+            return Err("A recording is already running".to_string());
+        }
+        let mut settings = self.recording_settings.clone();
+        settings.palette = self.palette;
+        settings.path = if settings.format.is_stream() && !settings.stream_target.trim().is_empty() {
+            settings.stream_target.trim().into()
+        } else {
+            format!("gpu_life_rec_gen{:08}.{}", self.simulation.generation, settings.format.extension()).into()
+        };
+        self.recorder = Some(Recorder::start(&self.device, settings, self.simulation.width, self.simulation.height)?);
+        Ok(())
+    }
+
+    /// Stops the active recording, flushing pending frames. Returns a summary for the menu.
+    pub fn stop_recording(&mut self) -> Result<String, String> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(&self.device),
+            None => Err("No recording is running".to_string()),
+        }
+    }
+
+    /// Starts logging per-generation statistics with the current settings
+    pub fn start_stats(&mut self) -> Result<(), String> {
+        if self.universe.is_some() {
+            return Err("Not available on the infinite plane".to_string());
+        }
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        if self.stats_recorder.is_some() {
+            return Err("A statistics log is already running".to_string());
+        }
+        let mut settings = self.stats_settings.clone();
+        settings.path = if settings.output.trim().is_empty() {
+            format!("gpu_life_stats_gen{:08}.{}", self.simulation.generation, settings.format.extension()).into()
+        } else {
+            settings.output.trim().into()
+        };
+        self.stats_recorder = Some(StatsRecorder::start(settings)?);
+        Ok(())
+    }
+
+    /// Stops the active statistics log, flushing pending samples. Returns a summary for the menu.
+    pub fn stop_stats(&mut self) -> Result<String, String> {
+        match self.stats_recorder.take() {
+            Some(stats) => stats.finish(self.stats_reducer.as_mut(), &self.device),
+            None => Err("No statistics log is running".to_string()),
+        }
+    }
+
+    /// Advance the simulation to `target` (see `Simulation::jump`). On the infinite plane
+    /// long jumps of plain life-like rules run on HashLife, which simulates the plane
+    /// itself, and the result is loaded back into tiles (colors become white).
+    pub fn jump_to_generation(&mut self, target: usize) -> Result<String, String> {
+        if self.packed.is_some() {
+            return Err("Not available in packed mode".to_string());
+        }
+        let current = self.generation();
+        if target < current {
+            return Err(format!("Already at generation {}", current));
+        }
+        let distance = target - current;
+        if self.universe.is_some() {
+            return self.jump_universe(target, distance);
+        }
+        self.simulation.lucky_rule_enabled = self.lucky_rule_enabled;
+        self.simulation.lucky_chance = self.lucky_chance_percent as f32 / 100.0;
+
+        let started = Instant::now();
+        let jump = self.simulation.jump(distance)?;
+        log::info!("Jumped {} generations in {:.2?}: {} with HashLife, {} on the GPU",
+                   distance, started.elapsed(), jump.hashlife_generations, jump.gpu_generations);
+        Ok(match (jump.hashlife_generations, jump.gpu_generations) {
+            (0, _) => format!("Generation {} (GPU, {:.2?})", target, started.elapsed()),
+            (_, 0) => format!("Generation {} (HashLife, {:.2?})", target, started.elapsed()),
+            (_, gpu) => format!("Generation {} (HashLife, {} on the GPU, {:.2?})", target, gpu, started.elapsed()),
+        })
+    }
+
+    fn jump_universe(&mut self, target: usize, distance: usize) -> Result<String, String> {
+        let Some(universe) = self.universe.as_mut() else {
+            return Err("The infinite plane is off".to_string());
+        };
+        universe.lucky_rule_enabled = self.lucky_rule_enabled;
+        universe.lucky_chance = self.lucky_chance_percent as f32 / 100.0;
+        let started = Instant::now();
+        if distance >= HASHLIFE_MIN_JUMP && !universe.lucky_rule_enabled {
+            let live: Vec<(i64, i64)> = universe.live_cells()?.into_iter().map(|(x, y, _)| (x, y)).collect();
+            let mut life = HashLife::from_cells(universe.rules(), &live)?;
+            life.step(distance as u64);
+            universe.load_cells(target, &life.live_cells())?;
+            log::info!("Jumped the infinite plane to generation {} with HashLife in {:.2?} ({} tiles)",
+                       target, started.elapsed(), universe.tile_count());
+            return Ok(format!("Generation {} (HashLife, {:.2?})", target, started.elapsed()));
+        }
+        if distance > GPU_MAX_JUMP {
+            return Err(format!("Jumps over {} generations need HashLife, which does not support the lucky rule", GPU_MAX_JUMP));
+        }
+        let mut remaining = distance;
+        while remaining > 0 {
+            let batch = remaining.min(GPU_JUMP_BATCH as usize) as u32;
+            universe.step(batch)?;
+            remaining -= batch as usize;
+        }
+        Ok(format!("Generation {} (GPU, {:.2?})", target, started.elapsed()))
+    }
+
     pub fn paint_cell(&mut self, screen_pos: PhysicalPosition<f64>) {
         // Convert screen pos to grid coordinate under current zoom & offset
-        let x_world = ((screen_pos.x as f32) + self.view_offset[0]) / self.zoom;
-        let y_world = ((screen_pos.y as f32) + self.view_offset[1]) / self.zoom;
-
-        let gx = x_world.floor() as i32;
-        let gy = y_world.floor() as i32;
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
+        let (gx, gy) = self.screen_to_grid(screen_pos);
+        if self.universe.is_none() && !self.in_grid(gx, gy) {
             return;
         }
-        // Paint a square brush of size (2*R+1)^2
-        let radius = self.brush_radius as i32;
+        // Paint a square brush of size (2*R+1)^2, one row at a time
+        let radius = self.brush_radius as i64;
+        let row = vec![self.current_cell_color.to_value(); 2 * radius as usize + 1];
         for by in -radius..=radius {
-            for bx in -radius..=radius {
-                let cx = gx + bx;
-                let cy = gy + by;
-                if cx < 0 || cy < 0 || cx >= self.grid_width as i32 || cy >= self.grid_height as i32 {
-                    continue;
-                }
-                let idx = (cy as u32 * self.grid_width + cx as u32) as usize;
-                let val: [f32;1] = [self.current_cell_color.to_value()];
-                // Write to the *input* buffer for the *next* frame's compute pass
-                self.queue.write_buffer(&self.grid_buffers[self.frame_num % 2], idx as u64 * 4, bytemuck::bytes_of(&val));
-            }
+            // Write to the *input* buffer for the *next* frame's compute pass
+            self.write_cells(gx - radius, gy + by, &row);
+        }
+    }
+
+    fn in_grid(&self, x: i64, y: i64) -> bool {
+        (0..self.simulation.width as i64).contains(&x) && (0..self.simulation.height as i64).contains(&y)
+    }
+
+    /// Overwrite a horizontal run of cells of the current generation starting at
+    /// (x, y), clipped to the grid unless the infinite plane is enabled
+    fn write_cells(&mut self, x: i64, y: i64, values: &[f32]) {
+        if let Some(universe) = self.universe.as_mut() {
+            universe.write_row(x, y, values);
+            return;
+        }
+        if y < 0 || y >= self.simulation.height as i64 || x + values.len() as i64 <= 0 {
+            return;
+        }
+        // Runs are clipped at the left edge here and at the right edge by write_row
+        let skip = (-x).max(0) as usize;
+        let (x, y) = ((x + skip as i64) as u32, y as u32);
+        match &self.packed {
+            Some(packed) => packed.write_row(x, y, &values[skip..]),
+            None => self.simulation.write_row(x, y, &values[skip..]),
         }
     }
 
@@ -878,243 +1637,103 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
     }
 
-    /// Convert a screen position to grid coordinates
-    pub fn screen_to_grid(&self, screen_pos: PhysicalPosition<f64>) -> (i32, i32) {
+    /// Convert a screen position to grid coordinates (world coordinates on the infinite plane)
+    pub fn screen_to_grid(&self, screen_pos: PhysicalPosition<f64>) -> (i64, i64) {
         let x_world = ((screen_pos.x as f32) + self.view_offset[0]) / self.zoom;
         let y_world = ((screen_pos.y as f32) + self.view_offset[1]) / self.zoom;
 
-        (x_world.floor() as i32, y_world.floor() as i32)
+        (self.view_origin[0] + x_world.floor() as i64, self.view_origin[1] + y_world.floor() as i64)
     }
     
-    /// Place a glider at the specified screen position
-    pub fn place_glider(&mut self, screen_pos: PhysicalPosition<f64>) {
-        let (gx, gy) = self.screen_to_grid(screen_pos);
-        
-        // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
-            return;
-        }
-        
-        // Glider pattern cells relative to center
-        let glider_cells = [
-            (0, 1),
-            (1, 2),
-            (2, 0), (2, 1), (2, 2)
-        ];
-        
-        // Place the glider cells
-        for (dx, dy) in &glider_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed glider at grid position ({}, {})", gx, gy);
+    /// Screen position of the top-left corner of grid cell (x, y)
+    pub fn grid_to_screen(&self, x: i64, y: i64) -> (f32, f32) {
+        (
+            (x - self.view_origin[0]) as f32 * self.zoom - self.view_offset[0],
+            (y - self.view_origin[1]) as f32 * self.zoom - self.view_offset[1],
+        )
     }
-    
-    /// Place a lightweight spaceship at the specified screen position
-    pub fn place_lwss(&mut self, screen_pos: PhysicalPosition<f64>) {
-        let (gx, gy) = self.screen_to_grid(screen_pos);
-        
-        // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
+
+    /// Start (or, while dragging, stretch) the selection rectangle at a screen position.
+    /// The selection is clipped to the grid.
+    pub fn update_selection(&mut self, screen_pos: PhysicalPosition<f64>, extend: bool) {
+        if self.universe.is_some() {
             return;
         }
-        
-        // Lightweight spaceship pattern
-        let lwss_cells = [
-            (0, 1), (0, 3),
-            (1, 0),
-            (2, 0),
-            (3, 0), (3, 3),
-            (4, 0), (4, 1), (4, 2)
-        ];
-        
-        // Place the cells
-        for (dx, dy) in &lwss_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed lightweight spaceship at grid position ({}, {})", gx, gy);
-    }
-    
-    /// Place a pulsar at the specified screen position
-    pub fn place_pulsar(&mut self, screen_pos: PhysicalPosition<f64>) {
         let (gx, gy) = self.screen_to_grid(screen_pos);
-        
-        // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
-            return;
-        }
-        
-        // Pulsar pattern (period 3 oscillator)
-        let pulsar_cells = [
-            // Top horizontal lines
-            (2, 0), (3, 0), (4, 0), (8, 0), (9, 0), (10, 0),
-            // Top middle horizontal lines
-            (2, 5), (3, 5), (4, 5), (8, 5), (9, 5), (10, 5),
-            // Bottom middle horizontal lines
-            (2, 7), (3, 7), (4, 7), (8, 7), (9, 7), (10, 7),
-            // Bottom horizontal lines
-            (2, 12), (3, 12), (4, 12), (8, 12), (9, 12), (10, 12),
-            
-            // Left vertical lines
-            (0, 2), (0, 3), (0, 4), (0, 8), (0, 9), (0, 10),
-            // Left middle vertical lines
-            (5, 2), (5, 3), (5, 4), (5, 8), (5, 9), (5, 10),
-            // Right middle vertical lines
-            (7, 2), (7, 3), (7, 4), (7, 8), (7, 9), (7, 10),
-            // Right vertical lines
-            (12, 2), (12, 3), (12, 4), (12, 8), (12, 9), (12, 10),
-        ];
-        
-        // Place the cells
-        for (dx, dy) in &pulsar_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed pulsar at grid position ({}, {})", gx, gy);
+        let clip = |x: i64, y: i64| {
+            (x.clamp(0, self.simulation.width as i64 - 1), y.clamp(0, self.simulation.height as i64 - 1))
+        };
+        let (gx, gy) = clip(gx, gy);
+        let anchor = match self.selection_anchor {
+            Some(anchor) if extend => anchor,
+            _ => (gx, gy),
+        };
+        self.selection_anchor = Some(anchor);
+        let (x0, y0) = (anchor.0.min(gx) as u32, anchor.1.min(gy) as u32);
+        let (x1, y1) = (anchor.0.max(gx) as u32, anchor.1.max(gy) as u32);
+        self.selection = Some([x0, y0, x1 - x0 + 1, y1 - y0 + 1]);
     }
-    
-    /// Place a Gosper glider gun at the specified screen position
-    pub fn place_gosper_glider_gun(&mut self, screen_pos: PhysicalPosition<f64>) {
+
+    /// Stamp the selected library pattern with its top-left corner at the specified screen position
+    pub fn place_pattern(&mut self, screen_pos: PhysicalPosition<f64>) {
         let (gx, gy) = self.screen_to_grid(screen_pos);
-        
+
         // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
+        if self.universe.is_none() && !self.in_grid(gx, gy) {
             return;
         }
-        
-        // Gosper glider gun pattern
-        let gun_cells = [
-            // Left block
-            (1, 5), (1, 6),
-            (2, 5), (2, 6),
-
-            // Left ship
-            (11, 5), (11, 6), (11, 7),
-            (12, 4), (12, 8),
-            (13, 3), (13, 9),
-            (14, 3), (14, 9),
-            (15, 6),
-            (16, 4), (16, 8),
-            (17, 5), (17, 6), (17, 7),
-            (18, 6),
-
-            // Right ship
-            (21, 3), (21, 4), (21, 5),
-            (22, 3), (22, 4), (22, 5),
-            (23, 2), (23, 6),
-            (25, 1), (25, 2), (25, 6), (25, 7),
-
-            // Right block
-            (35, 3), (35, 4),
-            (36, 3), (36, 4)
-        ];
-        
-        // Place the cells
-        for (dx, dy) in &gun_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed Gosper glider gun at grid position ({}, {})", gx, gy);
-    }
-    
-    /// Place a pentadecathlon (period 15 oscillator) at the specified screen position
-    pub fn place_pentadecathlon(&mut self, screen_pos: PhysicalPosition<f64>) {
-        let (gx, gy) = self.screen_to_grid(screen_pos);
-        
-        // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
+        let Some(entry) = self.selected_pattern.and_then(|i| self.pattern_library.entries.get(i)) else {
             return;
+        };
+        let (name, runs) = (entry.name.clone(), entry.pattern.row_runs());
+
+        // One buffer write per horizontal run instead of one per cell
+        let value = self.current_cell_color.to_value();
+        for (dx, dy, len) in runs {
+            self.write_cells(gx + dx as i64, gy + dy as i64, &vec![value; len as usize]);
         }
-        
-        // Pentadecathlon pattern
-        let penta_cells = [
-            (1, 0), 
-            (2, 0), 
-            (3, -1), (3, 1),
-            (4, 0),
-            (5, 0),
-            (6, 0),
-            (7, 0),
-            (8, -1), (8, 1),
-            (9, 0),
-            (10, 0)
-        ];
-        
-        // Place the cells
-        for (dx, dy) in &penta_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed pentadecathlon at grid position ({}, {})", gx, gy);
+
+        log::info!("Placed {} at grid position ({}, {})", name, gx, gy);
     }
-    
-    /// Place a Simkin glider gun (smaller than Gosper) at the specified screen position
-    pub fn place_simkin_glider_gun(&mut self, screen_pos: PhysicalPosition<f64>) {
-        let (gx, gy) = self.screen_to_grid(screen_pos);
-        
-        // Skip if out of bounds
-        if gx < 0 || gy < 0 || gx >= self.grid_width as i32 || gy >= self.grid_height as i32 {
-            return;
-        }
-        
-        // Simkin glider gun pattern
-        let simkin_cells = [
-            // Left blocks
-            (0, 0), (0, 1), (1, 0), (1, 1),
-            (4, 0), (4, 1), (5, 0), (5, 1),
-            
-            // Right side pattern 
-            (10, 2), (10, 3), (11, 2), (11, 3),
-            
-            (12, 0), (13, 0), (12, 1), (13, 1),
-            
-            (14, 10), (14, 11), (15, 10), (15, 11),
-            
-            (16, 8), (16, 9), (17, 7), (18, 7),
-            (17, 11), (18, 11), (19, 9), (19, 10),
-            
-            (20, 10),
-            
-            (21, 8),
-            
-            (22, 9), (22, 10), (22, 11),
-            
-            (24, 10), (24, 9), (24, 8),
-            
-            (24, 7), (25, 7),
-            
-            (26, 8), (26, 6),
-            
-            (27, 6), (27, 10),
-            
-            (28, 9)
-        ];
-        
-        // Place the cells
-        for (dx, dy) in &simkin_cells {
-            self.set_cell_alive(gx + dx, gy + dy);
-        }
-        
-        log::info!("Placed Simkin glider gun at grid position ({}, {})", gx, gy);
+
+    /// Rescan the pattern directory from the browser's directory field, keeping the selection by name
+    pub fn reload_pattern_library(&mut self) {
+        let selected_name = self.selected_pattern
+            .and_then(|i| self.pattern_library.entries.get(i))
+            .map(|e| (e.category.clone(), e.name.clone()));
+        self.pattern_library = PatternLibrary::load(std::path::Path::new(self.pattern_dir_input.trim()));
+        self.selected_pattern = selected_name.and_then(|(category, name)| {
+            self.pattern_library.entries.iter().position(|e| e.category == category && e.name == name)
+        });
+        self.pattern_thumbnails.clear();
     }
-    
-    /// Helper function to set a cell to alive state
-    fn set_cell_alive(&mut self, x: i32, y: i32) {
-        if x < 0 || y < 0 || x >= self.grid_width as i32 || y >= self.grid_height as i32 {
-            return; // Skip out of bounds cells
+
+    /// Build preview textures for library entries that do not have one yet
+    pub fn ensure_pattern_thumbnails(&mut self) {
+        let alive = self.palette.rgba8(1.0);
+        let dead = self.palette.rgba8(0.0);
+        for entry in self.pattern_library.entries.iter().skip(self.pattern_thumbnails.len()) {
+            let (width, height, mask) = entry.pattern.thumbnail(PATTERN_THUMBNAIL_SIZE);
+            let pixels: Vec<egui::Color32> = mask.iter()
+                .map(|&on| {
+                    let [r, g, b, a] = if on { alive } else { dead };
+                    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+                })
+                .collect();
+            let image = egui::ColorImage { size: [width as usize, height as usize], pixels };
+            let texture = self.egui_ctx.load_texture(
+                format!("pattern_thumbnail_{}_{}", entry.category, entry.name),
+                image,
+                egui::TextureOptions::NEAREST,
+            );
+            self.pattern_thumbnails.push(texture);
         }
-        
-        let idx = (y as u32 * self.grid_width + x as u32) as usize;
-        let val: [f32;1] = [self.current_cell_color.to_value()];
-        // Write to the *input* buffer for the *next* frame's compute pass
-        self.queue.write_buffer(&self.grid_buffers[self.frame_num % 2], idx as u64 * 4, bytemuck::bytes_of(&val));
     }
 
     /// Clear an area around the specified screen position
     pub fn clear_area(&mut self, screen_pos: PhysicalPosition<f64>, radius: u32) {
         let (gx, gy) = self.screen_to_grid(screen_pos);
-        let radius = radius as i32;
+        let radius = radius as i64;
         
         for dy in -radius..=radius {
             for dx in -radius..=radius {
@@ -1126,13 +1745,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
                 let cx = gx + dx;
                 let cy = gy + dy;
                 
-                if cx < 0 || cy < 0 || cx >= self.grid_width as i32 || cy >= self.grid_height as i32 {
-                    continue; // Skip out of bounds cells
-                }
-                
-                let idx = (cy as u32 * self.grid_width + cx as u32) as usize;
-                let val: [f32;1] = [0.0]; // Set to dead (0.0)
-                self.queue.write_buffer(&self.grid_buffers[self.frame_num % 2], idx as u64 * 4, bytemuck::bytes_of(&val));
+                // Out of bounds cells are skipped
+                self.write_cells(cx, cy, &[0.0]); // Set to dead (0.0)
             }
         }
         
@@ -1142,10 +1756,10 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     /// Fill an area with random cells around the specified screen position
     pub fn random_fill(&mut self, screen_pos: PhysicalPosition<f64>, radius: u32, density: f32) {
         let (gx, gy) = self.screen_to_grid(screen_pos);
-        let radius = radius as i32;
+        let radius = radius as i64;
         
-        // Use frame_num as a kind of seed for randomization
-        let seed = self.frame_num as u32;
+        // Use the generation as a kind of seed for randomization
+        let seed = self.simulation.generation as u32;
         
         for dy in -radius..=radius {
             for dx in -radius..=radius {
@@ -1157,10 +1771,6 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
                 let cx = gx + dx;
                 let cy = gy + dy;
                 
-                if cx < 0 || cy < 0 || cx >= self.grid_width as i32 || cy >= self.grid_height as i32 {
-                    continue; // Skip out of bounds cells
-                }
-                
                 // Simple deterministic random function based on coordinates and seed
                 let random_val = {
                     let h1 = (cx as u32).wrapping_mul(17).wrapping_add((cy as u32).wrapping_mul(31));
@@ -1170,9 +1780,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
                 
                 // Only fill some cells based on density
                 if random_val < density {
-                    let idx = (cy as u32 * self.grid_width + cx as u32) as usize;
-                    let val: [f32;1] = [self.current_cell_color.to_value()]; // Set to alive (1.0)
-                    self.queue.write_buffer(&self.grid_buffers[self.frame_num % 2], idx as u64 * 4, bytemuck::bytes_of(&val));
+                    // Out of bounds cells are skipped
+                    self.write_cells(cx, cy, &[self.current_cell_color.to_value()]);
                 }
             }
         }
@@ -0,0 +1,506 @@
+use crate::render::Palette;
+use crate::simulation::Simulation;
+use crate::readback::{ReadbackRing, DEFAULT_RING_SLOTS};
+use bytemuck::{Pod, Zeroable};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Species slots: 0 aggregates every live cell, 1-7 follow the palette indices
+pub const SPECIES_COUNT: usize = 8;
+pub const SPECIES_NAMES: [&str; SPECIES_COUNT] = [
+    "all", "white", "lucky_red", "red", "green", "blue", "yellow", "purple",
+];
+
+const STATS_WORKGROUP_SIZE: u32 = 16;
+// Field layout of the reduction buffer, SPECIES_COUNT words each (see stats.wgsl)
+const FIELD_POPULATION: usize = 0;
+const FIELD_BIRTHS: usize = 1;
+const FIELD_DEATHS: usize = 2;
+const FIELD_MIN_X_INV: usize = 3;
+const FIELD_MIN_Y_INV: usize = 4;
+const FIELD_MAX_X: usize = 5;
+const FIELD_MAX_Y: usize = 6;
+const RESULT_WORDS: usize = 7 * SPECIES_COUNT;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct StatsParams {
+    width: u32,
+    /// Grid rows read by this dispatch
+    rows: u32,
+    /// Buffer row holding the first of them (1 in chunk buffers, below the halo row)
+    buffer_row: u32,
+    /// Grid row of the first of them
+    grid_row: u32,
+}
+
+/// Numbers for one species in one generation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpeciesStats {
+    pub population: u32,
+    /// Cells of this species that were dead in the previous generation
+    pub births: u32,
+    /// Cells of this species in the previous generation that are dead now
+    pub deaths: u32,
+    /// Inclusive bounding box (min_x, min_y, max_x, max_y); `None` when extinct
+    pub bbox: Option<[u32; 4]>,
+}
+
+/// Statistics of a single generation
+#[derive(Debug, Clone)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub width: u32,
+    pub height: u32,
+    pub species: [SpeciesStats; SPECIES_COUNT],
+}
+
+impl GenerationStats {
+    fn from_words(generation: usize, width: u32, height: u32, words: &[u32]) -> Self {
+        let field = |field: usize, species: usize| words[field * SPECIES_COUNT + species];
+        let species = std::array::from_fn(|s| {
+            let population = field(FIELD_POPULATION, s);
+            SpeciesStats {
+                population,
+                births: field(FIELD_BIRTHS, s),
+                deaths: field(FIELD_DEATHS, s),
+                bbox: (population > 0).then(|| [
+                    u32::MAX - field(FIELD_MIN_X_INV, s),
+                    u32::MAX - field(FIELD_MIN_Y_INV, s),
+                    field(FIELD_MAX_X, s),
+                    field(FIELD_MAX_Y, s),
+                ]),
+            }
+        });
+        Self { generation, width, height, species }
+    }
+
+    /// Compute the statistics on the CPU from two consecutive generations
+    /// (same semantics as stats.wgsl)
+    pub fn from_cells(generation: usize, width: u32, height: u32, previous: &[f32], current: &[f32]) -> Self {
+        let mut species = [SpeciesStats::default(); SPECIES_COUNT];
+        let mut add_live = |s: usize, x: u32, y: u32| {
+            let stats = &mut species[s];
+            stats.population += 1;
+            stats.bbox = Some(match stats.bbox {
+                Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+                None => [x, y, x, y],
+            });
+        };
+        let mut births = [0u32; SPECIES_COUNT];
+        let mut deaths = [0u32; SPECIES_COUNT];
+        for (i, (&before, &now)) in previous.iter().zip(current).enumerate() {
+            let (x, y) = ((i % width as usize) as u32, (i / width as usize) as u32);
+            let (before, now) = (Palette::index_of(before), Palette::index_of(now));
+            if now != 0 {
+                add_live(now, x, y);
+                add_live(0, x, y);
+                if before == 0 {
+                    births[now] += 1;
+                    births[0] += 1;
+                }
+            } else if before != 0 {
+                deaths[before] += 1;
+                deaths[0] += 1;
+            }
+        }
+        for s in 0..SPECIES_COUNT {
+            species[s].births = births[s];
+            species[s].deaths = deaths[s];
+        }
+        Self { generation, width, height, species }
+    }
+
+    /// Fraction of the grid occupied by a species
+    pub fn density(&self, species: usize) -> f64 {
+        self.species[species].population as f64 / (self.width as f64 * self.height as f64).max(1.0)
+    }
+
+    /// One CSV row per species
+    pub fn write_csv(&self, out: &mut impl Write) -> std::io::Result<()> {
+        for (s, stats) in self.species.iter().enumerate() {
+            let bbox = stats.bbox
+                .map(|b| format!("{},{},{},{}", b[0], b[1], b[2], b[3]))
+                .unwrap_or_else(|| ",,,".to_string());
+            writeln!(out, "{},{},{},{},{},{:.6},{}",
+                     self.generation, SPECIES_NAMES[s], stats.population, stats.births, stats.deaths,
+                     self.density(s), bbox)?;
+        }
+        Ok(())
+    }
+
+    /// One JSON object per generation, species keyed by name
+    pub fn write_json_line(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write!(out, "{{\"generation\":{},\"width\":{},\"height\":{},\"species\":{{",
+               self.generation, self.width, self.height)?;
+        for (s, stats) in self.species.iter().enumerate() {
+            let bbox = stats.bbox
+                .map(|b| format!("[{},{},{},{}]", b[0], b[1], b[2], b[3]))
+                .unwrap_or_else(|| "null".to_string());
+            write!(out, "{}\"{}\":{{\"population\":{},\"births\":{},\"deaths\":{},\"density\":{:.6},\"bbox\":{}}}",
+                   if s == 0 { "" } else { "," }, SPECIES_NAMES[s],
+                   stats.population, stats.births, stats.deaths, self.density(s), bbox)?;
+        }
+        writeln!(out, "}}}}")
+    }
+}
+
+pub const CSV_HEADER: &str = "generation,species,population,births,deaths,density,min_x,min_y,max_x,max_y";
+
+/// Output format of the statistics log
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StatsFormat {
+    #[default]
+    Csv,
+    JsonLines,
+}
+
+impl StatsFormat {
+    pub const ALL: [StatsFormat; 2] = [StatsFormat::Csv, StatsFormat::JsonLines];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatsFormat::Csv => "CSV",
+            StatsFormat::JsonLines => "JSON Lines",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatsFormat::Csv => "csv",
+            StatsFormat::JsonLines => "jsonl",
+        }
+    }
+
+    /// JSON Lines for ".jsonl"/".json" files, CSV otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()) {
+            Some(ext) if ext == "jsonl" || ext == "json" => StatsFormat::JsonLines,
+            _ => StatsFormat::Csv,
+        }
+    }
+}
+
+/// A statistics file being written, one sample at a time
+pub struct StatsLog {
+    pub format: StatsFormat,
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    error: Option<String>,
+    pub samples_written: usize,
+}
+
+impl StatsLog {
+    /// Create the file and write the CSV header if needed
+    pub fn create(path: &Path, format: StatsFormat) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        if format == StatsFormat::Csv {
+            writeln!(writer, "{}", CSV_HEADER).map_err(|e| format!("Failed to write header: {}", e))?;
+        }
+        Ok(Self { format, path: path.to_path_buf(), writer, error: None, samples_written: 0 })
+    }
+
+    /// Append a sample. After the first write error further samples are ignored
+    /// and the error is reported by `finish`.
+    pub fn write(&mut self, sample: &GenerationStats) {
+        if self.error.is_some() {
+            return;
+        }
+        let written = match self.format {
+            StatsFormat::Csv => sample.write_csv(&mut self.writer),
+            StatsFormat::JsonLines => sample.write_json_line(&mut self.writer),
+        };
+        match written {
+            Ok(()) => self.samples_written += 1,
+            Err(e) => {
+                log::error!("Failed to write statistics: {}", e);
+                self.error = Some(format!("Failed to write {}: {}", self.path.display(), e));
+            }
+        }
+    }
+
+    /// Flush the file. Returns the number of samples written.
+    pub fn finish(mut self) -> Result<usize, String> {
+        if let Err(e) = self.writer.flush() {
+            self.error.get_or_insert(format!("Failed to write {}: {}", self.path.display(), e));
+        }
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.samples_written),
+        }
+    }
+}
+
+/// Everything needed to start a statistics log
+#[derive(Debug, Clone)]
+pub struct StatsSettings {
+    pub format: StatsFormat,
+    /// Sample one generation every `every_n` generations
+    pub every_n: u32,
+    pub path: PathBuf,
+    /// Output file chosen in the menu; empty picks a file name
+    pub output: String,
+}
+
+impl Default for StatsSettings {
+    fn default() -> Self {
+        Self {
+            format: StatsFormat::default(),
+            every_n: 1,
+            path: PathBuf::new(),
+            output: String::new(),
+        }
+    }
+}
+
+/// Part of the grid read by one dispatch of the reduction: the chunk buffers of a
+/// chunked grid, or the whole grid
+struct StatsRegion<'a> {
+    buffers: &'a [wgpu::Buffer; 2],
+    params: StatsParams,
+}
+
+struct StatsSlot {
+    /// Reduction target written by the kernel
+    result: wgpu::Buffer,
+    /// Per region, bind groups for reading generation parity 0 and 1 as "current"
+    bind_groups: Vec<[wgpu::BindGroup; 2]>,
+}
+
+/// Reduces a simulation's grid to `GenerationStats` on the GPU.
+///
+/// Samples are recorded into a command encoder and read back through a ring of
+/// staging buffers, so sampling never stalls the render loop unless every slot is
+/// still in flight. Only a few hundred bytes per sample leave the GPU.
+pub struct StatsReducer {
+    pipeline: wgpu::ComputePipeline,
+    /// Kernel resources of each slot of `ring`
+    slots: Vec<StatsSlot>,
+    /// Generations whose results are read back
+    ring: ReadbackRing<usize>,
+    /// Rows of each region, in the order of `StatsSlot::bind_groups`
+    region_rows: Vec<u32>,
+    grid_width: u32,
+    grid_height: u32,
+}
+
+impl StatsReducer {
+    /// Reduction of `simulation`'s grid buffers (or chunks); create a new one when
+    /// the simulation's buffers are replaced
+    pub fn new(device: &wgpu::Device, simulation: &Simulation) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Statistics Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("stats.wgsl").into()),
+        });
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Statistics Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, true),
+                storage_entry(3, false),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Statistics Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Statistics Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "main",
+        });
+
+        let (grid_width, grid_height) = (simulation.width, simulation.height);
+        let regions: Vec<StatsRegion> = match simulation.chunks() {
+            // Chunk rows start below the halo row
+            Some(chunked) => chunked.chunks.iter().map(|chunk| StatsRegion {
+                buffers: &chunk.buffers,
+                params: StatsParams { width: grid_width, rows: chunk.rows, buffer_row: 1, grid_row: chunk.first_row },
+            }).collect(),
+            None => vec![StatsRegion {
+                buffers: &simulation.grid_buffers,
+                params: StatsParams { width: grid_width, rows: grid_height, buffer_row: 0, grid_row: 0 },
+            }],
+        };
+        let params_buffers: Vec<wgpu::Buffer> = regions.iter().map(|region| {
+            wgpu::util::DeviceExt::create_buffer_init(device, &wgpu::util::BufferInitDescriptor {
+                label: Some("Statistics Parameters"),
+                contents: bytemuck::bytes_of(&region.params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+        }).collect();
+
+        let result_size = (RESULT_WORDS * std::mem::size_of::<u32>()) as u64;
+        let slots = (0..DEFAULT_RING_SLOTS)
+            .map(|i| {
+                let result = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Statistics Result Buffer {}", i)),
+                    size: result_size,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                // Parity p reads buffers[p] as the current generation
+                let bind_groups = regions.iter().zip(&params_buffers).map(|(region, params_buffer)| {
+                    [0, 1].map(|parity| device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some(&format!("Statistics Bind Group {} ({})", i, parity)),
+                        layout: &bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 1, resource: region.buffers[1 - parity].as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 2, resource: region.buffers[parity].as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 3, resource: result.as_entire_binding() },
+                        ],
+                    }))
+                }).collect();
+                StatsSlot { result, bind_groups }
+            })
+            .collect();
+
+        Self {
+            pipeline,
+            slots,
+            ring: ReadbackRing::new(device, "Statistics Staging Buffer", result_size, DEFAULT_RING_SLOTS),
+            region_rows: regions.iter().map(|region| region.params.rows).collect(),
+            grid_width,
+            grid_height,
+        }
+    }
+
+    pub fn has_free_slot(&self) -> bool {
+        self.ring.has_free_slot()
+    }
+
+    /// Whether samples are still on their way back from the GPU
+    pub fn in_flight(&self) -> bool {
+        self.ring.is_mapping()
+    }
+
+    /// Record the reduction of `generation` (held in `buffers[generation % 2]`, with the
+    /// previous generation in the other buffer) into the encoder.
+    /// Returns false (and records nothing) if every slot is busy.
+    pub fn encode_sample(&mut self, encoder: &mut wgpu::CommandEncoder, generation: usize) -> bool {
+        let Some(index) = self.ring.free_slot() else {
+            return false;
+        };
+        let slot = &self.slots[index];
+
+        encoder.clear_buffer(&slot.result, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Statistics Compute Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            // Every region adds to the same totals
+            for (bind_groups, &rows) in slot.bind_groups.iter().zip(&self.region_rows) {
+                pass.set_bind_group(0, &bind_groups[generation % 2], &[]);
+                pass.dispatch_workgroups(
+                    self.grid_width.div_ceil(STATS_WORKGROUP_SIZE),
+                    rows.div_ceil(STATS_WORKGROUP_SIZE),
+                    1,
+                );
+            }
+        }
+        encoder.copy_buffer_to_buffer(&slot.result, 0, self.ring.staging(index), 0, slot.result.size());
+        self.ring.fill(index, generation);
+        true
+    }
+
+    /// Must be called after the encoder holding samples has been submitted
+    pub fn after_submit(&mut self) {
+        self.ring.after_submit();
+    }
+
+    /// Samples that have arrived, oldest first (non-blocking)
+    pub fn poll(&mut self, device: &wgpu::Device) -> Vec<GenerationStats> {
+        let (width, height) = (self.grid_width, self.grid_height);
+        self.ring.poll(device, |generation, data| GenerationStats::from_words(generation, width, height, bytemuck::cast_slice(data)))
+    }
+
+    /// Wait for the oldest in-flight sample so a slot becomes free; returns the
+    /// samples that arrived meanwhile
+    pub fn wait_for_slot(&mut self, device: &wgpu::Device) -> Vec<GenerationStats> {
+        let (width, height) = (self.grid_width, self.grid_height);
+        self.ring.wait_oldest(device, |generation, data| GenerationStats::from_words(generation, width, height, bytemuck::cast_slice(data)))
+    }
+}
+
+/// Logs per-generation statistics computed by a `StatsReducer`.
+///
+/// The recorder does not reduce anything itself: the caller samples the wanted
+/// generations with its reducer (the same one that refreshes the live numbers) and
+/// hands every sample that comes back to `record`.
+pub struct StatsRecorder {
+    pub settings: StatsSettings,
+    log: StatsLog,
+    /// Most recent sample logged, shown in the menu
+    pub latest: Option<GenerationStats>,
+}
+
+impl StatsRecorder {
+    pub fn start(settings: StatsSettings) -> Result<Self, String> {
+        let log = StatsLog::create(&settings.path, settings.format)?;
+        log::info!("Logging statistics every {} generations to {}", settings.every_n, settings.path.display());
+        Ok(Self { settings, log, latest: None })
+    }
+
+    /// Whether the given generation should be sampled
+    pub fn wants_generation(&self, generation: usize) -> bool {
+        generation.is_multiple_of(self.settings.every_n.max(1) as usize)
+    }
+
+    pub fn samples_written(&self) -> usize {
+        self.log.samples_written
+    }
+
+    /// Write `sample` to the log if its generation is sampled and was not just logged
+    /// (a paused grid is reduced again every frame). Returns whether it was written.
+    pub fn record(&mut self, sample: &GenerationStats) -> bool {
+        let repeated = self.latest.as_ref().is_some_and(|latest| latest.generation == sample.generation);
+        if repeated || !self.wants_generation(sample.generation) {
+            return false;
+        }
+        self.log.write(sample);
+        self.latest = Some(sample.clone());
+        true
+    }
+
+    /// Log the samples still in flight in `reducer`, then close the file.
+    /// Returns a summary for the menu.
+    pub fn finish(mut self, reducer: Option<&mut StatsReducer>, device: &wgpu::Device) -> Result<String, String> {
+        if let Some(reducer) = reducer {
+            while reducer.in_flight() {
+                for sample in &reducer.wait_for_slot(device) {
+                    self.record(sample);
+                }
+            }
+        }
+        let written = self.log.finish()?;
+        log::info!("Statistics finished: {} samples written to {}", written, self.settings.path.display());
+        Ok(format!("Saved {} samples to {}", written, self.settings.path.display()))
+    }
+}
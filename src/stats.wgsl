@@ -0,0 +1,94 @@
+// Per-generation statistics, reduced on the GPU.
+//
+// Compares the current generation with the previous one and accumulates, per
+// species (palette index 1-7, slot 0 = all live cells): population, births,
+// deaths and bounding box. Each workgroup reduces into shared memory first so
+// only one global atomic per non-zero field and workgroup is needed.
+
+const SPECIES: u32 = 8u;
+const FIELD_POPULATION: u32 = 0u;
+const FIELD_BIRTHS: u32 = 8u;
+const FIELD_DEATHS: u32 = 16u;
+// Minimums are stored inverted (0xFFFFFFFF - v) so every field reduces with
+// atomicMax/atomicAdd and the result buffer can simply be cleared to zero
+const FIELD_MIN_X_INV: u32 = 24u;
+const FIELD_MIN_Y_INV: u32 = 32u;
+const FIELD_MAX_X: u32 = 40u;
+const FIELD_MAX_Y: u32 = 48u;
+const RESULT_WORDS: u32 = 56u;
+const WORKGROUP_CELLS: u32 = 256u;
+
+// A band of rows of the grid; chunked grids are reduced one chunk per dispatch
+struct StatsParams {
+    width: u32,
+    rows: u32,
+    buffer_row: u32, // Buffer row holding the first grid row (skips a chunk's halo row)
+    grid_row: u32,   // Grid row of the first row, for the bounding box
+}
+
+@group(0) @binding(0) var<uniform> params: StatsParams;
+@group(0) @binding(1) var<storage, read> previous: array<f32>;
+@group(0) @binding(2) var<storage, read> current: array<f32>;
+@group(0) @binding(3) var<storage, read_write> totals: array<atomic<u32>, 56>;
+
+var<workgroup> local_totals: array<atomic<u32>, 56>;
+
+// Same mapping as the render palette: 0 = dead, 1-7 = species
+fn species_of(value: f32) -> u32 {
+    if (value <= 0.5) {
+        return 0u;
+    }
+    return u32(clamp(round(value), 1.0, 7.0));
+}
+
+fn add_live_cell(species: u32, x: u32, y: u32) {
+    atomicAdd(&local_totals[FIELD_POPULATION + species], 1u);
+    atomicMax(&local_totals[FIELD_MIN_X_INV + species], 0xFFFFFFFFu - x);
+    atomicMax(&local_totals[FIELD_MIN_Y_INV + species], 0xFFFFFFFFu - y);
+    atomicMax(&local_totals[FIELD_MAX_X + species], x);
+    atomicMax(&local_totals[FIELD_MAX_Y + species], y);
+}
+
+@compute @workgroup_size(16, 16)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    for (var i = local_index; i < RESULT_WORDS; i = i + WORKGROUP_CELLS) {
+        atomicStore(&local_totals[i], 0u);
+    }
+    workgroupBarrier();
+
+    let x = global_id.x;
+    let y = global_id.y + params.grid_row;
+    if (x < params.width && global_id.y < params.rows) {
+        let idx = (global_id.y + params.buffer_row) * params.width + x;
+        let now = species_of(current[idx]);
+        let before = species_of(previous[idx]);
+
+        if (now != 0u) {
+            add_live_cell(now, x, y);
+            add_live_cell(0u, x, y);
+            if (before == 0u) {
+                atomicAdd(&local_totals[FIELD_BIRTHS + now], 1u);
+                atomicAdd(&local_totals[FIELD_BIRTHS], 1u);
+            }
+        } else if (before != 0u) {
+            atomicAdd(&local_totals[FIELD_DEATHS + before], 1u);
+            atomicAdd(&local_totals[FIELD_DEATHS], 1u);
+        }
+    }
+    workgroupBarrier();
+
+    // Flush the workgroup totals, skipping fields nothing contributed to
+    for (var i = local_index; i < RESULT_WORDS; i = i + WORKGROUP_CELLS) {
+        let value = atomicLoad(&local_totals[i]);
+        if (value != 0u) {
+            if (i < FIELD_MIN_X_INV) {
+                atomicAdd(&totals[i], value);
+            } else {
+                atomicMax(&totals[i], value);
+            }
+        }
+    }
+}
@@ -0,0 +1,803 @@
+//! Unbounded universe: a sparse plane of fixed-size tiles stepped on the GPU.
+//!
+//! Only tiles holding live cells, plus a one-tile halo around them, are allocated.
+//! Their cells live in a pool of `TILE_SIZE` x `TILE_SIZE` slots on the GPU, and a
+//! CPU-side map gives the slot of each tile. After every batch of steps the
+//! population of each tile is reduced on the GPU and read back: halos are grown
+//! around tiles with live cells and tiles that emptied out are returned to the pool.
+//!
+//! World coordinates are 64-bit. Tile (tx, ty) covers the cells
+//! `tx * TILE_SIZE..(tx + 1) * TILE_SIZE` by `ty * TILE_SIZE..(ty + 1) * TILE_SIZE`.
+
+use crate::compute::ShaderGameRules;
+use crate::render::RenderParams;
+use crate::rules::GameRules;
+use bytemuck::{Pod, Zeroable};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Cells along each side of a tile (`TILE` in tiled.wgsl and tiled_render.wgsl)
+pub const TILE_SIZE: u32 = 256;
+
+/// Generations per batch between tile updates. Activity spreads at most one cell
+/// per generation, so it cannot cross the one-tile halo within a batch.
+pub const MAX_BATCH: u32 = TILE_SIZE / 2;
+
+/// Slots in a new pool; the pool doubles when it runs out
+const INITIAL_CAPACITY: usize = 16;
+
+/// Marks a missing tile in the neighbor and view tables
+const NO_TILE: u32 = u32::MAX;
+
+const WORKGROUP_SIZE: u32 = 8;
+const TILE_CELLS: u64 = TILE_SIZE as u64 * TILE_SIZE as u64;
+const TILE_BYTES: u64 = TILE_CELLS * std::mem::size_of::<f32>() as u64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct TiledParams {
+    lucky_chance: f32,
+    seed: u32,
+    enable_lucky_rule: u32,
+    _padding: u32,
+}
+
+/// Per-slot entry of the neighbor table (matches `TileInfo` in tiled.wgsl)
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct TileInfo {
+    neighbors: [u32; 9], // Slots of the 3x3 block around the tile, row-major
+    tile_x: i32,         // Low 32 bits of the tile coordinates (lucky hash seed)
+    tile_y: i32,
+    _padding: u32,
+}
+
+/// Visible window into the tile map (matches `TileView` in tiled_render.wgsl)
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct TileView {
+    origin: [i32; 2], // Cell under screen pixel (0, 0) relative to the first visible tile
+    tiles: [u32; 2],  // Visible tiles along x and y
+}
+
+pub struct TiledUniverse {
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+
+    pub lucky_rule_enabled: bool,
+    /// Chance (0-1) for a dying cell to survive as a lucky cell
+    pub lucky_chance: f32,
+    pub generation: usize,
+    rules: GameRules,
+
+    tiles: HashMap<(i64, i64), u32>,
+    slots: Vec<Option<(i64, i64)>>, // Tile held by each slot; the length is the pool capacity
+    free_slots: Vec<u32>,
+    tile_populations: Vec<u32>,     // Per slot, as of the last tile update
+    population: u64,
+    pool_full: bool,
+    tables_dirty: bool,
+
+    cell_buffers: [wgpu::Buffer; 2],
+    tile_info_buffer: wgpu::Buffer,
+    active_buffer: wgpu::Buffer,
+    population_buffer: wgpu::Buffer,
+    population_staging: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    rules_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    step_pipeline: wgpu::ComputePipeline,
+    count_pipeline: wgpu::ComputePipeline,
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+/// Buffers whose size follows the pool capacity
+struct PoolBuffers {
+    cells: [wgpu::Buffer; 2],
+    tile_info: wgpu::Buffer,
+    active: wgpu::Buffer,
+    population: wgpu::Buffer,
+    population_staging: wgpu::Buffer,
+}
+
+impl TiledUniverse {
+    /// Create an empty plane. Rules that give birth on 0 neighbors fill the whole
+    /// plane and are rejected.
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, rules: GameRules) -> Result<Self, String> {
+        check_rules(&rules)?;
+        let pool = create_pool_buffers(&device, INITIAL_CAPACITY);
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tiled Parameters"),
+            size: std::mem::size_of::<TiledParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let rules_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tiled Game Rules"),
+            size: std::mem::size_of::<ShaderGameRules>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tiled Compute Bind Group Layout"),
+            entries: &[
+                layout_entry(0, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(2, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: false }),
+                layout_entry(3, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Uniform),
+                layout_entry(4, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(5, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(6, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tiled Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tiled Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("tiled.wgsl").into()),
+        });
+        let [step_pipeline, count_pipeline] = ["step", "count"].map(|entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(&format!("Tiled {} Pipeline", entry_point)),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+            })
+        });
+        let bind_groups = create_bind_groups(&device, &bind_group_layout, &pool, &params_buffer, &rules_buffer);
+
+        let universe = Self {
+            device,
+            queue,
+            lucky_rule_enabled: false,
+            lucky_chance: 0.1,
+            generation: 0,
+            rules,
+            tiles: HashMap::new(),
+            slots: vec![None; INITIAL_CAPACITY],
+            free_slots: (0..INITIAL_CAPACITY as u32).rev().collect(),
+            tile_populations: vec![0; INITIAL_CAPACITY],
+            population: 0,
+            pool_full: false,
+            tables_dirty: true,
+            cell_buffers: pool.cells,
+            tile_info_buffer: pool.tile_info,
+            active_buffer: pool.active,
+            population_buffer: pool.population,
+            population_staging: pool.population_staging,
+            params_buffer,
+            rules_buffer,
+            bind_group_layout,
+            step_pipeline,
+            count_pipeline,
+            bind_groups,
+        };
+        universe.queue.write_buffer(&universe.rules_buffer, 0, bytemuck::bytes_of(&ShaderGameRules::from(&rules)));
+        Ok(universe)
+    }
+
+    pub fn rules(&self) -> GameRules {
+        self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: GameRules) -> Result<(), String> {
+        check_rules(&rules)?;
+        self.rules = rules;
+        self.queue.write_buffer(&self.rules_buffer, 0, bytemuck::bytes_of(&ShaderGameRules::from(&rules)));
+        Ok(())
+    }
+
+    /// Live cells as of the last tile update (after `step` or `update_tiles`)
+    pub fn population(&self) -> u64 {
+        self.population
+    }
+
+    /// Allocated tiles, including halos
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Slots in the GPU tile pool
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// True if the last tile update needed more tiles than the device can hold.
+    /// Cells that would have been born in the missing tiles are lost.
+    pub fn pool_full(&self) -> bool {
+        self.pool_full
+    }
+
+    /// The pool buffer holding the current generation
+    pub fn current_buffer(&self) -> &wgpu::Buffer {
+        &self.cell_buffers[self.generation % 2]
+    }
+
+    /// Pool slot of a tile, if allocated
+    fn slot_at(&self, tile: (i64, i64)) -> Option<u32> {
+        self.tiles.get(&tile).copied()
+    }
+
+    /// Overwrite a horizontal run of cells starting at world cell (x, y).
+    /// Tiles receiving live cells are allocated with their halo; runs of dead
+    /// cells over unallocated tiles are skipped.
+    pub fn write_row(&mut self, x: i64, y: i64, values: &[f32]) {
+        let tile_size = TILE_SIZE as i64;
+        let tile_y = y.div_euclid(tile_size);
+        let local_y = y.rem_euclid(tile_size) as u64;
+        let mut start = 0;
+        while start < values.len() {
+            let cell_x = x + start as i64;
+            let tile_x = cell_x.div_euclid(tile_size);
+            let local_x = cell_x.rem_euclid(tile_size) as u64;
+            let len = (values.len() - start).min((TILE_SIZE as u64 - local_x) as usize);
+            let run = &values[start..start + len];
+            start += len;
+
+            let slot = match self.slot_at((tile_x, tile_y)) {
+                Some(slot) => slot,
+                None if run.iter().any(|&value| value > 0.5) => {
+                    self.allocate_with_halo(&[(tile_x, tile_y)]);
+                    match self.slot_at((tile_x, tile_y)) {
+                        Some(slot) => slot,
+                        None => continue, // Pool full
+                    }
+                }
+                None => continue,
+            };
+            let offset = (slot as u64 * TILE_CELLS + local_y * TILE_SIZE as u64 + local_x) * 4;
+            self.queue.write_buffer(self.current_buffer(), offset, bytemuck::cast_slice(run));
+        }
+    }
+
+    /// Copy a row-major `width` x `height` grid onto the plane with its top-left
+    /// corner at world cell (x, y)
+    pub fn write_grid(&mut self, x: i64, y: i64, cells: &[f32], width: u32, height: u32) -> Result<(), String> {
+        if cells.len() != width as usize * height as usize {
+            return Err(format!("Expected {} cells, got {}", width as usize * height as usize, cells.len()));
+        }
+        for (row_index, row) in cells.chunks(width as usize).enumerate() {
+            self.write_row(x, y + row_index as i64, row);
+        }
+        Ok(())
+    }
+
+    /// Replace every cell with the live (white) `cells`, given in world coordinates and
+    /// sorted by (y, x), as generation `generation`. Fails without changing anything if
+    /// their tiles and halos need more slots than the device holds.
+    pub fn load_cells(&mut self, generation: usize, cells: &[(i64, i64)]) -> Result<(), String> {
+        let tile_size = TILE_SIZE as i64;
+        let mut needed = HashSet::new();
+        for &(x, y) in cells {
+            let (tile_x, tile_y) = (x.div_euclid(tile_size), y.div_euclid(tile_size));
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    needed.insert((tile_x + dx, tile_y + dy));
+                }
+            }
+        }
+        if needed.len() > self.max_capacity() {
+            return Err(format!("The cells need {} tiles, the device holds {}", needed.len(), self.max_capacity()));
+        }
+        self.clear();
+        self.generation = generation;
+        // Horizontal runs of live cells are written at once
+        let mut start = 0;
+        while start < cells.len() {
+            let (x, y) = cells[start];
+            let mut end = start + 1;
+            while end < cells.len() && cells[end] == (x + (end - start) as i64, y) {
+                end += 1;
+            }
+            self.write_row(x, y, &vec![1.0; end - start]);
+            start = end;
+        }
+        self.update_tiles()
+    }
+
+    /// Kill every cell and return all tiles to the pool
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.free_slots = (0..self.capacity() as u32).rev().collect();
+        self.tile_populations.iter_mut().for_each(|population| *population = 0);
+        self.population = 0;
+        self.pool_full = false;
+        self.tables_dirty = true;
+    }
+
+    /// Advance `count` generations, in batches of at most `MAX_BATCH` with a tile
+    /// update (blocking population readback) after each batch
+    pub fn step(&mut self, count: u32) -> Result<(), String> {
+        let mut remaining = count;
+        while remaining > 0 {
+            let batch = remaining.min(MAX_BATCH);
+            self.upload_tables();
+            self.queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&TiledParams {
+                lucky_chance: self.lucky_chance,
+                seed: self.generation as u32,
+                enable_lucky_rule: if self.lucky_rule_enabled { 1 } else { 0 },
+                _padding: 0,
+            }));
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Tiled Step Encoder"),
+            });
+            for _ in 0..batch {
+                if !self.tiles.is_empty() {
+                    let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: Some("Tiled Step Pass"),
+                        timestamp_writes: None,
+                    });
+                    pass.set_pipeline(&self.step_pipeline);
+                    pass.set_bind_group(0, &self.bind_groups[self.generation % 2], &[]);
+                    pass.dispatch_workgroups(TILE_SIZE / WORKGROUP_SIZE, TILE_SIZE / WORKGROUP_SIZE, self.tiles.len() as u32);
+                }
+                self.generation += 1;
+            }
+            self.encode_count(&mut encoder);
+            self.queue.submit(Some(encoder.finish()));
+            self.finish_tile_update()?;
+            remaining -= batch;
+        }
+        Ok(())
+    }
+
+    /// Recount the population of every tile and reallocate halos, e.g. after
+    /// cells were written. Blocks until the GPU is done.
+    pub fn update_tiles(&mut self) -> Result<(), String> {
+        self.upload_tables();
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Tiled Count Encoder"),
+        });
+        self.encode_count(&mut encoder);
+        self.queue.submit(Some(encoder.finish()));
+        self.finish_tile_update()
+    }
+
+    /// Read the current generation inside a `width` x `height` rectangle with its
+    /// top-left corner at world cell (x, y). Blocks until the GPU is done.
+    pub fn read_region(&self, x: i64, y: i64, width: u32, height: u32) -> Result<Vec<f32>, String> {
+        let pool = self.read_pool()?;
+        let mut cells = vec![0.0; width as usize * height as usize];
+        let tile_size = TILE_SIZE as i64;
+        for (&(tile_x, tile_y), &slot) in &self.tiles {
+            let (left, top) = (tile_x * tile_size, tile_y * tile_size);
+            let x0 = left.max(x);
+            let x1 = (left + tile_size).min(x + width as i64);
+            let y0 = top.max(y);
+            let y1 = (top + tile_size).min(y + height as i64);
+            for cell_y in y0..y1 {
+                for cell_x in x0..x1 {
+                    let source = slot as u64 * TILE_CELLS + ((cell_y - top) * tile_size + cell_x - left) as u64;
+                    cells[((cell_y - y) * width as i64 + cell_x - x) as usize] = pool[source as usize];
+                }
+            }
+        }
+        Ok(cells)
+    }
+
+    /// World coordinates and values of every live cell, sorted by (y, x).
+    /// Blocks until the GPU is done.
+    pub fn live_cells(&self) -> Result<Vec<(i64, i64, f32)>, String> {
+        let pool = self.read_pool()?;
+        let tile_size = TILE_SIZE as i64;
+        let mut live = Vec::new();
+        for (&(tile_x, tile_y), &slot) in &self.tiles {
+            let start = slot as usize * TILE_CELLS as usize;
+            for (i, &value) in pool[start..start + TILE_CELLS as usize].iter().enumerate() {
+                if value > 0.5 {
+                    let (local_x, local_y) = (i as i64 % tile_size, i as i64 / tile_size);
+                    live.push((tile_x * tile_size + local_x, tile_y * tile_size + local_y, value));
+                }
+            }
+        }
+        live.sort_by_key(|&(x, y, _)| (y, x));
+        Ok(live)
+    }
+
+    /// Clear the per-slot counters and reduce the current generation into them
+    fn encode_count(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.clear_buffer(&self.population_buffer, 0, None);
+        if !self.tiles.is_empty() {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Tiled Count Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.count_pipeline);
+            pass.set_bind_group(0, &self.bind_groups[self.generation % 2], &[]);
+            pass.dispatch_workgroups(TILE_SIZE / WORKGROUP_SIZE, TILE_SIZE / WORKGROUP_SIZE, self.tiles.len() as u32);
+        }
+        encoder.copy_buffer_to_buffer(
+            &self.population_buffer, 0, &self.population_staging, 0, self.population_staging.size(),
+        );
+    }
+
+    /// Read the counts written by `encode_count`, then free empty tiles and grow
+    /// halos around tiles with live cells
+    fn finish_tile_update(&mut self) -> Result<(), String> {
+        let counts = map_read(&self.device, &self.population_staging)?;
+        self.tile_populations = bytemuck::cast_slice::<u8, u32>(&counts).to_vec();
+        self.population = self.tiles.values().map(|&slot| self.tile_populations[slot as usize] as u64).sum();
+
+        let live: Vec<(i64, i64)> = self.tiles.iter()
+            .filter(|(_, &slot)| self.tile_populations[slot as usize] > 0)
+            .map(|(&tile, _)| tile)
+            .collect();
+        let mut needed = HashSet::with_capacity(live.len() * 9);
+        for &(tile_x, tile_y) in &live {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    needed.insert((tile_x + dx, tile_y + dy));
+                }
+            }
+        }
+
+        let unneeded: Vec<(i64, i64)> = self.tiles.keys().filter(|tile| !needed.contains(tile)).copied().collect();
+        for tile in unneeded {
+            if let Some(slot) = self.tiles.remove(&tile) {
+                self.slots[slot as usize] = None;
+                self.free_slots.push(slot);
+                self.tables_dirty = true;
+            }
+        }
+        self.pool_full = false;
+        self.allocate_with_halo(&live);
+        Ok(())
+    }
+
+    /// Allocate every missing tile among `tiles` and their 8 neighbors, growing the
+    /// pool if needed. New slots are cleared in both generations.
+    fn allocate_with_halo(&mut self, tiles: &[(i64, i64)]) {
+        let mut missing = Vec::new();
+        for &(tile_x, tile_y) in tiles {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let tile = (tile_x + dx, tile_y + dy);
+                    if !self.tiles.contains_key(&tile) && !missing.contains(&tile) {
+                        missing.push(tile);
+                    }
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+        if missing.len() > self.free_slots.len() {
+            self.grow(self.tiles.len() + missing.len());
+        }
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Tile Clear Encoder"),
+        });
+        for tile in missing {
+            let Some(slot) = self.free_slots.pop() else {
+                if !self.pool_full {
+                    log::warn!("Tile pool is full ({} tiles), activity at the edge is lost", self.capacity());
+                }
+                self.pool_full = true;
+                break;
+            };
+            self.tiles.insert(tile, slot);
+            self.slots[slot as usize] = Some(tile);
+            for buffer in &self.cell_buffers {
+                encoder.clear_buffer(buffer, slot as u64 * TILE_BYTES, Some(TILE_BYTES));
+            }
+        }
+        // Submitted right away so the clears land before any later write to the slots
+        self.queue.submit(Some(encoder.finish()));
+        self.tables_dirty = true;
+    }
+
+    /// Largest pool the device can bind
+    fn max_capacity(&self) -> usize {
+        let limits = self.device.limits();
+        let bytes = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+        ((bytes / TILE_BYTES) as usize).min(limits.max_compute_workgroups_per_dimension as usize)
+    }
+
+    /// Grow the pool to hold at least `needed` tiles (as far as the device allows),
+    /// keeping the cells of every allocated slot
+    fn grow(&mut self, needed: usize) {
+        let old_capacity = self.capacity();
+        let new_capacity = needed.max(old_capacity * 2).min(self.max_capacity());
+        if new_capacity <= old_capacity {
+            return;
+        }
+        let pool = create_pool_buffers(&self.device, new_capacity);
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Tile Pool Grow Encoder"),
+        });
+        for (old, new) in self.cell_buffers.iter().zip(&pool.cells) {
+            encoder.copy_buffer_to_buffer(old, 0, new, 0, old.size());
+        }
+        self.queue.submit(Some(encoder.finish()));
+
+        self.bind_groups = create_bind_groups(
+            &self.device, &self.bind_group_layout, &pool, &self.params_buffer, &self.rules_buffer,
+        );
+        self.cell_buffers = pool.cells;
+        self.tile_info_buffer = pool.tile_info;
+        self.active_buffer = pool.active;
+        self.population_buffer = pool.population;
+        self.population_staging = pool.population_staging;
+        self.slots.resize(new_capacity, None);
+        self.tile_populations.resize(new_capacity, 0);
+        self.free_slots.splice(0..0, (old_capacity as u32..new_capacity as u32).rev());
+        self.tables_dirty = true;
+        log::info!("Grew tile pool from {} to {} tiles", old_capacity, new_capacity);
+    }
+
+    /// Upload the neighbor table and the list of active slots if tiles changed
+    fn upload_tables(&mut self) {
+        if !self.tables_dirty {
+            return;
+        }
+        let mut infos = vec![TileInfo::zeroed(); self.capacity()];
+        let mut active = Vec::with_capacity(self.tiles.len());
+        for (&(tile_x, tile_y), &slot) in &self.tiles {
+            let info = &mut infos[slot as usize];
+            for (i, neighbor) in info.neighbors.iter_mut().enumerate() {
+                let tile = (tile_x + i as i64 % 3 - 1, tile_y + i as i64 / 3 - 1);
+                *neighbor = self.slot_at(tile).unwrap_or(NO_TILE);
+            }
+            info.tile_x = tile_x as i32;
+            info.tile_y = tile_y as i32;
+            active.push(slot);
+        }
+        active.sort_unstable();
+        self.queue.write_buffer(&self.tile_info_buffer, 0, bytemuck::cast_slice(&infos));
+        if !active.is_empty() {
+            self.queue.write_buffer(&self.active_buffer, 0, bytemuck::cast_slice(&active));
+        }
+        self.tables_dirty = false;
+    }
+
+    /// Copy the whole current pool buffer to the CPU
+    fn read_pool(&self) -> Result<Vec<f32>, String> {
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tile Pool Readback Buffer"),
+            size: self.current_buffer().size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Tile Pool Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(self.current_buffer(), 0, &staging, 0, staging.size());
+        self.queue.submit(Some(encoder.finish()));
+        let bytes = map_read(&self.device, &staging)?;
+        Ok(bytemuck::cast_slice::<u8, f32>(&bytes).to_vec())
+    }
+}
+
+/// Draws the visible tiles of a `TiledUniverse`, with the same zoom, offset and
+/// palette semantics as the grid renderer
+pub struct TiledRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_param_buffer: wgpu::Buffer,
+    view_buffer: wgpu::Buffer,
+    view_slots_buffer: wgpu::Buffer,
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+impl TiledRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tiled Render Bind Group Layout"),
+            entries: &[
+                layout_entry(0, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(2, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Uniform),
+                layout_entry(3, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Storage { read_only: true }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tiled Render Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tiled Render Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../tiled_render.wgsl").into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tiled Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let render_param_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tiled Render Parameters"),
+            size: std::mem::size_of::<RenderParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let view_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tile View"),
+            size: std::mem::size_of::<TileView>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let view_slots_buffer = create_view_slots_buffer(device, 64);
+        Self { pipeline, bind_group_layout, render_param_buffer, view_buffer, view_slots_buffer, bind_group: None }
+    }
+
+    pub fn write_render_params(&self, queue: &wgpu::Queue, params: &RenderParams) {
+        queue.write_buffer(&self.render_param_buffer, 0, bytemuck::bytes_of(params));
+    }
+
+    /// Upload the table of tiles visible in a `screen_size` view whose pixel (0, 0)
+    /// shows world cell `origin + (view_offset / zoom)`
+    pub fn prepare(
+        &mut self,
+        universe: &TiledUniverse,
+        origin: [i64; 2],
+        view_offset: [f32; 2],
+        zoom: f32,
+        screen_size: (u32, u32),
+    ) {
+        let tile_size = TILE_SIZE as i64;
+        let screen = [screen_size.0 as f32, screen_size.1 as f32];
+        let mut first = [0i64; 2];
+        let mut count = [0u32; 2];
+        let mut view_origin = [0i32; 2];
+        for axis in 0..2 {
+            let min = origin[axis] + (view_offset[axis] / zoom).floor() as i64;
+            let max = origin[axis] + ((view_offset[axis] + screen[axis]) / zoom).ceil() as i64;
+            first[axis] = min.div_euclid(tile_size);
+            count[axis] = (max.div_euclid(tile_size) - first[axis] + 1) as u32;
+            view_origin[axis] = (origin[axis] - first[axis] * tile_size) as i32;
+        }
+
+        let mut slots = Vec::with_capacity(count[0] as usize * count[1] as usize);
+        for ty in 0..count[1] as i64 {
+            for tx in 0..count[0] as i64 {
+                slots.push(universe.slot_at((first[0] + tx, first[1] + ty)).unwrap_or(NO_TILE));
+            }
+        }
+        let slots_bytes = (slots.len() * std::mem::size_of::<u32>()) as u64;
+        if slots_bytes > self.view_slots_buffer.size() {
+            self.view_slots_buffer = create_view_slots_buffer(&universe.device, slots.len().next_power_of_two());
+        }
+        universe.queue.write_buffer(&self.view_slots_buffer, 0, bytemuck::cast_slice(&slots));
+        universe.queue.write_buffer(&self.view_buffer, 0, bytemuck::bytes_of(&TileView { origin: view_origin, tiles: count }));
+
+        // The pool buffers are replaced when the pool grows, so bind them per frame
+        self.bind_group = Some(universe.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tiled Render Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.render_param_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: universe.current_buffer().as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: self.view_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: self.view_slots_buffer.as_entire_binding() },
+            ],
+        }));
+    }
+
+    /// Draw the tiles uploaded by the last `prepare`
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if let Some(bind_group) = &self.bind_group {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw(0..3, 0..1); // Full-screen triangle
+        }
+    }
+}
+
+fn check_rules(rules: &GameRules) -> Result<(), String> {
+    if rules.births_on(0) {
+        return Err(format!("{} gives birth on empty space, which fills the infinite plane", rules.to_rulestring()));
+    }
+    Ok(())
+}
+
+fn layout_entry(binding: u32, visibility: wgpu::ShaderStages, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    }
+}
+
+fn create_pool_buffers(device: &wgpu::Device, capacity: usize) -> PoolBuffers {
+    let storage = |label: &str, size: u64, usage: wgpu::BufferUsages| {
+        device.create_buffer(&wgpu::BufferDescriptor { label: Some(label), size, usage, mapped_at_creation: false })
+    };
+    let slots = capacity as u64;
+    PoolBuffers {
+        cells: [0, 1].map(|i| storage(
+            &format!("Tile Pool {}", i),
+            slots * TILE_BYTES,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        )),
+        tile_info: storage(
+            "Tile Neighbor Table",
+            slots * std::mem::size_of::<TileInfo>() as u64,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        ),
+        active: storage("Active Tiles", slots * 4, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST),
+        population: storage(
+            "Tile Populations",
+            slots * 4,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        ),
+        population_staging: storage(
+            "Tile Population Staging",
+            slots * 4,
+            wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        ),
+    }
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    pool: &PoolBuffers,
+    params_buffer: &wgpu::Buffer,
+    rules_buffer: &wgpu::Buffer,
+) -> [wgpu::BindGroup; 2] {
+    [0, 1].map(|i| device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(&format!("Tiled Compute Bind Group {}", i)),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: pool.cells[i].as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 2, resource: pool.cells[1 - i].as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 3, resource: rules_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 4, resource: pool.tile_info.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 5, resource: pool.active.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 6, resource: pool.population.as_entire_binding() },
+        ],
+    }))
+}
+
+fn create_view_slots_buffer(device: &wgpu::Device, entries: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Visible Tile Slots"),
+        size: (entries.max(1) * std::mem::size_of::<u32>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Map a MAP_READ buffer and copy its contents out.
+/// WARNING: This is a blocking operation and will stall the GPU pipeline!
+fn map_read(device: &wgpu::Device, buffer: &wgpu::Buffer) -> Result<Vec<u8>, String> {
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    match receiver.recv() {
+        Ok(Ok(())) => {
+            let bytes = slice.get_mapped_range().to_vec();
+            buffer.unmap();
+            Ok(bytes)
+        }
+        Ok(Err(e)) => Err(format!("Failed to map buffer: {:?}", e)),
+        Err(e) => Err(format!("Failed to receive map result: {:?}", e)),
+    }
+}
@@ -0,0 +1,166 @@
+// Life-like rules on a sparse plane of fixed-size tiles.
+//
+// Same rule semantics as rules/conway_classic.wgsl (birth/survival masks, lucky
+// rule, color-majority births), but cells live in a pool of TILE x TILE tiles.
+// One workgroup layer (z) per active tile; neighbors across tile edges are read
+// through the tile's neighbor table, and missing tiles count as dead.
+
+const TILE: u32 = 256u; // Must match tiled::TILE_SIZE
+const NO_TILE: u32 = 0xFFFFFFFFu;
+
+struct TiledParams {
+    lucky_chance: f32,
+    seed: u32,
+    enable_lucky_rule: u32,
+    _pad0: u32,
+}
+
+struct GameRules {
+    birth_mask: u32,
+    survival_mask: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+struct TileInfo {
+    // Slots of the 3x3 block of tiles around this one (row-major, 4 = self)
+    neighbors: array<u32, 9>,
+    // Tile coordinates (low 32 bits), used to seed the lucky hash
+    tile_x: i32,
+    tile_y: i32,
+    _pad0: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: TiledParams;
+@group(0) @binding(1) var<storage, read> cells_in: array<f32>;
+@group(0) @binding(2) var<storage, read_write> cells_out: array<f32>;
+@group(0) @binding(3) var<uniform> game_rules: GameRules;
+@group(0) @binding(4) var<storage, read> tiles: array<TileInfo>;
+@group(0) @binding(5) var<storage, read> active_slots: array<u32>;
+@group(0) @binding(6) var<storage, read_write> populations: array<atomic<u32>>;
+
+fn hash(value: u32) -> f32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    let result = (word >> 22u) ^ word;
+    return f32(result) / 4294967295.0;
+}
+
+fn is_color(value: f32, base_color: f32) -> bool {
+    return abs(value - base_color) < 0.1;
+}
+
+// Cell at (x, y) relative to the tile in `slot`; x and y may be one tile outside
+fn cell_at(slot: u32, x: i32, y: i32) -> f32 {
+    let size = i32(TILE);
+    let tx = select(select(1u, 2u, x >= size), 0u, x < 0);
+    let ty = select(select(1u, 2u, y >= size), 0u, y < 0);
+    let neighbor = tiles[slot].neighbors[ty * 3u + tx];
+    if (neighbor == NO_TILE) {
+        return 0.0;
+    }
+    let lx = u32((x + size) % size);
+    let ly = u32((y + size) % size);
+    return cells_in[neighbor * TILE * TILE + ly * TILE + lx];
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn step(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    let slot = active_slots[workgroup_id.z];
+    let x = global_id.x;
+    let y = global_id.y;
+    let idx = slot * TILE * TILE + y * TILE + x;
+    let cell = cells_in[idx];
+
+    // One pass collects the count and the colors of the living neighbors
+    var neighbors = 0u;
+    var color_counts = array<u32, 6>(0u, 0u, 0u, 0u, 0u, 0u);
+    for (var dy: i32 = -1; dy <= 1; dy = dy + 1) {
+        for (var dx: i32 = -1; dx <= 1; dx = dx + 1) {
+            if (dx == 0 && dy == 0) {
+                continue;
+            }
+            let value = cell_at(slot, i32(x) + dx, i32(y) + dy);
+            if (value > 0.5) {
+                neighbors = neighbors + 1u;
+                if (is_color(value, 1.0)) {
+                    color_counts[0] += 1u;
+                } else if (is_color(value, 2.0) || is_color(value, 3.0)) {
+                    color_counts[1] += 1u;
+                } else if (is_color(value, 4.0)) {
+                    color_counts[2] += 1u;
+                } else if (is_color(value, 5.0)) {
+                    color_counts[3] += 1u;
+                } else if (is_color(value, 6.0)) {
+                    color_counts[4] += 1u;
+                } else if (is_color(value, 7.0)) {
+                    color_counts[5] += 1u;
+                }
+            }
+        }
+    }
+
+    if (cell > 0.5) {
+        if (((game_rules.survival_mask >> neighbors) & 1u) == 1u) {
+            cells_out[idx] = cell;
+            return;
+        }
+        // The plane has no width, so the hash is seeded from wrapped world coordinates
+        let world_x = u32(tiles[slot].tile_x) * TILE + x;
+        let world_y = u32(tiles[slot].tile_y) * TILE + y;
+        let random_value = hash(world_x + world_y * 65521u + params.seed);
+        if (params.enable_lucky_rule == 1u && random_value < params.lucky_chance) {
+            if (is_color(cell, 1.0) || is_color(cell, 2.0) || is_color(cell, 3.0)) {
+                cells_out[idx] = 3.0;
+            } else {
+                cells_out[idx] = cell;
+            }
+        } else {
+            cells_out[idx] = 0.0;
+        }
+    } else if (((game_rules.birth_mask >> neighbors) & 1u) == 1u) {
+        var max_count = 0u;
+        var dominant = 0u;
+        for (var i: u32 = 0u; i < 6u; i = i + 1u) {
+            if (color_counts[i] > max_count) {
+                max_count = color_counts[i];
+                dominant = i;
+            }
+        }
+        var colors = array<f32, 6>(1.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+        cells_out[idx] = colors[dominant];
+    } else {
+        cells_out[idx] = 0.0;
+    }
+}
+
+var<workgroup> block_population: atomic<u32>;
+
+// Live cells per tile of the current generation (`cells_in`), reduced per 8x8 block
+@compute @workgroup_size(8, 8, 1)
+fn count(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    if (local_index == 0u) {
+        atomicStore(&block_population, 0u);
+    }
+    workgroupBarrier();
+
+    let slot = active_slots[workgroup_id.z];
+    if (cells_in[slot * TILE * TILE + global_id.y * TILE + global_id.x] > 0.5) {
+        atomicAdd(&block_population, 1u);
+    }
+    workgroupBarrier();
+
+    if (local_index == 0u) {
+        let total = atomicLoad(&block_population);
+        if (total != 0u) {
+            atomicAdd(&populations[slot], total);
+        }
+    }
+}
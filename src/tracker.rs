@@ -0,0 +1,345 @@
+//! Spaceship tracker: follows moving objects from sample to sample, for the
+//! velocity arrows and trails of the GUI overlay and to count gliders leaving guns.
+//!
+//! Samples are the object labels of `components::ComponentLabeler`, copied to a
+//! staging buffer whenever a labeling finishes and read back asynchronously. Each
+//! object is run on its own on the CPU once per distinct shape
+//! (`census::object_motion`), which gives its period and displacement; a moving
+//! object continues the track whose predicted position it is closest to.
+
+use crate::census::{object_motion, Motion};
+use crate::components::{ComponentLabeler, NO_LABEL};
+use crate::readback::ReadbackRing;
+use crate::rules::{Boundary, GameRules};
+use crate::simulation::Simulation;
+use std::collections::{HashMap, VecDeque};
+
+/// Longest period (in generations) of the spaceships that are recognized
+pub const MAX_PERIOD: usize = 16;
+/// Larger objects are never spaceships here (guns, ash clusters)
+const MAX_OBJECT_CELLS: usize = 64;
+/// Objects with cells this close (Chebyshev distance) are taken as one
+const MERGE_DISTANCE: i32 = 2;
+/// A track continues with an object at most this far from where it was expected
+const MATCH_DISTANCE: f32 = 4.0;
+/// Tracks not seen for this many generations (and three samples) are gone
+const LOST_AFTER: usize = 32;
+/// Positions kept for the trail of a track
+pub const TRAIL_LENGTH: usize = 64;
+/// Shapes remembered before the cache starts over
+const SHAPE_CACHE_SIZE: usize = 16384;
+pub const GLIDER_CODE: &str = "xq4_153";
+
+/// Common name of a spaceship apgcode, else the code itself
+pub fn spaceship_name(code: &str) -> &str {
+    match code {
+        GLIDER_CODE => "glider",
+        "xq4_6frc" => "LWSS",
+        "xq4_27dee6" => "MWSS",
+        "xq4_27deee6" => "HWSS",
+        _ => code,
+    }
+}
+
+/// One spaceship followed across samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: u64,
+    pub motion: Motion,
+    /// Generation and center of the bounding box, oldest first
+    pub trail: VecDeque<(usize, (f32, f32))>,
+    /// Generation the track started at
+    pub first_seen: usize,
+}
+
+impl Track {
+    /// Cells per generation
+    pub fn velocity(&self) -> (f32, f32) {
+        (self.motion.dx as f32 / self.motion.period as f32, self.motion.dy as f32 / self.motion.period as f32)
+    }
+
+    pub fn last_seen(&self) -> usize {
+        self.trail.back().map_or(self.first_seen, |&(generation, _)| generation)
+    }
+
+    pub fn position(&self) -> (f32, f32) {
+        self.trail.back().map_or((0.0, 0.0), |&(_, position)| position)
+    }
+
+    /// Where the track should be at `generation`
+    fn predict(&self, generation: usize) -> (f32, f32) {
+        let (x, y) = self.position();
+        let (vx, vy) = self.velocity();
+        let elapsed = generation.saturating_sub(self.last_seen()) as f32;
+        (x + vx * elapsed, y + vy * elapsed)
+    }
+}
+
+/// Tracks of the moving objects of a grid
+pub struct SpaceshipTracker {
+    pub width: u32,
+    pub height: u32,
+    pub boundary: Boundary,
+    rules: GameRules,
+    shapes: HashMap<Vec<(i32, i32)>, Option<Motion>>,
+    pub tracks: Vec<Track>,
+    next_id: u64,
+    /// Generations of the samples pushed so far, the latest last
+    samples: Vec<usize>,
+    /// Generations at which gliders appeared after the first sample, e.g. leaving a gun
+    pub glider_emissions: Vec<usize>,
+    /// Tracks that ended: collisions, or the edge of a dead-bounded grid
+    pub lost: usize,
+    /// Labels being read back, tagged with their generation; created with the first sample
+    readback: Option<ReadbackRing<usize>>,
+}
+
+impl SpaceshipTracker {
+    pub fn new(width: u32, height: u32, boundary: Boundary, rules: GameRules) -> Self {
+        Self {
+            width,
+            height,
+            boundary,
+            rules,
+            shapes: HashMap::new(),
+            tracks: Vec::new(),
+            next_id: 0,
+            samples: Vec::new(),
+            glider_emissions: Vec::new(),
+            lost: 0,
+            readback: None,
+        }
+    }
+
+    /// Start over (tracks, counts and shapes) under other rules
+    pub fn set_rules(&mut self, rules: GameRules) {
+        if rules != self.rules {
+            *self = Self::new(self.width, self.height, self.boundary, rules);
+        }
+    }
+
+    /// Generation of the latest sample
+    pub fn latest_generation(&self) -> Option<usize> {
+        self.samples.last().copied()
+    }
+
+    /// Average generations between glider emissions, once there were two
+    pub fn emission_interval(&self) -> Option<f32> {
+        match (self.glider_emissions.first(), self.glider_emissions.last()) {
+            (Some(first), Some(last)) if self.glider_emissions.len() > 1 => {
+                Some((last - first) as f32 / (self.glider_emissions.len() - 1) as f32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy the labels of `labeler`'s latest finished labeling for a sample, unless it
+    /// was sampled already or a sample is still being read back. Must be recorded
+    /// before the labeler starts on the next generation.
+    pub fn encode_sample(&mut self, encoder: &mut wgpu::CommandEncoder, simulation: &Simulation, labeler: &ComponentLabeler) -> bool {
+        let Some(labeling) = labeler.latest else {
+            return false;
+        };
+        let busy = self.readback.as_ref().is_some_and(|readback| !readback.has_free_slot());
+        if busy || self.latest_generation() == Some(labeling.generation) {
+            return false;
+        }
+        let size = self.width as u64 * self.height as u64 * std::mem::size_of::<u32>() as u64;
+        let readback = self.readback.get_or_insert_with(|| ReadbackRing::new(&simulation.device, "Spaceship Tracker Staging", size, 1));
+        encoder.copy_buffer_to_buffer(labeler.labels(), 0, readback.staging(0), 0, size);
+        readback.fill(0, labeling.generation);
+        true
+    }
+
+    /// Must be called after the encoder holding a sample has been submitted
+    pub fn after_submit(&mut self) {
+        if let Some(readback) = &mut self.readback {
+            readback.after_submit();
+        }
+    }
+
+    /// Process a sample once it has been read back (non-blocking); returns whether one was
+    pub fn poll(&mut self, device: &wgpu::Device) -> bool {
+        let Some(mut readback) = self.readback.take() else {
+            return false;
+        };
+        let samples = readback.poll(device, |generation, data| (generation, self.objects_from_labels(bytemuck::cast_slice(data))));
+        self.readback = Some(readback);
+        for (generation, objects) in &samples {
+            self.push(*generation, objects);
+        }
+        !samples.is_empty()
+    }
+
+    /// Cells of each labeled object. On a torus, objects across an edge are unwrapped
+    /// next to their first cell.
+    pub fn objects_from_labels(&self, labels: &[u32]) -> Vec<Vec<(i32, i32)>> {
+        let mut objects: HashMap<u32, Vec<(i32, i32)>> = HashMap::new();
+        for (index, &label) in labels.iter().enumerate() {
+            if label != NO_LABEL {
+                let cell = ((index as u32 % self.width) as i32, (index as u32 / self.width) as i32);
+                objects.entry(label).or_default().push(cell);
+            }
+        }
+        let mut objects: Vec<(u32, Vec<(i32, i32)>)> = objects.into_iter().collect();
+        objects.sort_unstable_by_key(|(label, _)| *label);
+        objects.into_iter().map(|(_, mut cells)| {
+            self.unwrap(&mut cells);
+            cells
+        }).collect()
+    }
+
+    /// Move the cells of an object across an edge of a torus next to its first cell
+    fn unwrap(&self, cells: &mut [(i32, i32)]) {
+        if self.boundary == Boundary::Torus && !cells.is_empty() {
+            let (x0, y0) = cells[0];
+            for cell in cells.iter_mut() {
+                *cell = (x0 + self.wrap(cell.0 - x0, self.width), y0 + self.wrap(cell.1 - y0, self.height));
+            }
+        }
+    }
+
+    /// Small objects within `MERGE_DISTANCE` of each other joined into one: some phases
+    /// of spaceships (e.g. the LWSS) are not 8-connected
+    fn merge_nearby(&self, objects: &[Vec<(i32, i32)>]) -> Vec<Vec<(i32, i32)>> {
+        let small: Vec<&Vec<(i32, i32)>> = objects.iter().filter(|cells| cells.len() <= MAX_OBJECT_CELLS).collect();
+        let fold = |(x, y): (i32, i32)| match self.boundary {
+            Boundary::Torus => (x.rem_euclid(self.width as i32), y.rem_euclid(self.height as i32)),
+            Boundary::Dead => (x, y),
+        };
+        let mut owner: HashMap<(i32, i32), usize> = HashMap::new();
+        for (index, cells) in small.iter().enumerate() {
+            for &cell in cells.iter() {
+                owner.insert(fold(cell), index);
+            }
+        }
+        // Union-find over the small objects
+        let mut parent: Vec<usize> = (0..small.len()).collect();
+        fn root(parent: &mut [usize], mut index: usize) -> usize {
+            while parent[index] != index {
+                parent[index] = parent[parent[index]];
+                index = parent[index];
+            }
+            index
+        }
+        // Cells next to a large object make the small one large too
+        let mut large = vec![false; small.len()];
+        let large_cells: std::collections::HashSet<(i32, i32)> = objects.iter()
+            .filter(|cells| cells.len() > MAX_OBJECT_CELLS)
+            .flatten()
+            .map(|&cell| fold(cell))
+            .collect();
+        for (index, cells) in small.iter().enumerate() {
+            for &(x, y) in cells.iter() {
+                for dy in -MERGE_DISTANCE..=MERGE_DISTANCE {
+                    for dx in -MERGE_DISTANCE..=MERGE_DISTANCE {
+                        let neighbor = fold((x + dx, y + dy));
+                        if let Some(&other) = owner.get(&neighbor) {
+                            let (a, b) = (root(&mut parent, index), root(&mut parent, other));
+                            parent[a] = b;
+                        }
+                        if large_cells.contains(&neighbor) {
+                            large[index] = true;
+                        }
+                    }
+                }
+            }
+        }
+        let mut groups: HashMap<usize, (bool, Vec<(i32, i32)>)> = HashMap::new();
+        for (index, cells) in small.iter().enumerate() {
+            let group = groups.entry(root(&mut parent, index)).or_default();
+            group.0 |= large[index];
+            group.1.extend(cells.iter().copied());
+        }
+        let mut merged: Vec<(usize, Vec<(i32, i32)>)> = groups.into_iter()
+            .filter(|(_, (large, _))| !large)
+            .map(|(root, (_, mut cells))| {
+                self.unwrap(&mut cells);
+                (root, cells)
+            })
+            .collect();
+        merged.sort_unstable_by_key(|(root, _)| *root);
+        merged.into_iter().map(|(_, cells)| cells).collect()
+    }
+
+    /// Offset folded into [-length/2, length/2) on a torus
+    fn wrap(&self, offset: i32, length: u32) -> i32 {
+        match self.boundary {
+            Boundary::Torus => (offset + length as i32 / 2).rem_euclid(length as i32) - length as i32 / 2,
+            Boundary::Dead => offset,
+        }
+    }
+
+    fn distance(&self, a: (f32, f32), b: (f32, f32)) -> f32 {
+        let fold = |d: f32, length: u32| match self.boundary {
+            Boundary::Torus => (d + length as f32 / 2.0).rem_euclid(length as f32) - length as f32 / 2.0,
+            Boundary::Dead => d,
+        };
+        fold(a.0 - b.0, self.width).hypot(fold(a.1 - b.1, self.height))
+    }
+
+    /// Match the moving objects of `generation` to the tracks
+    pub fn push(&mut self, generation: usize, objects: &[Vec<(i32, i32)>]) {
+        if self.latest_generation().is_some_and(|latest| generation <= latest) {
+            // The grid went back (history, edits): start over
+            *self = Self::new(self.width, self.height, self.boundary, self.rules);
+        }
+        if self.shapes.len() > SHAPE_CACHE_SIZE {
+            self.shapes.clear();
+        }
+        let first_sample = self.samples.is_empty();
+        let mut unmatched: Vec<usize> = (0..self.tracks.len()).collect();
+        for cells in self.merge_nearby(objects).iter().filter(|cells| cells.len() <= MAX_OBJECT_CELLS) {
+            let (min_x, min_y) = cells.iter().fold((i32::MAX, i32::MAX), |(x, y), c| (x.min(c.0), y.min(c.1)));
+            let mut shape: Vec<(i32, i32)> = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+            shape.sort_unstable_by_key(|&(x, y)| (y, x));
+            let rules = self.rules;
+            let motion = self.shapes.entry(shape)
+                .or_insert_with_key(|shape| object_motion(shape, rules, MAX_PERIOD).ok().flatten())
+                .clone();
+            let Some(motion) = motion.filter(Motion::is_moving) else {
+                continue;
+            };
+            let max_x = cells.iter().map(|c| c.0).max().unwrap_or(min_x);
+            let max_y = cells.iter().map(|c| c.1).max().unwrap_or(min_y);
+            let center = ((min_x + max_x) as f32 / 2.0 + 0.5, (min_y + max_y) as f32 / 2.0 + 0.5);
+            let center = (center.0.rem_euclid(self.width as f32), center.1.rem_euclid(self.height as f32));
+
+            let closest = unmatched.iter().enumerate()
+                .filter(|(_, &track)| self.tracks[track].motion == motion)
+                .map(|(slot, &track)| (slot, self.distance(self.tracks[track].predict(generation), center)))
+                .filter(|&(_, distance)| distance <= MATCH_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match closest {
+                Some((slot, _)) => {
+                    let track = &mut self.tracks[unmatched.swap_remove(slot)];
+                    track.trail.push_back((generation, center));
+                    if track.trail.len() > TRAIL_LENGTH {
+                        track.trail.pop_front();
+                    }
+                }
+                None => {
+                    if !first_sample && motion.code == GLIDER_CODE {
+                        self.glider_emissions.push(generation);
+                    }
+                    self.tracks.push(Track {
+                        id: self.next_id,
+                        motion,
+                        trail: VecDeque::from([(generation, center)]),
+                        first_seen: generation,
+                    });
+                    self.next_id += 1;
+                }
+            }
+        }
+        self.samples.push(generation);
+        // Tracks missing for a while (and at least two samples) are gone
+        let since = self.samples.iter().rev().nth(2).copied().unwrap_or(0).min(generation.saturating_sub(LOST_AFTER));
+        let before = self.tracks.len();
+        self.tracks.retain(|track| track.last_seen() >= since);
+        self.lost += before - self.tracks.len();
+        if self.samples.len() > 3 {
+            self.samples.drain(..self.samples.len() - 3);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Soup generation, object naming (apgcodes) and the GPU soup search.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+use gpu_life::census::{apgcode, components, separate_objects, soup, Census, CensusSettings, SoupSearch, SOUP_SIZE};
+use gpu_life::library::builtin_pattern;
+use gpu_life::rules::GameRules;
+use gpu_life::simulation::request_headless_device;
+
+fn code(cells: &[(i32, i32)]) -> String {
+    apgcode(cells, GameRules::default(), 60).unwrap()
+}
+
+#[test]
+fn objects_get_their_apgcodes() {
+    let named = |name: &str| code(&builtin_pattern(name).cells);
+    assert_eq!(named("Block"), "xs4_33");
+    assert_eq!(named("Blinker"), "xp2_7");
+    assert_eq!(named("Glider"), "xq4_153");
+    assert_eq!(named("Lightweight spaceship"), "xq4_6frc");
+    assert_eq!(named("Pulsar"), "xp3_co9nas0san9oczgoldlo0oldlogz1047210127401");
+
+    let beehive = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
+    assert_eq!(code(&beehive), "xs6_696");
+    let loaf = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (3, 2), (2, 3)];
+    assert_eq!(code(&loaf), "xs7_2596");
+    let toad = [(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)];
+    assert_eq!(code(&toad), "xp2_7e");
+    // Wherever it is and however it is turned
+    let moved: Vec<_> = beehive.iter().map(|&(x, y)| (y + 40, x - 7)).collect();
+    assert_eq!(code(&moved), "xs6_696");
+}
+
+#[test]
+fn ash_is_separated_into_objects() {
+    let mut cells = vec![(0, 0), (1, 0), (0, 1), (1, 1)];
+    cells.extend([(10, 5), (11, 5), (12, 5)]);
+    assert_eq!(components(&cells).len(), 2);
+    let mut objects = separate_objects(&cells, 20, 20, GameRules::default(), 2, 60).unwrap();
+    objects.sort();
+    assert_eq!(objects, vec!["xp2_7", "xs4_33"]);
+
+    let mut census = Census::default();
+    census.add_soup(3, &objects);
+    census.add_soup(1, &["xs4_33".to_string()]);
+    census.add_unstable_soup();
+    assert_eq!(census.to_csv(), "apgcode,count,first_soup\nxs4_33,2,1\nxp2_7,1,3\n");
+    assert_eq!(census.summary(), "3 soups (1 unstable), 3 objects of 2 kinds");
+}
+
+#[test]
+fn soups_are_reproducible() {
+    let first = soup(7, 3);
+    assert_eq!(first, soup(7, 3));
+    assert_ne!(first, soup(7, 4));
+    assert_ne!(first, soup(8, 3));
+    assert!(first.iter().all(|&(x, y)| (0..SOUP_SIZE as i32).contains(&x) && (0..SOUP_SIZE as i32).contains(&y)));
+    assert!((64..192).contains(&first.len()));
+}
+
+#[test]
+fn census_does_not_depend_on_the_tiling() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let settings = CensusSettings {
+        seed: 42,
+        soups: 6,
+        width: 128,
+        height: 128,
+        tile_size: 64,
+        max_generations: 4000,
+        ..CensusSettings::default()
+    };
+    let mut search = SoupSearch::new(device.clone(), queue.clone(), settings.clone()).unwrap();
+    let census = search.run().unwrap().clone();
+    assert_eq!(census.soups, 6);
+    assert!(census.objects() > 0);
+    assert!(census.counts.keys().all(|code| code.contains('_')), "{:?}", census.counts);
+
+    // Two tiles instead of four: soups run in other tiles and at other times
+    let mut narrow = SoupSearch::new(device, queue, CensusSettings { height: 64, ..settings }).unwrap();
+    assert_eq!(narrow.run().unwrap(), &census);
+}
@@ -0,0 +1,37 @@
+//! Fixtures shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use gpu_life::library::Pattern;
+
+/// Deterministic `width` x `height` soup with roughly 35% live cells, white or,
+/// if `colored`, of a random color (1-7)
+pub fn soup(seed: u32, width: u32, height: u32, colored: bool) -> Vec<f32> {
+    let mut state = seed.wrapping_mul(2654435761).wrapping_add(1);
+    (0..width * height)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            match state % 100 {
+                0..=34 if colored => (state / 100 % 7 + 1) as f32,
+                0..=34 => 1.0,
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Empty `width` x `height` grid with `cells` alive
+pub fn grid_of(cells: &[(u32, u32)], width: u32, height: u32) -> Vec<f32> {
+    let mut grid = vec![0.0; width as usize * height as usize];
+    for &(x, y) in cells {
+        grid[y as usize * width as usize + x as usize] = 1.0;
+    }
+    grid
+}
+
+/// `pattern` with its top-left corner at (x, y) on an empty `width` x `height` grid
+pub fn grid_with(pattern: &Pattern, width: u32, height: u32, x: i32, y: i32) -> Vec<f32> {
+    let cells: Vec<(u32, u32)> = pattern.cells.iter().map(|&(px, py)| ((x + px) as u32, (y + py) as u32)).collect();
+    grid_of(&cells, width, height)
+}
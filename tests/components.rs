@@ -0,0 +1,102 @@
+//! Connected-component labeling of the grid and the objects it finds.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::grid_of;
+use gpu_life::census::{components, soup};
+use gpu_life::components::{ComponentLabeler, MAX_RADIUS};
+use gpu_life::period::{analyze_object, Classification};
+use gpu_life::rules::Boundary;
+use gpu_life::simulation::{request_headless_device, Simulation};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+#[test]
+fn labeling_counts_the_objects_of_the_grid() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut simulation = Simulation::new(device, queue, WIDTH, HEIGHT).unwrap();
+    simulation.boundary = Boundary::Dead;
+    // A soup, away from the edges, left to fall apart into several objects
+    let cells: Vec<(u32, u32)> = soup(3, 0).iter().map(|&(x, y)| (x as u32 + 24, y as u32 + 16)).collect();
+    simulation.load_generation(0, &grid_of(&cells, WIDTH, HEIGHT)).unwrap();
+    let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    for _ in 0..30 {
+        simulation.encode_step(&mut encoder);
+    }
+    simulation.queue.submit(Some(encoder.finish()));
+
+    let mut labeler = ComponentLabeler::new(&simulation, 1).unwrap();
+    let labeling = labeler.label(&simulation).unwrap();
+    let live: Vec<(i32, i32)> = simulation.read_cells().unwrap().iter().enumerate()
+        .filter(|&(_, &value)| value > 0.5)
+        .map(|(index, _)| ((index as u32 % WIDTH) as i32, (index as u32 / WIDTH) as i32))
+        .collect();
+    let expected = components(&live);
+    assert!(expected.len() > 1);
+    assert_eq!(labeling.objects as usize, expected.len());
+    assert_eq!(labeling.generation, 30);
+
+    // Each object holds exactly the cells of one component
+    let (x, y) = expected[0][0];
+    let object = labeler.object_at(&simulation, x as u32, y as u32).unwrap().unwrap();
+    let mut cells: Vec<(i32, i32)> = object.cells.iter().map(|&(x, y)| (x as i32, y as i32)).collect();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    assert_eq!(cells, expected[0]);
+    assert_eq!(labeler.object_at(&simulation, 0, 0).unwrap(), None);
+
+    // A larger radius joins nearby objects
+    labeler.set_radius(MAX_RADIUS);
+    assert!(labeler.label(&simulation).unwrap().objects < labeling.objects);
+}
+
+#[test]
+fn objects_wrap_around_a_torus() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // A block split by the corner, a long diagonal line and a lone cell
+    let mut cells = vec![(0, 0), (WIDTH - 1, 0), (0, HEIGHT - 1), (WIDTH - 1, HEIGHT - 1), (20, 20)];
+    cells.extend((0..40).map(|i| (10 + i, 5 + i)));
+    let mut simulation = Simulation::new(device, queue, WIDTH, HEIGHT).unwrap();
+    simulation.load_generation(0, &grid_of(&cells, WIDTH, HEIGHT)).unwrap();
+
+    let mut labeler = ComponentLabeler::new(&simulation, 1).unwrap();
+    assert_eq!(labeler.label(&simulation).unwrap().objects, 3);
+    let corner = labeler.object_at(&simulation, WIDTH - 1, HEIGHT - 1).unwrap().unwrap();
+    assert_eq!(corner.cells.len(), 4);
+    let line = labeler.object_at(&simulation, 49, 44).unwrap().unwrap();
+    assert_eq!((line.cells.len(), line.bounds), (40, [10, 5, 40, 40]));
+    assert_eq!(line.pattern().width, 40);
+
+    simulation.boundary = Boundary::Dead;
+    simulation.write_cells(&grid_of(&cells, WIDTH, HEIGHT)).unwrap();
+    assert_eq!(labeler.label(&simulation).unwrap().objects, 6);
+}
+
+#[test]
+fn an_object_is_analyzed_without_its_neighbors() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // A blinker next to a glider
+    let glider = [(11, 10), (12, 11), (10, 12), (11, 12), (12, 12)];
+    let mut cells = glider.to_vec();
+    cells.extend([(16, 10), (16, 11), (16, 12)]);
+    let mut simulation = Simulation::new(device, queue, WIDTH, HEIGHT).unwrap();
+    simulation.load_generation(0, &grid_of(&cells, WIDTH, HEIGHT)).unwrap();
+
+    let mut labeler = ComponentLabeler::new(&simulation, 1).unwrap();
+    let blinker = labeler.object_at(&simulation, 16, 11).unwrap().unwrap();
+    let analysis = analyze_object(&simulation, &blinker.cells, 100).unwrap();
+    assert_eq!(analysis.classification, Classification::Oscillator { period: 2 });
+    let glider = labeler.object_at(&simulation, 10, 12).unwrap().unwrap();
+    let analysis = analyze_object(&simulation, &glider.cells, 100).unwrap();
+    assert_eq!(analysis.classification, Classification::Spaceship { period: 4, dx: 1, dy: 1 });
+}
@@ -0,0 +1,304 @@
+//! Cross-validation of the WGSL rule kernel against the CPU reference engine.
+//!
+//! Both engines run the same soup for a number of generations and the grids are
+//! diffed after every step. Tests are skipped when no wgpu adapter (hardware or
+//! software) is available.
+
+mod common;
+
+use common::soup;
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::{request_headless_device, Kernel, Simulation};
+
+const WIDTH: u32 = 77;
+const HEIGHT: u32 = 53;
+const GENERATIONS: u32 = 60;
+
+struct Case {
+    rules: GameRules,
+    boundary: Boundary,
+    lucky_chance: Option<f32>,
+    /// Live cells get a random color (1-7) instead of white
+    colored: bool,
+    kernel: Kernel,
+    /// Generations per GPU submission; more than 1 only without the lucky rule, whose
+    /// seed the GPU reuses for a whole batch
+    batch: u32,
+    /// Split the GPU grid into buffers of this many rows
+    chunk_rows: Option<u32>,
+}
+
+impl Default for Case {
+    fn default() -> Self {
+        Self { rules: GameRules::default(), boundary: Boundary::Torus, lucky_chance: None, colored: false, kernel: Kernel::Global, batch: 1, chunk_rows: None }
+    }
+}
+
+/// Run `case` on both engines, returning a description of the first divergence
+fn cross_validate(case: &Case, seed: u32) -> Result<(), String> {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return Ok(());
+    };
+    let mut gpu = Simulation::with_chunk_rows(device, queue, WIDTH, HEIGHT, case.chunk_rows)?;
+    let mut cpu = CpuSimulation::new(WIDTH, HEIGHT)?;
+    gpu.set_kernel(case.kernel)?;
+
+    gpu.set_rules(case.rules);
+    cpu.rules = case.rules;
+    gpu.boundary = case.boundary;
+    cpu.boundary = case.boundary;
+    if let Some(chance) = case.lucky_chance {
+        gpu.lucky_rule_enabled = true;
+        gpu.lucky_chance = chance;
+        cpu.lucky_rule_enabled = true;
+        cpu.lucky_chance = chance;
+    }
+
+    let cells = soup(seed, WIDTH, HEIGHT, case.colored);
+    gpu.write_cells(&cells)?;
+    cpu.write_cells(&cells)?;
+
+    for generation in (case.batch..=GENERATIONS).step_by(case.batch as usize) {
+        // One generation per submission so both engines seed the lucky hash identically
+        gpu.step(case.batch);
+        cpu.step(case.batch);
+        let gpu_cells = gpu.read_cells()?;
+        let mismatches: Vec<String> = gpu_cells.iter().zip(cpu.cells()).enumerate()
+            .filter(|(_, (g, c))| g != c)
+            .take(5)
+            .map(|(i, (g, c))| format!("({}, {}): gpu {} cpu {}", i as u32 % WIDTH, i as u32 / WIDTH, g, c))
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(format!("generation {} differs at {}", generation, mismatches.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn conway_torus() {
+    cross_validate(&Case::default(), 1).unwrap();
+}
+
+#[test]
+fn conway_dead_boundary() {
+    cross_validate(&Case { boundary: Boundary::Dead, ..Default::default() }, 2).unwrap();
+}
+
+#[test]
+fn other_rules() {
+    for (seed, rules) in [GameRules::high_life(), GameRules::day_and_night(), GameRules::parse("B2/S").unwrap()]
+        .into_iter()
+        .enumerate()
+    {
+        cross_validate(&Case { rules, ..Default::default() }, 10 + seed as u32).unwrap();
+    }
+}
+
+#[test]
+fn color_majority_births() {
+    cross_validate(&Case { colored: true, ..Default::default() }, 3).unwrap();
+}
+
+#[test]
+fn lucky_rule() {
+    cross_validate(&Case { lucky_chance: Some(0.3), colored: true, ..Default::default() }, 4).unwrap();
+    cross_validate(&Case { lucky_chance: Some(0.1), boundary: Boundary::Dead, ..Default::default() }, 5).unwrap();
+}
+
+#[test]
+fn shared_memory_kernel() {
+    // Sizes that do not divide the grid, so partial workgroups sit on both edges
+    for (seed, workgroup_size) in [4, 8, 16].into_iter().enumerate() {
+        let kernel = Kernel::Shared { workgroup_size };
+        let seed = 20 + seed as u32;
+        cross_validate(&Case { kernel, lucky_chance: Some(0.3), colored: true, ..Default::default() }, seed).unwrap();
+        cross_validate(&Case { kernel, boundary: Boundary::Dead, colored: true, ..Default::default() }, seed).unwrap();
+    }
+    cross_validate(&Case { kernel: Kernel::Shared { workgroup_size: 8 }, rules: GameRules::parse("B0/S8").unwrap(), ..Default::default() }, 30).unwrap();
+}
+
+#[test]
+fn temporally_blocked_kernel() {
+    // Batches of 7 mix blocked dispatches with single-generation leftovers
+    for (seed, (workgroup_size, generations)) in [(8, 1), (16, 3), (32, 5)].into_iter().enumerate() {
+        let kernel = Kernel::Blocked { workgroup_size, generations };
+        let seed = 40 + seed as u32;
+        cross_validate(&Case { kernel, batch: 7, colored: true, ..Default::default() }, seed).unwrap();
+        cross_validate(&Case { kernel, batch: 7, boundary: Boundary::Dead, colored: true, ..Default::default() }, seed).unwrap();
+        cross_validate(&Case { kernel, lucky_chance: Some(0.3), ..Default::default() }, seed).unwrap();
+    }
+    // Dead cells outside a bounded grid must not be born inside the halo
+    let kernel = Kernel::Blocked { workgroup_size: 16, generations: 5 };
+    let rules = GameRules::parse("B0/S8").unwrap();
+    cross_validate(&Case { kernel, rules, batch: 10, boundary: Boundary::Dead, ..Default::default() }, 50).unwrap();
+    cross_validate(&Case { kernel, rules, batch: 10, ..Default::default() }, 51).unwrap();
+}
+
+#[test]
+fn blocked_lucky_batches_match_global_kernel() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let cells = soup(60, WIDTH, HEIGHT, true);
+    let mut results = Vec::new();
+    for kernel in [Kernel::Global, Kernel::Blocked { workgroup_size: 16, generations: 3 }] {
+        let mut gpu = Simulation::new(device.clone(), queue.clone(), WIDTH, HEIGHT).unwrap();
+        gpu.set_kernel(kernel).unwrap();
+        gpu.lucky_rule_enabled = true;
+        gpu.lucky_chance = 0.3;
+        gpu.write_cells(&cells).unwrap();
+        for batch in [10, 1, 6, 3] {
+            gpu.step(batch);
+        }
+        assert_eq!(gpu.generation, 20);
+        results.push(gpu.read_cells().unwrap());
+    }
+    assert!(results[0] == results[1], "blocked kernel diverged from the global kernel");
+}
+
+#[test]
+fn active_tile_kernel() {
+    for (seed, workgroup_size) in [8, 16].into_iter().enumerate() {
+        let kernel = Kernel::Active { workgroup_size };
+        let seed = 70 + seed as u32;
+        cross_validate(&Case { kernel, colored: true, batch: 4, ..Default::default() }, seed).unwrap();
+        cross_validate(&Case { kernel, boundary: Boundary::Dead, colored: true, ..Default::default() }, seed).unwrap();
+        // Falls back to stepping every tile
+        cross_validate(&Case { kernel, lucky_chance: Some(0.3), ..Default::default() }, seed).unwrap();
+    }
+    let kernel = Kernel::Active { workgroup_size: 8 };
+    for rules in ["B36/S23", "B0/S8", "B2/S"] {
+        let rules = GameRules::parse(rules).unwrap();
+        cross_validate(&Case { kernel, rules, ..Default::default() }, 80).unwrap();
+        cross_validate(&Case { kernel, rules, boundary: Boundary::Dead, ..Default::default() }, 81).unwrap();
+    }
+}
+
+#[test]
+fn active_tiles_follow_activity() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let (width, height) = (200, 120);
+    let mut engines: Vec<Simulation> = [Kernel::Global, Kernel::Active { workgroup_size: 8 }]
+        .into_iter()
+        .map(|kernel| {
+            let mut gpu = Simulation::new(device.clone(), queue.clone(), width, height).unwrap();
+            gpu.set_kernel(kernel).unwrap();
+            gpu
+        })
+        .collect();
+
+    // A blinker on the corner of four 8x8 tiles, and a block that never changes
+    for gpu in &engines {
+        gpu.write_row(39, 40, &[1.0, 1.0, 1.0]);
+        gpu.write_row(100, 100, &[1.0, 1.0]);
+        gpu.write_row(100, 101, &[1.0, 1.0]);
+    }
+    for gpu in engines.iter_mut() {
+        gpu.step(5);
+    }
+    // The blinker flips cells in three tiles, which with their neighbors cover 15 tiles
+    assert_eq!(engines[1].active_tiles().unwrap().unwrap(), (15, 25 * 15));
+    assert!(engines[0].active_tiles().is_none());
+
+    // A glider painted into the settled area wakes the whole grid for one generation
+    for gpu in &engines {
+        gpu.write_row(150, 10, &[0.0, 1.0, 0.0]);
+        gpu.write_row(150, 11, &[0.0, 0.0, 1.0]);
+        gpu.write_row(150, 12, &[1.0, 1.0, 1.0]);
+    }
+    assert_eq!(engines[1].active_tiles().unwrap().unwrap().0, 25 * 15);
+    for batch in [1, 3, 40] {
+        for gpu in engines.iter_mut() {
+            gpu.step(batch);
+        }
+        assert_eq!(engines[0].read_cells().unwrap(), engines[1].read_cells().unwrap(), "after {} generations", engines[0].generation);
+    }
+    let (active, _) = engines[1].active_tiles().unwrap().unwrap();
+    // The glider touches at most four tiles at a time
+    assert!((15..=15 + 16).contains(&active), "{} active tiles", active);
+}
+
+#[test]
+fn unsupported_workgroup_size_is_rejected() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut gpu = Simulation::new(device, queue, WIDTH, HEIGHT).unwrap();
+    assert!(gpu.set_kernel(Kernel::Shared { workgroup_size: 0 }).is_err());
+    assert!(gpu.set_kernel(Kernel::Shared { workgroup_size: 4096 }).is_err());
+    assert!(gpu.set_kernel(Kernel::Blocked { workgroup_size: 16, generations: 2 }).is_err());
+    assert!(gpu.set_kernel(Kernel::Blocked { workgroup_size: 6, generations: 3 }).is_err());
+    assert_eq!(gpu.kernel(), Kernel::Global);
+
+    gpu.load_shader(gpu.shader_source().replace("// Simple hash", "// Hash")).unwrap();
+    assert!(gpu.set_kernel(Kernel::Shared { workgroup_size: 8 }).is_err());
+}
+
+#[test]
+fn chunked_grid() {
+    // Single-row chunks and a short last chunk, so every row sits on a seam
+    for (seed, chunk_rows) in [1, 10].into_iter().enumerate() {
+        let chunk_rows = Some(chunk_rows);
+        let seed = 70 + seed as u32;
+        cross_validate(&Case { chunk_rows, lucky_chance: Some(0.3), colored: true, ..Default::default() }, seed).unwrap();
+        cross_validate(&Case { chunk_rows, boundary: Boundary::Dead, colored: true, batch: 6, ..Default::default() }, seed).unwrap();
+        let kernel = Kernel::Shared { workgroup_size: 8 };
+        cross_validate(&Case { chunk_rows, kernel, lucky_chance: Some(0.3), ..Default::default() }, seed).unwrap();
+    }
+    // Nothing may be born from the halo rows at the edges of a bounded grid
+    let rules = GameRules::parse("B0/S8").unwrap();
+    cross_validate(&Case { chunk_rows: Some(9), rules, boundary: Boundary::Dead, ..Default::default() }, 75).unwrap();
+}
+
+#[test]
+fn chunked_grid_edits_and_kernels() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut gpu = Simulation::with_chunk_rows(device, queue, WIDTH, HEIGHT, Some(8)).unwrap();
+    assert_eq!(gpu.chunks().map(|chunked| chunked.chunks.len()), Some(7));
+    assert!(gpu.set_kernel(Kernel::Active { workgroup_size: 8 }).is_err());
+    assert!(gpu.set_kernel(Kernel::Blocked { workgroup_size: 16, generations: 3 }).is_err());
+
+    // A run of cells across the seam between the first two chunks
+    let cells = soup(80, WIDTH, HEIGHT, true);
+    gpu.write_cells(&cells).unwrap();
+    let edits = gpu.edit_count();
+    gpu.write_row(70, 7, &[5.0; 20]);
+    assert_eq!(gpu.edit_count(), edits + 1);
+    gpu.write_row(0, 8, &[6.0; 3]);
+    assert_eq!(gpu.edit_count(), edits + 2);
+    let mut expected = cells.clone();
+    expected[(7 * WIDTH + 70) as usize..(8 * WIDTH) as usize].fill(5.0);
+    expected[(8 * WIDTH) as usize..(8 * WIDTH + 3) as usize].fill(6.0);
+    assert_eq!(gpu.read_cells().unwrap(), expected);
+
+    gpu.clear();
+    assert!(gpu.read_cells().unwrap().iter().all(|&cell| cell == 0.0));
+}
+
+#[test]
+fn thread_count_does_not_change_output() {
+    let mut reference: Option<Vec<f32>> = None;
+    for threads in [1, 3, 8] {
+        let mut cpu = CpuSimulation::new(300, 200).unwrap();
+        cpu.threads = threads;
+        cpu.lucky_rule_enabled = true;
+        let cells: Vec<f32> = (0..300 * 200u32).map(|i| if (i * 7919) % 13 < 5 { 1.0 } else { 0.0 }).collect();
+        cpu.write_cells(&cells).unwrap();
+        cpu.step(20);
+        match &reference {
+            Some(reference) => assert_eq!(reference.as_slice(), cpu.cells(), "threads = {}", threads),
+            None => reference = Some(cpu.cells().to_vec()),
+        }
+    }
+}
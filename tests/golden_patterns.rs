@@ -0,0 +1,160 @@
+//! Golden behavior of well-known patterns on the CPU reference engine.
+//!
+//! Patterns are placed on a bounded (dead edge) grid large enough that nothing
+//! reaches the edge, so the results match the infinite plane.
+
+mod common;
+
+use common::grid_with;
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::library::{builtin_pattern, parse_rle, to_rle, Pattern, PatternLibrary};
+use gpu_life::rules::Boundary;
+
+type Cells = Vec<(i32, i32)>;
+
+/// A bounded `width` x `height` universe with `pattern` stamped at (x, y)
+fn universe(pattern: &Pattern, width: u32, height: u32, x: i32, y: i32) -> CpuSimulation {
+    let cells = grid_with(pattern, width, height, x, y);
+    let mut simulation = CpuSimulation::new(width, height).unwrap();
+    simulation.boundary = Boundary::Dead;
+    simulation.write_cells(&cells).unwrap();
+    simulation
+}
+
+/// Live cells in absolute coordinates, sorted by (y, x)
+fn live(simulation: &CpuSimulation) -> Cells {
+    let width = simulation.width as usize;
+    simulation.cells().iter().enumerate()
+        .filter(|(_, &value)| value > 0.5)
+        .map(|(i, _)| ((i % width) as i32, (i / width) as i32))
+        .collect()
+}
+
+fn population(simulation: &CpuSimulation) -> usize {
+    simulation.cells().iter().filter(|&&value| value > 0.5).count()
+}
+
+fn shifted(cells: &Cells, dx: i32, dy: i32) -> Cells {
+    cells.iter().map(|&(x, y)| (x + dx, y + dy)).collect()
+}
+
+/// Smallest period after which the pattern is back at the same place, if at most `max`
+fn period(pattern: &Pattern, max: u32) -> Option<u32> {
+    let mut simulation = universe(pattern, 64, 64, 20, 20);
+    let start = live(&simulation);
+    (1..=max).find(|_| {
+        simulation.step(1);
+        live(&simulation) == start
+    })
+}
+
+#[test]
+fn builtin_library_loads_and_round_trips() {
+    let library = PatternLibrary::builtin();
+    assert!(library.errors.is_empty(), "{:?}", library.errors);
+    for entry in &library.entries {
+        assert!(!entry.pattern.cells.is_empty(), "{} has no cells", entry.name);
+        let reparsed = parse_rle(&to_rle(&entry.pattern)).unwrap();
+        assert_eq!(reparsed.cells, entry.pattern.cells, "{} RLE round trip", entry.name);
+        assert_eq!((reparsed.width, reparsed.height), (entry.pattern.width, entry.pattern.height));
+    }
+}
+
+#[test]
+fn every_builtin_pattern_is_covered() {
+    // Bundled patterns (including the Glider and Gosper gun stamped by the GUI at startup)
+    // each need a golden test below
+    const COVERED: [&str; 9] = [
+        "Block", "Blinker", "Toad", "Pulsar", "Pentadecathlon",
+        "Glider", "Lightweight spaceship", "Gosper glider gun", "Simkin glider gun",
+    ];
+    for entry in &PatternLibrary::builtin().entries {
+        assert!(COVERED.contains(&entry.name.as_str()), "no golden test for {}", entry.name);
+    }
+}
+
+#[test]
+fn still_lifes_and_oscillators() {
+    for (name, expected) in [
+        ("Block", 1),
+        ("Blinker", 2),
+        ("Toad", 2),
+        ("Pulsar", 3),
+        ("Pentadecathlon", 15),
+    ] {
+        assert_eq!(period(&builtin_pattern(name), 30), Some(expected), "{}", name);
+    }
+}
+
+#[test]
+fn glider_translates_diagonally() {
+    let mut simulation = universe(&builtin_pattern("Glider"), 96, 96, 4, 4);
+    let start = live(&simulation);
+    for cycle in 1..=20 {
+        simulation.step(4);
+        assert_eq!(live(&simulation), shifted(&start, cycle, cycle), "after {} generations", cycle * 4);
+    }
+}
+
+#[test]
+fn lightweight_spaceship_translates_orthogonally() {
+    let mut simulation = universe(&builtin_pattern("Lightweight spaceship"), 96, 16, 4, 6);
+    let start = live(&simulation);
+    for cycle in 1..=15 {
+        simulation.step(4);
+        assert_eq!(live(&simulation), shifted(&start, 2 * cycle, 0), "after {} generations", cycle * 4);
+    }
+}
+
+/// Checks that a gun's own cells repeat every `period` generations while each period
+/// adds one glider (5 cells) to the universe
+fn check_gun(name: &str, period: u32, width: u32, height: u32, x: i32, y: i32) {
+    let pattern = builtin_pattern(name);
+    let mut simulation = universe(&pattern, width, height, x, y);
+    let in_gun = |&&(cx, cy): &&(i32, i32)| {
+        (cx - x) < pattern.width as i32 + 2 && (cy - y) < pattern.height as i32 + 2
+            && cx >= x - 2 && cy >= y - 2
+    };
+
+    // Let the first glider leave the gun's bounding box
+    simulation.step(2 * period);
+    let gun: Cells = live(&simulation).iter().filter(in_gun).copied().collect();
+    let mut previous = population(&simulation);
+    for cycle in 1..=3 {
+        simulation.step(period);
+        let now: Cells = live(&simulation).iter().filter(in_gun).copied().collect();
+        assert_eq!(now, gun, "{} is not periodic at cycle {}", name, cycle);
+        assert_eq!(population(&simulation), previous + 5, "{} should emit one glider per cycle", name);
+        previous = population(&simulation);
+    }
+}
+
+// Both guns fire their gliders away from the corner they are placed in
+#[test]
+fn gosper_gun_emits_a_glider_every_30_generations() {
+    check_gun("Gosper glider gun", 30, 160, 160, 4, 4);
+}
+
+#[test]
+fn simkin_gun_emits_a_glider_every_120_generations() {
+    check_gun("Simkin glider gun", 120, 320, 320, 280, 290);
+}
+
+#[test]
+fn r_pentomino_stabilizes_at_generation_1103() {
+    // Six gliders escape; by generation 1103 they are ~260 cells out, so leave room
+    let r_pentomino = parse_rle("x = 3, y = 3\nb2o$2o$bo!").unwrap();
+    let mut simulation = universe(&r_pentomino, 640, 640, 320, 320);
+
+    simulation.step(1102);
+    assert_ne!(population(&simulation), 116);
+    simulation.step(1);
+    assert_eq!(population(&simulation), 116);
+
+    // From here on only period 2 oscillators and escaping gliders remain
+    let generation_1103 = live(&simulation);
+    simulation.step(4);
+    assert_eq!(population(&simulation), 116);
+    let moved = live(&simulation).iter().filter(|cell| !generation_1103.contains(cell)).count();
+    assert!(moved <= 6 * 5, "only gliders should move, {} cells changed", moved);
+}
@@ -0,0 +1,205 @@
+//! HashLife against the CPU reference engine and known long-term behavior.
+
+mod common;
+
+use common::{grid_with, soup};
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::hashlife::{advance_grid, HashLife};
+use gpu_life::library::{builtin_pattern, parse_rle};
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::Simulation;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Runs both engines on a bounded grid large enough that nothing reaches the edge
+fn compare_with_cpu(rules: GameRules, cells: &[f32], width: u32, height: u32, generations: u32) {
+    let mut cpu = CpuSimulation::new(width, height).unwrap();
+    cpu.rules = rules;
+    cpu.boundary = Boundary::Dead;
+    cpu.write_cells(cells).unwrap();
+    cpu.step(generations);
+
+    let mut life = HashLife::from_grid(rules, cells, width, height).unwrap();
+    life.step(generations as u64);
+    assert_eq!(life.generation, generations as u64);
+    assert_eq!(life.to_grid(0, 0, width, height), cpu.cells(), "after {} generations", generations);
+    assert_eq!(life.population(), cpu.cells().iter().filter(|&&c| c > 0.5).count() as u64);
+}
+
+#[test]
+fn matches_cpu_engine() {
+    let r_pentomino = grid_with(&parse_rle("x = 3, y = 3\nb2o$2o$bo!").unwrap(), 256, 256, 128, 128);
+    for generations in [0, 1, 2, 3, 7, 64, 100, 333] {
+        compare_with_cpu(GameRules::default(), &r_pentomino, 256, 256, generations);
+    }
+    let gun = grid_with(&builtin_pattern("Gosper glider gun"), 200, 200, 10, 10);
+    compare_with_cpu(GameRules::default(), &gun, 200, 200, 250);
+}
+
+#[test]
+fn matches_cpu_engine_for_other_rules() {
+    // HighLife replicator
+    let replicator = grid_with(&parse_rle("x = 5, y = 5, rule = B36/S23\n2b3o$bo2bo$o3bo$o2bo$3o!").unwrap(), 200, 200, 100, 100);
+    compare_with_cpu(GameRules::high_life(), &replicator, 200, 200, 90);
+}
+
+#[test]
+fn grid_margin_follows_the_bounding_box() {
+    let cells = grid_with(&builtin_pattern("Glider"), 64, 64, 20, 20);
+    let life = HashLife::from_grid(GameRules::default(), &cells, 64, 64).unwrap();
+    assert_eq!(life.bounding_box(), Some((20, 20, 22, 22)));
+    // 20 cells from the top and left edges; on a torus 61 dead cells from its copies
+    assert_eq!(life.grid_margin(64, 64, Boundary::Dead), 20);
+    assert_eq!(life.grid_margin(64, 64, Boundary::Torus), 30);
+    compare_with_cpu(GameRules::default(), &cells, 64, 64, 20);
+
+    let empty = HashLife::new(GameRules::default()).unwrap();
+    assert_eq!(empty.bounding_box(), None);
+    assert_eq!(empty.grid_margin(4, 4, Boundary::Dead), u64::MAX);
+}
+
+#[test]
+fn from_cells_and_clip() {
+    let cells = [(-1_000_000_007, 5), (-1_000_000_006, 5), (3, -2), (40, 9)];
+    let mut life = HashLife::from_cells(GameRules::default(), &cells).unwrap();
+    let mut sorted = cells.to_vec();
+    sorted.sort_by_key(|&(x, y)| (y, x));
+    assert_eq!(life.live_cells(), sorted);
+    life.clip(0, -10, 41, 20);
+    assert_eq!(life.live_cells(), vec![(3, -2), (40, 9)]);
+}
+
+/// The CPU engine's grid after `generations` generations, skipping whole periods once
+/// a generation repeats (by hash)
+fn cpu_far_future(cells: &[f32], width: u32, height: u32, boundary: Boundary, generations: u64) -> Vec<f32> {
+    let mut cpu = CpuSimulation::new(width, height).unwrap();
+    cpu.boundary = boundary;
+    cpu.write_cells(cells).unwrap();
+    let mut seen = HashMap::new();
+    let mut generation = 0;
+    while generation < generations {
+        let mut hasher = DefaultHasher::new();
+        cpu.cells().iter().map(|&cell| cell > 0.5).collect::<Vec<bool>>().hash(&mut hasher);
+        if let Some(earlier) = seen.insert(hasher.finish(), generation) {
+            let period = generation - earlier;
+            generation += (generations - generation) / period * period;
+            seen.clear();
+            continue;
+        }
+        cpu.step(1);
+        generation += 1;
+    }
+    cpu.cells().iter().map(|&cell| if cell > 0.5 { 1.0 } else { 0.0 }).collect()
+}
+
+/// `advance_grid` against the CPU engine on the same bounded grid
+fn advance_like_cpu(cells: &[f32], width: u32, height: u32, boundary: Boundary, generations: u64) -> u64 {
+    let (grid, advanced) = advance_grid(GameRules::default(), cells, width, height, boundary, generations).unwrap();
+    let expected = cpu_far_future(cells, width, height, boundary, advanced);
+    assert_eq!(grid, expected, "{:?} {}x{} after {} generations", boundary, width, height, advanced);
+    advanced
+}
+
+#[test]
+fn advance_grid_follows_a_glider_around_a_torus() {
+    // The glider crosses the edges thousands of times; HashLife takes it a margin at a time
+    let cells = grid_with(&builtin_pattern("Glider"), 32, 24, 29, 21);
+    assert_eq!(advance_like_cpu(&cells, 32, 24, Boundary::Torus, 10_000), 10_000);
+    // A soup spans the torus, which HashLife cannot follow
+    assert_eq!(advance_like_cpu(&soup(4, 32, 24, false), 32, 24, Boundary::Torus, 10_000), 0);
+}
+
+#[test]
+fn advance_grid_clips_at_a_dead_edge() {
+    // The soup touches the edges from the start and settles into ash along them
+    let cells = soup(3, 48, 40, false);
+    for generations in [1, 2, 50, 700] {
+        assert_eq!(advance_like_cpu(&cells, 48, 40, Boundary::Dead, generations), generations);
+    }
+    assert_eq!(advance_like_cpu(&cells, 48, 40, Boundary::Dead, 1_000_000_000), 1_000_000_000);
+}
+
+#[test]
+fn rejects_b0_rules() {
+    assert!(HashLife::new(GameRules::parse("B0/S").unwrap()).is_err());
+}
+
+#[test]
+fn glider_after_a_billion_generations() {
+    let cells = grid_with(&builtin_pattern("Glider"), 8, 8, 0, 0);
+    let mut life = HashLife::from_grid(GameRules::default(), &cells, 8, 8).unwrap();
+    let start = life.live_cells();
+    life.step(1_000_000_000);
+    let moved: Vec<(i64, i64)> = start.iter().map(|&(x, y)| (x + 250_000_000, y + 250_000_000)).collect();
+    assert_eq!(life.live_cells(), moved);
+}
+
+#[test]
+fn gosper_gun_far_future() {
+    let cells = grid_with(&builtin_pattern("Gosper glider gun"), 36, 9, 0, 0);
+    let mut life = HashLife::from_grid(GameRules::default(), &cells, 36, 9).unwrap();
+    // The gun repeats every 30 generations and adds one 5-cell glider each time
+    life.step(30 * 1000);
+    let gun_population = 36;
+    assert_eq!(life.population(), gun_population + 5 * 1000);
+    life.step(1 << 40);
+    assert!(life.population() > 5 * ((1u64 << 40) / 30));
+}
+
+#[test]
+fn garbage_collection_keeps_the_pattern() {
+    let cells = grid_with(&parse_rle("x = 3, y = 3\nb2o$2o$bo!").unwrap(), 256, 256, 128, 128);
+    let mut collected = HashLife::from_grid(GameRules::default(), &cells, 256, 256).unwrap();
+    collected.max_nodes = 2_000;
+    let mut reference = HashLife::from_grid(GameRules::default(), &cells, 256, 256).unwrap();
+    for _ in 0..20 {
+        collected.step(97);
+        reference.step(97);
+    }
+    assert_eq!(collected.live_cells(), reference.live_cells());
+    collected.collect_garbage();
+    assert!(collected.node_count() < reference.node_count());
+    collected.step(2000 - 97 * 20);
+    assert_eq!(collected.population(), 116);
+}
+
+#[test]
+fn simulation_jumps_a_billion_generations() {
+    let (width, height) = (192, 192);
+    let Ok(mut simulation) = pollster::block_on(Simulation::new_headless(width, height)) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // A colored soup in the middle of a dead-edged grid: its gliders crash into the
+    // edges and the rest settles into ash
+    let patch = soup(11, 48, 48, true);
+    let mut cells = vec![0.0; (width * height) as usize];
+    for (i, &cell) in patch.iter().enumerate() {
+        cells[(72 + i / 48) * width as usize + 72 + i % 48] = cell;
+    }
+    simulation.boundary = Boundary::Dead;
+    simulation.write_cells(&cells).unwrap();
+
+    let jump = simulation.jump(1_000_000_000).unwrap();
+    assert_eq!(simulation.generation, 1_000_000_000);
+    assert_eq!(jump.hashlife_generations + jump.gpu_generations, 1_000_000_000);
+    assert!(jump.gpu_generations <= 1_000_000);
+    assert_eq!(simulation.read_cells().unwrap(), cpu_far_future(&cells, width, height, Boundary::Dead, 1_000_000_000));
+}
+
+#[test]
+fn simulation_jump_steps_a_full_torus_on_the_gpu() {
+    let (width, height) = (64, 48);
+    let Ok(mut simulation) = pollster::block_on(Simulation::new_headless(width, height)) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // The soup spans the torus, so HashLife only gets to run once its ash leaves a gap
+    let cells = soup(12, width, height, false);
+    simulation.write_cells(&cells).unwrap();
+    let jump = simulation.jump(25_000).unwrap();
+    assert!(jump.gpu_generations >= 10_000);
+    assert_eq!(jump.hashlife_generations + jump.gpu_generations, 25_000);
+    assert_eq!(simulation.read_cells().unwrap(), cpu_far_future(&cells, width, height, Boundary::Torus, 25_000));
+}
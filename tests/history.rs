@@ -0,0 +1,103 @@
+//! Population history samples and restoring generations from GPU snapshots.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::soup;
+use gpu_life::history::{plot_fraction, HistorySample, PopulationHistory, SnapshotRing};
+use gpu_life::simulation::{request_headless_device, Simulation};
+
+const WIDTH: u32 = 40;
+const HEIGHT: u32 = 30;
+
+fn sample(generation: usize, population: u32) -> HistorySample {
+    let mut populations = [0; gpu_life::stats::SPECIES_COUNT];
+    populations[0] = population;
+    HistorySample { generation, population: populations, births: 0, deaths: 0 }
+}
+
+fn generations(history: &PopulationHistory) -> Vec<usize> {
+    history.samples().iter().map(|sample| sample.generation).collect()
+}
+
+#[test]
+fn history_scrolls_and_branches() {
+    let mut history = PopulationHistory::new(3);
+    for generation in [1, 2, 3, 4] {
+        history.push(sample(generation, generation as u32 * 10));
+    }
+    assert_eq!(generations(&history), vec![2, 3, 4]);
+
+    // Going back in time replaces the samples that followed
+    history.push(sample(3, 99));
+    assert_eq!(generations(&history), vec![2, 3]);
+    assert_eq!(history.samples()[1].population[0], 99);
+
+    history.truncate_after(2);
+    assert_eq!(generations(&history), vec![2]);
+    assert_eq!(history.nearest(100).map(|sample| sample.generation), Some(2));
+}
+
+#[test]
+fn spacing_of_frame_samples() {
+    // Several steps per frame leave gaps between the samples
+    let mut history = PopulationHistory::default();
+    assert_eq!(history.mean_spacing(0), None);
+    for generation in [0, 7, 14, 21, 30] {
+        history.push(sample(generation, 5));
+    }
+    assert_eq!(history.mean_spacing(0), Some(7.5));
+    assert_eq!(history.mean_spacing(14), Some(8.0));
+    assert_eq!(history.mean_spacing(30), None);
+}
+
+#[test]
+fn plot_axes() {
+    assert_eq!(plot_fraction(50.0, 100.0, false), 0.5);
+    assert_eq!(plot_fraction(0.0, 100.0, true), 0.0);
+    assert_eq!(plot_fraction(100.0, 100.0, true), 1.0);
+    // Small values get more room on the log axis
+    assert!(plot_fraction(10.0, 1000.0, true) > 0.3);
+    assert_eq!(plot_fraction(5.0, 0.0, false), 0.0);
+}
+
+#[test]
+fn snapshots_restore_past_generations() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut simulation = Simulation::new(device.clone(), queue.clone(), WIDTH, HEIGHT).unwrap();
+    simulation.load_generation(0, &soup(1, WIDTH, HEIGHT, false)).unwrap();
+    let mut ring = SnapshotRing::new(&device, simulation.current_buffer().size(), 2, 5);
+    ring.sync_edits(simulation.edit_count());
+
+    // Snapshot every 5 generations; only the newest two are kept
+    let mut expected = Vec::new();
+    for _ in 0..=15 {
+        expected.push(simulation.read_cells().unwrap());
+        if ring.is_due(simulation.generation) {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            ring.encode_snapshot(&mut encoder, simulation.current_buffer(), simulation.generation);
+            queue.submit(Some(encoder.finish()));
+        }
+        simulation.step(1);
+    }
+    assert_eq!(ring.generations(), vec![10, 15]);
+    assert!(ring.latest_before(9).is_none());
+
+    // Generation 12: the snapshot of generation 10 stepped twice
+    let (start, snapshot) = ring.latest_before(12).unwrap();
+    assert_eq!(start, 10);
+    simulation.restore_from(start, snapshot).unwrap();
+    simulation.step(2);
+    assert_eq!(simulation.generation, 12);
+    assert_eq!(simulation.read_cells().unwrap(), expected[12]);
+
+    // Snapshots after the restored generation are dropped, so are all of them after an edit
+    ring.discard_after(12);
+    assert_eq!(ring.generations(), vec![10]);
+    simulation.clear();
+    ring.sync_edits(simulation.edit_count());
+    assert!(ring.generations().is_empty());
+}
@@ -0,0 +1,121 @@
+//! Cell inspector: neighbor counts and next states of a cell window, and the GPU
+//! window query with cell ages.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::inspector::{CellAges, CellQuery, CellWindow, NextState, WINDOW_RADIUS, WINDOW_SIDE};
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::{request_headless_device, Simulation};
+
+const WIDTH: u32 = 24;
+const HEIGHT: u32 = 20;
+
+/// Window around a cell with the given live cells, as (dx, dy) offsets
+fn window(live: &[(i64, i64)], value: f32) -> CellWindow {
+    let mut cells = [Some(0.0); WINDOW_SIDE * WINDOW_SIDE];
+    for &(dx, dy) in live {
+        cells[((dy + WINDOW_RADIUS) * WINDOW_SIDE as i64 + dx + WINDOW_RADIUS) as usize] = Some(value);
+    }
+    CellWindow { x: 3, y: 4, generation: 0, cells, age: None, age_is_lower_bound: false }
+}
+
+#[test]
+fn next_state_follows_the_rules() {
+    let life = GameRules::default();
+    // Cells two away are not neighbors
+    let born = window(&[(-1, -1), (1, 0), (0, 1), (2, 2)], 4.0);
+    assert_eq!(born.neighbors(), 3);
+    assert_eq!(born.value(), 0.0);
+    assert_eq!(born.next_state(life, None), NextState::Born(4.0));
+    assert_eq!(born.next_state(GameRules::parse("B36/S23").unwrap(), None), NextState::Born(4.0));
+    assert_eq!(born.next_state(GameRules::parse("B2/S").unwrap(), None), NextState::StaysDead);
+
+    let lonely = window(&[(0, 0), (1, 1)], 1.0);
+    assert_eq!(lonely.neighbors(), 1);
+    assert_eq!(lonely.next_state(life, None), NextState::Dies);
+    assert_eq!(lonely.next_state(life, Some(0.25)), NextState::DiesOrLucky { chance: 0.25, lucky: 3.0 });
+    assert_eq!(lonely.next_state(life, Some(0.25)).to_string(), "dies (25% chance to survive as red)");
+
+    let crowded = window(&[(0, 0), (-1, 0), (1, 0)], 1.0);
+    assert_eq!(crowded.next_state(life, None), NextState::Survives(1.0));
+    assert_eq!(crowded.cell(3, 0), None);
+}
+
+/// Window around (x, y), waiting for it to arrive
+fn inspect(query: &mut CellQuery, simulation: &Simulation, ages: &CellAges, x: u32, y: u32) -> CellWindow {
+    let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    assert!(query.encode(&mut encoder, simulation, Some(ages), x, y));
+    simulation.queue.submit(Some(encoder.finish()));
+    query.after_submit();
+    query.wait(&simulation.device).cloned().unwrap()
+}
+
+#[test]
+fn query_reads_the_window_and_ages_of_the_current_generation() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // A block near the corner, which windows wrap around to, a blinker and a glider
+    let mut cells = vec![0.0; (WIDTH * HEIGHT) as usize];
+    for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2), (10, 5), (11, 5), (12, 5), (17, 12), (18, 13), (16, 14), (17, 14), (18, 14)] {
+        cells[(y * WIDTH + x) as usize] = 1.0;
+    }
+    let mut simulation = Simulation::new(device.clone(), queue.clone(), WIDTH, HEIGHT).unwrap();
+    simulation.boundary = Boundary::Torus;
+    simulation.write_cells(&cells).unwrap();
+    let mut cpu = CpuSimulation::new(WIDTH, HEIGHT).unwrap();
+    cpu.boundary = Boundary::Torus;
+    cpu.write_cells(&cells).unwrap();
+
+    let mut ages = CellAges::new(&simulation).unwrap();
+    let steps = 7;
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    for _ in 0..steps {
+        simulation.encode_step(&mut encoder);
+        ages.encode_update(&mut encoder, simulation.generation);
+    }
+    queue.submit(Some(encoder.finish()));
+    cpu.step(steps);
+
+    let mut query = CellQuery::new(&device);
+    let mut inspect = |simulation: &Simulation, x: u32, y: u32| inspect(&mut query, simulation, &ages, x, y);
+
+    // The block has been alive all along; the blinker's end cells flip every generation
+    let block = inspect(&simulation, 1, 1);
+    assert_eq!((block.value(), block.neighbors(), block.age, block.age_is_lower_bound), (1.0, 3, Some(steps), true));
+    let center = inspect(&simulation, 11, 5);
+    assert_eq!((center.age, center.age_is_lower_bound), (Some(steps), true));
+    let end = inspect(&simulation, 11, 4);
+    assert_eq!((end.value(), end.age, end.age_is_lower_bound), (1.0, Some(1), false));
+    assert_eq!(end.next_state(GameRules::default(), None), NextState::Dies);
+
+    // Windows match the CPU grid, wrapping around the edges
+    let expected = cpu.cells().to_vec();
+    let cpu_cell = |x: i64, y: i64| expected[(y.rem_euclid(HEIGHT as i64) * WIDTH as i64 + x.rem_euclid(WIDTH as i64)) as usize];
+    for (x, y) in [(0, 0), (23, 19), (22, 1), (11, 4)] {
+        let window = inspect(&simulation, x, y);
+        assert_eq!(window.generation, steps as usize);
+        for dy in -WINDOW_RADIUS..=WINDOW_RADIUS {
+            for dx in -WINDOW_RADIUS..=WINDOW_RADIUS {
+                assert_eq!(window.cell(dx, dy), Some(cpu_cell(x as i64 + dx, y as i64 + dy)), "({}, {}) + ({}, {})", x, y, dx, dy);
+            }
+        }
+    }
+
+    // ...and predict its next generation
+    let predicted: Vec<_> = (0..HEIGHT).flat_map(|y| (0..WIDTH).map(move |x| (x, y))).map(|(x, y)| {
+        match inspect(&simulation, x, y).next_state(GameRules::default(), None) {
+            NextState::Born(value) | NextState::Survives(value) => value,
+            _ => 0.0,
+        }
+    }).collect();
+    cpu.step(1);
+    assert_eq!(predicted, cpu.cells());
+
+    // Past a dead edge there are no cells
+    simulation.boundary = Boundary::Dead;
+    let corner = inspect(&simulation, 0, 0);
+    assert_eq!(corner.cell(-1, 0), None);
+    assert_eq!(corner.cell(1, 1), Some(cpu_cell(1, 1)));
+}
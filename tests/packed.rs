@@ -0,0 +1,142 @@
+//! Cross-validation of the bit-packed kernel against the CPU reference engine.
+//! Tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::soup;
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::packed::{pack, unpack, PackedSimulation};
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::{fits_one_buffer, request_headless_device, Simulation};
+
+const GENERATIONS: u32 = 40;
+
+/// Step both engines and diff the grids after every generation
+fn cross_validate(rules: GameRules, boundary: Boundary, width: u32, height: u32) {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut packed = PackedSimulation::new(device, queue, width, height).unwrap();
+    let mut cpu = CpuSimulation::new(width, height).unwrap();
+    packed.set_rules(rules);
+    cpu.rules = rules;
+    packed.boundary = boundary;
+    cpu.boundary = boundary;
+
+    let cells = soup(width ^ height, width, height, false);
+    packed.write_cells(&cells).unwrap();
+    cpu.write_cells(&cells).unwrap();
+    for generation in 1..=GENERATIONS {
+        packed.step(1);
+        cpu.step(1);
+        let actual = packed.read_cells().unwrap();
+        if let Some(i) = (0..actual.len()).find(|&i| actual[i] != cpu.cells()[i]) {
+            panic!(
+                "{} {:?} {}x{}: cell ({}, {}) differs at generation {}: packed {}, cpu {}",
+                rules.to_rulestring(), boundary, width, height,
+                i as u32 % width, i as u32 / width, generation, actual[i], cpu.cells()[i]
+            );
+        }
+    }
+}
+
+#[test]
+fn matches_cpu_on_the_torus() {
+    // Word-aligned, partial last word, and narrower than one word
+    for (width, height) in [(64, 48), (77, 53), (20, 30)] {
+        cross_validate(GameRules::default(), Boundary::Torus, width, height);
+    }
+}
+
+#[test]
+fn matches_cpu_with_dead_boundary() {
+    for (width, height) in [(64, 48), (77, 53)] {
+        cross_validate(GameRules::default(), Boundary::Dead, width, height);
+    }
+}
+
+#[test]
+fn matches_cpu_for_other_rules() {
+    for rule in ["B36/S23", "B2/S", "B3678/S34678", "B0/S8"] {
+        cross_validate(GameRules::parse(rule).unwrap(), Boundary::Torus, 77, 53);
+    }
+    cross_validate(GameRules::parse("B0/S8").unwrap(), Boundary::Dead, 45, 20);
+}
+
+#[test]
+fn edits_keep_neighboring_bits() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let (width, height) = (70, 3);
+    let packed = PackedSimulation::new(device, queue, width, height).unwrap();
+    let mut cells = soup(7, width, height, false);
+    packed.write_cells(&cells).unwrap();
+
+    // A run across a word boundary, clipped at the right edge
+    packed.write_row(30, 1, &[1.0, 0.0, 1.0, 1.0]);
+    packed.write_row(68, 2, &[0.0, 1.0, 1.0, 1.0]);
+    cells[(width + 30) as usize..(width + 34) as usize].copy_from_slice(&[1.0, 0.0, 1.0, 1.0]);
+    cells[(2 * width + 68) as usize..(3 * width) as usize].copy_from_slice(&[0.0, 1.0]);
+
+    assert_eq!(packed.read_cells().unwrap(), cells);
+    assert_eq!(packed.population().unwrap(), cells.iter().filter(|&&c| c > 0.5).count() as u64);
+}
+
+#[test]
+fn pack_round_trip() {
+    let cells = soup(3, 45, 7, false);
+    let words = pack(&cells, 45, 7);
+    assert_eq!(words.len(), 2 * 7);
+    assert_eq!(unpack(&words, 45, 7), cells);
+}
+
+#[test]
+fn released_f32_grid_comes_back_on_resize() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // Packed mode in the GUI frees the f32 grid and sizes it on its own
+    let mut simulation = Simulation::new(device, queue, 64, 48).unwrap();
+    simulation.release_grid(1024, 512);
+    assert!(simulation.is_released());
+    assert_eq!((simulation.width, simulation.height), (1024, 512));
+    assert!(simulation.grid_buffers.iter().all(|buffer| buffer.size() == 4));
+    assert!(simulation.read_cells().is_err());
+    assert!(simulation.write_cells(&vec![0.0; 1024 * 512]).is_err());
+
+    simulation.resize(1024, 512).unwrap();
+    assert!(!simulation.is_released());
+    let cells = soup(9, 1024, 512, false);
+    simulation.load_generation(7, &cells).unwrap();
+    assert_eq!(simulation.read_cells().unwrap(), cells);
+}
+
+#[test]
+fn grids_too_large_for_one_f32_buffer_start_packed() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // One row more than an f32 buffer holds: the GUI allocates only the packed grid
+    let limits = device.limits();
+    let limit = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    let width = 4096;
+    let height = (limit / (width as u64 * 4) + 1) as u32;
+    assert!(fits_one_buffer(&device, width, height - 1));
+    assert!(!fits_one_buffer(&device, width, height));
+
+    let simulation = Simulation::new_released(device.clone(), queue.clone(), width, height).unwrap();
+    assert!(simulation.is_released());
+    assert_eq!((simulation.width, simulation.height), (width, height));
+    assert!(simulation.grid_buffers.iter().all(|buffer| buffer.size() == 4));
+    assert!(simulation.chunks().is_none());
+
+    let mut packed = PackedSimulation::new(device, queue, width, height).unwrap();
+    packed.write_row(10, height - 2, &[1.0, 1.0, 1.0]);
+    packed.step(2);
+    assert_eq!(packed.population().unwrap(), 3);
+}
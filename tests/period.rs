@@ -0,0 +1,143 @@
+//! Period detection of still lifes, oscillators and spaceships.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::grid_with;
+use gpu_life::library::{builtin_pattern, Pattern};
+use gpu_life::period::{analyze, Analysis, Classification, HashRecord, PeriodDetector, Stabilization, StabilityHistory, StabilityMonitor};
+use gpu_life::simulation::{request_headless_device, Simulation};
+use std::sync::Arc;
+
+const WIDTH: u32 = 48;
+const HEIGHT: u32 = 40;
+
+#[test]
+fn detector_sees_translated_states() {
+    let glider = builtin_pattern("Glider");
+    let mut detector = PeriodDetector::new();
+    assert_eq!(detector.push(0, &HashRecord::from_cells(&grid_with(&glider, WIDTH, HEIGHT, 5, 5), WIDTH)), None);
+    assert_eq!(detector.push(1, &HashRecord::from_cells(&grid_with(&glider, WIDTH, HEIGHT, 20, 9), WIDTH)), Some(Analysis {
+        classification: Classification::Spaceship { period: 1, dx: 15, dy: 4 },
+        settled_after: 0,
+    }));
+
+    let mut detector = PeriodDetector::new();
+    let empty = HashRecord::from_cells(&vec![0.0; (WIDTH * HEIGHT) as usize], WIDTH);
+    assert_eq!(detector.push(7, &empty).map(|analysis| analysis.classification), Some(Classification::DiesOut { generation: 7 }));
+}
+
+#[test]
+fn classifications_read_well() {
+    assert_eq!(Classification::Spaceship { period: 4, dx: 1, dy: -1 }.to_string(), "Spaceship, speed (1, -1)/4");
+    let settled = Analysis { classification: Classification::Oscillator { period: 2 }, settled_after: 3 };
+    assert_eq!(settled.to_string(), "Oscillator, period 2 (after 3 generations)");
+}
+
+/// Record of a grid whose only live cell is at (x, 0)
+fn lone_cell(x: u32) -> HashRecord {
+    let mut cells = vec![0.0; (WIDTH * HEIGHT) as usize];
+    cells[x as usize] = 1.0;
+    HashRecord::from_cells(&cells, WIDTH)
+}
+
+#[test]
+fn stability_finds_the_start_of_the_cycle() {
+    // 1, 2, 3, 4, 5, 4, 5, 4: period 2 from generation 3
+    let mut history = StabilityHistory::new(4);
+    let states = [1, 2, 3, 4, 5, 4, 5, 4];
+    let found: Vec<_> = states.iter().enumerate().filter_map(|(generation, &x)| history.push(generation, &lone_cell(x))).collect();
+    assert_eq!(found[0], Stabilization { generation: 3, period: 2, population: 1, detected_at: 5 });
+
+    // Unlike the period detector, moving states are not stable
+    let mut history = StabilityHistory::new(4);
+    assert!((0..10).all(|generation| history.push(generation, &lone_cell(generation as u32)).is_none()));
+
+    // Periods above the threshold are ignored
+    let mut history = StabilityHistory::new(2);
+    assert!((0..10).all(|generation| history.push(generation, &lone_cell(generation as u32 % 3)).is_none()));
+}
+
+/// A grid holding `cells` on the shared test device
+fn simulation_with(device: &Arc<wgpu::Device>, queue: &Arc<wgpu::Queue>, cells: &[f32]) -> Simulation {
+    let mut simulation = Simulation::new(device.clone(), queue.clone(), WIDTH, HEIGHT).unwrap();
+    simulation.load_generation(0, cells).unwrap();
+    simulation
+}
+
+#[test]
+fn patterns_are_classified_on_the_gpu() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let cases = [
+        ("Block", Classification::StillLife),
+        ("Blinker", Classification::Oscillator { period: 2 }),
+        ("Pulsar", Classification::Oscillator { period: 3 }),
+        ("Glider", Classification::Spaceship { period: 4, dx: 1, dy: 1 }),
+        ("Lightweight spaceship", Classification::Spaceship { period: 4, dx: 2, dy: 0 }),
+    ];
+    for (name, expected) in cases {
+        let simulation = simulation_with(&device, &queue, &grid_with(&builtin_pattern(name), WIDTH, HEIGHT, 20, 15));
+        let analysis = analyze(&simulation, None, 100).unwrap();
+        assert_eq!(analysis, Analysis { classification: expected, settled_after: 0 }, "{}", name);
+    }
+
+    // A pre-block (three cells of a block) settles into a block
+    let settles = simulation_with(&device, &queue, &grid_with(&Pattern::from_cells([(0, 0), (1, 0), (0, 1)]), WIDTH, HEIGHT, 10, 10));
+    assert_eq!(analyze(&settles, None, 100).unwrap(), Analysis { classification: Classification::StillLife, settled_after: 1 });
+
+    // A blinker next to a glider: selections classify each one in isolation
+    let mut cells = grid_with(&builtin_pattern("Blinker"), WIDTH, HEIGHT, 2, 2);
+    for (i, cell) in grid_with(&builtin_pattern("Glider"), WIDTH, HEIGHT, 30, 25).into_iter().enumerate() {
+        cells[i] += cell;
+    }
+    let simulation = simulation_with(&device, &queue, &cells);
+    let glider = analyze(&simulation, Some([28, 23, 8, 8]), 100).unwrap();
+    assert_eq!(glider.classification, Classification::Spaceship { period: 4, dx: 1, dy: 1 });
+    let blinker = analyze(&simulation, Some([0, 0, 8, 8]), 100).unwrap();
+    assert_eq!(blinker.classification, Classification::Oscillator { period: 2 });
+    let lone_cell = analyze(&simulation, Some([2, 3, 1, 1]), 100).unwrap();
+    assert_eq!(lone_cell.classification, Classification::DiesOut { generation: 1 });
+}
+
+#[test]
+fn monitor_notices_a_periodic_grid_once() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    // A pre-block turns into a block at generation 1; the blinker keeps the grid at period 2
+    let mut cells = grid_with(&Pattern::from_cells([(0, 0), (1, 0), (0, 1)]), WIDTH, HEIGHT, 10, 10);
+    for (i, cell) in grid_with(&builtin_pattern("Blinker"), WIDTH, HEIGHT, 30, 25).into_iter().enumerate() {
+        cells[i] += cell;
+    }
+    let mut simulation = simulation_with(&device, &queue, &cells);
+    let mut monitor = StabilityMonitor::new(&simulation, 4).unwrap();
+
+    let mut found = Vec::new();
+    for _ in 0..20 {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        simulation.encode_step(&mut encoder);
+        if !monitor.encode_hash(&mut encoder, simulation.generation) {
+            queue.submit(Some(encoder.finish()));
+            monitor.after_submit();
+            found.extend(monitor.wait_for_slot(&device));
+            encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            assert!(monitor.encode_hash(&mut encoder, simulation.generation));
+        }
+        queue.submit(Some(encoder.finish()));
+        monitor.after_submit();
+    }
+    for _ in 0..3 {
+        found.extend(monitor.wait_for_slot(&device));
+    }
+    assert_eq!(found.len(), 1, "{:?}", found);
+    assert_eq!((found[0].generation, found[0].period, found[0].population), (1, 2, 7));
+
+    // An edit starts over
+    simulation.clear();
+    monitor.sync_edits(simulation.edit_count());
+    assert!(monitor.history.latest_generation().is_none());
+}
@@ -0,0 +1,48 @@
+//! The staging ring shared by every asynchronous readback.
+//! Tests are skipped when no wgpu adapter (hardware or software) is available.
+
+use gpu_life::readback::ReadbackRing;
+use gpu_life::simulation::request_headless_device;
+
+/// Fill a free slot of `ring` with `value`, tagged `tag`, and submit it
+fn fill(ring: &mut ReadbackRing<u32>, device: &wgpu::Device, queue: &wgpu::Queue, source: &wgpu::Buffer, tag: u32, value: u32) {
+    queue.write_buffer(source, 0, bytemuck::bytes_of(&value));
+    let index = ring.free_slot().unwrap();
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    encoder.copy_buffer_to_buffer(source, 0, ring.staging(index), 0, 4);
+    queue.submit(Some(encoder.finish()));
+    ring.fill(index, tag);
+    ring.after_submit();
+}
+
+#[test]
+fn slots_come_back_in_the_order_they_were_filled() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let source = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let decode = |tag: u32, data: &[u8]| (tag, bytemuck::pod_read_unaligned::<u32>(data));
+
+    let mut ring = ReadbackRing::new(&device, "Test Staging Buffer", 4, 3);
+    for tag in 0..3 {
+        fill(&mut ring, &device, &queue, &source, tag, 100 + tag);
+    }
+    assert!(!ring.has_free_slot());
+    assert_eq!(ring.in_use(), 3);
+
+    // Waiting for the oldest slot frees at least it; the rest follow in order
+    let mut decoded = ring.wait_oldest(&device, decode);
+    assert_eq!(decoded.first(), Some(&(0, 100)));
+    fill(&mut ring, &device, &queue, &source, 3, 103);
+    decoded.extend(ring.drain(&device, decode));
+    assert_eq!(decoded, vec![(0, 100), (1, 101), (2, 102), (3, 103)]);
+    assert!(!ring.is_mapping());
+    assert_eq!(ring.in_use(), 0);
+    assert!(ring.poll(&device, decode).is_empty());
+}
@@ -0,0 +1,197 @@
+//! Recording to animated images and to stream targets, in particular named pipes
+//! (FIFOs). Tests are skipped when no wgpu adapter (hardware or software) is
+//! available or `mkfifo` cannot create a pipe.
+
+mod common;
+
+use common::soup;
+use gpu_life::capture::CaptureRegion;
+use gpu_life::recording::{Recorder, RecordingFormat, RecordingSettings};
+use gpu_life::render::Palette;
+use gpu_life::simulation::{request_headless_device, Simulation};
+use std::path::{Path, PathBuf};
+
+const SIZE: u32 = 16;
+
+#[cfg(unix)]
+fn make_fifo(name: &str) -> Option<PathBuf> {
+    let path = temp_path(name);
+    let _ = std::fs::remove_file(&path);
+    let created = std::process::Command::new("mkfifo").arg(&path).status().is_ok_and(|status| status.success());
+    created.then_some(path)
+}
+
+/// Capture `frames` generations of a grid buffer, the way the render loop does
+#[cfg(unix)]
+fn capture(recorder: &mut Recorder, device: &wgpu::Device, queue: &wgpu::Queue, source: &wgpu::Buffer, frames: usize) {
+    for generation in 0..frames {
+        if !recorder.has_free_slot() {
+            recorder.wait_for_slot(device);
+        }
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        assert!(recorder.encode_capture(&mut encoder, source, generation));
+        queue.submit(Some(encoder.finish()));
+        recorder.after_submit();
+        recorder.poll(device);
+    }
+}
+
+#[cfg(unix)]
+fn y4m_settings(path: &Path) -> RecordingSettings {
+    RecordingSettings { format: RecordingFormat::Y4m, path: path.to_path_buf(), ..Default::default() }
+}
+
+/// Record generations 0 to `generations` of a colored soup to `format`, capturing
+/// each one like the render loop does (waiting for the oldest slot when the ring is
+/// full). Returns the recorder's summary and the grid of the first and the last frame.
+fn record_soup(format: RecordingFormat, path: &Path, generations: usize) -> Option<(String, Vec<f32>, Vec<f32>)> {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return None;
+    };
+    let mut simulation = Simulation::new(device.clone(), queue.clone(), 40, 30).unwrap();
+    simulation.load_generation(0, &soup(27, 40, 30, true)).unwrap();
+    let first = simulation.read_cells().unwrap();
+
+    let settings = RecordingSettings {
+        format,
+        path: path.to_path_buf(),
+        scale: 2,
+        crop: Some(CaptureRegion { x: 5, y: 3, width: 20, height: 12 }),
+        palette: Palette::CellColors,
+        ..Default::default()
+    };
+    let mut recorder = Recorder::start(&device, settings, 40, 30).unwrap();
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    for generation in 0..=generations {
+        if generation > 0 {
+            simulation.encode_step(&mut encoder);
+        }
+        if !recorder.has_free_slot() {
+            queue.submit(Some(encoder.finish()));
+            recorder.after_submit();
+            recorder.wait_for_slot(&device);
+            encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        }
+        assert!(recorder.encode_capture(&mut encoder, simulation.current_buffer(), simulation.generation));
+    }
+    queue.submit(Some(encoder.finish()));
+    recorder.after_submit();
+    recorder.poll(&device);
+    assert_eq!(recorder.frames_dropped, 0);
+    let summary = recorder.finish(&device).unwrap();
+    Some((summary, first, simulation.read_cells().unwrap()))
+}
+
+/// RGB pixels of the recorded crop of `cells`, two pixels per cell
+fn expected_pixels(cells: &[f32]) -> Vec<[u8; 3]> {
+    let mut pixels = Vec::new();
+    for y in 0..24 {
+        for x in 0..40 {
+            let [r, g, b, _] = Palette::CellColors.rgba8(cells[(3 + y / 2) * 40 + 5 + x / 2]);
+            pixels.push([r, g, b]);
+        }
+    }
+    pixels
+}
+
+fn rgb_pixels(bytes: &[u8], channels: usize) -> Vec<[u8; 3]> {
+    bytes.chunks_exact(channels).map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gpu_life_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn gif_holds_every_captured_generation() {
+    let path = temp_path("soup.gif");
+    let Some((summary, first, last)) = record_soup(RecordingFormat::Gif, &path, 20) else { return };
+    assert_eq!(summary, format!("Saved 21 frames to {}", path.display()));
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (40, 24));
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push(rgb_pixels(&frame.buffer, 4));
+    }
+    assert_eq!(frames.len(), 21);
+    assert_eq!(frames[0], expected_pixels(&first));
+    assert_eq!(frames[20], expected_pixels(&last));
+    assert_ne!(frames[0], frames[20]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn apng_holds_every_captured_generation() {
+    let path = temp_path("soup.png");
+    let Some((summary, first, last)) = record_soup(RecordingFormat::Apng, &path, 20) else { return };
+    assert_eq!(summary, format!("Saved 21 frames to {}", path.display()));
+
+    let mut decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().unwrap();
+    assert_eq!(reader.info().animation_control().map(|control| control.num_frames), Some(21));
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let mut frames = Vec::new();
+    for _ in 0..21 {
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (40, 24));
+        frames.push(rgb_pixels(&buffer[..info.buffer_size()], info.color_type.samples()));
+    }
+    assert_eq!(frames[0], expected_pixels(&first));
+    assert_eq!(frames[20], expected_pixels(&last));
+    assert_ne!(frames[0], frames[20]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(unix)]
+#[test]
+fn fifo_without_a_reader_drops_frames_instead_of_blocking() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let Some(path) = make_fifo("unread.y4m") else {
+        eprintln!("skipping: mkfifo unavailable");
+        return;
+    };
+    let source = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (SIZE * SIZE) as u64 * 4,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    // More frames than the encoder queue holds, with nobody reading
+    let mut recorder = Recorder::start(&device, y4m_settings(&path), SIZE, SIZE).unwrap();
+    assert!(recorder.waiting_for_reader());
+    capture(&mut recorder, &device, &queue, &source, 40);
+    assert!(recorder.frames_dropped > 16, "{} dropped", recorder.frames_dropped);
+    assert_eq!(recorder.frames_captured, 0);
+    let summary = recorder.finish(&device).unwrap();
+    assert!(summary.starts_with("No reader connected"), "{}", summary);
+
+    // Once a reader is connected, every frame arrives
+    let reader_path = path.clone();
+    let reader = std::thread::spawn(move || {
+        use std::io::Read;
+        let mut bytes = Vec::new();
+        std::fs::File::open(reader_path).unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    });
+    let mut recorder = Recorder::start(&device, y4m_settings(&path), SIZE, SIZE).unwrap();
+    let started = std::time::Instant::now();
+    while recorder.waiting_for_reader() {
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "reader never connected");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    capture(&mut recorder, &device, &queue, &source, 3);
+    assert_eq!(recorder.finish(&device).unwrap(), format!("Saved 3 frames to {}", path.display()));
+    let bytes = reader.join().unwrap();
+    assert!(bytes.starts_with(b"YUV4MPEG2 W16 H16"));
+    assert_eq!(bytes.windows(6).filter(|window| window == b"FRAME\n").count(), 3);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,73 @@
+//! Carrying cells over to a grid of another size.
+
+use gpu_life::resize::{parse_grid_size, resize_cells, ResizeMode};
+
+/// A grid whose cells hold their own index plus one, so every cell is distinguishable
+fn numbered(width: u32, height: u32) -> Vec<f32> {
+    (0..width * height).map(|i| (i + 1) as f32).collect()
+}
+
+#[test]
+fn crop_keeps_the_top_left_corner() {
+    let cells = numbered(4, 3);
+    let smaller = resize_cells(&cells, 4, 3, 2, 2, ResizeMode::Crop);
+    assert_eq!(smaller, vec![1.0, 2.0, 5.0, 6.0]);
+
+    let larger = resize_cells(&cells, 4, 3, 5, 4, ResizeMode::Crop);
+    assert_eq!(&larger[0..5], &[1.0, 2.0, 3.0, 4.0, 0.0]);
+    assert_eq!(&larger[10..15], &[9.0, 10.0, 11.0, 12.0, 0.0]);
+    assert!(larger[15..].iter().all(|&cell| cell == 0.0));
+}
+
+#[test]
+fn pad_centered_keeps_the_center() {
+    let cells = numbered(2, 2);
+    let padded = resize_cells(&cells, 2, 2, 4, 4, ResizeMode::PadCentered);
+    #[rustfmt::skip]
+    assert_eq!(padded, vec![
+        0.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 2.0, 0.0,
+        0.0, 3.0, 4.0, 0.0,
+        0.0, 0.0, 0.0, 0.0,
+    ]);
+
+    // Shrinking trims every side evenly
+    let cells = numbered(4, 4);
+    let trimmed = resize_cells(&cells, 4, 4, 2, 2, ResizeMode::PadCentered);
+    assert_eq!(trimmed, vec![6.0, 7.0, 10.0, 11.0]);
+
+    // Growing one axis while shrinking the other
+    let mixed = resize_cells(&cells, 4, 4, 6, 2, ResizeMode::PadCentered);
+    assert_eq!(mixed, vec![0.0, 5.0, 6.0, 7.0, 8.0, 0.0, 0.0, 9.0, 10.0, 11.0, 12.0, 0.0]);
+}
+
+#[test]
+fn rescale_stretches_the_grid() {
+    let cells = numbered(2, 2);
+    let doubled = resize_cells(&cells, 2, 2, 4, 4, ResizeMode::Rescale);
+    #[rustfmt::skip]
+    assert_eq!(doubled, vec![
+        1.0, 1.0, 2.0, 2.0,
+        1.0, 1.0, 2.0, 2.0,
+        3.0, 3.0, 4.0, 4.0,
+        3.0, 3.0, 4.0, 4.0,
+    ]);
+    assert_eq!(resize_cells(&doubled, 4, 4, 2, 2, ResizeMode::Rescale), cells);
+}
+
+#[test]
+fn same_size_is_unchanged() {
+    let cells = numbered(5, 3);
+    for mode in ResizeMode::ALL {
+        assert_eq!(resize_cells(&cells, 5, 3, 5, 3, mode), cells, "{:?}", mode);
+    }
+}
+
+#[test]
+fn grid_sizes_parse() {
+    assert_eq!(parse_grid_size("2048x1024"), Ok((2048, 1024)));
+    assert_eq!(parse_grid_size("512"), Ok((512, 512)));
+    assert_eq!(parse_grid_size("64X32"), Ok((64, 32)));
+    assert!(parse_grid_size("0x10").is_err());
+    assert!(parse_grid_size("wide").is_err());
+}
@@ -0,0 +1,51 @@
+//! The GPU statistics reduction against the CPU computation of the same numbers.
+//! Tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::soup;
+use gpu_life::simulation::{request_headless_device, Simulation};
+use gpu_life::stats::{GenerationStats, StatsReducer};
+
+const WIDTH: u32 = 61;
+const HEIGHT: u32 = 45;
+
+/// Step a few generations, reducing every one, and compare with `from_cells`
+fn check_reduction(chunk_rows: Option<u32>) {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut simulation = Simulation::with_chunk_rows(device.clone(), queue.clone(), WIDTH, HEIGHT, chunk_rows).unwrap();
+    simulation.load_generation(0, &soup(7, WIDTH, HEIGHT, true)).unwrap();
+    let mut reducer = StatsReducer::new(&device, &simulation);
+
+    let mut previous = simulation.read_cells().unwrap();
+    for _ in 0..4 {
+        simulation.step(1);
+        let current = simulation.read_cells().unwrap();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        assert!(reducer.encode_sample(&mut encoder, simulation.generation));
+        queue.submit(Some(encoder.finish()));
+        reducer.after_submit();
+
+        let samples = reducer.wait_for_slot(&device);
+        assert_eq!(samples.len(), 1);
+        let expected = GenerationStats::from_cells(simulation.generation, WIDTH, HEIGHT, &previous, &current);
+        assert_eq!(samples[0].generation, simulation.generation);
+        assert_eq!(samples[0].species, expected.species, "generation {}", simulation.generation);
+        assert!(!reducer.in_flight());
+        previous = current;
+    }
+}
+
+#[test]
+fn reduction_matches_cpu() {
+    check_reduction(None);
+}
+
+#[test]
+fn reduction_of_chunked_grid_matches_cpu() {
+    // Uneven chunks, so the last one is shorter
+    check_reduction(Some(8));
+}
@@ -0,0 +1,214 @@
+//! The sparse tiled GPU universe against HashLife, which also runs on an infinite
+//! plane. Tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::grid_with;
+use gpu_life::hashlife::HashLife;
+use gpu_life::library::{builtin_pattern, parse_rle, to_rle};
+use gpu_life::render::{Palette, RenderParams};
+use gpu_life::rules::GameRules;
+use gpu_life::simulation::request_headless_device;
+use gpu_life::tiled::{TiledRenderer, TiledUniverse, TILE_SIZE};
+
+fn universe(rules: GameRules) -> Option<TiledUniverse> {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return None;
+    };
+    Some(TiledUniverse::new(device, queue, rules).unwrap())
+}
+
+/// Runs `rle` with its top-left corner at world cell (x, y) on both engines and
+/// compares the live cells after each of `checkpoints`
+fn compare_with_hashlife(rules: GameRules, rle: &str, x: i64, y: i64, checkpoints: &[u32]) {
+    let Some(mut tiled) = universe(rules) else { return };
+    let pattern = parse_rle(rle).unwrap();
+    let cells = grid_with(&pattern, pattern.width, pattern.height, 0, 0);
+    tiled.write_grid(x, y, &cells, pattern.width, pattern.height).unwrap();
+    let mut life = HashLife::from_grid(rules, &cells, pattern.width, pattern.height).unwrap();
+
+    let mut generation = 0;
+    for &checkpoint in checkpoints {
+        tiled.step(checkpoint - generation).unwrap();
+        life.step((checkpoint - generation) as u64);
+        generation = checkpoint;
+
+        let expected: Vec<(i64, i64)> = life.live_cells().into_iter().map(|(cx, cy)| (cx + x, cy + y)).collect();
+        let actual: Vec<(i64, i64)> = tiled.live_cells().unwrap().into_iter().map(|(cx, cy, _)| (cx, cy)).collect();
+        assert_eq!(actual, expected, "after {} generations", generation);
+        assert_eq!(tiled.population(), life.population());
+        assert_eq!(tiled.generation, generation as usize);
+    }
+}
+
+#[test]
+fn gun_matches_hashlife_across_tile_edges() {
+    // The gun straddles the four tiles around the world origin
+    compare_with_hashlife(GameRules::default(), &to_rle(&builtin_pattern("Gosper glider gun")), -18, -5, &[1, 2, 30, 120]);
+}
+
+#[test]
+fn r_pentomino_matches_hashlife() {
+    compare_with_hashlife(GameRules::default(), "x = 3, y = 3\nb2o$2o$bo!", TILE_SIZE as i64 - 2, -1, &[60]);
+}
+
+#[test]
+fn other_rules_match_hashlife() {
+    // HighLife replicator, far from the origin
+    compare_with_hashlife(
+        GameRules::parse("B36/S23").unwrap(),
+        "x = 5, y = 5, rule = B36/S23\n2b3o$bo2bo$o3bo$o2bo$3o!",
+        -3_000_000_000, 5_000_000_000, &[12, 90],
+    );
+}
+
+#[test]
+fn glider_crosses_into_negative_tiles() {
+    // A glider flying up-left from the top-left corner of tile (0, 0)
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    let glider = parse_rle("x = 3, y = 3\n3o$o$bo!").unwrap();
+    tiled.write_grid(1, 1, &grid_with(&glider, 3, 3, 0, 0), 3, 3).unwrap();
+    assert_eq!(tiled.tile_count(), 9);
+
+    // 4 generations move it one cell up and left
+    tiled.step(4 * 8).unwrap();
+    let live: Vec<(i64, i64)> = tiled.live_cells().unwrap().into_iter().map(|(x, y, _)| (x, y)).collect();
+    let expected: Vec<(i64, i64)> = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 2)]
+        .iter()
+        .map(|&(x, y)| (x + 1 - 8, y + 1 - 8))
+        .collect();
+    assert_eq!(live, expected);
+    assert_eq!(tiled.population(), 5);
+    // Only tile (-1, -1) holds cells now, so its halo is all that stays allocated
+    assert_eq!(tiled.tile_count(), 9);
+}
+
+#[test]
+fn empty_tiles_are_freed() {
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    tiled.write_row(1000, 1000, &[1.0; 3]);
+    tiled.write_row(-1000, 1000, &[1.0; 3]);
+    tiled.update_tiles().unwrap();
+    assert_eq!(tiled.tile_count(), 18);
+    assert_eq!(tiled.population(), 6);
+
+    tiled.write_row(1000, 1000, &[0.0; 3]);
+    tiled.update_tiles().unwrap();
+    assert_eq!(tiled.tile_count(), 9);
+    assert_eq!(tiled.population(), 3);
+}
+
+#[test]
+fn read_region_and_colors() {
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    // A green blinker across the vertical edge between tiles -1 and 0
+    tiled.write_row(-1, 10, &[4.0, 4.0, 4.0]);
+    tiled.step(1).unwrap();
+    let region = tiled.read_region(-1, 9, 3, 3).unwrap();
+    assert_eq!(region, vec![0.0, 4.0, 0.0, 0.0, 4.0, 0.0, 0.0, 4.0, 0.0]);
+
+    // Dead cells do not allocate tiles
+    tiled.clear();
+    tiled.write_row(5_000, 5_000, &[0.0; 10]);
+    assert_eq!(tiled.tile_count(), 0);
+}
+
+#[test]
+fn hashlife_jump_loads_back_into_tiles() {
+    // The GUI's long jumps on the plane: read the tiles, run HashLife, load its cells back
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    let gun = builtin_pattern("Gosper glider gun");
+    tiled.write_grid(-100, -3, &grid_with(&gun, gun.width, gun.height, 0, 0), gun.width, gun.height).unwrap();
+    tiled.step(30).unwrap();
+    let live: Vec<(i64, i64)> = tiled.live_cells().unwrap().into_iter().map(|(x, y, _)| (x, y)).collect();
+    let mut life = HashLife::from_cells(GameRules::default(), &live).unwrap();
+    life.step(2_000);
+    tiled.load_cells(2_030, &life.live_cells()).unwrap();
+    assert_eq!(tiled.generation, 2_030);
+    assert_eq!(tiled.population(), life.population());
+
+    // The loaded tiles keep stepping like the plane
+    tiled.step(30).unwrap();
+    life.step(30);
+    let actual: Vec<(i64, i64)> = tiled.live_cells().unwrap().into_iter().map(|(x, y, _)| (x, y)).collect();
+    assert_eq!(actual, life.live_cells());
+}
+
+#[test]
+fn rejects_birth_on_zero_neighbors() {
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    assert!(tiled.set_rules(GameRules::parse("B0/S8").unwrap()).is_err());
+    assert_eq!(tiled.rules(), GameRules::default());
+}
+
+#[test]
+fn renders_cells_at_world_coordinates() {
+    let Some(mut tiled) = universe(GameRules::default()) else { return };
+    tiled.write_row(-3, -2, &[1.0]);
+    let (device, queue) = (tiled.device.clone(), tiled.queue.clone());
+
+    // 16x16 pixels at zoom 2, with world cell (-4, -4) in the top-left corner
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+    let mut renderer = TiledRenderer::new(&device, format);
+    renderer.write_render_params(&queue, &RenderParams {
+        zoom: 2.0,
+        _padding: 0.0,
+        view_offset: [0.0, 0.0],
+        palette: Palette::Monochrome.colors(),
+    });
+    renderer.prepare(&tiled, [-4, -4], [0.0, 0.0], 2.0, (16, 16));
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d { width: 16, height: 16, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 256 * 16,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::RED), store: wgpu::StoreOp::Store },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        renderer.draw(&mut pass);
+    }
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(256), rows_per_image: None },
+        },
+        wgpu::Extent3d { width: 16, height: 16, depth_or_array_layers: 1 },
+    );
+    queue.submit(Some(encoder.finish()));
+    readback.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+    let pixels = readback.slice(..).get_mapped_range();
+
+    for y in 0..16 {
+        for x in 0..16 {
+            let offset = y * 256 + x * 4;
+            let expected = if (2..4).contains(&x) && (4..6).contains(&y) { [255; 4] } else { [0, 0, 0, 255] };
+            assert_eq!(pixels[offset..offset + 4], expected, "pixel ({}, {})", x, y);
+        }
+    }
+}
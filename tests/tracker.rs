@@ -0,0 +1,113 @@
+//! Spaceship tracking: velocities, trails and gliders counted as they leave guns.
+//! GPU tests are skipped when no wgpu adapter (hardware or software) is available.
+
+mod common;
+
+use common::grid_with;
+use gpu_life::census::components;
+use gpu_life::components::ComponentLabeler;
+use gpu_life::cpu::CpuSimulation;
+use gpu_life::library::builtin_pattern;
+use gpu_life::rules::{Boundary, GameRules};
+use gpu_life::simulation::{request_headless_device, Simulation};
+use gpu_life::tracker::{spaceship_name, SpaceshipTracker};
+
+const SIZE: u32 = 160;
+
+/// Objects of a CPU grid
+fn objects(simulation: &CpuSimulation) -> Vec<Vec<(i32, i32)>> {
+    let live: Vec<(i32, i32)> = simulation.cells().iter().enumerate()
+        .filter(|&(_, &value)| value > 0.5)
+        .map(|(index, _)| ((index as u32 % SIZE) as i32, (index as u32 / SIZE) as i32))
+        .collect();
+    components(&live)
+}
+
+#[test]
+fn gliders_leaving_a_gun_are_counted() {
+    let mut simulation = CpuSimulation::new(SIZE, SIZE).unwrap();
+    simulation.boundary = Boundary::Dead;
+    simulation.write_cells(&grid_with(&builtin_pattern("Gosper glider gun"), SIZE, SIZE, 10, 10)).unwrap();
+    let mut tracker = SpaceshipTracker::new(SIZE, SIZE, Boundary::Dead, GameRules::default());
+    for generation in (0..=330).step_by(6) {
+        if generation > 0 {
+            simulation.step(6);
+        }
+        tracker.push(generation, &objects(&simulation));
+    }
+    // One glider every 30 generations, all flying the same way at c/4
+    let interval = tracker.emission_interval().unwrap();
+    assert!((interval - 30.0).abs() < 1.0, "{:?}", tracker.glider_emissions);
+    assert!((9..=11).contains(&tracker.glider_emissions.len()), "{:?}", tracker.glider_emissions);
+    assert_eq!(tracker.lost, 0);
+    assert!(tracker.tracks.len() >= 9);
+    let velocity = tracker.tracks[0].velocity();
+    assert_eq!((velocity.0.abs(), velocity.1.abs()), (0.25, 0.25));
+    assert!(tracker.tracks.iter().all(|track| track.velocity() == velocity && spaceship_name(&track.motion.code) == "glider"));
+    // The oldest glider has a trail along its path
+    let trail = &tracker.tracks[0].trail;
+    assert!(trail.len() > 20);
+    let ((_, first), (_, last)) = (trail.front().unwrap(), trail.back().unwrap());
+    assert!((last.0 - first.0).abs() > 30.0 && (last.1 - first.1).abs() > 30.0);
+}
+
+#[test]
+fn tracks_follow_spaceships_across_sparse_samples() {
+    let mut simulation = CpuSimulation::new(SIZE, SIZE).unwrap();
+    let lwss = builtin_pattern("Lightweight spaceship");
+    simulation.write_cells(&grid_with(&lwss, SIZE, SIZE, 100, 40)).unwrap();
+    let mut tracker = SpaceshipTracker::new(SIZE, SIZE, Boundary::Torus, GameRules::default());
+    // Across the edge of the torus and back in, 20 generations (10 cells) apart
+    for generation in (0..=400).step_by(20) {
+        if generation > 0 {
+            simulation.step(20);
+        }
+        tracker.push(generation, &objects(&simulation));
+    }
+    assert_eq!(tracker.tracks.len(), 1);
+    let track = &tracker.tracks[0];
+    assert_eq!(spaceship_name(&track.motion.code), "LWSS");
+    assert_eq!(track.velocity().1, 0.0);
+    assert_eq!(track.velocity().0.abs(), 0.5);
+    assert_eq!(track.first_seen, 0);
+    assert!(tracker.glider_emissions.is_empty());
+
+    // A glider split by the corner of the torus is one object
+    let mut labels = vec![0; (SIZE * SIZE) as usize];
+    for (x, y) in [(SIZE - 1, SIZE - 2), (0, SIZE - 1), (SIZE - 2, 0), (SIZE - 1, 0), (0, 0)] {
+        labels[(y * SIZE + x) as usize] = 7;
+    }
+    let objects = tracker.objects_from_labels(&labels);
+    assert_eq!(objects.len(), 1);
+    let xs: Vec<i32> = objects[0].iter().map(|c| c.0).collect();
+    assert!(xs.iter().max().unwrap() - xs.iter().min().unwrap() == 2, "{:?}", objects);
+}
+
+#[test]
+fn samples_are_read_back_from_the_labeler() {
+    let Ok((device, queue)) = pollster::block_on(request_headless_device()) else {
+        eprintln!("skipping: no wgpu adapter available");
+        return;
+    };
+    let mut simulation = Simulation::new(device, queue, SIZE, SIZE).unwrap();
+    simulation.load_generation(0, &grid_with(&builtin_pattern("Glider"), SIZE, SIZE, 20, 20)).unwrap();
+    let mut labeler = ComponentLabeler::new(&simulation, 1).unwrap();
+    let mut tracker = SpaceshipTracker::new(SIZE, SIZE, simulation.boundary, simulation.rules);
+    for _ in 0..5 {
+        labeler.label(&simulation).unwrap();
+        let mut encoder = simulation.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        assert!(tracker.encode_sample(&mut encoder, &simulation, &labeler));
+        for _ in 0..8 {
+            simulation.encode_step(&mut encoder);
+        }
+        simulation.queue.submit(Some(encoder.finish()));
+        tracker.after_submit();
+        while !tracker.poll(&simulation.device) {
+            simulation.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+    assert_eq!(tracker.latest_generation(), Some(32));
+    assert_eq!(tracker.tracks.len(), 1);
+    assert_eq!(tracker.tracks[0].trail.len(), 5);
+    assert_eq!(tracker.tracks[0].velocity(), (0.25, 0.25));
+}
@@ -0,0 +1,64 @@
+// Renders the visible tiles of a tiled::TiledUniverse.
+// Zoom, view offset and palette work as in render.wgsl; the view offset is relative
+// to a 64-bit world origin that the CPU folds into `view.origin`.
+
+const TILE: i32 = 256; // Must match tiled::TILE_SIZE
+const NO_TILE: u32 = 0xFFFFFFFFu;
+
+struct RenderParams {
+    zoom: f32,
+    _padding: f32,
+    view_offset: vec2<f32>,
+    palette: array<vec4<f32>, 8>, // Color per rounded cell value (see render::Palette)
+};
+
+struct TileView {
+    origin: vec2<i32>, // Cell at the view origin, relative to the first visible tile
+    tiles: vec2<u32>,  // Visible tiles along x and y
+};
+
+@group(0) @binding(0) var<uniform> render_params: RenderParams;
+@group(0) @binding(1) var<storage, read> cells: array<f32>; // Current tile pool
+@group(0) @binding(2) var<uniform> view: TileView;
+@group(0) @binding(3) var<storage, read> view_slots: array<u32>; // Pool slot per visible tile, row-major
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Vertex shader: Output a full-screen triangle
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32(in_vertex_index / 2u) * 4.0 - 1.0;
+    let y = f32(in_vertex_index % 2u) * 4.0 - 1.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let scaled_coord = (frag_coord.xy + render_params.view_offset) / render_params.zoom;
+    let cell = view.origin + vec2<i32>(floor(scaled_coord));
+
+    let extent = vec2<i32>(view.tiles) * TILE;
+    if (cell.x < 0 || cell.y < 0 || cell.x >= extent.x || cell.y >= extent.y) {
+        return render_params.palette[0];
+    }
+    let tile = cell / TILE;
+    let slot = view_slots[u32(tile.y) * view.tiles.x + u32(tile.x)];
+    if (slot == NO_TILE) {
+        return render_params.palette[0]; // Unallocated tiles are empty
+    }
+    let local = cell % TILE;
+    let index = slot * u32(TILE * TILE) + u32(local.y * TILE + local.x);
+    return render_params.palette[palette_index(cells[index])];
+}
+
+// Map a raw cell value to its palette slot (mirrors Palette::index_of in render.rs)
+fn palette_index(value: f32) -> u32 {
+    if (value <= 0.5) {
+        return 0u;
+    }
+    return u32(clamp(round(value), 1.0, 7.0));
+}